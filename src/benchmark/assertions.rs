@@ -0,0 +1,201 @@
+//! Thresholds for the statistics of a benchmark, as specified via `--assert 'mean < 250ms'`.
+
+use std::fmt;
+
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::output::format::format_duration;
+use crate::util::units::{parse_duration, Second, Unit};
+
+/// A statistic of the run times that can be used in an assertion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Mean,
+    Median,
+    Stddev,
+    Min,
+    Max,
+    User,
+    System,
+
+    /// The given percentile (in the range 0..=100), written as `p95`, for example
+    Percentile(f64),
+}
+
+impl Statistic {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "mean" => Statistic::Mean,
+            "median" => Statistic::Median,
+            "stddev" => Statistic::Stddev,
+            "min" => Statistic::Min,
+            "max" => Statistic::Max,
+            "user" => Statistic::User,
+            "system" => Statistic::System,
+            _ => {
+                let p = name.strip_prefix('p')?.parse::<f64>().ok()?;
+                if !(0.0..=100.0).contains(&p) {
+                    return None;
+                }
+                Statistic::Percentile(p)
+            }
+        })
+    }
+
+    /// Value of the statistic for the given result, if available
+    fn value(self, result: &BenchmarkResult) -> Option<Second> {
+        match self {
+            Statistic::Mean => Some(result.mean),
+            Statistic::Median => Some(result.median),
+            Statistic::Stddev => result.stddev,
+            Statistic::Min => Some(result.min),
+            Statistic::Max => Some(result.max),
+            Statistic::User => Some(result.user),
+            Statistic::System => Some(result.system),
+            Statistic::Percentile(p) => result.percentile(p),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, value: Second, threshold: Second) -> bool {
+        match self {
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+        }
+    }
+}
+
+/// An assertion of the form `STATISTIC OPERATOR DURATION`, like `p95 < 400ms`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    /// The assertion as it has been specified on the command line
+    expression: String,
+
+    statistic: Statistic,
+    comparison: Comparison,
+    threshold: Second,
+}
+
+impl Assertion {
+    /// Parse an assertion like `mean < 250ms`. Returns `None` if the expression is invalid.
+    pub fn parse(expression: &str) -> Option<Self> {
+        let operator_start = expression.find(['<', '>'])?;
+        let (statistic, rest) = expression.split_at(operator_start);
+        let (comparison, threshold) = if let Some(threshold) = rest.strip_prefix("<=") {
+            (Comparison::LessOrEqual, threshold)
+        } else if let Some(threshold) = rest.strip_prefix(">=") {
+            (Comparison::GreaterOrEqual, threshold)
+        } else if let Some(threshold) = rest.strip_prefix('<') {
+            (Comparison::Less, threshold)
+        } else {
+            (Comparison::Greater, &rest[1..])
+        };
+
+        Some(Assertion {
+            expression: expression.trim().to_string(),
+            statistic: Statistic::parse(statistic.trim())?,
+            comparison,
+            threshold: parse_duration(threshold)?,
+        })
+    }
+
+    /// Check the assertion for the given result. Returns a description of the violation if the
+    /// assertion does not hold, or if the statistic is not available (e.g. the standard deviation
+    /// of a single run).
+    pub fn check(&self, result: &BenchmarkResult, unit: Option<Unit>) -> Option<String> {
+        match self.statistic.value(result) {
+            Some(value) if self.comparison.holds(value, self.threshold) => None,
+            Some(value) => Some(format!(
+                "{} (actual value: {})",
+                self.expression,
+                format_duration(value, unit)
+            )),
+            None => Some(format!("{} (the value is not available)", self.expression)),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+#[test]
+fn test_parse_assertion() {
+    let assertion = Assertion::parse("mean < 250ms").unwrap();
+    assert_eq!(assertion.statistic, Statistic::Mean);
+    assert_eq!(assertion.comparison, Comparison::Less);
+    assert_eq!(assertion.threshold, 0.25);
+    assert_eq!(assertion.to_string(), "mean < 250ms");
+
+    let assertion = Assertion::parse("p95<=1.5s").unwrap();
+    assert_eq!(assertion.statistic, Statistic::Percentile(95.0));
+    assert_eq!(assertion.comparison, Comparison::LessOrEqual);
+    assert_eq!(assertion.threshold, 1.5);
+
+    let assertion = Assertion::parse(" min >= 2 ").unwrap();
+    assert_eq!(assertion.statistic, Statistic::Min);
+    assert_eq!(assertion.comparison, Comparison::GreaterOrEqual);
+    assert_eq!(assertion.threshold, 2.0);
+
+    assert_eq!(
+        Assertion::parse("stddev > 10us").unwrap().comparison,
+        Comparison::Greater
+    );
+
+    for invalid in [
+        "mean",
+        "mean = 1s",
+        "average < 1s",
+        "p101 < 1s",
+        "mean < fast",
+        "mean <",
+        "< 1s",
+    ] {
+        assert_eq!(Assertion::parse(invalid), None, "{invalid}");
+    }
+}
+
+#[test]
+fn test_check_assertion() {
+    let result = BenchmarkResult {
+        mean: 0.3,
+        median: 0.2,
+        times: Some(vec![0.1, 0.2, 0.6]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Assertion::parse("median < 250ms")
+            .unwrap()
+            .check(&result, None),
+        None
+    );
+    assert_eq!(
+        Assertion::parse("mean < 250ms")
+            .unwrap()
+            .check(&result, Some(Unit::MilliSecond)),
+        Some(String::from("mean < 250ms (actual value: 300.0 ms)"))
+    );
+    assert_eq!(
+        Assertion::parse("p50 <= 0.2").unwrap().check(&result, None),
+        None
+    );
+    assert_eq!(
+        Assertion::parse("stddev < 1s")
+            .unwrap()
+            .check(&result, None),
+        Some(String::from("stddev < 1s (the value is not available)"))
+    );
+}
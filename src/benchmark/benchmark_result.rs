@@ -1,22 +1,37 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use statistical::{mean, median, standard_deviation};
 
-use crate::util::units::Second;
+use super::significance::student_t_critical_value;
+use crate::outlier_detection::median_absolute_deviation;
+use crate::util::min_max::{max, min};
+use crate::util::units::{MegaHertz, Scalar, Second, SizeUnit};
+
+/// Confidence level of the confidence interval for the mean run time
+pub const CONFIDENCE_LEVEL: Scalar = 0.95;
 
 /// Set of values that will be exported.
 // NOTE: `serde` is used for JSON serialization, but not for CSV serialization due to the
 // `parameters` map. Update `src/hyperfine/export/csv.rs` with new fields, as appropriate.
-#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BenchmarkResult {
     /// The full command line of the program that is being benchmarked
     pub command: String,
 
+    /// The name of the benchmark, as specified via `--command-name` (defaults to the command line)
+    #[serde(default)]
+    pub name: String,
+
     /// The full command line of the program that is being benchmarked, possibly including a list of
     /// parameters that were not used in the command line template.
-    #[serde(skip_serializing)]
+    #[serde(skip_serializing, default)]
     pub command_with_unused_parameters: String,
 
+    /// The name or the command line template without parameter substitution, like `sleep {time}`
+    #[serde(skip_serializing, default)]
+    pub command_template: String,
+
     /// The average run time
     pub mean: Second,
 
@@ -26,6 +41,16 @@ pub struct BenchmarkResult {
     /// The median run time
     pub median: Second,
 
+    /// The median absolute deviation of all run times from the median run time. Unlike the
+    /// standard deviation, it is not sensitive to outliers.
+    #[serde(default)]
+    pub mad: Second,
+
+    /// The coefficient of variation of all run times (standard deviation divided by the mean).
+    /// Not available if only one run has been performed
+    #[serde(default)]
+    pub cv: Option<Scalar>,
+
     /// Time spent in user mode
     pub user: Second,
 
@@ -38,14 +63,679 @@ pub struct BenchmarkResult {
     /// Maximum of all measured times
     pub max: Second,
 
+    /// Number of timing runs, without the warmup runs and the repetitions because of `--retries`
+    #[serde(default)]
+    pub runs: usize,
+
     /// All run time measurements
     #[serde(skip_serializing_if = "Option::is_none")]
     pub times: Option<Vec<Second>>,
 
+    /// Whether `times` and the other values of the individual runs (except for the exit codes)
+    /// only contain a uniform sample of all runs (see `--max-export-samples`). The statistics
+    /// are computed over all runs.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub times_subsampled: bool,
+
+    /// Time spent in user mode, for all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_times: Option<Vec<Second>>,
+
+    /// Time spent in kernel mode, for all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_times: Option<Vec<Second>>,
+
+    /// Times that the command has reported itself in its output, for all command invocations
+    /// (`--extract-time`). Runs without a reported time are `None`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reported_times: Option<Vec<Option<Second>>>,
+
+    /// Unix timestamps (in seconds) of the start of all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_timestamps: Option<Vec<f64>>,
+
+    /// Peak memory usage (maximum resident set size) of all command invocations, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_usage_byte: Option<Vec<u64>>,
+
+    /// Maximum of the peak memory usage over all command invocations, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_usage_byte: Option<u64>,
+
+    /// Number of voluntary context switches of all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voluntary_context_switches: Option<Vec<u64>>,
+
+    /// Number of involuntary context switches of all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub involuntary_context_switches: Option<Vec<u64>>,
+
+    /// Number of major page faults of all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub major_page_faults: Option<Vec<u64>>,
+
+    /// Number of minor page faults of all command invocations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minor_page_faults: Option<Vec<u64>>,
+
     /// Exit codes of all command invocations
+    #[serde(default)]
     pub exit_codes: Vec<Option<i32>>,
 
+    /// Number of command invocations that have been aborted because they exceeded the timeout
+    #[serde(default)]
+    pub timeouts: usize,
+
+    /// Number of failed timing runs that have been repeated because of `--retries` (without the
+    /// repetitions of warmup runs)
+    #[serde(default)]
+    pub retries: usize,
+
+    /// Number of timing runs whose wall clock time was shorter than the estimated shell (or
+    /// process) spawning time and has been set to zero. Only the runs that are part of the
+    /// statistics are counted, i.e. not the outliers or the runs that failed the validation.
+    #[serde(default)]
+    pub clamped_runs: usize,
+
+    /// Number of warmup runs that have been performed before the timing runs
+    #[serde(default)]
+    pub warmup_runs: usize,
+
+    /// Number of timing runs for which the `--validate` command failed. These runs are excluded
+    /// from the statistics, but their times are still exported.
+    #[serde(default)]
+    pub validation_failures: usize,
+
+    /// Indices of the runs for which the `--validate` command failed
+    #[serde(default)]
+    pub failed_validations: Vec<usize>,
+
+    /// Indices of the runs that have been detected as statistical outliers (see
+    /// `--outlier-threshold`)
+    #[serde(default)]
+    pub outliers: Vec<usize>,
+
+    /// Whether the outliers have been excluded from the statistics (with `--trim-outliers`)
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub outliers_excluded: bool,
+
+    /// Whether no further runs have been performed because the `--max-benchmark-time` has been
+    /// exceeded
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub truncated: bool,
+
+    /// Iteration of the benchmark suite in which this result has been measured, starting at 1
+    /// (only with `--repeat-suite`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub iteration: Option<usize>,
+
+    /// Amount of data (or number of items) that is processed per second (with `--throughput`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub throughput: Option<Throughput>,
+
+    /// Frequency of the CPUs during the timing runs (only available on Linux)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cpu_frequency: Option<CpuFrequency>,
+
+    /// Estimated shell spawning time that has been subtracted from all measurements. This is
+    /// exported once in the metadata of the JSON export, not for every single result.
+    #[serde(skip)]
+    pub shell_spawning_time: Option<ShellSpawningTime>,
+
+    /// Estimated process spawning time that has been subtracted from all measurements with
+    /// `--shell=none`. Like the shell spawning time, this is exported in the metadata.
+    #[serde(skip)]
+    pub spawn_calibration: Option<ShellSpawningTime>,
+
+    /// The program and the arguments of the process that has been spawned for each run,
+    /// including the intermediate shell
+    #[serde(default)]
+    pub executed_argv: Vec<String>,
+
+    /// Directory in which the command has been executed (if specified via `--working-dir`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub working_dir: Option<String>,
+
+    /// Parameter values for this benchmark
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub parameters: BTreeMap<String, String>,
+    /// Environment variables that have been set via `--env` or `--env-file`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub environment: BTreeMap<String, String>,
+}
+
+/// A benchmark that has not been performed because the `--total-time-budget` was exhausted
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkippedBenchmark {
+    /// The full command line of the program that would have been benchmarked
+    pub command: String,
+
+    /// The name of the benchmark, as specified via `--command-name` (defaults to the command line)
+    pub name: String,
+
     /// Parameter values for this benchmark
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub parameters: BTreeMap<String, String>,
 }
+
+/// Statistics of the calibration runs that measure the time it takes to spawn the shell (or a
+/// process that exits immediately, with `--shell=none`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ShellSpawningTime {
+    /// Mean wall clock time of spawning the shell
+    pub mean: Second,
+
+    /// Standard deviation of the wall clock time of spawning the shell
+    pub stddev: Second,
+
+    /// Mean user and system time of spawning the shell, which are subtracted from the user and
+    /// system times of all measurements
+    pub user: Second,
+    pub system: Second,
+
+    /// Number of calibration runs
+    pub runs: usize,
+
+    /// Whether the calibration stopped because the estimate of the mean was precise enough
+    /// (rather than because the maximum number of runs or the maximum time was reached)
+    pub precise: bool,
+}
+
+/// Throughput statistics, derived from the run time statistics and the size that is processed by
+/// a single run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Throughput {
+    /// Size that is processed by a single run (in bytes or items)
+    pub size: Scalar,
+    pub unit: SizeUnit,
+
+    /// Size divided by the mean run time (per second)
+    pub mean: Scalar,
+
+    /// Standard deviation, propagated from the standard deviation of the run time (not available
+    /// for a single run)
+    pub stddev: Option<Scalar>,
+
+    /// Size divided by the maximum and the minimum run time, respectively
+    pub min: Scalar,
+    pub max: Scalar,
+}
+
+impl Throughput {
+    /// Compute the throughput from the run time statistics. Not available if some runs took
+    /// no measurable time.
+    pub fn compute(
+        size: Scalar,
+        unit: SizeUnit,
+        mean: Second,
+        stddev: Option<Second>,
+        min: Second,
+        max: Second,
+    ) -> Option<Self> {
+        (min > 0.0).then(|| Throughput {
+            size,
+            unit,
+            mean: size / mean,
+            stddev: stddev.map(|stddev| size * stddev / (mean * mean)),
+            min: size / max,
+            max: size / min,
+        })
+    }
+}
+
+/// Statistics of the CPU frequency that has been observed before and after every timing run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CpuFrequency {
+    /// Mean, minimum and maximum of all samples (in MHz)
+    pub mean: MegaHertz,
+    pub min: MegaHertz,
+    pub max: MegaHertz,
+}
+
+impl CpuFrequency {
+    /// Compute the statistics of the given samples. Not available without any samples.
+    pub fn from_samples(samples: &[MegaHertz]) -> Option<Self> {
+        (!samples.is_empty()).then(|| CpuFrequency {
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+            min: samples.iter().copied().fold(f64::INFINITY, f64::min),
+            max: samples.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+/// Selected percentiles of the measured run times
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Percentiles {
+    pub p25: Second,
+    pub p75: Second,
+    pub p90: Second,
+    pub p95: Second,
+    pub p99: Second,
+}
+
+impl BenchmarkResult {
+    /// Indices of the runs that have been excluded from the statistics, i.e. the runs that
+    /// failed the validation and the excluded outliers
+    fn excluded_runs(&self) -> HashSet<usize> {
+        let outliers: &[usize] = if self.outliers_excluded {
+            &self.outliers
+        } else {
+            &[]
+        };
+        self.failed_validations
+            .iter()
+            .chain(outliers)
+            .copied()
+            .collect()
+    }
+
+    /// All run times, except for the ones that have been excluded from the statistics
+    pub fn trimmed_times(&self) -> Option<Vec<Second>> {
+        let excluded_runs = self.excluded_runs();
+        self.times.as_ref().map(|times| {
+            times
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !excluded_runs.contains(&self.run_index(*i)))
+                .map(|(_, &t)| t)
+                .collect()
+        })
+    }
+
+    /// Number of timing runs that are part of the statistics, i.e. without the runs that failed
+    /// the validation and the excluded outliers
+    pub fn num_included_runs(&self) -> usize {
+        let excluded_outliers = if self.outliers_excluded {
+            self.outliers.len()
+        } else {
+            0
+        };
+        self.runs - self.validation_failures - excluded_outliers
+    }
+
+    /// Number of timing runs. If the run times have been subsampled, this is the number of exit
+    /// codes, which are always kept for all runs.
+    pub fn num_runs(&self) -> Option<usize> {
+        if self.times_subsampled {
+            Some(self.exit_codes.len())
+        } else {
+            self.times.as_ref().map(Vec::len)
+        }
+    }
+
+    /// Index of the run (e.g. in `exit_codes` or `outliers`) that the given entry of `times`
+    /// belongs to
+    pub fn run_index(&self, sample: usize) -> usize {
+        match &self.times {
+            Some(times) if self.times_subsampled => sample * self.exit_codes.len() / times.len(),
+            _ => sample,
+        }
+    }
+
+    /// Keep a uniform sample of at most `max_samples` entries of the values of the individual
+    /// runs. The exit codes are kept for all runs.
+    pub fn subsample(&mut self, max_samples: usize) {
+        let num_runs = self.exit_codes.len();
+        if self.times_subsampled || num_runs <= max_samples {
+            return;
+        }
+
+        // Same mapping as in `run_index`
+        let indices: Vec<usize> = (0..max_samples)
+            .map(|i| i * num_runs / max_samples)
+            .collect();
+        fn pick<T: Copy>(values: &mut Option<Vec<T>>, indices: &[usize]) {
+            if let Some(values) = values {
+                *values = indices.iter().map(|&i| values[i]).collect();
+            }
+        }
+        pick(&mut self.times, &indices);
+        pick(&mut self.user_times, &indices);
+        pick(&mut self.system_times, &indices);
+        pick(&mut self.reported_times, &indices);
+        pick(&mut self.run_timestamps, &indices);
+        pick(&mut self.memory_usage_byte, &indices);
+        pick(&mut self.voluntary_context_switches, &indices);
+        pick(&mut self.involuntary_context_switches, &indices);
+        pick(&mut self.major_page_faults, &indices);
+        pick(&mut self.minor_page_faults, &indices);
+        self.times_subsampled = true;
+    }
+
+    /// A copy of this result with at most `max_samples` entries of the values of the individual
+    /// runs (all of them for `None`), see `subsample`
+    pub fn subsampled(&self, max_samples: Option<usize>) -> BenchmarkResult {
+        let mut result = self.clone();
+        if let Some(max_samples) = max_samples {
+            result.subsample(max_samples);
+        }
+        result
+    }
+
+    /// Compute the given percentile (in the range 0..=100) of all run time measurements, using
+    /// linear interpolation between the closest ranks. Returns `None` if no individual run times
+    /// are available.
+    pub fn percentile(&self, p: f64) -> Option<Second> {
+        let mut times = self.trimmed_times().filter(|t| !t.is_empty())?;
+        times.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+        Some(percentile_of_sorted(&times, p))
+    }
+
+    /// Confidence interval (lower and upper bound) for the mean run time, based on Student's
+    /// t-distribution. Returns `None` for benchmarks with a single run.
+    pub fn confidence_interval(&self) -> Option<(Second, Second)> {
+        let n = self.num_runs()? - self.excluded_runs().len();
+        mean_confidence_interval(self.mean, self.stddev?, n)
+    }
+
+    /// Number of runs for each distinct exit code, with the key "none" for runs without an exit
+    /// code (terminated by a signal or aborted after a timeout)
+    pub fn exit_code_summary(&self) -> BTreeMap<String, usize> {
+        count_exit_codes(&self.exit_codes)
+            .into_iter()
+            .map(|(code, count)| (code.map_or("none".into(), |c| c.to_string()), count))
+            .collect()
+    }
+
+    /// Compute the percentiles that are part of the exported results
+    pub fn percentiles(&self) -> Option<Percentiles> {
+        Some(Percentiles {
+            p25: self.percentile(25.0)?,
+            p75: self.percentile(75.0)?,
+            p90: self.percentile(90.0)?,
+            p95: self.percentile(95.0)?,
+            p99: self.percentile(99.0)?,
+        })
+    }
+
+    /// Combine the results of the same benchmark from several iterations of the suite
+    /// (`--repeat-suite`) into a single result, with the statistics of the runs of all
+    /// iterations. Returns `None` if the individual run times are not available.
+    pub fn aggregate(results: &[&BenchmarkResult]) -> Option<BenchmarkResult> {
+        let first = results.first()?;
+
+        let mut times = vec![];
+        let (mut user, mut system) = (0.0, 0.0);
+        for result in results {
+            let trimmed_times = result.trimmed_times()?;
+            user += result.user * trimmed_times.len() as Scalar;
+            system += result.system * trimmed_times.len() as Scalar;
+            times.extend(trimmed_times);
+        }
+        if times.is_empty() {
+            return None;
+        }
+
+        let num = times.len() as Scalar;
+        let t_mean = mean(&times);
+        let t_stddev = (times.len() > 1).then(|| standard_deviation(&times, Some(t_mean)));
+        Some(BenchmarkResult {
+            mean: t_mean,
+            stddev: t_stddev,
+            median: median(&times),
+            mad: median_absolute_deviation(&times),
+            cv: t_stddev
+                .filter(|_| t_mean > 0.0)
+                .map(|stddev| stddev / t_mean),
+            user: user / num,
+            system: system / num,
+            min: min(&times),
+            max: max(&times),
+            runs: results.iter().map(|result| result.runs).sum(),
+            times: Some(times),
+            failed_validations: vec![],
+            outliers: vec![],
+            outliers_excluded: false,
+            iteration: None,
+            ..(*first).clone()
+        })
+    }
+}
+
+/// Confidence interval for the mean of a sample with `n` values and the given (sample) standard
+/// deviation. Returns `None` if there are fewer than two values.
+pub(crate) fn mean_confidence_interval(
+    mean: Second,
+    stddev: Second,
+    n: usize,
+) -> Option<(Second, Second)> {
+    if n < 2 {
+        return None;
+    }
+
+    let t = student_t_critical_value(1.0 - CONFIDENCE_LEVEL, (n - 1) as Scalar);
+    let half_width = t * stddev / (n as Scalar).sqrt();
+    Some((mean - half_width, mean + half_width))
+}
+
+/// Number of runs for each distinct exit code, ordered by the exit code. Runs without an exit code
+/// come last.
+pub(crate) fn count_exit_codes(exit_codes: &[Option<i32>]) -> Vec<(Option<i32>, usize)> {
+    let mut counts = BTreeMap::new();
+    for &code in exit_codes {
+        *counts.entry((code.is_none(), code)).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|((_, code), count)| (code, count))
+        .collect()
+}
+
+/// Percentile of a non-empty, sorted sample (linear interpolation between closest ranks)
+pub(crate) fn percentile_of_sorted(sorted: &[Second], p: f64) -> Second {
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = rank - lower as f64;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
+#[test]
+fn test_percentile() {
+    use approx::assert_relative_eq;
+
+    let result = BenchmarkResult {
+        times: Some(vec![5.0, 1.0, 4.0, 2.0, 3.0]),
+        ..Default::default()
+    };
+
+    assert_relative_eq!(1.0, result.percentile(0.0).unwrap());
+    assert_relative_eq!(2.0, result.percentile(25.0).unwrap());
+    assert_relative_eq!(3.0, result.percentile(50.0).unwrap());
+    assert_relative_eq!(4.6, result.percentile(90.0).unwrap());
+    assert_relative_eq!(4.96, result.percentile(99.0).unwrap());
+    assert_relative_eq!(5.0, result.percentile(100.0).unwrap());
+}
+
+#[test]
+fn test_count_exit_codes() {
+    assert_eq!(count_exit_codes(&[]), vec![]);
+    assert_eq!(
+        count_exit_codes(&[Some(1), None, Some(0), Some(1), Some(-2), Some(0), Some(1)]),
+        vec![(Some(-2), 1), (Some(0), 2), (Some(1), 3), (None, 1)]
+    );
+
+    let result = BenchmarkResult {
+        exit_codes: vec![Some(0), Some(2), None, Some(0)],
+        ..Default::default()
+    };
+    assert_eq!(
+        result.exit_code_summary(),
+        BTreeMap::from([
+            ("0".to_string(), 2),
+            ("2".to_string(), 1),
+            ("none".to_string(), 1)
+        ])
+    );
+}
+
+#[test]
+fn test_percentiles_single_run() {
+    let result = BenchmarkResult {
+        times: Some(vec![0.5]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        result.percentiles(),
+        Some(Percentiles {
+            p25: 0.5,
+            p75: 0.5,
+            p90: 0.5,
+            p95: 0.5,
+            p99: 0.5,
+        })
+    );
+}
+
+#[test]
+fn test_percentiles_without_times() {
+    let result = BenchmarkResult::default();
+    assert_eq!(result.percentiles(), None);
+}
+
+#[test]
+fn test_confidence_interval() {
+    use approx::assert_relative_eq;
+
+    let result = BenchmarkResult {
+        mean: 3.0,
+        stddev: Some(1.0),
+        times: Some(vec![2.0, 2.0, 3.0, 4.0, 4.0]),
+        ..Default::default()
+    };
+
+    // t = 2.776 for 4 degrees of freedom
+    let (lower, upper) = result.confidence_interval().unwrap();
+    assert_relative_eq!(3.0 - 2.776_445_105 / 5f64.sqrt(), lower, epsilon = 1e-6);
+    assert_relative_eq!(3.0 + 2.776_445_105 / 5f64.sqrt(), upper, epsilon = 1e-6);
+
+    let single_run = BenchmarkResult {
+        mean: 3.0,
+        stddev: None,
+        times: Some(vec![3.0]),
+        ..Default::default()
+    };
+    assert_eq!(None, single_run.confidence_interval());
+}
+
+#[test]
+fn test_statistics_without_failed_validations() {
+    // The second run failed the validation, so its time is not part of the statistics
+    let result = BenchmarkResult {
+        mean: 2.0,
+        stddev: Some(1.0),
+        runs: 4,
+        times: Some(vec![1.0, 100.0, 2.0, 3.0]),
+        exit_codes: vec![Some(0); 4],
+        validation_failures: 1,
+        failed_validations: vec![1],
+        ..Default::default()
+    };
+    let validated = BenchmarkResult {
+        runs: 3,
+        times: Some(vec![1.0, 2.0, 3.0]),
+        exit_codes: vec![Some(0); 3],
+        validation_failures: 0,
+        failed_validations: vec![],
+        ..result.clone()
+    };
+
+    assert_eq!(result.trimmed_times(), Some(vec![1.0, 2.0, 3.0]));
+    assert_eq!(result.num_included_runs(), 3);
+    assert_eq!(result.percentiles(), validated.percentiles());
+    assert_eq!(result.percentile(99.0), Some(2.98));
+    assert_eq!(
+        result.confidence_interval(),
+        validated.confidence_interval()
+    );
+}
+
+#[test]
+fn test_subsample() {
+    let mut result = BenchmarkResult {
+        mean: 4.5,
+        stddev: Some(3.0),
+        times: Some((0..10).map(|t| t as Second).collect()),
+        user_times: Some((0..10).map(|t| t as Second / 10.0).collect()),
+        exit_codes: vec![Some(0); 10],
+        outliers: vec![8, 9],
+        ..Default::default()
+    };
+
+    result.subsample(10);
+    assert!(!result.times_subsampled);
+
+    result.subsample(4);
+    assert!(result.times_subsampled);
+    assert_eq!(result.times, Some(vec![0.0, 2.0, 5.0, 7.0]));
+    assert_eq!(result.user_times, Some(vec![0.0, 0.2, 0.5, 0.7]));
+    assert_eq!(result.exit_codes.len(), 10);
+    assert_eq!(result.mean, 4.5);
+    assert_eq!(
+        (0..4).map(|i| result.run_index(i)).collect::<Vec<_>>(),
+        [0, 2, 5, 7]
+    );
+    assert_eq!(result.num_runs(), Some(10));
+
+    // The confidence interval is still based on the number of all runs
+    let full = BenchmarkResult {
+        times: Some(vec![0.0; 10]),
+        ..result.clone()
+    };
+    assert_eq!(full.num_runs(), Some(10));
+    assert_eq!(result.confidence_interval(), full.confidence_interval());
+}
+
+#[test]
+fn test_aggregate() {
+    use approx::assert_relative_eq;
+
+    let first = BenchmarkResult {
+        name: "sleep 1".into(),
+        user: 0.1,
+        times: Some(vec![1.0, 3.0, 100.0]),
+        outliers: vec![2],
+        outliers_excluded: true,
+        iteration: Some(1),
+        ..Default::default()
+    };
+    let second = BenchmarkResult {
+        name: "sleep 1".into(),
+        user: 0.4,
+        times: Some(vec![2.0]),
+        iteration: Some(2),
+        ..Default::default()
+    };
+
+    let aggregate = BenchmarkResult::aggregate(&[&first, &second]).unwrap();
+    assert_eq!(aggregate.name, "sleep 1");
+    assert_eq!(aggregate.times, Some(vec![1.0, 3.0, 2.0]));
+    assert_eq!(aggregate.iteration, None);
+    assert_relative_eq!(aggregate.mean, 2.0);
+    assert_relative_eq!(aggregate.stddev.unwrap(), 1.0);
+    assert_relative_eq!(aggregate.median, 2.0);
+    assert_relative_eq!(aggregate.min, 1.0);
+    assert_relative_eq!(aggregate.max, 3.0);
+    assert_relative_eq!(aggregate.user, 0.2);
+
+    assert_eq!(BenchmarkResult::aggregate(&[]), None);
+    assert_eq!(
+        BenchmarkResult::aggregate(&[&BenchmarkResult::default()]),
+        None
+    );
+}
+
+#[test]
+fn test_cpu_frequency_from_samples() {
+    assert_eq!(
+        CpuFrequency::from_samples(&[2000.0, 3500.0, 2900.0, 3600.0]),
+        Some(CpuFrequency {
+            mean: 3000.0,
+            min: 2000.0,
+            max: 3600.0,
+        })
+    );
+    assert_eq!(CpuFrequency::from_samples(&[]), None);
+}
@@ -1,27 +1,51 @@
+use std::cell::RefCell;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
-use std::process::ExitStatus;
+use std::process::{ExitStatus, Output, Stdio};
+use std::time::Instant;
 
 use crate::command::Command;
 use crate::options::{
-    CmdFailureAction, CommandInputPolicy, CommandOutputPolicy, Options, OutputStyleOption, Shell,
+    CmdFailureAction, CommandOutputPolicy, Options, OutputStyleOption, Shell, ShellMode,
 };
+use crate::output::format::format_duration;
 use crate::output::progress_bar::get_progress_bar;
-use crate::timer::{execute_and_measure, TimerResult};
-use crate::util::randomized_environment_offset;
+use crate::timer::{execute_and_measure, priority, TimerResult};
+use crate::util::file_caches;
+use crate::util::randomized_environment_offset::RandomizedEnvironmentOffset;
 use crate::util::units::Second;
 
+use super::benchmark_result::ShellSpawningTime;
+use super::persistent_shell::{self, PersistentShell};
 use super::timing_result::TimingResult;
 
 use anyhow::{bail, Context, Result};
-use statistical::mean;
+use statistical::{mean, standard_deviation};
+
+/// Minimum and maximum number of runs to estimate the shell spawning time
+const MIN_CALIBRATION_RUNS: usize = 50;
+const MAX_CALIBRATION_RUNS: usize = 500;
+
+/// The calibration stops after this time, even if the estimate is not precise enough yet
+const MAX_CALIBRATION_TIME: Second = 10.0;
+
+/// The shell spawning time is measured until the standard error of its mean is below this
+/// fraction of the mean, or below `MAX_ABSOLUTE_STANDARD_ERROR`
+const MAX_RELATIVE_STANDARD_ERROR: f64 = 0.01;
+const MAX_ABSOLUTE_STANDARD_ERROR: Second = 1e-4;
+
+/// A program that exits immediately, which is run to measure the process spawning time with
+/// `--shell=none`. There is none that is available on every Windows system.
+const NO_OP_PROGRAM: Option<&str> = if cfg!(unix) { Some("true") } else { None };
 
 pub trait Executor {
-    /// Run the given command and measure the execution time
+    /// Run the given command and measure the execution time. `run` is the number of the timing
+    /// run (starting at 1), or `None` for warmup runs and intermediate commands.
     fn run_command_and_measure(
         &self,
         command: &Command<'_>,
         command_failure_action: Option<CmdFailureAction>,
+        run: Option<usize>,
     ) -> Result<(TimingResult, ExitStatus)>;
 
     /// Perform a calibration of this executor. For example,
@@ -35,26 +59,111 @@ pub trait Executor {
     /// that is being used in addition to the actual runtime
     /// of the command.
     fn time_overhead(&self) -> Second;
+
+    /// Return the statistics of the calibration runs, if the shell spawning time is subtracted
+    /// from the measured times
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime>;
+
+    /// Return the statistics of the calibration runs, if the process spawning time is subtracted
+    /// from the measured times (`--shell=none`)
+    fn spawn_calibration(&self) -> Option<ShellSpawningTime>;
+
+    /// The program and the arguments of the process that is spawned to run the given command
+    /// (the shell, `-c` and the command line, or the split command line with `--shell=none`)
+    fn executed_argv(&self, command: &Command<'_>) -> Result<Vec<String>>;
+
+    /// Run the given command once, without measuring its execution time. The output of the
+    /// command on stderr is captured, such that it can be reported in case of a failure.
+    fn run_command_once(&self, command: &Command<'_>) -> Result<Output>;
+
+    /// Drop the file system caches of the operating system (`--drop-file-caches`). The first
+    /// call acquires the necessary privileges, which might prompt for a password.
+    fn drop_file_caches(&self) -> Result<()>;
+}
+
+/// The program and the arguments of the given process
+fn argv(command: &std::process::Command) -> Vec<String> {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn run_command_once_common(
+    mut command: std::process::Command,
+    environment: &[(String, String)],
+    command_name: &str,
+) -> Result<Output> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .envs(environment.iter().map(|(name, value)| (name, value)));
+
+    command
+        .output()
+        .with_context(|| format!("Failed to run command '{command_name}'"))
 }
 
 fn run_command_and_measure_common(
     mut command: std::process::Command,
+    benchmarked_command: &Command<'_>,
+    run: Option<usize>,
     command_failure_action: CmdFailureAction,
-    command_input_policy: &CommandInputPolicy,
-    command_output_policy: &CommandOutputPolicy,
-    command_name: &str,
+    options: &Options,
+    environment_offset: Option<String>,
 ) -> Result<TimerResult> {
-    let stdin = command_input_policy.get_stdin()?;
-    let (stdout, stderr) = command_output_policy.get_stdout_stderr()?;
+    let command_name = &benchmarked_command.get_command_line();
+    let stdin = options.command_input_policy.get_stdin()?;
+    let (stdout, stderr) = options
+        .command_output_policy
+        .get_stdout_stderr(benchmarked_command, run)
+        .context("Failed to create the output file")?;
     command.stdin(stdin).stdout(stdout).stderr(stderr);
 
-    command.env(
-        "HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET",
-        randomized_environment_offset::value(),
+    command.envs(
+        options
+            .environment
+            .iter()
+            .map(|(name, value)| (name, value)),
     );
+    if let Some(environment_offset) = environment_offset {
+        command.env(
+            "HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET",
+            environment_offset,
+        );
+    }
+
+    let timeout = options.timeout;
+    let result = execute_and_measure(
+        command,
+        timeout,
+        options.cpu_affinity.as_deref(),
+        priority::effective(options.priority),
+        options.command_output_policy == CommandOutputPolicy::Capture,
+    )
+    .with_context(|| format!("Failed to run command '{command_name}'"))?;
+
+    check_result(&result, command_name, command_failure_action, timeout)?;
+    Ok(result)
+}
 
-    let result = execute_and_measure(command)
-        .with_context(|| format!("Failed to run command '{command_name}'"))?;
+/// Fail if the command has timed out or exited with a non-zero exit code, unless failures are
+/// ignored
+fn check_result(
+    result: &TimerResult,
+    command_name: &str,
+    command_failure_action: CmdFailureAction,
+    timeout: Option<Second>,
+) -> Result<()> {
+    if command_failure_action == CmdFailureAction::RaiseError && result.timed_out {
+        bail!(
+            "Command '{}' exceeded the timeout of {}. Use the '-i'/'--ignore-failure' option \
+            if you want to ignore this.",
+            command_name,
+            format_duration(timeout.unwrap_or_default(), None)
+        );
+    }
 
     if command_failure_action == CmdFailureAction::RaiseError && !result.status.success() {
         bail!(
@@ -67,16 +176,121 @@ fn run_command_and_measure_common(
         );
     }
 
-    Ok(result)
+    Ok(())
+}
+
+/// Subtract the estimated spawning time (if any) from the measured times. Times that are
+/// shorter are set to zero. Returns whether this has been the case for the wall clock time.
+fn subtract_spawning_time(result: &mut TimerResult, spawning_time: Option<TimingResult>) -> bool {
+    let Some(spawning_time) = spawning_time else {
+        return false;
+    };
+    let clamped = result.time_real < spawning_time.time_real;
+    result.time_real = (result.time_real - spawning_time.time_real).max(0.0);
+    result.time_user = (result.time_user - spawning_time.time_user).max(0.0);
+    result.time_system = (result.time_system - spawning_time.time_system).max(0.0);
+    clamped
+}
+
+/// Perform calibration runs until the standard error of the mean is small enough (or the maximum
+/// number of runs or time is reached). Returns the mean times and the statistics of the runs.
+fn measure_spawning_time(
+    description: &str,
+    options: &Options,
+    mut run: impl FnMut() -> Result<TimingResult>,
+) -> Result<(TimingResult, ShellSpawningTime)> {
+    let progress_bar = if options.output_style != OutputStyleOption::Disabled {
+        Some(get_progress_bar(
+            MIN_CALIBRATION_RUNS as u64,
+            description,
+            options.output_style,
+        ))
+    } else {
+        None
+    };
+
+    let mut times_real: Vec<Second> = vec![];
+    let mut times_user: Vec<Second> = vec![];
+    let mut times_system: Vec<Second> = vec![];
+
+    let start = Instant::now();
+    let precise = loop {
+        let r = run()?;
+        times_real.push(r.time_real);
+        times_user.push(r.time_user);
+        times_system.push(r.time_system);
+
+        if let Some(bar) = progress_bar.as_ref() {
+            bar.inc(1)
+        }
+
+        let runs = times_real.len();
+        if runs < MIN_CALIBRATION_RUNS {
+            continue;
+        }
+        if is_precise_estimate(&times_real) {
+            break true;
+        }
+        if runs >= MAX_CALIBRATION_RUNS || start.elapsed().as_secs_f64() >= MAX_CALIBRATION_TIME {
+            break false;
+        }
+
+        if let Some(bar) = progress_bar.as_ref() {
+            bar.inc_length(1)
+        }
+    };
+
+    if let Some(bar) = progress_bar.as_ref() {
+        bar.finish_and_clear()
+    }
+
+    let mean_real = mean(&times_real);
+    let mean_user = mean(&times_user);
+    let mean_system = mean(&times_system);
+    let spawning_time = TimingResult {
+        time_real: mean_real,
+        time_user: mean_user,
+        time_system: mean_system,
+        memory_usage_byte: 0,
+        timed_out: false,
+        start_timestamp: 0.0,
+        resource_usage: None,
+        cpu_frequency: None,
+        reported_time: None,
+        clamped: false,
+    };
+    let stats = ShellSpawningTime {
+        mean: mean_real,
+        stddev: standard_deviation(&times_real, Some(mean_real)),
+        user: mean_user,
+        system: mean_system,
+        runs: times_real.len(),
+        precise,
+    };
+    Ok((spawning_time, stats))
+}
+
+/// The time that the command has reported in its output (`--extract-time`)
+fn reported_time(options: &Options, result: &TimerResult) -> Option<Second> {
+    let output = String::from_utf8_lossy(result.output.as_deref()?);
+    options.time_extraction.as_ref()?.extract(&output)
 }
 
 pub struct RawExecutor<'a> {
     options: &'a Options,
+    environment_offset: RandomizedEnvironmentOffset,
+    spawning_time: Option<TimingResult>,
+    spawning_time_stats: Option<ShellSpawningTime>,
 }
 
 impl<'a> RawExecutor<'a> {
     pub fn new(options: &'a Options) -> Self {
-        RawExecutor { options }
+        RawExecutor {
+            options,
+            environment_offset: RandomizedEnvironmentOffset::new(options.randomization),
+            spawning_time: None,
+            spawning_time_stats: None,
+        }
     }
 }
 
@@ -85,38 +299,105 @@ impl<'a> Executor for RawExecutor<'a> {
         &self,
         command: &Command<'_>,
         command_failure_action: Option<CmdFailureAction>,
+        run: Option<usize>,
     ) -> Result<(TimingResult, ExitStatus)> {
-        let result = run_command_and_measure_common(
+        let mut result = run_command_and_measure_common(
             command.get_command()?,
+            command,
+            run,
             command_failure_action.unwrap_or(self.options.command_failure_action),
-            &self.options.command_input_policy,
-            &self.options.command_output_policy,
-            &command.get_command_line(),
+            self.options,
+            self.environment_offset.next_value(),
         )?;
+        let clamped = subtract_spawning_time(&mut result, self.spawning_time);
 
         Ok((
             TimingResult {
                 time_real: result.time_real,
                 time_user: result.time_user,
                 time_system: result.time_system,
+                memory_usage_byte: result.memory_usage_byte,
+                timed_out: result.timed_out,
+                start_timestamp: result.start_timestamp,
+                resource_usage: result.resource_usage,
+                cpu_frequency: result.cpu_frequency,
+                reported_time: reported_time(self.options, &result),
+                clamped,
             },
             result.status,
         ))
     }
 
+    /// Measure the average time it takes to spawn a process, by running a program that exits
+    /// immediately
     fn calibrate(&mut self) -> Result<()> {
+        let Some(program) = NO_OP_PROGRAM.filter(|_| self.options.spawn_calibration) else {
+            return Ok(());
+        };
+
+        let command = Command::new(None, program);
+        let (spawning_time, stats) = measure_spawning_time(
+            "Measuring process spawning time",
+            self.options,
+            || match self.run_command_and_measure(&command, None, None) {
+                Ok((r, _)) => Ok(r),
+                Err(_) => bail!(
+                    "Could not measure the process spawning time. Make sure you can run \
+                         '{program}', or use the '--no-spawn-calibration' option."
+                ),
+            },
+        )?;
+        self.spawning_time = Some(spawning_time);
+        self.spawning_time_stats = Some(stats);
+
+        // The number of calibration runs varies, so the seeded sequence starts after them
+        self.environment_offset = RandomizedEnvironmentOffset::new(self.options.randomization);
+
         Ok(())
     }
 
     fn time_overhead(&self) -> Second {
-        0.0
+        self.spawning_time
+            .map_or(0.0, |spawning_time| spawning_time.time_real)
+    }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        None
+    }
+
+    fn spawn_calibration(&self) -> Option<ShellSpawningTime> {
+        self.spawning_time_stats
+    }
+
+    fn executed_argv(&self, command: &Command<'_>) -> Result<Vec<String>> {
+        Ok(argv(&command.get_command()?))
+    }
+
+    fn run_command_once(&self, command: &Command<'_>) -> Result<Output> {
+        run_command_once_common(
+            command.get_command()?,
+            &self.options.environment,
+            &command.get_command_line(),
+        )
+    }
+
+    fn drop_file_caches(&self) -> Result<()> {
+        file_caches::drop()
     }
 }
 
 pub struct ShellExecutor<'a> {
     options: &'a Options,
     shell: &'a Shell,
+    environment_offset: RandomizedEnvironmentOffset,
     shell_spawning_time: Option<TimingResult>,
+    shell_spawning_time_stats: Option<ShellSpawningTime>,
+
+    /// Whether the runs are sent to a single long-lived shell (`--shell-mode=persistent`)
+    persistent_mode: bool,
+
+    /// The long-lived shell, which is started before the first run
+    persistent_shell: RefCell<Option<PersistentShell>>,
 }
 
 impl<'a> ShellExecutor<'a> {
@@ -124,17 +405,19 @@ impl<'a> ShellExecutor<'a> {
         ShellExecutor {
             shell,
             options,
+            environment_offset: RandomizedEnvironmentOffset::new(options.randomization),
             shell_spawning_time: None,
+            shell_spawning_time_stats: None,
+            persistent_mode: options.shell_mode == ShellMode::Persistent
+                && persistent_shell::unsupported_reason(shell, options).is_none(),
+            persistent_shell: RefCell::new(None),
         }
     }
 }
 
-impl<'a> Executor for ShellExecutor<'a> {
-    fn run_command_and_measure(
-        &self,
-        command: &Command<'_>,
-        command_failure_action: Option<CmdFailureAction>,
-    ) -> Result<(TimingResult, ExitStatus)> {
+impl<'a> ShellExecutor<'a> {
+    /// Build the process that runs the given command through the shell
+    fn shell_command(&self, command: &Command<'_>) -> std::process::Command {
         let on_windows_cmd = cfg!(windows) && *self.shell == Shell::Default("cmd.exe");
         let mut command_builder = self.shell.command();
         command_builder.arg(if on_windows_cmd { "/C" } else { "-c" });
@@ -147,92 +430,145 @@ impl<'a> Executor for ShellExecutor<'a> {
             command_builder.arg(command.get_command_line());
         }
 
-        let mut result = run_command_and_measure_common(
-            command_builder,
-            command_failure_action.unwrap_or(self.options.command_failure_action),
-            &self.options.command_input_policy,
-            &self.options.command_output_policy,
-            &command.get_command_line(),
-        )?;
+        if let Some(working_dir) = command.get_working_dir() {
+            command_builder.current_dir(working_dir);
+        }
+
+        command_builder
+    }
 
-        // Subtract shell spawning time
-        if let Some(spawning_time) = self.shell_spawning_time {
-            result.time_real = (result.time_real - spawning_time.time_real).max(0.0);
-            result.time_user = (result.time_user - spawning_time.time_user).max(0.0);
-            result.time_system = (result.time_system - spawning_time.time_system).max(0.0);
+    /// Run the given command in the long-lived shell, which is started if necessary
+    fn run_in_persistent_shell(
+        &self,
+        command: &Command<'_>,
+        command_failure_action: CmdFailureAction,
+    ) -> Result<TimerResult> {
+        let mut persistent_shell = self.persistent_shell.borrow_mut();
+        if persistent_shell.is_none() {
+            *persistent_shell = Some(PersistentShell::start(self.shell, self.options)?);
         }
+        let shell = persistent_shell
+            .as_mut()
+            .expect("the shell has been started");
+
+        let command_name = command.get_command_line();
+        let result = shell
+            .run(command, self.environment_offset.next_value())
+            .with_context(|| format!("Failed to run command '{command_name}'"))?;
+        check_result(&result, &command_name, command_failure_action, None)?;
+        Ok(result)
+    }
+}
+
+impl<'a> Executor for ShellExecutor<'a> {
+    fn run_command_and_measure(
+        &self,
+        command: &Command<'_>,
+        command_failure_action: Option<CmdFailureAction>,
+        run: Option<usize>,
+    ) -> Result<(TimingResult, ExitStatus)> {
+        let command_failure_action =
+            command_failure_action.unwrap_or(self.options.command_failure_action);
+        let mut result = if self.persistent_mode {
+            self.run_in_persistent_shell(command, command_failure_action)?
+        } else {
+            run_command_and_measure_common(
+                self.shell_command(command),
+                command,
+                run,
+                command_failure_action,
+                self.options,
+                self.environment_offset.next_value(),
+            )?
+        };
+
+        let clamped = subtract_spawning_time(&mut result, self.shell_spawning_time);
 
         Ok((
             TimingResult {
                 time_real: result.time_real,
                 time_user: result.time_user,
                 time_system: result.time_system,
+                memory_usage_byte: result.memory_usage_byte,
+                timed_out: result.timed_out,
+                start_timestamp: result.start_timestamp,
+                resource_usage: result.resource_usage,
+                cpu_frequency: result.cpu_frequency,
+                reported_time: reported_time(self.options, &result),
+                clamped,
             },
             result.status,
         ))
     }
 
-    /// Measure the average shell spawning time
+    /// Measure the average shell spawning time. The startup time of some shells (PowerShell,
+    /// for example) varies a lot, so the shell is run until the standard error of the mean is
+    /// small enough.
     fn calibrate(&mut self) -> Result<()> {
-        const COUNT: u64 = 50;
-        let progress_bar = if self.options.output_style != OutputStyleOption::Disabled {
-            Some(get_progress_bar(
-                COUNT,
-                "Measuring shell spawning time",
-                self.options.output_style,
-            ))
-        } else {
-            None
-        };
-
-        let mut times_real: Vec<Second> = vec![];
-        let mut times_user: Vec<Second> = vec![];
-        let mut times_system: Vec<Second> = vec![];
-
-        for _ in 0..COUNT {
-            // Just run the shell without any command
-            let res = self.run_command_and_measure(&Command::new(None, ""), None);
-
-            match res {
-                Err(_) => {
-                    let shell_cmd = if cfg!(windows) {
-                        format!("{} /C \"\"", self.shell)
-                    } else {
-                        format!("{} -c \"\"", self.shell)
-                    };
-
-                    bail!(
-                        "Could not measure shell execution time. Make sure you can run '{}'.",
-                        shell_cmd
-                    );
-                }
-                Ok((r, _)) => {
-                    times_real.push(r.time_real);
-                    times_user.push(r.time_user);
-                    times_system.push(r.time_system);
-                }
-            }
-
-            if let Some(bar) = progress_bar.as_ref() {
-                bar.inc(1)
-            }
+        if !self.options.shell_calibration {
+            return Ok(());
         }
 
-        if let Some(bar) = progress_bar.as_ref() {
-            bar.finish_and_clear()
-        }
+        let (spawning_time, stats) =
+            measure_spawning_time("Measuring shell spawning time", self.options, || {
+                // Just run the shell without any command
+                match self.run_command_and_measure(&Command::new(None, ""), None, None) {
+                    Ok((r, _)) => Ok(r),
+                    Err(_) => {
+                        let shell_cmd = if cfg!(windows) {
+                            format!("{} /C \"\"", self.shell)
+                        } else {
+                            format!("{} -c \"\"", self.shell)
+                        };
+
+                        bail!(
+                            "Could not measure shell execution time. Make sure you can run '{}'.",
+                            shell_cmd
+                        );
+                    }
+                }
+            })?;
+        self.shell_spawning_time = Some(spawning_time);
+        self.shell_spawning_time_stats = Some(stats);
 
-        self.shell_spawning_time = Some(TimingResult {
-            time_real: mean(&times_real),
-            time_user: mean(&times_user),
-            time_system: mean(&times_system),
-        });
+        // The number of calibration runs varies, so the seeded sequence starts after them
+        self.environment_offset = RandomizedEnvironmentOffset::new(self.options.randomization);
 
         Ok(())
     }
 
     fn time_overhead(&self) -> Second {
-        self.shell_spawning_time.unwrap().time_real
+        self.shell_spawning_time
+            .map_or(0.0, |spawning_time| spawning_time.time_real)
+    }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        self.shell_spawning_time_stats
+    }
+
+    fn spawn_calibration(&self) -> Option<ShellSpawningTime> {
+        None
+    }
+
+    /// In the persistent mode, this is the long-lived shell, which reads the command from stdin
+    fn executed_argv(&self, command: &Command<'_>) -> Result<Vec<String>> {
+        Ok(if self.persistent_mode {
+            argv(&self.shell.command())
+        } else {
+            argv(&self.shell_command(command))
+        })
+    }
+
+    fn run_command_once(&self, command: &Command<'_>) -> Result<Output> {
+        run_command_once_common(
+            self.shell_command(command),
+            &self.options.environment,
+            &command.get_command_line(),
+        )
+    }
+
+    fn drop_file_caches(&self) -> Result<()> {
+        file_caches::drop()
     }
 }
 
@@ -246,13 +582,32 @@ impl MockExecutor {
         MockExecutor { shell }
     }
 
-    fn extract_time<S: AsRef<str>>(sleep_command: S) -> Second {
+    /// The mocked time of a "sleep" command. Several times ("sleep 0.1 0.3") are used in turn
+    /// for the timing runs, and the first one for all other runs.
+    fn extract_time<S: AsRef<str>>(sleep_command: S, run: Option<usize>) -> Second {
         assert!(sleep_command.as_ref().starts_with("sleep "));
-        sleep_command
+        let times: Vec<Second> = sleep_command
             .as_ref()
             .trim_start_matches("sleep ")
-            .parse::<Second>()
-            .unwrap()
+            .split(' ')
+            .map(|time| time.parse::<Second>().unwrap())
+            .collect();
+        times[run.map_or(0, |run| (run - 1) % times.len())]
+    }
+
+    /// A successful exit status, as reported for all mocked commands
+    fn success() -> ExitStatus {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            ExitStatus::from_raw(0)
+        }
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::ExitStatusExt;
+            ExitStatus::from_raw(0)
+        }
     }
 }
 
@@ -261,24 +616,26 @@ impl Executor for MockExecutor {
         &self,
         command: &Command<'_>,
         _command_failure_action: Option<CmdFailureAction>,
+        run: Option<usize>,
     ) -> Result<(TimingResult, ExitStatus)> {
-        #[cfg(unix)]
-        let status = {
-            use std::os::unix::process::ExitStatusExt;
-            ExitStatus::from_raw(0)
-        };
+        let status = Self::success();
 
-        #[cfg(windows)]
-        let status = {
-            use std::os::windows::process::ExitStatusExt;
-            ExitStatus::from_raw(0)
-        };
+        // A negative time mocks a command that is faster than the spawning time, such that its
+        // measured time is set to zero
+        let time_real = Self::extract_time(command.get_command_line(), run);
 
         Ok((
             TimingResult {
-                time_real: Self::extract_time(command.get_command_line()),
+                time_real: time_real.max(0.0),
                 time_user: 0.0,
                 time_system: 0.0,
+                memory_usage_byte: 0,
+                timed_out: false,
+                start_timestamp: 0.0,
+                resource_usage: None,
+                cpu_frequency: None,
+                reported_time: None,
+                clamped: time_real < 0.0,
             },
             status,
         ))
@@ -291,12 +648,113 @@ impl Executor for MockExecutor {
     fn time_overhead(&self) -> Second {
         match &self.shell {
             None => 0.0,
-            Some(shell) => Self::extract_time(shell),
+            Some(shell) => Self::extract_time(shell, None),
         }
     }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        None
+    }
+
+    fn spawn_calibration(&self) -> Option<ShellSpawningTime> {
+        None
+    }
+
+    fn executed_argv(&self, command: &Command<'_>) -> Result<Vec<String>> {
+        Ok(argv(&command.get_command()?))
+    }
+
+    fn run_command_once(&self, _command: &Command<'_>) -> Result<Output> {
+        Ok(Output {
+            status: Self::success(),
+            stdout: vec![],
+            stderr: vec![],
+        })
+    }
+
+    fn drop_file_caches(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Whether or not the standard error of the mean of the given times is small enough
+fn is_precise_estimate(times: &[Second]) -> bool {
+    let mean = mean(times);
+    let standard_error = standard_deviation(times, Some(mean)) / (times.len() as f64).sqrt();
+    standard_error <= (MAX_RELATIVE_STANDARD_ERROR * mean).max(MAX_ABSOLUTE_STANDARD_ERROR)
 }
 
 #[test]
 fn test_mock_executor_extract_time() {
-    assert_eq!(MockExecutor::extract_time("sleep 0.1"), 0.1);
+    assert_eq!(MockExecutor::extract_time("sleep 0.1", None), 0.1);
+    assert_eq!(MockExecutor::extract_time("sleep 0.1", Some(3)), 0.1);
+
+    assert_eq!(MockExecutor::extract_time("sleep 0.1 0.3", None), 0.1);
+    assert_eq!(MockExecutor::extract_time("sleep 0.1 0.3", Some(1)), 0.1);
+    assert_eq!(MockExecutor::extract_time("sleep 0.1 0.3", Some(2)), 0.3);
+    assert_eq!(MockExecutor::extract_time("sleep 0.1 0.3", Some(3)), 0.1);
+}
+
+#[test]
+fn test_executed_argv() {
+    let options = Options::default();
+    let command = Command::new(None, "echo 'a b'");
+
+    let shell = Shell::Custom(vec!["bash".into(), "--norc".into()]);
+    let executor = ShellExecutor::new(&shell, &options);
+    assert_eq!(
+        executor.executed_argv(&command).unwrap(),
+        vec!["bash", "--norc", "-c", "echo 'a b'"]
+    );
+
+    let executor = RawExecutor::new(&options);
+    assert_eq!(
+        executor.executed_argv(&command).unwrap(),
+        vec!["echo", "a b"]
+    );
+}
+
+#[test]
+fn test_subtract_spawning_time() {
+    let measured = |time_real| TimerResult {
+        time_real,
+        time_user: 0.002,
+        time_system: 0.001,
+        memory_usage_byte: 0,
+        timed_out: false,
+        start_timestamp: 0.0,
+        resource_usage: None,
+        cpu_frequency: None,
+        status: ExitStatus::default(),
+        output: None,
+    };
+    let spawning_time = Some(TimingResult {
+        time_real: 0.005,
+        time_user: 0.001,
+        time_system: 0.002,
+        ..Default::default()
+    });
+
+    let mut result = measured(0.015);
+    assert!(!subtract_spawning_time(&mut result, spawning_time));
+    assert!((result.time_real - 0.01).abs() < 1e-12);
+    assert_eq!(result.time_system, 0.0);
+
+    let mut result = measured(0.004);
+    assert!(subtract_spawning_time(&mut result, spawning_time));
+    assert_eq!(result.time_real, 0.0);
+
+    let mut result = measured(0.004);
+    assert!(!subtract_spawning_time(&mut result, None));
+    assert_eq!(result.time_real, 0.004);
+}
+
+#[test]
+fn test_is_precise_estimate() {
+    // Consistent startup times of a fast shell
+    assert!(is_precise_estimate(&[0.001, 0.0012, 0.0011, 0.0009, 0.001]));
+
+    // Large variations of a slow shell
+    assert!(!is_precise_estimate(&[0.2, 0.5, 0.3, 0.9, 0.25]));
+    assert!(is_precise_estimate(&[0.3, 0.301, 0.299, 0.3, 0.302]));
 }
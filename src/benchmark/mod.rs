@@ -1,25 +1,45 @@
+pub mod assertions;
 pub mod benchmark_result;
 pub mod executor;
+pub mod persistent_shell;
 pub mod relative_speed;
+pub mod resume;
 pub mod scheduler;
+pub mod significance;
 pub mod timing_result;
 
 use std::cmp;
+use std::process::ExitStatus;
+use std::time::Instant;
 
 use crate::command::Command;
-use crate::options::{CmdFailureAction, ExecutorKind, Options, OutputStyleOption};
-use crate::outlier_detection::{modified_zscores, OUTLIER_THRESHOLD};
-use crate::output::format::{format_duration, format_duration_unit};
-use crate::output::progress_bar::get_progress_bar;
+use crate::drift_detection::linear_drift;
+use crate::error::InterruptedError;
+use crate::export::{ExportManager, ExportedRun};
+use crate::options::{
+    CmdFailureAction, CommandOutputPolicy, ExecutorKind, Options, OutputStyleOption,
+    PreparationMode, Randomization, WarmupMode, RUN_PLACEHOLDER,
+};
+use crate::outlier_detection::{median_absolute_deviation, modified_zscores, outlier_indices};
+use crate::output::command_output::{self, run_marker};
+use crate::output::format::{
+    format_bytes, format_duration, format_duration_unit, format_frequency, throughput_unit,
+};
+use crate::output::histogram;
+use crate::output::progress_bar::{ProgressEvent, ProgressReporter};
+use crate::output::theme::Role;
 use crate::output::warnings::{OutlierWarningOptions, Warnings};
-use crate::parameter::ParameterNameAndValue;
 use crate::util::exit_code::extract_exit_code;
+use crate::util::interrupt;
 use crate::util::min_max::{max, min};
-use crate::util::units::Second;
-use benchmark_result::BenchmarkResult;
-use timing_result::TimingResult;
+use crate::util::units::{MegaHertz, Scalar, Second};
+use benchmark_result::{
+    count_exit_codes, mean_confidence_interval, BenchmarkResult, CpuFrequency, Throughput,
+    CONFIDENCE_LEVEL,
+};
+use timing_result::{ResourceUsage, TimingResult};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use colored::*;
 use statistical::{mean, median, standard_deviation};
 
@@ -28,25 +48,113 @@ use self::executor::Executor;
 /// Threshold for warning about fast execution time
 pub const MIN_EXECUTION_TIME: Second = 5e-3;
 
+/// Number of warmup runs whose times are averaged to decide whether the run times have
+/// stabilized (`--warmup auto`)
+const WARMUP_AUTO_WINDOW: usize = 3;
+
+/// Relative change of the moving average below which the run times count as stable
+const WARMUP_AUTO_THRESHOLD: Scalar = 0.02;
+
+/// Maximum number of warmup runs with `--warmup auto`
+const WARMUP_AUTO_MAX_RUNS: usize = 30;
+
 pub struct Benchmark<'a> {
     number: usize,
+
+    /// Number of the benchmark that the per-benchmark options (like `--prepare`) refer to
+    options_number: usize,
+    num_benchmarks: usize,
     command: &'a Command<'a>,
     options: &'a Options,
     executor: &'a dyn Executor,
+    export_manager: &'a ExportManager,
+    time_budget: Option<Second>,
 }
 
 impl<'a> Benchmark<'a> {
     pub fn new(
         number: usize,
+        num_benchmarks: usize,
         command: &'a Command<'a>,
         options: &'a Options,
         executor: &'a dyn Executor,
+        export_manager: &'a ExportManager,
     ) -> Self {
         Benchmark {
             number,
+            options_number: number,
+            num_benchmarks,
             command,
             options,
             executor,
+            export_manager,
+            time_budget: None,
+        }
+    }
+
+    /// Use the per-benchmark options of the benchmark with the given number, in the list of all
+    /// benchmarks after the parameter expansion. This differs from the number of the benchmark
+    /// if some duplicate commands are skipped (`--deduplicate`).
+    pub fn with_options_number(mut self, options_number: usize) -> Self {
+        self.options_number = options_number;
+        self
+    }
+
+    /// Limit the number of runs, such that the benchmark takes at most the given time (but
+    /// still respect `--min-runs`)
+    pub fn with_time_budget(mut self, time_budget: Option<Second>) -> Self {
+        self.time_budget = time_budget;
+        self
+    }
+
+    /// Pass a single completed run to the export manager
+    fn export_run(&self, run: usize, result: &TimingResult, exit_code: Option<i32>) -> Result<()> {
+        self.export_manager.write_run(&ExportedRun {
+            command: self.command.get_command_line(),
+            name: self.command.get_name(),
+            run,
+            time: result.time_real,
+            user: result.time_user,
+            system: result.time_system,
+            exit_code,
+            parameters: self
+                .command
+                .get_parameters()
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        })
+    }
+
+    /// Run the benchmarked command once. A failed run is repeated up to `--retries` times, and
+    /// `retries` is increased for every repetition. If hyperfine has been interrupted in the
+    /// meantime, an `InterruptedError` is returned instead of the result (which is most likely
+    /// a failure).
+    fn run_benchmarked_command(
+        &self,
+        retries: &mut usize,
+        run: Option<usize>,
+    ) -> Result<(TimingResult, ExitStatus)> {
+        let mut attempt = 0;
+        loop {
+            // Failures are only reported for the last attempt
+            let command_failure_action =
+                Some(CmdFailureAction::Ignore).filter(|_| attempt < self.options.retries);
+            let result =
+                self.executor
+                    .run_command_and_measure(self.command, command_failure_action, run);
+            if interrupt::is_interrupted() {
+                return Err(InterruptedError.into());
+            }
+
+            let failed = result
+                .as_ref()
+                .is_ok_and(|(res, status)| res.timed_out || !status.success());
+            if !failed || attempt >= self.options.retries {
+                return result;
+            }
+            attempt += 1;
+            *retries += 1;
         }
     }
 
@@ -54,24 +162,42 @@ impl<'a> Benchmark<'a> {
     fn run_intermediate_command(
         &self,
         command: &Command<'_>,
-        error_output: &'static str,
+        error_output: &str,
     ) -> Result<TimingResult> {
         self.executor
-            .run_command_and_measure(command, Some(CmdFailureAction::RaiseError))
+            .run_command_and_measure(command, Some(CmdFailureAction::RaiseError), None)
             .map(|r| r.0)
-            .map_err(|_| anyhow!(error_output))
+            .map_err(|_| {
+                if interrupt::is_interrupted() {
+                    InterruptedError.into()
+                } else {
+                    anyhow!("{error_output}")
+                }
+            })
     }
 
-    /// Run the command specified by `--setup`.
-    fn run_setup_command(
+    /// Set the marker in front of every line of output of the following commands, given the
+    /// number of this benchmark (`--show-output=prefixed`). The progress bar is suspended while
+    /// a line is printed.
+    fn mark_output(
         &self,
-        parameters: impl IntoIterator<Item = ParameterNameAndValue<'a>>,
-    ) -> Result<TimingResult> {
-        let command = self
-            .options
-            .setup_command
-            .as_ref()
-            .map(|setup_command| Command::new_parametrized(None, setup_command, parameters));
+        marker: impl FnOnce(usize) -> String,
+        progress: Option<&ProgressReporter>,
+    ) {
+        if self.options.command_output_policy == CommandOutputPolicy::Prefixed {
+            command_output::set_context(
+                marker(self.number + 1),
+                progress.and_then(|progress| progress.bar()).cloned(),
+                self.options.theme,
+            );
+        }
+    }
+
+    /// Run the command specified by `--setup`.
+    fn run_setup_command(&self) -> Result<TimingResult> {
+        self.mark_output(|number| format!("[cmd {number}, setup]"), None);
+        let command = Options::command_for(&self.options.setup_command, self.options_number)
+            .map(|setup_command| self.command.related_command(setup_command));
 
         let error_output = "The setup command terminated with a non-zero exit code. \
                             Append ' || true' to the command if you are sure that this can be ignored.";
@@ -83,15 +209,10 @@ impl<'a> Benchmark<'a> {
     }
 
     /// Run the command specified by `--cleanup`.
-    fn run_cleanup_command(
-        &self,
-        parameters: impl IntoIterator<Item = ParameterNameAndValue<'a>>,
-    ) -> Result<TimingResult> {
-        let command = self
-            .options
-            .cleanup_command
-            .as_ref()
-            .map(|cleanup_command| Command::new_parametrized(None, cleanup_command, parameters));
+    fn run_cleanup_command(&self) -> Result<TimingResult> {
+        self.mark_output(|number| format!("[cmd {number}, cleanup]"), None);
+        let command = Options::command_for(&self.options.cleanup_command, self.options_number)
+            .map(|cleanup_command| self.command.related_command(cleanup_command));
 
         let error_output = "The cleanup command terminated with a non-zero exit code. \
                             Append ' || true' to the command if you are sure that this can be ignored.";
@@ -110,16 +231,111 @@ impl<'a> Benchmark<'a> {
         self.run_intermediate_command(command, error_output)
     }
 
-    /// Run the command specified by `--conclude`.
-    fn run_conclusion_command(&self, command: &Command<'_>) -> Result<TimingResult> {
-        let error_output = "The conclusion command terminated with a non-zero exit code. \
-                            Append ' || true' to the command if you are sure that this can be ignored.";
+    /// Run the command specified by `--prepare` before a single run, unless it is only supposed
+    /// to run once per benchmark
+    fn run_preparation_command_per_run(
+        &self,
+        command: Option<&Command<'_>>,
+    ) -> Result<Option<TimingResult>> {
+        command
+            .filter(|_| self.options.preparation_mode == PreparationMode::PerRun)
+            .map(|cmd| self.run_preparation_command(cmd))
+            .transpose()
+    }
 
-        self.run_intermediate_command(command, error_output)
+    /// Drop the file system caches before a timing run (with `--drop-file-caches`). Returns the
+    /// time that this has taken.
+    fn drop_file_caches(&self) -> Result<Second> {
+        if !self.options.drop_file_caches {
+            return Ok(0.0);
+        }
+        let start = Instant::now();
+        self.executor.drop_file_caches()?;
+        Ok(start.elapsed().as_secs_f64())
+    }
+
+    /// Run the command specified by `--conclude` (if any). The description of the preceding run
+    /// (like "run 3") is included in the error message.
+    fn run_conclusion_command(
+        &self,
+        command: Option<&Command<'_>>,
+        run: &str,
+    ) -> Result<Option<TimingResult>> {
+        let error_output = format!(
+            "The conclusion command terminated with a non-zero exit code after {run}. \
+             Append ' || true' to the command if you are sure that this can be ignored."
+        );
+
+        command
+            .map(|cmd| self.run_intermediate_command(cmd, &error_output))
+            .transpose()
+    }
+
+    /// Run the command specified by `--validate` (if any) after the given timing run. Returns
+    /// the time that the command has taken and whether the validation succeeded. Unless failures
+    /// are ignored, a failed validation aborts the benchmark.
+    fn run_validation_command(
+        &self,
+        expression: Option<&str>,
+        run: usize,
+    ) -> Result<Option<(TimingResult, bool)>> {
+        let Some(expression) = expression else {
+            return Ok(None);
+        };
+        let expression = expression.replace(RUN_PLACEHOLDER, &run.to_string());
+        let command = self.command.related_command(&expression);
+
+        let (result, status) = self.executor.run_command_and_measure(
+            &command,
+            Some(CmdFailureAction::Ignore),
+            None,
+        )?;
+        if interrupt::is_interrupted() {
+            return Err(InterruptedError.into());
+        }
+
+        let succeeded = status.success() && !result.timed_out;
+        if !succeeded && self.options.command_failure_action == CmdFailureAction::RaiseError {
+            bail!(
+                "The validation command failed after run {run}. Use the '-i'/'--ignore-failure' \
+                 option to exclude such runs from the statistics instead."
+            );
+        }
+
+        Ok(Some((result, succeeded)))
     }
 
-    /// Run the benchmark for a single command
+    /// Run the benchmark for a single command. If it is interrupted with Ctrl-C, the cleanup
+    /// command is still executed (unless disabled).
     pub fn run(&self) -> Result<BenchmarkResult> {
+        let result = self.measure();
+        if let Err(error) = &result {
+            self.cleanup_after_interrupt(error)?;
+        }
+        result
+    }
+
+    /// Run the cleanup command if the benchmark failed because it has been interrupted (unless
+    /// this is disabled)
+    fn cleanup_after_interrupt(&self, error: &anyhow::Error) -> Result<()> {
+        if error.is::<InterruptedError>() && self.options.cleanup_on_interrupt {
+            self.run_cleanup_command()?;
+        }
+        Ok(())
+    }
+
+    fn measure(&self) -> Result<BenchmarkResult> {
+        self.print_header();
+
+        let mut state = self.start()?;
+        while !state.is_complete() {
+            self.perform_run(&mut state)?;
+        }
+
+        self.finish(state)
+    }
+
+    fn print_header(&self) {
         if self.options.output_style != OutputStyleOption::Disabled {
             println!(
                 "{}{}: {}",
@@ -127,250 +343,739 @@ impl<'a> Benchmark<'a> {
                 (self.number + 1).to_string().bold(),
                 self.command.get_name_with_unused_parameters(),
             );
+
+            // An invalid command is reported when it is run
+            if let Some(argv) = self
+                .executor
+                .executed_argv(self.command)
+                .ok()
+                .filter(|_| self.options.verbose)
+            {
+                self.print_executed_command(&argv);
+            }
+            if self.options.verbose
+                && Options::command_for(&self.options.preparation_command, self.options_number)
+                    .is_some()
+            {
+                println!(
+                    "  Warmup mode: {}",
+                    self.options
+                        .theme
+                        .paint(Role::Detail, self.options.warmup_mode.to_string())
+                );
+            }
         }
+    }
 
-        let mut times_real: Vec<Second> = vec![];
-        let mut times_user: Vec<Second> = vec![];
-        let mut times_system: Vec<Second> = vec![];
-        let mut exit_codes: Vec<Option<i32>> = vec![];
-        let mut all_succeeded = true;
+    /// Print the exact process and the environment variables with which the command is run
+    /// (`--verbose`)
+    fn print_executed_command(&self, argv: &[String]) {
+        let theme = &self.options.theme;
+        println!(
+            "  Executed:    {}",
+            theme.paint(Role::Detail, format!("{argv:?}"))
+        );
 
-        let preparation_command = self.options.preparation_command.as_ref().map(|values| {
-            let preparation_command = if values.len() == 1 {
-                &values[0]
-            } else {
-                &values[self.number]
-            };
-            Command::new_parametrized(
-                None,
-                preparation_command,
-                self.command.get_parameters().iter().cloned(),
-            )
-        });
-        let run_preparation_command = || {
-            preparation_command
-                .as_ref()
-                .map(|cmd| self.run_preparation_command(cmd))
-                .transpose()
-        };
+        let environment: Vec<String> = self
+            .options
+            .environment
+            .iter()
+            .map(|(name, value)| format!("{name}={value:?}"))
+            .chain(match self.options.randomization {
+                Randomization::On => {
+                    Some("HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET=<random>".into())
+                }
+                Randomization::Off => None,
+                Randomization::Seed(seed) => Some(format!(
+                    "HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET=<random, seed {seed}>"
+                )),
+            })
+            .collect();
+        println!(
+            "  Environment: {}",
+            theme.paint(Role::Detail, environment.join(" "))
+        );
+        if let Some(working_dir) = self.command.get_working_dir() {
+            println!(
+                "  Directory:   {}",
+                theme.paint(Role::Detail, format!("{working_dir:?}"))
+            );
+        }
+    }
 
-        let conclusion_command = self.options.conclusion_command.as_ref().map(|values| {
-            let conclusion_command = if values.len() == 1 {
-                &values[0]
-            } else {
-                &values[self.number]
-            };
-            Command::new_parametrized(
-                None,
-                conclusion_command,
-                self.command.get_parameters().iter().cloned(),
-            )
-        });
-        let run_conclusion_command = || {
-            conclusion_command
-                .as_ref()
-                .map(|cmd| self.run_conclusion_command(cmd))
-                .transpose()
+    /// Run the setup command, the warmup runs and the initial timing run, which determines the
+    /// total number of runs
+    fn start(&self) -> Result<MeasurementState<'a>> {
+        let run_bounds = self.options.run_bounds_for(self.options_number);
+        let warmup_count = self.options.warmup_count_for(self.options_number);
+
+        let mut progress = ProgressReporter::new(self.options, run_bounds.min);
+        progress.report(ProgressEvent::BenchmarkStarted {
+            command: &self.command.get_name_with_unused_parameters(),
+            index: self.number + 1,
+            total: self.num_benchmarks,
+        })?;
+
+        let related_command = |values: &'a Option<Vec<String>>| {
+            Options::command_for(values, self.options_number)
+                .map(|expression| self.command.related_command(expression))
+        };
+        let mut state = MeasurementState {
+            progress,
+            preparation_command: related_command(&self.options.preparation_command),
+            conclusion_command: related_command(&self.options.conclusion_command),
+            validation_command: Options::command_for(
+                &self.options.validation_command,
+                self.options_number,
+            ),
+            measurements: Measurements::default(),
+            warmup_times: vec![],
+            count: 0,
+            measured_time: 0.0,
+            truncated: false,
         };
 
-        self.run_setup_command(self.command.get_parameters().iter().cloned())?;
+        self.run_setup_command()?;
 
-        // Warmup phase
-        if self.options.warmup_count > 0 {
-            let progress_bar = if self.options.output_style != OutputStyleOption::Disabled {
-                Some(get_progress_bar(
-                    self.options.warmup_count,
-                    "Performing warmup runs",
-                    self.options.output_style,
-                ))
-            } else {
-                None
-            };
-
-            for _ in 0..self.options.warmup_count {
-                let _ = run_preparation_command()?;
-                let _ = self.executor.run_command_and_measure(self.command, None)?;
-                let _ = run_conclusion_command()?;
-                if let Some(bar) = progress_bar.as_ref() {
-                    bar.inc(1)
-                }
-            }
-            if let Some(bar) = progress_bar.as_ref() {
-                bar.finish_and_clear()
-            }
+        // With `--prepare-mode per-benchmark`, the preparation command runs once, before the
+        // first (warmup) run. With `--warmup-mode before-prepare`, it runs after the warmup runs.
+        let has_preparation_command = state.preparation_command.is_some();
+        let warmup_mode = self.options.warmup_mode;
+        if warmup_mode != WarmupMode::BeforePrepare {
+            self.run_preparation_command_once(&state)?;
+        }
+        if !(warmup_mode == WarmupMode::SkipWithPrepare && has_preparation_command) {
+            self.warmup(&mut state, warmup_count)?;
+        }
+        if warmup_mode == WarmupMode::BeforePrepare {
+            self.run_preparation_command_once(&state)?;
         }
 
-        // Set up progress bar (and spinner for initial measurement)
-        let progress_bar = if self.options.output_style != OutputStyleOption::Disabled {
-            Some(get_progress_bar(
-                self.options.run_bounds.min,
-                "Initial time measurement",
-                self.options.output_style,
-            ))
-        } else {
-            None
-        };
-
-        let preparation_result = run_preparation_command()?;
-        let preparation_overhead =
-            preparation_result.map_or(0.0, |res| res.time_real + self.executor.time_overhead());
+        state.progress.report(ProgressEvent::MeasurementStarted)?;
 
         // Initial timing run
-        let (res, status) = self.executor.run_command_and_measure(self.command, None)?;
-        let success = status.success();
-
-        let conclusion_result = run_conclusion_command()?;
-        let conclusion_overhead =
-            conclusion_result.map_or(0.0, |res| res.time_real + self.executor.time_overhead());
+        let (res, status, time_per_run) = self.timed_run(&mut state)?;
 
         // Determine number of benchmark runs
-        let runs_in_min_time = (self.options.min_benchmarking_time
-            / (res.time_real
-                + self.executor.time_overhead()
-                + preparation_overhead
-                + conclusion_overhead)) as u64;
-
-        let count = {
-            let min = cmp::max(runs_in_min_time, self.options.run_bounds.min);
-
-            self.options
-                .run_bounds
-                .max
-                .as_ref()
-                .map(|max| cmp::min(min, *max))
-                .unwrap_or(min)
+        let runs_in_min_time = (self.options.min_benchmarking_time / time_per_run) as u64;
+        let runs_in_min_time = match self.time_budget {
+            Some(time_budget) => cmp::min(runs_in_min_time, (time_budget / time_per_run) as u64),
+            None => runs_in_min_time,
         };
 
-        let count_remaining = count - 1;
+        state.count = cmp::min(
+            cmp::max(runs_in_min_time, run_bounds.min),
+            run_bounds.effective_max(),
+        );
 
-        // Save the first result
-        times_real.push(res.time_real);
-        times_user.push(res.time_user);
-        times_system.push(res.time_system);
-        exit_codes.push(extract_exit_code(status));
+        self.record_run(&mut state, &res, status)?;
+        self.limit_to_max_benchmark_time(&mut state);
 
-        all_succeeded = all_succeeded && success;
+        Ok(state)
+    }
 
-        // Re-configure the progress bar
-        if let Some(bar) = progress_bar.as_ref() {
-            bar.set_length(count)
-        }
-        if let Some(bar) = progress_bar.as_ref() {
-            bar.inc(1)
+    /// Run the preparation command once for the whole benchmark (with
+    /// `--prepare-mode per-benchmark`)
+    fn run_preparation_command_once(&self, state: &MeasurementState<'a>) -> Result<()> {
+        if let Some(cmd) = state
+            .preparation_command
+            .as_ref()
+            .filter(|_| self.options.preparation_mode != PreparationMode::PerRun)
+        {
+            self.mark_output(|number| format!("[cmd {number}, prepare]"), None);
+            self.run_preparation_command(cmd)?;
         }
+        Ok(())
+    }
 
-        // Gather statistics (perform the actual benchmark)
-        for _ in 0..count_remaining {
-            run_preparation_command()?;
+    /// Perform the warmup runs (a fixed number, for a given duration or until the run times
+    /// have stabilized)
+    fn warmup(&self, state: &mut MeasurementState<'a>, warmup_count: u64) -> Result<()> {
+        if warmup_count > 0 {
+            state.progress.report(ProgressEvent::WarmupStarted {
+                runs: Some(warmup_count),
+            })?;
 
-            let msg = {
-                let mean = format_duration(mean(&times_real), self.options.time_unit);
-                format!("Current estimate: {}", mean.to_string().green())
-            };
+            for _ in 0..warmup_count {
+                self.warmup_run(state, Some(warmup_count))?;
+            }
+            state
+                .progress
+                .report(ProgressEvent::WarmupFinished { runs: warmup_count })?;
+        } else if let Some(warmup_duration) = self.options.warmup_duration {
+            state
+                .progress
+                .report(ProgressEvent::WarmupStarted { runs: None })?;
 
-            if let Some(bar) = progress_bar.as_ref() {
-                bar.set_message(msg.to_owned())
+            let warmup_start = Instant::now();
+            while state.warmup_times.is_empty()
+                || warmup_start.elapsed().as_secs_f64() < warmup_duration
+            {
+                self.warmup_run(state, None)?;
             }
+            state.progress.report(ProgressEvent::WarmupFinished {
+                runs: state.warmup_times.len() as u64,
+            })?;
+        } else if self.options.warmup_auto {
+            state
+                .progress
+                .report(ProgressEvent::WarmupStarted { runs: None })?;
 
-            let (res, status) = self.executor.run_command_and_measure(self.command, None)?;
-            let success = status.success();
+            while state.warmup_times.len() < WARMUP_AUTO_MAX_RUNS
+                && !warmup_has_converged(&state.warmup_times)
+            {
+                self.warmup_run(state, None)?;
+            }
+            state.progress.report(ProgressEvent::WarmupFinished {
+                runs: state.warmup_times.len() as u64,
+            })?;
+        }
+        Ok(())
+    }
 
-            times_real.push(res.time_real);
-            times_user.push(res.time_user);
-            times_system.push(res.time_system);
-            exit_codes.push(extract_exit_code(status));
+    /// Perform a single warmup run together with the preparation and conclusion commands
+    fn warmup_run(&self, state: &mut MeasurementState<'a>, total: Option<u64>) -> Result<()> {
+        let run = state.warmup_times.len() as u64 + 1;
+        self.mark_output(
+            |number| run_marker(number, "warmup", run, total),
+            Some(&state.progress),
+        );
+        if self.options.warmup_mode != WarmupMode::BeforePrepare {
+            self.run_preparation_command_per_run(state.preparation_command.as_ref())?;
+        }
+        // Only the repetitions of timing runs are counted as retries
+        let (res, _) = self.run_benchmarked_command(&mut 0, None)?;
+        self.run_conclusion_command(
+            state.conclusion_command.as_ref(),
+            &format!("warmup run {run}"),
+        )?;
 
-            all_succeeded = all_succeeded && success;
+        state.warmup_times.push(res.time_real);
+        state.progress.report(ProgressEvent::WarmupRunCompleted {
+            run,
+            time: res.time_real,
+            estimate: moving_average(&state.warmup_times),
+        })
+    }
 
-            if let Some(bar) = progress_bar.as_ref() {
-                bar.inc(1)
-            }
+    /// Perform a single timing run together with the preparation and conclusion commands. Also
+    /// returns the time that is needed for the run, including these commands.
+    fn timed_run(
+        &self,
+        state: &mut MeasurementState<'a>,
+    ) -> Result<(TimingResult, ExitStatus, Second)> {
+        let run = state.measurements.times_real.len() + 1;
+        // The number of runs is only known after the initial run
+        let total = Some(state.count).filter(|&count| count > 0);
+        self.mark_output(
+            |number| run_marker(number, "run", run as u64, total),
+            Some(&state.progress),
+        );
 
-            run_conclusion_command()?;
+        let preparation_result =
+            self.run_preparation_command_per_run(state.preparation_command.as_ref())?;
+        let cache_drop_time = self.drop_file_caches()?;
+        let (res, status) =
+            self.run_benchmarked_command(&mut state.measurements.retries, Some(run))?;
+        let validation_result = self.run_validation_command(state.validation_command, run)?;
+        if let Some((_, false)) = validation_result {
+            state.measurements.failed_validations.push(run - 1);
         }
+        let conclusion_result =
+            self.run_conclusion_command(state.conclusion_command.as_ref(), &format!("run {run}"))?;
+
+        let overhead = |result: Option<TimingResult>| {
+            result.map_or(0.0, |res| res.time_real + self.executor.time_overhead())
+        };
+        let time_per_run = res.time_real
+            + self.executor.time_overhead()
+            + overhead(preparation_result)
+            + overhead(validation_result.map(|(result, _)| result))
+            + overhead(conclusion_result)
+            + cache_drop_time;
+        state.measured_time += time_per_run;
+
+        Ok((res, status, time_per_run))
+    }
+
+    /// Save the result of a timing run, and pass it on to the export manager and the progress
+    /// reporter
+    fn record_run(
+        &self,
+        state: &mut MeasurementState<'a>,
+        res: &TimingResult,
+        status: ExitStatus,
+    ) -> Result<()> {
+        let measurements = &mut state.measurements;
+        measurements.push(res, status);
 
-        if let Some(bar) = progress_bar.as_ref() {
-            bar.finish_and_clear()
+        self.export_run(
+            measurements.exit_codes.len(),
+            res,
+            measurements.exit_codes.last().copied().flatten(),
+        )?;
+        state.progress.report(ProgressEvent::RunCompleted {
+            benchmark: self.options.interleave.then_some(self.number + 1),
+            run: measurements.times_real.len(),
+            runs: state.count,
+            time: res.time_real,
+            mean: mean(&measurements.times_real),
+        })
+    }
+
+    /// Perform one of the remaining timing runs
+    fn perform_run(&self, state: &mut MeasurementState<'a>) -> Result<()> {
+        let (res, status, _) = self.timed_run(state)?;
+        self.record_run(state, &res, status)?;
+        self.limit_to_max_benchmark_time(state);
+        Ok(())
+    }
+
+    /// Skip the remaining timing runs if they have taken longer than the `--max-benchmark-time`
+    /// so far. Unless `--max-benchmark-time-hard` is used, at least `--min-runs` are performed.
+    fn limit_to_max_benchmark_time(&self, state: &mut MeasurementState<'a>) {
+        let Some(max_benchmark_time) = self.options.max_benchmark_time else {
+            return;
+        };
+        let runs = state.measurements.times_real.len() as u64;
+        let min_runs = if self.options.max_benchmark_time_hard {
+            1
+        } else {
+            self.options.run_bounds_for(self.options_number).min
+        };
+        if state.measured_time > max_benchmark_time && runs >= min_runs && runs < state.count {
+            state.count = runs;
+            state.truncated = true;
         }
+    }
+
+    /// Compute the statistics of all timing runs, print them, and run the cleanup command
+    fn finish(&self, state: MeasurementState<'a>) -> Result<BenchmarkResult> {
+        let MeasurementState {
+            mut progress,
+            count,
+            measurements,
+            warmup_times,
+            truncated,
+            ..
+        } = state;
+        let Measurements {
+            times_real,
+            times_user,
+            times_system,
+            memory_usage_byte,
+            run_timestamps,
+            resource_usage,
+            cpu_frequency,
+            exit_codes,
+            all_succeeded,
+            timeouts,
+            retries,
+            failed_validations,
+            reported_times,
+            clamped,
+        } = measurements;
+
+        let run_bounds = self.options.run_bounds_for(self.options_number);
+
+        // Runs that failed the validation are never part of the statistics, and they are not
+        // taken into account for the detection of outliers
+        if failed_validations.len() == times_real.len() {
+            bail!(
+                "The validation command failed after all {} runs, so no statistics can be \
+                 computed.",
+                times_real.len()
+            );
+        }
+        let mut excluded_runs = vec![false; times_real.len()];
+        for &run in &failed_validations {
+            excluded_runs[run] = true;
+        }
+        let validated_runs: Vec<usize> = (0..times_real.len())
+            .filter(|&run| !excluded_runs[run])
+            .collect();
+        let validated_times_real: Vec<Second> =
+            validated_runs.iter().map(|&run| times_real[run]).collect();
+
+        // With `--trim-outliers`, the statistics are computed without the outliers, but all
+        // measurements are still exported
+        let outliers: Vec<usize> =
+            outlier_indices(&validated_times_real, self.options.outlier_threshold)
+                .into_iter()
+                .map(|index| validated_runs[index])
+                .collect();
+        let excluded_outliers: &[usize] = if self.options.trim_outliers {
+            &outliers
+        } else {
+            &[]
+        };
+        for &run in excluded_outliers {
+            excluded_runs[run] = true;
+        }
+        // Only the runs that are part of the statistics can make the comparison impossible
+        let clamped_runs = clamped
+            .iter()
+            .zip(&excluded_runs)
+            .filter(|&(&clamped, &excluded)| clamped && !excluded)
+            .count();
+        let trimmed = |values: &[Second]| -> Vec<Second> {
+            values
+                .iter()
+                .enumerate()
+                .filter(|&(run, _)| !excluded_runs[run])
+                .map(|(_, &v)| v)
+                .collect()
+        };
+        let trimmed_times_real = trimmed(&times_real);
 
         // Compute statistical quantities
-        let t_num = times_real.len();
-        let t_mean = mean(&times_real);
-        let t_stddev = if times_real.len() > 1 {
-            Some(standard_deviation(&times_real, Some(t_mean)))
+        let t_num = trimmed_times_real.len();
+        let t_mean = mean(&trimmed_times_real);
+        let t_stddev = if t_num > 1 {
+            Some(standard_deviation(&trimmed_times_real, Some(t_mean)))
         } else {
             None
         };
-        let t_median = median(&times_real);
-        let t_min = min(&times_real);
-        let t_max = max(&times_real);
+        let confidence_interval =
+            t_stddev.and_then(|stddev| mean_confidence_interval(t_mean, stddev, t_num));
+        let t_median = median(&trimmed_times_real);
+        let t_mad = median_absolute_deviation(&trimmed_times_real);
+        let t_cv = t_stddev
+            .filter(|_| t_mean > 0.0)
+            .map(|stddev| stddev / t_mean);
+        let t_min = min(&trimmed_times_real);
+        let t_max = max(&trimmed_times_real);
+
+        let user_mean = mean(&trimmed(&times_user));
+        let system_mean = mean(&trimmed(&times_system));
+        let max_memory_usage_byte = memory_usage_byte.iter().copied().max().unwrap_or(0);
+        let exit_code_counts = count_exit_codes(&exit_codes);
+
+        let throughput_size = self
+            .options
+            .throughput_size_for(self.options_number, self.command)?;
+        let throughput = throughput_size.and_then(|size| {
+            Throughput::compute(size.value, size.unit, t_mean, t_stddev, t_min, t_max)
+        });
+        let cpu_frequency = cpu_frequency
+            .as_deref()
+            .and_then(CpuFrequency::from_samples);
 
-        let user_mean = mean(&times_user);
-        let system_mean = mean(&times_system);
+        progress.report(ProgressEvent::BenchmarkFinished {
+            runs: times_real.len(),
+            mean: t_mean,
+            stddev: t_stddev,
+            median: t_median,
+            min: t_min,
+            max: t_max,
+            user: user_mean,
+            system: system_mean,
+        })?;
 
         // Formatting and console output
         let (mean_str, time_unit) = format_duration_unit(t_mean, self.options.time_unit);
         let min_str = format_duration(t_min, Some(time_unit));
         let max_str = format_duration(t_max, Some(time_unit));
-        let num_str = format!("{t_num} runs");
+        let num_str = match times_real.len() {
+            1 => "1 run".to_string(),
+            runs => format!("{runs} runs"),
+        };
 
         let user_str = format_duration(user_mean, Some(time_unit));
         let system_str = format_duration(system_mean, Some(time_unit));
 
+        let theme = self.options.theme;
         if self.options.output_style != OutputStyleOption::Disabled {
-            if times_real.len() == 1 {
+            if t_num == 1 {
                 println!(
-                    "  Time ({} ≡):        {:>8}  {:>8}     [User: {}, System: {}]",
-                    "abs".green().bold(),
-                    mean_str.green().bold(),
+                    "  Time ({} ≡):        {:>8}  {:>8}     [User: {}, System: {}]    {}",
+                    theme.paint(Role::Value, "abs").bold(),
+                    theme.paint(Role::Value, &mean_str).bold(),
                     "        ", // alignment
-                    user_str.blue(),
-                    system_str.blue()
+                    theme.paint(Role::CpuTime, &user_str),
+                    theme.paint(Role::CpuTime, &system_str),
+                    num_str.dimmed()
                 );
             } else {
                 let stddev_str = format_duration(t_stddev.unwrap(), Some(time_unit));
+                let cv_str = match t_cv.filter(|_| self.options.show_cv) {
+                    Some(cv) => format!(" (CV {:.1}%)", cv * 100.0),
+                    None => String::new(),
+                };
 
                 println!(
-                    "  Time ({} ± {}):     {:>8} ± {:>8}    [User: {}, System: {}]",
-                    "mean".green().bold(),
-                    "σ".green(),
-                    mean_str.green().bold(),
-                    stddev_str.green(),
-                    user_str.blue(),
-                    system_str.blue()
+                    "  Time ({} ± {}):     {:>8} ± {:>8}{}    [User: {}, System: {}]",
+                    theme.paint(Role::Value, "mean").bold(),
+                    theme.paint(Role::Value, "σ"),
+                    theme.paint(Role::Value, &mean_str).bold(),
+                    theme.paint(Role::Value, &stddev_str),
+                    theme.paint(Role::Value, &cv_str),
+                    theme.paint(Role::CpuTime, &user_str),
+                    theme.paint(Role::CpuTime, &system_str)
                 );
 
                 println!(
                     "  Range ({} … {}):   {:>8} … {:>8}    {}",
-                    "min".cyan(),
-                    "max".purple(),
-                    min_str.cyan(),
-                    max_str.purple(),
+                    theme.paint(Role::Min, "min"),
+                    theme.paint(Role::Max, "max"),
+                    theme.paint(Role::Min, &min_str),
+                    theme.paint(Role::Max, &max_str),
                     num_str.dimmed()
                 );
             }
+
+            let reported: Vec<Second> = reported_times.iter().flatten().copied().collect();
+            if self.options.time_extraction.is_some() && !reported.is_empty() {
+                let reported_mean = mean(&reported);
+                let (reported_mean_str, reported_unit) =
+                    format_duration_unit(reported_mean, self.options.time_unit);
+                if reported.len() > 1 {
+                    let reported_stddev = standard_deviation(&reported, Some(reported_mean));
+                    println!(
+                        "  Self-reported:       {:>8} ± {:>8}",
+                        theme.paint(Role::Value, &reported_mean_str).bold(),
+                        theme.paint(
+                            Role::Value,
+                            format_duration(reported_stddev, Some(reported_unit))
+                        ),
+                    );
+                } else {
+                    println!(
+                        "  Self-reported:       {:>8}",
+                        theme.paint(Role::Value, &reported_mean_str).bold()
+                    );
+                }
+            }
+
+            if let (Some(throughput), Some(size)) = (&throughput, throughput_size) {
+                let (factor, unit) = throughput_unit(throughput.mean, size);
+                let value = |v: Scalar| format!("{:.1}", v / factor);
+                let mean_str = format!("{} {unit}", value(throughput.mean));
+                match throughput.stddev {
+                    Some(stddev) => println!(
+                        "  Throughput:         {:>10} ± {}    [Range: {} … {}]",
+                        theme.paint(Role::Value, &mean_str).bold(),
+                        theme.paint(Role::Value, value(stddev)),
+                        theme.paint(Role::Min, value(throughput.min)),
+                        theme.paint(Role::Max, format!("{} {unit}", value(throughput.max))),
+                    ),
+                    None => println!(
+                        "  Throughput:         {:>10}",
+                        theme.paint(Role::Value, &mean_str).bold()
+                    ),
+                }
+            }
+
+            if truncated {
+                println!(
+                    "  {}",
+                    format!(
+                        "Stopped after {} runs because of the --max-benchmark-time",
+                        times_real.len()
+                    )
+                    .dimmed()
+                );
+            }
+
+            if !failed_validations.is_empty() {
+                println!(
+                    "  {}",
+                    format!(
+                        "{} of {} runs failed the validation and are excluded",
+                        failed_validations.len(),
+                        times_real.len()
+                    )
+                    .dimmed()
+                );
+            }
+
+            if !excluded_outliers.is_empty() {
+                println!(
+                    "  {}",
+                    format!(
+                        "{} of {} runs excluded as outliers",
+                        excluded_outliers.len(),
+                        times_real.len()
+                    )
+                    .dimmed()
+                );
+            }
+
+            // Show which exit codes the timings are based on if failures are ignored
+            if self.options.command_failure_action == CmdFailureAction::Ignore
+                && (!all_succeeded || timeouts > 0)
+            {
+                let times = if self.options.output_style == OutputStyleOption::Ascii {
+                    "x"
+                } else {
+                    "×"
+                };
+                let counts: Vec<String> = exit_code_counts
+                    .iter()
+                    .map(|(code, count)| {
+                        let code = code.map_or("none".into(), |c| c.to_string());
+                        format!("{} ({count}{times})", theme.paint(Role::Detail, &code))
+                    })
+                    .collect();
+                println!("  Exit codes:         {}", counts.join(", "));
+            }
+
+            if let Some((lower, upper)) = confidence_interval
+                .as_ref()
+                .filter(|_| self.options.show_confidence_interval)
+            {
+                println!(
+                    "  {}:                {:>8}  [{:.0}% CI: {} … {}]",
+                    theme.paint(Role::Value, "Mean"),
+                    theme.paint(Role::Value, &mean_str).bold(),
+                    CONFIDENCE_LEVEL * 100.0,
+                    theme.paint(Role::Value, format_duration(*lower, Some(time_unit))),
+                    theme.paint(Role::Value, format_duration(*upper, Some(time_unit))),
+                );
+            }
+
+            if self.options.show_memory {
+                println!(
+                    "  Peak memory:        {:>8}",
+                    theme.paint(Role::Detail, format_bytes(max_memory_usage_byte))
+                );
+            }
+
+            if let Some(usage) = resource_usage
+                .as_ref()
+                .filter(|_| self.options.show_resource_usage)
+            {
+                let mean_of = |f: fn(&ResourceUsage) -> u64| {
+                    usage.iter().map(|u| f(u) as f64).sum::<f64>() / usage.len() as f64
+                };
+
+                println!(
+                    "  Context switches:   {:>8} voluntary, {} involuntary (mean)",
+                    theme.paint(
+                        Role::Detail,
+                        format!("{:.1}", mean_of(|u| u.voluntary_context_switches))
+                    ),
+                    theme.paint(
+                        Role::Detail,
+                        format!("{:.1}", mean_of(|u| u.involuntary_context_switches))
+                    )
+                );
+                println!(
+                    "  Page faults:        {:>8} major, {} minor (mean)",
+                    theme.paint(
+                        Role::Detail,
+                        format!("{:.1}", mean_of(|u| u.major_page_faults))
+                    ),
+                    theme.paint(
+                        Role::Detail,
+                        format!("{:.1}", mean_of(|u| u.minor_page_faults))
+                    )
+                );
+            }
+
+            if let Some(frequency) = cpu_frequency
+                .as_ref()
+                .filter(|_| self.options.show_cpu_frequency)
+            {
+                println!(
+                    "  CPU frequency:      {:>8}  [Range: {} … {}]",
+                    theme.paint(Role::Detail, format_frequency(frequency.mean)),
+                    theme.paint(Role::Detail, format_frequency(frequency.min)),
+                    theme.paint(Role::Detail, format_frequency(frequency.max))
+                );
+            }
+
+            // In debug mode, explain how the number of runs has been determined
+            if matches!(self.options.executor_kind, ExecutorKind::Mock(_)) {
+                println!(
+                    "{}",
+                    format!(
+                        "  Number of runs:     {count} (minimum benchmarking time: {}, min runs: {}, max runs: {})",
+                        format_duration(self.options.min_benchmarking_time, None),
+                        run_bounds.min,
+                        run_bounds.max.map_or_else(
+                            || format!("{} (default)", run_bounds.effective_max()),
+                            |max| max.to_string()
+                        ),
+                    )
+                    .dimmed()
+                );
+            }
+
+            if self.options.show_histogram && times_real.len() > 1 {
+                histogram::print_histogram(
+                    &times_real,
+                    time_unit,
+                    self.options.output_style,
+                    self.options.theme,
+                );
+            }
         }
 
         // Warnings
         let mut warnings = vec![];
 
-        // Check execution time
-        if matches!(self.options.executor_kind, ExecutorKind::Shell(_))
-            && times_real.iter().any(|&t| t < MIN_EXECUTION_TIME)
-        {
-            warnings.push(Warnings::FastExecutionTime);
+        // Check execution time. Measured times that are shorter than the estimated shell (or
+        // process) spawning time have been set to zero.
+        if matches!(
+            self.options.executor_kind_for(self.command),
+            ExecutorKind::Shell(_)
+        ) {
+            if clamped_runs > 0 {
+                warnings.push(Warnings::ShorterThanShellSpawningTime(clamped_runs));
+            } else if times_real.iter().any(|&t| t < MIN_EXECUTION_TIME) {
+                warnings.push(Warnings::FastExecutionTime);
+            }
+        } else if self.executor.spawn_calibration().is_some() && clamped_runs > 0 {
+            warnings.push(Warnings::ShorterThanSpawningTime(clamped_runs));
         }
 
         // Check program exit codes
         if !all_succeeded {
             warnings.push(Warnings::NonZeroExitCode);
         }
+        if exit_code_counts.len() > 1 {
+            warnings.push(Warnings::MixedExitCodes(exit_code_counts.len()));
+        }
+
+        // Check for runs that have been aborted
+        if timeouts > 0 {
+            warnings.push(Warnings::TimedOut(
+                timeouts,
+                self.options.timeout.unwrap_or_default(),
+            ));
+        }
+
+        // Check for runs that had to be repeated
+        if retries > 0 {
+            warnings.push(Warnings::Retried(retries));
+        }
+
+        // Check for runs without a self-reported time
+        let num_unreported = reported_times.iter().filter(|t| t.is_none()).count();
+        if self.options.time_extraction.is_some() && num_unreported > 0 {
+            warnings.push(Warnings::TimeNotReported(num_unreported));
+        }
+
+        // Check for benchmarks that have been stopped before reaching `--min-runs`
+        if truncated && (times_real.len() as u64) < run_bounds.min {
+            warnings.push(Warnings::FewerThanMinRuns(
+                times_real.len() as u64,
+                run_bounds.min,
+                self.options.max_benchmark_time.unwrap_or_default(),
+            ));
+        }
 
-        // Run outlier detection
-        let scores = modified_zscores(&times_real);
+        // Check the relative spread of the run times
+        if let (Some(cv), Some(threshold)) = (t_cv, self.options.cv_warning_threshold) {
+            if cv > threshold {
+                warnings.push(Warnings::HighVariation(cv, threshold));
+            }
+        }
 
+        // Report the outliers
         let outlier_warning_options = OutlierWarningOptions {
-            warmup_in_use: self.options.warmup_count > 0,
+            warmup_in_use: !warmup_times.is_empty(),
             prepare_in_use: self
                 .options
                 .preparation_command
@@ -380,20 +1085,36 @@ impl<'a> Benchmark<'a> {
                 > 0,
         };
 
-        if scores[0] > OUTLIER_THRESHOLD {
+        // Outliers are only reported if they have not been excluded from the statistics
+        let first_run_slow = validated_runs.first() == Some(&0)
+            && modified_zscores(&validated_times_real)[0] > self.options.outlier_threshold;
+        let first_run_excluded = excluded_outliers.first() == Some(&0);
+        if first_run_slow && !first_run_excluded {
             warnings.push(Warnings::SlowInitialRun(
                 times_real[0],
                 outlier_warning_options,
             ));
-        } else if scores.iter().any(|&s| s.abs() > OUTLIER_THRESHOLD) {
-            warnings.push(Warnings::OutliersDetected(outlier_warning_options));
+        } else if excluded_outliers.is_empty() && !outliers.is_empty() {
+            warnings.push(Warnings::OutliersDetected(
+                outliers.iter().map(|&i| (i + 1, times_real[i])).collect(),
+                outlier_warning_options,
+            ));
+        }
+
+        // Check for a systematic change of the run times
+        if let Some(drift) = linear_drift(&times_real).filter(|drift| drift.is_significant()) {
+            warnings.push(Warnings::Drift(drift.relative_change));
         }
 
         if !warnings.is_empty() {
             eprintln!(" ");
 
             for warning in &warnings {
-                eprintln!("  {}: {}", "Warning".yellow(), warning);
+                eprintln!(
+                    "  {}: {}",
+                    self.options.theme.paint(Role::Warning, "Warning"),
+                    warning
+                );
             }
         }
 
@@ -401,26 +1122,223 @@ impl<'a> Benchmark<'a> {
             println!(" ");
         }
 
-        self.run_cleanup_command(self.command.get_parameters().iter().cloned())?;
+        self.run_cleanup_command()?;
+
+        let resource_usage_values = |f: fn(&ResourceUsage) -> u64| {
+            resource_usage
+                .as_ref()
+                .map(|usage| usage.iter().map(f).collect())
+        };
 
         Ok(BenchmarkResult {
-            command: self.command.get_name(),
+            command: self.command.get_command_line(),
+            name: self.command.get_name(),
             command_with_unused_parameters: self.command.get_name_with_unused_parameters(),
+            command_template: self.command.get_template(),
             mean: t_mean,
             stddev: t_stddev,
             median: t_median,
+            mad: t_mad,
+            cv: t_cv,
             user: user_mean,
             system: system_mean,
             min: t_min,
             max: t_max,
+            runs: times_real.len(),
             times: Some(times_real),
+            times_subsampled: false,
+            user_times: Some(times_user),
+            system_times: Some(times_system),
+            reported_times: self
+                .options
+                .time_extraction
+                .is_some()
+                .then_some(reported_times),
+            run_timestamps: Some(run_timestamps),
+            memory_usage_byte: Some(memory_usage_byte),
+            max_memory_usage_byte: Some(max_memory_usage_byte),
+            voluntary_context_switches: resource_usage_values(|u| u.voluntary_context_switches),
+            involuntary_context_switches: resource_usage_values(|u| u.involuntary_context_switches),
+            major_page_faults: resource_usage_values(|u| u.major_page_faults),
+            minor_page_faults: resource_usage_values(|u| u.minor_page_faults),
             exit_codes,
+            timeouts,
+            retries,
+            clamped_runs,
+            warmup_runs: warmup_times.len(),
+            validation_failures: failed_validations.len(),
+            failed_validations,
+            outliers,
+            outliers_excluded: self.options.trim_outliers,
+            truncated,
+            iteration: None,
+            throughput,
+            cpu_frequency,
+            shell_spawning_time: self.executor.shell_spawning_time(),
+            spawn_calibration: self.executor.spawn_calibration(),
+            executed_argv: self.executor.executed_argv(self.command)?,
+            working_dir: self.command.get_working_dir(),
             parameters: self
                 .command
                 .get_parameters()
                 .iter()
                 .map(|(name, value)| (name.to_string(), value.to_string()))
                 .collect(),
+            environment: self.options.environment.iter().cloned().collect(),
         })
     }
 }
+
+/// State of a benchmark whose timing runs have started
+struct MeasurementState<'a> {
+    progress: ProgressReporter,
+    preparation_command: Option<Command<'a>>,
+    conclusion_command: Option<Command<'a>>,
+
+    /// The `--validate` command, which still contains the `{run}` placeholder
+    validation_command: Option<&'a str>,
+    measurements: Measurements,
+
+    /// Wall clock times of the warmup runs that have been performed so far
+    warmup_times: Vec<Second>,
+
+    /// Total number of timing runs, which is determined after the initial run
+    count: u64,
+
+    /// Time that the timing runs have taken so far, including the preparation and conclusion
+    /// commands (see `--max-benchmark-time`)
+    measured_time: Second,
+
+    /// Whether the number of runs has been reduced because of the `--max-benchmark-time`
+    truncated: bool,
+}
+
+impl MeasurementState<'_> {
+    fn remaining_runs(&self) -> usize {
+        (self.count as usize).saturating_sub(self.measurements.times_real.len())
+    }
+
+    fn is_complete(&self) -> bool {
+        self.remaining_runs() == 0
+    }
+}
+
+/// The measurements of all timing runs of a benchmark
+struct Measurements {
+    times_real: Vec<Second>,
+    times_user: Vec<Second>,
+    times_system: Vec<Second>,
+    memory_usage_byte: Vec<u64>,
+    run_timestamps: Vec<f64>,
+    resource_usage: Option<Vec<ResourceUsage>>,
+
+    /// Samples of the CPU frequency before and after every run
+    cpu_frequency: Option<Vec<MegaHertz>>,
+    exit_codes: Vec<Option<i32>>,
+    all_succeeded: bool,
+    timeouts: usize,
+    retries: usize,
+
+    /// Indices of the runs for which the `--validate` command failed
+    failed_validations: Vec<usize>,
+
+    /// Times that the command has reported itself (`--extract-time`)
+    reported_times: Vec<Option<Second>>,
+
+    /// Whether the wall clock time of each run has been set to zero by the subtraction of the
+    /// spawning time
+    clamped: Vec<bool>,
+}
+
+impl Default for Measurements {
+    fn default() -> Self {
+        Measurements {
+            times_real: vec![],
+            times_user: vec![],
+            times_system: vec![],
+            memory_usage_byte: vec![],
+            run_timestamps: vec![],
+            resource_usage: Some(vec![]),
+            cpu_frequency: Some(vec![]),
+            exit_codes: vec![],
+            all_succeeded: true,
+            timeouts: 0,
+            retries: 0,
+            failed_validations: vec![],
+            reported_times: vec![],
+            clamped: vec![],
+        }
+    }
+}
+
+impl Measurements {
+    fn push(&mut self, res: &TimingResult, status: ExitStatus) {
+        self.times_real.push(res.time_real);
+        self.times_user.push(res.time_user);
+        self.times_system.push(res.time_system);
+        self.memory_usage_byte.push(res.memory_usage_byte);
+        self.run_timestamps.push(res.start_timestamp);
+        self.reported_times.push(res.reported_time);
+        self.clamped.push(res.clamped);
+        self.resource_usage = push_resource_usage(self.resource_usage.take(), res.resource_usage);
+        self.cpu_frequency =
+            self.cpu_frequency
+                .take()
+                .zip(res.cpu_frequency)
+                .map(|(mut samples, run)| {
+                    samples.extend(run);
+                    samples
+                });
+        if res.timed_out {
+            self.exit_codes.push(None);
+            self.timeouts += 1;
+        } else {
+            self.exit_codes.push(extract_exit_code(status));
+            self.all_succeeded = self.all_succeeded && status.success();
+        }
+    }
+}
+
+/// Add the resource usage of a single run to the list of all runs. The statistics are only
+/// reported if they are available for every run.
+fn push_resource_usage(
+    all_runs: Option<Vec<ResourceUsage>>,
+    run: Option<ResourceUsage>,
+) -> Option<Vec<ResourceUsage>> {
+    all_runs.zip(run).map(|(mut all_runs, run)| {
+        all_runs.push(run);
+        all_runs
+    })
+}
+
+/// Mean of the last few warmup run times
+fn moving_average(times: &[Second]) -> Second {
+    mean(&times[times.len().saturating_sub(WARMUP_AUTO_WINDOW)..])
+}
+
+/// Whether the moving average of the warmup run times has changed by less than the threshold
+/// with the last run (`--warmup auto`)
+fn warmup_has_converged(times: &[Second]) -> bool {
+    if times.len() <= WARMUP_AUTO_WINDOW {
+        return false;
+    }
+    let previous = moving_average(&times[..times.len() - 1]);
+    let current = moving_average(times);
+    (current - previous).abs() <= WARMUP_AUTO_THRESHOLD * previous
+}
+
+#[test]
+fn test_warmup_has_converged() {
+    assert!(!warmup_has_converged(&[]));
+    assert!(!warmup_has_converged(&[1.0, 1.0, 1.0]));
+    assert!(warmup_has_converged(&[1.0, 1.0, 1.0, 1.0]));
+
+    // A JIT compiler that speeds up the command over the first runs
+    let times = [3.0, 2.0, 1.5, 1.2, 1.0, 1.0, 1.0, 1.01, 0.99, 1.0];
+    let converged_after = (1..=times.len())
+        .find(|&n| warmup_has_converged(&times[..n]))
+        .unwrap();
+    assert_eq!(converged_after, 8);
+
+    assert!(!warmup_has_converged(&[1.0, 1.0, 1.0, 1.5]));
+}
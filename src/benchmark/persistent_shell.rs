@@ -0,0 +1,244 @@
+//! Run the benchmarked commands in a single long-lived shell (`--shell-mode=persistent`), instead
+//! of spawning a new shell for every run. The shell reads the commands from its stdin and runs
+//! each of them in a subshell, such that they can not change its state. Only the time between
+//! sending a command and receiving the marker that the shell prints after it is measured, so the
+//! startup time of the shell is not included (but the time to fork the subshell is, which is
+//! estimated by the calibration runs). The user and system times are the differences between the
+//! outputs of the `times` builtin before and after the command.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, ExitStatus, Stdio};
+use std::time::Instant;
+
+use anyhow::{bail, Context, Result};
+
+use crate::command::Command;
+use crate::options::{CommandInputPolicy, CommandOutputPolicy, Options, Shell};
+use crate::timer::{priority, unix_timestamp, TimerResult};
+use crate::util::units::Second;
+
+/// Shells that can read the commands from stdin and support subshells and the `times` builtin
+const POSIX_SHELLS: &[&str] = &["sh", "bash", "dash", "ash", "ksh", "mksh", "zsh", "busybox"];
+
+/// Printed by the shell after every command (followed by the exit code of the command)
+const MARKER: &str = "__HYPERFINE_PERSISTENT_SHELL__";
+
+/// The reason why the persistent shell mode can not be used with the given shell and options
+/// (in which case a new shell is spawned for every run)
+pub fn unsupported_reason(shell: &Shell, options: &Options) -> Option<&'static str> {
+    let program = match shell {
+        Shell::Default(program) => program.to_string(),
+        Shell::Custom(cmdline) => cmdline[0].clone(),
+    };
+    let program = program.rsplit('/').next().unwrap_or_default();
+    if !cfg!(unix) {
+        Some("it is only available on Unix")
+    } else if !POSIX_SHELLS.contains(&program) {
+        Some("it requires a POSIX shell like 'sh' or 'bash'")
+    } else if options.command_output_policy != CommandOutputPolicy::Null {
+        Some("the output of the commands can not be shown, captured or written to a file")
+    } else if options.command_input_policy != CommandInputPolicy::Null {
+        Some("it can not be combined with '--input'")
+    } else if options.timeout.is_some() {
+        Some("it can not be combined with '--timeout'")
+    } else {
+        None
+    }
+}
+
+pub struct PersistentShell {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl PersistentShell {
+    /// Start the shell. The CPU affinity and the scheduling priority are inherited by the
+    /// benchmarked commands.
+    pub fn start(shell: &Shell, options: &Options) -> Result<PersistentShell> {
+        let mut command = shell.command();
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .envs(
+                options
+                    .environment
+                    .iter()
+                    .map(|(name, value)| (name, value)),
+            );
+
+        #[cfg(unix)]
+        priority::apply_to_command(&mut command, priority::effective(options.priority));
+
+        #[cfg(target_os = "linux")]
+        if let Some(cpus) = options.cpu_affinity.as_deref() {
+            crate::timer::cpu_affinity::restrict_command(&mut command, cpus)?;
+        }
+
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Failed to start the persistent shell '{shell}'"))?;
+        let stdin = child.stdin.take();
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(PersistentShell {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Run the given command in a subshell and measure it
+    pub fn run(
+        &mut self,
+        command: &Command<'_>,
+        environment_offset: Option<String>,
+    ) -> Result<TimerResult> {
+        let cpu_times_before = self.children_cpu_times()?;
+
+        let mut script = String::from("(\n");
+        if let Some(environment_offset) = environment_offset {
+            script.push_str(&format!(
+                "export HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET={environment_offset}\n"
+            ));
+        }
+        if let Some(working_dir) = command.get_working_dir() {
+            script.push_str(&format!(
+                "cd -- {} || exit\n",
+                shell_words::quote(&working_dir)
+            ));
+        }
+        // The command is quoted and passed to `eval`, such that incomplete input (like an
+        // unterminated quote) fails instead of consuming the rest of the script
+        script.push_str(&format!(
+            "eval {}",
+            shell_words::quote(&command.get_command_line())
+        ));
+        script.push_str(&format!(
+            "\n) </dev/null >/dev/null 2>&1; echo \"{MARKER} $?\"\n"
+        ));
+
+        let start_timestamp = unix_timestamp();
+        let start = Instant::now();
+        self.send(&script)?;
+        let (_, exit_code) = self.read_until_marker()?;
+        let time_real = start.elapsed().as_secs_f64();
+
+        let cpu_times_after = self.children_cpu_times()?;
+
+        Ok(TimerResult {
+            time_real,
+            time_user: (cpu_times_after.0 - cpu_times_before.0).max(0.0),
+            time_system: (cpu_times_after.1 - cpu_times_before.1).max(0.0),
+            memory_usage_byte: 0,
+            timed_out: false,
+            start_timestamp,
+            resource_usage: None,
+            cpu_frequency: None,
+            status: exit_status(exit_code),
+            output: None,
+        })
+    }
+
+    /// The user and system time of all commands that the shell has run so far
+    fn children_cpu_times(&mut self) -> Result<(Second, Second)> {
+        self.send(&format!("times; echo \"{MARKER} 0\"\n"))?;
+        let (output, _) = self.read_until_marker()?;
+        match output.lines().last().and_then(parse_times) {
+            Some(times) => Ok(times),
+            None => {
+                bail!("Could not read the output of the 'times' builtin of the persistent shell")
+            }
+        }
+    }
+
+    fn send(&mut self, script: &str) -> Result<()> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .expect("stdin is open until the shell is dropped");
+        stdin
+            .write_all(script.as_bytes())
+            .and_then(|()| stdin.flush())
+            .context("The persistent shell has exited unexpectedly")
+    }
+
+    /// Read the output of the shell up to the next marker, and return it together with the exit
+    /// code that follows the marker
+    fn read_until_marker(&mut self) -> Result<(String, i32)> {
+        let mut output = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                bail!(
+                    "The persistent shell has exited unexpectedly. Make sure that the command is \
+                     valid shell syntax, or use '--shell-mode=spawn'."
+                );
+            }
+            if let Some(exit_code) = line.trim_end().strip_prefix(MARKER) {
+                return Ok((output, exit_code.trim().parse().unwrap_or(1)));
+            }
+            output.push_str(&line);
+        }
+    }
+}
+
+impl Drop for PersistentShell {
+    fn drop(&mut self) {
+        // The shell exits once its stdin is closed
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(exit_code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw((exit_code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(exit_code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(exit_code as u32)
+}
+
+/// Parse a line of the output of the `times` builtin, like "0m0.012s 0m0.004s", into the user and
+/// system time
+fn parse_times(line: &str) -> Option<(Second, Second)> {
+    let parse = |time: &str| -> Option<Second> {
+        let (minutes, seconds) = time.strip_suffix('s')?.split_once('m')?;
+        Some(minutes.parse::<Second>().ok()? * 60.0 + seconds.parse::<Second>().ok()?)
+    };
+    let mut parts = line.split_whitespace();
+    let user = parse(parts.next()?)?;
+    let system = parse(parts.next()?)?;
+    Some((user, system))
+}
+
+#[test]
+fn test_parse_times() {
+    assert_eq!(parse_times("0m0.012s 0m0.004s"), Some((0.012, 0.004)));
+    assert_eq!(parse_times("1m2.500000s 0m0.000000s"), Some((62.5, 0.0)));
+    assert_eq!(parse_times("0m0.00s 0m0.00s\n"), Some((0.0, 0.0)));
+    assert_eq!(parse_times("user 0.01"), None);
+    assert_eq!(parse_times(""), None);
+}
+
+#[test]
+fn test_unsupported_reason() {
+    let options = Options::default();
+    let shell = |cmdline: &[&str]| Shell::Custom(cmdline.iter().map(|s| s.to_string()).collect());
+
+    assert_eq!(
+        unsupported_reason(&shell(&["/usr/bin/bash", "--norc"]), &options).is_none(),
+        cfg!(unix)
+    );
+    assert!(unsupported_reason(&shell(&["pwsh"]), &options).is_some());
+
+    let options = Options {
+        command_output_policy: CommandOutputPolicy::Inherit,
+        ..Default::default()
+    };
+    assert!(unsupported_reason(&shell(&["bash"]), &options).is_some());
+}
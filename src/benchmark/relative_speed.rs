@@ -1,26 +1,74 @@
 use std::cmp::Ordering;
 
 use super::benchmark_result::BenchmarkResult;
-use crate::{options::SortOrder, util::units::Scalar};
+use crate::{
+    options::SortOrder,
+    util::units::{Scalar, Second},
+};
 
 #[derive(Debug)]
 pub struct BenchmarkResultWithRelativeSpeed<'a> {
     pub result: &'a BenchmarkResult,
     pub relative_speed: Scalar,
     pub relative_speed_stddev: Option<Scalar>,
+    /// Difference between the compared time of this result and the one of the reference
+    /// (positive if this result is slower than the reference)
+    pub time_difference: Second,
     pub is_reference: bool,
     // Less means faster
     pub relative_ordering: Ordering,
 }
 
-pub fn compare_mean_time(l: &BenchmarkResult, r: &BenchmarkResult) -> Ordering {
-    l.mean.partial_cmp(&r.mean).unwrap_or(Ordering::Equal)
+impl BenchmarkResultWithRelativeSpeed<'_> {
+    /// The run time as a multiple of the run time of the reference, along with its
+    /// uncertainty. In contrast to `relative_speed`, this is less than one for results that are
+    /// faster than the reference.
+    pub fn relative_time(&self) -> (Scalar, Option<Scalar>) {
+        match self.relative_ordering {
+            Ordering::Less => (
+                1.0 / self.relative_speed,
+                self.relative_speed_stddev
+                    .map(|stddev| stddev / self.relative_speed.powi(2)),
+            ),
+            _ => (self.relative_speed, self.relative_speed_stddev),
+        }
+    }
+}
+
+/// The time by which results are compared for the given sort order. This is the mean run time,
+/// unless the results are sorted by a different statistic.
+pub fn compared_time(result: &BenchmarkResult, sort_order: SortOrder) -> Second {
+    match sort_order {
+        SortOrder::Command | SortOrder::MeanTime => result.mean,
+        SortOrder::MedianTime => result.median,
+        SortOrder::MaxTime => result.max,
+        SortOrder::UserTime => result.user,
+    }
+}
+
+pub fn compare_time(l: &BenchmarkResult, r: &BenchmarkResult, sort_order: SortOrder) -> Ordering {
+    compared_time(l, sort_order)
+        .partial_cmp(&compared_time(r, sort_order))
+        .unwrap_or(Ordering::Equal)
 }
 
-pub fn fastest_of(results: &[BenchmarkResult]) -> &BenchmarkResult {
+/// Like `compare_time`, but results with equal times are ordered by their name. Sorting with
+/// this (stable) comparison keeps the original order of results that are equal in both, such
+/// that ties do not depend on the order of the measurements.
+fn compare_time_and_name(
+    l: &BenchmarkResult,
+    r: &BenchmarkResult,
+    sort_order: SortOrder,
+) -> Ordering {
+    compare_time(l, r, sort_order).then_with(|| l.name.cmp(&r.name))
+}
+
+/// The fastest result. Of several results with the same time, this is the one whose name comes
+/// first, and then the first one in `results`.
+pub fn fastest_of(results: &[BenchmarkResult], sort_order: SortOrder) -> &BenchmarkResult {
     results
         .iter()
-        .min_by(|&l, &r| compare_mean_time(l, r))
+        .min_by(|&l, &r| compare_time_and_name(l, r, sort_order))
         .expect("at least one benchmark result")
 }
 
@@ -29,32 +77,43 @@ fn compute_relative_speeds<'a>(
     reference: &'a BenchmarkResult,
     sort_order: SortOrder,
 ) -> Vec<BenchmarkResultWithRelativeSpeed<'a>> {
+    let reference_time = compared_time(reference, sort_order);
+
     let mut results: Vec<_> = results
         .iter()
         .map(|result| {
-            let is_reference = result == reference;
-            let relative_ordering = compare_mean_time(result, reference);
+            // Results with identical statistics would compare as equal
+            let is_reference = std::ptr::eq(result, reference);
+            let relative_ordering = compare_time(result, reference, sort_order);
+            let time = compared_time(result, sort_order);
+            let time_difference = time - reference_time;
 
-            if result.mean == 0.0 {
+            if time == 0.0 {
                 return BenchmarkResultWithRelativeSpeed {
                     result,
                     relative_speed: if is_reference { 1.0 } else { f64::INFINITY },
                     relative_speed_stddev: None,
+                    time_difference,
                     is_reference,
                     relative_ordering,
                 };
             }
 
             let ratio = match relative_ordering {
-                Ordering::Less => reference.mean / result.mean,
+                Ordering::Less => reference_time / time,
                 Ordering::Equal => 1.0,
-                Ordering::Greater => result.mean / reference.mean,
+                Ordering::Greater => time / reference_time,
             };
 
             // https://en.wikipedia.org/wiki/Propagation_of_uncertainty#Example_formulas
-            // Covariance asssumed to be 0, i.e. variables are assumed to be independent
+            // Covariance asssumed to be 0, i.e. variables are assumed to be independent.
+            // The uncertainty is only known for ratios of mean times.
+            let compares_mean_times =
+                matches!(sort_order, SortOrder::Command | SortOrder::MeanTime);
+            // Ties are shown as a ratio of exactly one, without an uncertainty
             let ratio_stddev = match (result.stddev, reference.stddev) {
-                (Some(result_stddev), Some(fastest_stddev)) => Some(
+                _ if relative_ordering == Ordering::Equal => None,
+                (Some(result_stddev), Some(fastest_stddev)) if compares_mean_times => Some(
                     ratio
                         * ((result_stddev / result.mean).powi(2)
                             + (fastest_stddev / reference.mean).powi(2))
@@ -67,17 +126,15 @@ fn compute_relative_speeds<'a>(
                 result,
                 relative_speed: ratio,
                 relative_speed_stddev: ratio_stddev,
+                time_difference,
                 is_reference,
                 relative_ordering,
             }
         })
         .collect();
 
-    match sort_order {
-        SortOrder::Command => {}
-        SortOrder::MeanTime => {
-            results.sort_unstable_by(|r1, r2| compare_mean_time(r1.result, r2.result));
-        }
+    if sort_order != SortOrder::Command {
+        results.sort_by(|r1, r2| compare_time_and_name(r1.result, r2.result, sort_order));
     }
 
     results
@@ -88,53 +145,83 @@ pub fn compute_with_check_from_reference<'a>(
     reference: &'a BenchmarkResult,
     sort_order: SortOrder,
 ) -> Option<Vec<BenchmarkResultWithRelativeSpeed<'a>>> {
-    if fastest_of(results).mean == 0.0 || reference.mean == 0.0 {
+    if compared_time(fastest_of(results, sort_order), sort_order) == 0.0
+        || compared_time(reference, sort_order) == 0.0
+    {
         return None;
     }
 
     Some(compute_relative_speeds(results, reference, sort_order))
 }
 
-pub fn compute_with_check(
-    results: &[BenchmarkResult],
-    sort_order: SortOrder,
-) -> Option<Vec<BenchmarkResultWithRelativeSpeed>> {
-    let fastest = fastest_of(results);
-
-    if fastest.mean == 0.0 {
+/// Geometric mean of the run times of all results relative to the reference (which itself is not
+/// included). Values below one mean that the other commands are faster than the reference on
+/// average. Returns `None` if there is nothing to compare to.
+pub fn geometric_mean_relative_time(
+    annotated_results: &[BenchmarkResultWithRelativeSpeed],
+) -> Option<Scalar> {
+    let log_relative_times: Vec<Scalar> = annotated_results
+        .iter()
+        .filter(|r| !r.is_reference)
+        .map(|r| r.relative_time().0.ln())
+        .collect();
+    if log_relative_times.is_empty() {
         return None;
     }
 
-    Some(compute_relative_speeds(results, fastest, sort_order))
+    Some((log_relative_times.iter().sum::<Scalar>() / log_relative_times.len() as Scalar).exp())
 }
 
-/// Same as compute_with_check, potentially resulting in relative speeds of infinity
+/// Same as compute_with_check_from_reference, potentially resulting in relative speeds of
+/// infinity. The reference is given as an index into `results`. If it is unset (or not yet
+/// available), the fastest result is used as the reference.
 pub fn compute(
     results: &[BenchmarkResult],
+    reference: Option<usize>,
     sort_order: SortOrder,
-) -> Vec<BenchmarkResultWithRelativeSpeed> {
-    let fastest = fastest_of(results);
+) -> Vec<BenchmarkResultWithRelativeSpeed<'_>> {
+    compute_relative_speeds(
+        results,
+        reference_of(results, reference, sort_order),
+        sort_order,
+    )
+}
 
-    compute_relative_speeds(results, fastest, sort_order)
+/// The result at the given reference index, or the fastest result if the reference is unset
+/// (or not yet available)
+pub fn reference_of(
+    results: &[BenchmarkResult],
+    reference: Option<usize>,
+    sort_order: SortOrder,
+) -> &BenchmarkResult {
+    reference
+        .and_then(|index| results.get(index))
+        .unwrap_or_else(|| fastest_of(results, sort_order))
 }
 
+/// Relative speeds with respect to the fastest result
 #[cfg(test)]
-fn create_result(name: &str, mean: Scalar) -> BenchmarkResult {
-    use std::collections::BTreeMap;
+fn compute_with_check(
+    results: &[BenchmarkResult],
+    sort_order: SortOrder,
+) -> Option<Vec<BenchmarkResultWithRelativeSpeed<'_>>> {
+    compute_with_check_from_reference(results, fastest_of(results, sort_order), sort_order)
+}
 
+#[cfg(test)]
+fn create_result(name: &str, mean: Scalar) -> BenchmarkResult {
     BenchmarkResult {
         command: name.into(),
+        name: name.into(),
         command_with_unused_parameters: name.into(),
+        command_template: name.into(),
         mean,
         stddev: Some(1.0),
         median: mean,
         user: mean,
-        system: 0.0,
         min: mean,
         max: mean,
-        times: None,
-        exit_codes: Vec::new(),
-        parameters: BTreeMap::new(),
+        ..Default::default()
     }
 }
 
@@ -169,6 +256,93 @@ fn test_compute_relative_speed_with_reference() {
     assert_relative_eq!(1.25, annotated_results[1].relative_speed);
 }
 
+#[test]
+fn test_compute_relative_time_with_reference_index() {
+    use approx::assert_relative_eq;
+
+    let results = vec![
+        create_result("cmd1", 4.0),
+        create_result("cmd2", 2.0),
+        create_result("cmd3", 5.0),
+    ];
+
+    let annotated_results = compute(&results, Some(0), SortOrder::Command);
+
+    assert!(annotated_results[0].is_reference);
+    assert_relative_eq!(1.0, annotated_results[0].relative_time().0);
+    assert_relative_eq!(0.5, annotated_results[1].relative_time().0);
+    assert_relative_eq!(1.25, annotated_results[2].relative_time().0);
+    assert_relative_eq!(0.0, annotated_results[0].time_difference);
+    assert_relative_eq!(-2.0, annotated_results[1].time_difference);
+    assert_relative_eq!(1.0, annotated_results[2].time_difference);
+
+    // Without a reference, the fastest command is used
+    let annotated_results = compute(&results, None, SortOrder::Command);
+    assert!(annotated_results[1].is_reference);
+    assert_relative_eq!(2.0, annotated_results[0].relative_time().0);
+}
+
+#[test]
+fn test_compute_relative_speed_by_other_statistics() {
+    use approx::assert_relative_eq;
+
+    let results = vec![
+        BenchmarkResult {
+            median: 1.0,
+            max: 8.0,
+            ..create_result("cmd1", 3.0)
+        },
+        BenchmarkResult {
+            median: 4.0,
+            max: 2.0,
+            ..create_result("cmd2", 2.0)
+        },
+    ];
+
+    let annotated_results = compute_with_check(&results, SortOrder::MedianTime).unwrap();
+    assert_eq!(annotated_results[0].result.name, "cmd1");
+    assert!(annotated_results[0].is_reference);
+    assert_relative_eq!(4.0, annotated_results[1].relative_speed);
+    assert_eq!(None, annotated_results[1].relative_speed_stddev);
+
+    let annotated_results = compute_with_check(&results, SortOrder::MaxTime).unwrap();
+    assert_eq!(annotated_results[0].result.name, "cmd2");
+    assert_relative_eq!(4.0, annotated_results[1].relative_speed);
+
+    // The mean time is used when sorting by command
+    let annotated_results = compute_with_check(&results, SortOrder::Command).unwrap();
+    assert_eq!(annotated_results[0].result.name, "cmd1");
+    assert_relative_eq!(1.5, annotated_results[0].relative_speed);
+    assert!(annotated_results[1].is_reference);
+}
+
+#[test]
+fn test_geometric_mean_relative_time() {
+    use approx::assert_relative_eq;
+
+    let results = vec![
+        create_result("cmd1", 2.0),
+        create_result("cmd2", 1.0),
+        create_result("cmd3", 8.0),
+    ];
+
+    let annotated_results = compute_with_check(&results, SortOrder::Command).unwrap();
+    assert_relative_eq!(
+        4.0,
+        geometric_mean_relative_time(&annotated_results).unwrap()
+    );
+
+    // Faster commands contribute relative times below one
+    let annotated_results = compute(&results, Some(0), SortOrder::Command);
+    assert_relative_eq!(
+        (0.5_f64 * 4.0).sqrt(),
+        geometric_mean_relative_time(&annotated_results).unwrap()
+    );
+
+    let annotated_results = compute_with_check(&results[..1], SortOrder::Command).unwrap();
+    assert_eq!(None, geometric_mean_relative_time(&annotated_results));
+}
+
 #[test]
 fn test_compute_relative_speed_for_zero_times() {
     let results = vec![create_result("cmd1", 1.0), create_result("cmd2", 0.0)];
@@ -177,3 +351,56 @@ fn test_compute_relative_speed_for_zero_times() {
 
     assert!(annotated_results.is_none());
 }
+
+#[test]
+fn test_compute_relative_speed_for_equal_times() {
+    let names = |annotated_results: &[BenchmarkResultWithRelativeSpeed]| -> Vec<(String, bool)> {
+        annotated_results
+            .iter()
+            .map(|r| (r.result.name.clone(), r.is_reference))
+            .collect::<Vec<_>>()
+    };
+    let results = vec![
+        create_result("cmd3", 2.0),
+        create_result("cmd2", 1.0),
+        create_result("cmd1", 1.0),
+        create_result("cmd1", 1.0),
+    ];
+
+    // The fastest result is the one whose name comes first, then the first one in the list
+    assert!(std::ptr::eq(
+        fastest_of(&results, SortOrder::MeanTime),
+        &results[2]
+    ));
+
+    let annotated_results = compute_with_check(&results, SortOrder::Command).unwrap();
+    assert_eq!(
+        names(&annotated_results),
+        [
+            ("cmd3".to_string(), false),
+            ("cmd2".to_string(), false),
+            ("cmd1".to_string(), true),
+            ("cmd1".to_string(), false)
+        ]
+    );
+
+    let annotated_results = compute_with_check(&results, SortOrder::MeanTime).unwrap();
+    assert_eq!(
+        names(&annotated_results),
+        [
+            ("cmd1".to_string(), true),
+            ("cmd1".to_string(), false),
+            ("cmd2".to_string(), false),
+            ("cmd3".to_string(), false)
+        ]
+    );
+    assert!(std::ptr::eq(annotated_results[1].result, &results[3]));
+
+    // Ties are exactly as fast as the reference
+    for tie in &annotated_results[1..3] {
+        assert_eq!(tie.relative_ordering, Ordering::Equal);
+        assert_eq!(tie.relative_speed, 1.0);
+        assert_eq!(tie.relative_speed_stddev, None);
+    }
+    assert!(annotated_results[3].relative_speed_stddev.is_some());
+}
@@ -0,0 +1,145 @@
+//! Continue an earlier invocation that has been aborted, using the results of its JSON export
+//! (`--resume-from`). Benchmarks that already have a result are not performed again.
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use super::benchmark_result::BenchmarkResult;
+use crate::command::Command;
+use crate::compare::JsonExport;
+use crate::error::OptionsError;
+
+pub struct PreviousResults {
+    /// The JSON export from which the results have been read
+    pub path: String,
+
+    results: Vec<BenchmarkResult>,
+
+    /// Benchmarks whose name or command line matches this pattern are performed again, even
+    /// if there is a result (`--force-rerun`)
+    force_rerun: Option<Regex>,
+}
+
+impl PreviousResults {
+    /// Read the results from the given JSON export
+    pub fn load(
+        path: &str,
+        force_rerun: Option<&str>,
+    ) -> Result<PreviousResults, OptionsError<'static>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| OptionsError::ResumeFileReadError(path.to_string(), e.to_string()))?;
+        let export: JsonExport = serde_json::from_str(&content)
+            .map_err(|e| OptionsError::ResumeFileReadError(path.to_string(), e.to_string()))?;
+        PreviousResults::new(path, export.results, force_rerun)
+    }
+
+    fn new(
+        path: &str,
+        results: Vec<BenchmarkResult>,
+        force_rerun: Option<&str>,
+    ) -> Result<PreviousResults, OptionsError<'static>> {
+        let force_rerun = force_rerun
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    OptionsError::InvalidForceRerunPattern(pattern.to_string(), e.to_string())
+                })
+            })
+            .transpose()?;
+        Ok(PreviousResults {
+            path: path.to_string(),
+            results,
+            force_rerun,
+        })
+    }
+
+    /// The earlier result of the given command (with the same command line and parameter
+    /// values), unless it is to be performed again
+    pub fn result_for(&self, cmd: &Command) -> Option<BenchmarkResult> {
+        let command_line = cmd.get_command_line();
+        let name = cmd.get_name();
+        if self
+            .force_rerun
+            .as_ref()
+            .is_some_and(|pattern| pattern.is_match(&name) || pattern.is_match(&command_line))
+        {
+            return None;
+        }
+
+        let parameters: BTreeMap<String, String> = cmd
+            .get_parameters()
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let result = self
+            .results
+            .iter()
+            .find(|result| result.command == command_line && result.parameters == parameters)?;
+
+        // These are not part of the export
+        Some(BenchmarkResult {
+            command_with_unused_parameters: cmd.get_name_with_unused_parameters(),
+            command_template: cmd.get_template(),
+            ..result.clone()
+        })
+    }
+}
+
+#[test]
+fn test_result_for() {
+    use crate::parameter::ParameterValue;
+
+    let result = |command: &str, parameters: &[(&str, &str)]| BenchmarkResult {
+        command: command.into(),
+        name: command.into(),
+        mean: 1.0,
+        parameters: parameters
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    let results = vec![
+        result("sleep 1", &[]),
+        result("make -j 2", &[("threads", "2")]),
+    ];
+    let threads = |value: &str| vec![("threads", ParameterValue::Text(value.into()))];
+
+    let previous = PreviousResults::new("results.json", results.clone(), None).unwrap();
+    let found = previous.result_for(&Command::new(None, "sleep 1")).unwrap();
+    assert_eq!(found.mean, 1.0);
+    assert_eq!(found.command_with_unused_parameters, "sleep 1");
+    assert!(previous
+        .result_for(&Command::new(None, "sleep 2"))
+        .is_none());
+
+    let found = previous
+        .result_for(&Command::new_parametrized(
+            None,
+            "make -j {threads}",
+            threads("2"),
+        ))
+        .unwrap();
+    assert_eq!(found.command_template, "make -j {threads}");
+    assert!(previous
+        .result_for(&Command::new_parametrized(
+            None,
+            "make -j {threads}",
+            threads("4")
+        ))
+        .is_none());
+
+    let previous = PreviousResults::new("results.json", results, Some("^make")).unwrap();
+    assert!(previous
+        .result_for(&Command::new(None, "sleep 1"))
+        .is_some());
+    assert!(previous
+        .result_for(&Command::new_parametrized(
+            None,
+            "make -j {threads}",
+            threads("2")
+        ))
+        .is_none());
+
+    assert!(PreviousResults::new("results.json", vec![], Some("(")).is_err());
+}
@@ -1,20 +1,49 @@
-use super::benchmark_result::BenchmarkResult;
+use super::benchmark_result::{BenchmarkResult, SkippedBenchmark};
 use super::executor::{Executor, MockExecutor, RawExecutor, ShellExecutor};
-use super::{relative_speed, Benchmark};
+use super::persistent_shell;
+use super::significance::{self, SIGNIFICANCE_LEVEL};
+use super::{relative_speed, Benchmark, MeasurementState};
 use colored::*;
-use std::cmp::Ordering;
+use rand::seq::SliceRandom;
+use std::cmp::{self, Ordering};
+use std::time::Instant;
 
 use crate::command::{Command, Commands};
 use crate::export::ExportManager;
-use crate::options::{ExecutorKind, Options, OutputStyleOption, SortOrder};
+use crate::options::{
+    ExecutorKind, Options, OutputStyleOption, ShellMode, SortOrder, SummaryFormat,
+};
+use crate::output::format::format_duration;
+use crate::output::progress_bar::get_progress_bar;
+use crate::output::theme::Role;
+use crate::output::warnings::Warnings;
+use crate::timer::{cpu_affinity, priority};
+use crate::util::interrupt;
+use crate::util::units::Second;
+use crate::util::{cpu_settings, system_load};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+
+/// Show a warning if more than this number of benchmarks (e.g. combinations of parameter values)
+/// would be performed
+const MAX_BENCHMARKS_WITHOUT_WARNING: usize = 1000;
 
 pub struct Scheduler<'a> {
     commands: &'a Commands<'a>,
     options: &'a Options,
     export_manager: &'a ExportManager,
     results: Vec<BenchmarkResult>,
+    skipped: Vec<SkippedBenchmark>,
+
+    /// Descriptions of all assertions that do not hold, together with the benchmark name
+    failed_assertions: Vec<(String, String)>,
+
+    /// Iteration of the benchmark suite that is currently performed (only with `--repeat-suite`)
+    iteration: Option<usize>,
+
+    /// Results of an earlier invocation (`--resume-from`) for the benchmark with the same
+    /// number, which are used instead of performing it again
+    resumed: Vec<Option<BenchmarkResult>>,
 }
 
 impl<'a> Scheduler<'a> {
@@ -28,122 +57,811 @@ impl<'a> Scheduler<'a> {
             options,
             export_manager,
             results: vec![],
+            skipped: vec![],
+            failed_assertions: vec![],
+            iteration: None,
+            resumed: vec![],
         }
     }
 
     pub fn run_benchmarks(&mut self) -> Result<()> {
-        let mut executor: Box<dyn Executor> = match self.options.executor_kind {
-            ExecutorKind::Raw => Box::new(RawExecutor::new(self.options)),
-            ExecutorKind::Mock(ref shell) => Box::new(MockExecutor::new(shell.clone())),
-            ExecutorKind::Shell(ref shell) => Box::new(ShellExecutor::new(shell, self.options)),
-        };
+        let start = Instant::now();
+
+        // One executor for each shell if several shells are compared
+        let executor_kinds = self.options.executor_kinds();
+        let mut executors: Vec<Box<dyn Executor>> = executor_kinds
+            .iter()
+            .map(|kind| new_executor(kind, self.options))
+            .collect();
 
         let reference = self
             .options
             .reference_command
             .as_ref()
-            .map(|cmd| Command::new(None, cmd));
+            .filter(|cmd| self.commands.position_of(cmd).is_none())
+            .map(|cmd| {
+                Command::new(None, cmd).with_working_dir(self.options.working_dir.as_deref())
+            });
 
-        executor.calibrate()?;
+        let num_benchmarks = self.commands.num_commands() + reference.iter().count();
+        if num_benchmarks > MAX_BENCHMARKS_WITHOUT_WARNING
+            && self.options.output_style != OutputStyleOption::Disabled
+        {
+            eprintln!(
+                "  {}: {}\n",
+                self.options.theme.paint(Role::Warning, "Warning"),
+                Warnings::ManyBenchmarks(num_benchmarks)
+            );
+        }
 
-        for (number, cmd) in reference.iter().chain(self.commands.iter()).enumerate() {
-            self.results
-                .push(Benchmark::new(number, cmd, self.options, &*executor).run()?);
-
-            // We export results after each individual benchmark, because
-            // we would risk losing them if a later benchmark fails.
-            self.export_manager.write_results(
-                &self.results,
-                self.options.sort_order_exports,
-                true,
+        if self.options.output_style != OutputStyleOption::Disabled {
+            for group in self.commands.duplicates() {
+                eprintln!(
+                    "  {}: {}\n",
+                    self.options.theme.paint(Role::Warning, "Warning"),
+                    Warnings::DuplicateCommands(
+                        group.iter().map(|index| index + 1).collect(),
+                        self.commands.is_deduplicated()
+                    )
+                );
+            }
+        }
+
+        if self.options.cpu_affinity.is_some()
+            && !cpu_affinity::IS_SUPPORTED
+            && self.options.output_style != OutputStyleOption::Disabled
+        {
+            eprintln!(
+                "  {}: {}\n",
+                self.options.theme.paint(Role::Warning, "Warning"),
+                Warnings::CpuAffinityNotSupported
+            );
+        }
+
+        for shell in executor_kinds.iter().filter_map(|kind| match kind {
+            ExecutorKind::Shell(shell) => Some(shell),
+            _ => None,
+        }) {
+            if self.options.shell_mode == ShellMode::Persistent
+                && self.options.output_style != OutputStyleOption::Disabled
+            {
+                if let Some(reason) = persistent_shell::unsupported_reason(shell, self.options) {
+                    eprintln!(
+                        "  {}: {}\n",
+                        self.options.theme.paint(Role::Warning, "Warning"),
+                        Warnings::PersistentShellNotSupported(reason)
+                    );
+                }
+            }
+        }
+
+        if priority::effective(self.options.priority) != self.options.priority
+            && self.options.output_style != OutputStyleOption::Disabled
+        {
+            eprintln!(
+                "  {}: {}\n",
+                self.options.theme.paint(Role::Warning, "Warning"),
+                Warnings::PriorityNotPermitted(self.options.priority)
+            );
+        }
+
+        // Progress events on stderr (`--progress-json`) are not interrupted by this warning,
+        // since it depends on the state of the machine
+        if self.options.load_check
+            && !self.options.progress_json
+            && self.options.output_style != OutputStyleOption::Disabled
+        {
+            if let Some(load) = system_load::current().filter(|load| load.is_high()) {
+                eprintln!(
+                    "  {}: {}\n",
+                    self.options.theme.paint(Role::Warning, "Warning"),
+                    Warnings::HighSystemLoad(load.load, load.cores)
+                );
+            }
+        }
+
+        // Like the load check, this depends on the state of the machine
+        if self.options.system_checks
+            && !self.options.progress_json
+            && self.options.output_style != OutputStyleOption::Disabled
+        {
+            for warning in cpu_settings::current(self.options.cpu_affinity.as_deref()).warnings() {
+                eprintln!(
+                    "  {}: {}\n",
+                    self.options.theme.paint(Role::Warning, "Warning"),
+                    warning
+                );
+            }
+        }
+
+        // The privileges are acquired before anything is measured, which might prompt for a
+        // password
+        if self.options.drop_file_caches {
+            executors[0].drop_file_caches()?;
+        }
+
+        if let Some(setup_global_command) = &self.options.setup_global_command {
+            run_global_setup_command(&*executors[0], &Command::new(None, setup_global_command))?;
+        }
+
+        for (kind, executor) in executor_kinds.iter().zip(executors.iter_mut()) {
+            executor.calibrate()?;
+
+            if self.options.verbose && self.options.output_style != OutputStyleOption::Disabled {
+                self.print_shell_calibration(kind, &**executor);
+            }
+        }
+
+        let benchmarks: Vec<&Command> = reference.iter().chain(self.commands.iter()).collect();
+        let options_numbers: Vec<usize> = reference
+            .iter()
+            .map(|_| 0)
+            .chain(
+                self.commands
+                    .iter_with_expanded_index()
+                    .map(|(index, _)| index + reference.iter().count()),
+            )
+            .collect();
+
+        if let Some(previous_results) = &self.options.previous_results {
+            self.resumed = benchmarks
+                .iter()
+                .map(|cmd| previous_results.result_for(cmd))
+                .collect();
+            let num_resumed = self.resumed.iter().flatten().count();
+            if self.options.output_style != OutputStyleOption::Disabled {
+                println!(
+                    "Resuming from '{}': {} of {} benchmarks have a result already\n",
+                    previous_results.path,
+                    num_resumed,
+                    benchmarks.len()
+                );
+            }
+        }
+
+        let repetitions = self.options.suite_repetitions as usize;
+        for iteration in 1..=repetitions {
+            if repetitions > 1 {
+                self.iteration = Some(iteration);
+                if self.options.output_style != OutputStyleOption::Disabled {
+                    println!(
+                        "{}\n",
+                        format!("Iteration {iteration}/{repetitions}")
+                            .bold()
+                            .underline()
+                    );
+                }
+            }
+
+            let completed = self.run_suite(
+                &executors,
+                &benchmarks,
+                &options_numbers,
+                start,
+                repetitions - iteration,
             )?;
+            if !completed {
+                break;
+            }
         }
 
         Ok(())
     }
 
+    /// Perform all benchmarks once. The given number of remaining iterations of the suite
+    /// (`--repeat-suite`) is taken into account when the total time budget is divided. Returns
+    /// false if some benchmarks have been skipped because the time budget is exhausted.
+    fn run_suite(
+        &mut self,
+        executors: &[Box<dyn Executor + '_>],
+        benchmarks: &[&Command],
+        options_numbers: &[usize],
+        start: Instant,
+        remaining_iterations: usize,
+    ) -> Result<bool> {
+        let num_benchmarks = benchmarks.len();
+
+        if self.options.interleave {
+            // The total time budget is divided evenly among all benchmarks in advance
+            let time_budget = self.options.total_time_budget.map(|budget| {
+                budget / (num_benchmarks * self.options.suite_repetitions as usize) as Second
+            });
+            let commands = benchmarks;
+            let benchmarks: Vec<Benchmark> = benchmarks
+                .iter()
+                .enumerate()
+                .filter(|(number, _)| !self.is_resumed(*number))
+                .map(|(number, cmd)| {
+                    Benchmark::new(
+                        number,
+                        num_benchmarks,
+                        cmd,
+                        self.options,
+                        self.executor_for(executors, cmd),
+                        self.export_manager,
+                    )
+                    .with_options_number(options_numbers[number])
+                    .with_time_budget(time_budget)
+                })
+                .collect();
+            self.run_interleaved_benchmarks(&benchmarks, commands)?;
+            return Ok(true);
+        }
+
+        for (number, cmd) in benchmarks.iter().enumerate() {
+            if self.add_resumed_result(number, cmd)? {
+                continue;
+            }
+
+            // The remaining time budget is divided evenly among the remaining benchmarks
+            let remaining_time = self
+                .options
+                .total_time_budget
+                .map(|budget| budget - self.elapsed_time(start));
+            if remaining_time.is_some_and(|remaining_time| remaining_time <= 0.0) {
+                self.skip_benchmarks(number, &benchmarks[number..])?;
+                return Ok(false);
+            }
+            let remaining_benchmarks =
+                num_benchmarks - number + num_benchmarks * remaining_iterations;
+            let time_budget = remaining_time
+                .map(|remaining_time| remaining_time / remaining_benchmarks as Second);
+
+            let result = Benchmark::new(
+                number,
+                num_benchmarks,
+                cmd,
+                self.options,
+                self.executor_for(executors, cmd),
+                self.export_manager,
+            )
+            .with_options_number(options_numbers[number])
+            .with_time_budget(time_budget)
+            .run();
+            if interrupt::is_interrupted() && !self.results.is_empty() {
+                // Export the results of all completed benchmarks before exiting
+                self.final_export()?;
+            }
+            self.add_result(result?)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Perform the setup, warmup and initial runs of one benchmark after another, then all
+    /// remaining timing runs in a random order, and finally compute the results (`--interleave`)
+    fn run_interleaved_benchmarks(
+        &mut self,
+        benchmarks: &[Benchmark],
+        commands: &[&Command],
+    ) -> Result<()> {
+        let mut states = Vec::with_capacity(benchmarks.len());
+        let measured = benchmarks
+            .iter()
+            .try_for_each(|benchmark| {
+                let mut state = benchmark.start()?;
+                state.progress.hide_bar();
+                states.push(state);
+                Ok(())
+            })
+            .and_then(|()| perform_interleaved_runs(benchmarks, &mut states, self.options));
+
+        if let Err(error) = measured {
+            // If interrupted, the cleanup commands of all benchmarks that have been started run
+            let num_started = cmp::min(states.len() + 1, benchmarks.len());
+            for benchmark in &benchmarks[..num_started] {
+                benchmark.cleanup_after_interrupt(&error)?;
+            }
+            return Err(error);
+        }
+
+        // The results of an earlier invocation are added in between, in the order of the
+        // benchmarks
+        for (benchmark, state) in benchmarks.iter().zip(states) {
+            for (number, cmd) in commands.iter().enumerate().take(benchmark.number) {
+                self.add_resumed_result(number, cmd)?;
+            }
+            benchmark.print_header();
+            self.add_result(benchmark.finish(state)?)?;
+        }
+        for (number, cmd) in commands.iter().enumerate() {
+            self.add_resumed_result(number, cmd)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the benchmark with the given number has a result of an earlier invocation
+    /// (`--resume-from`), which has not been added yet
+    fn is_resumed(&self, number: usize) -> bool {
+        self.resumed.get(number).is_some_and(Option::is_some)
+    }
+
+    /// Use the result of an earlier invocation for the benchmark with the given number
+    /// (`--resume-from`). Returns false if there is none, such that it needs to be performed.
+    fn add_resumed_result(&mut self, number: usize, cmd: &Command) -> Result<bool> {
+        let Some(result) = self.resumed.get_mut(number).and_then(Option::take) else {
+            return Ok(false);
+        };
+        if self.options.output_style != OutputStyleOption::Disabled {
+            println!(
+                "{}{}: {} {}\n",
+                "Benchmark ".bold(),
+                (number + 1).to_string().bold(),
+                cmd.get_name_with_unused_parameters(),
+                self.options
+                    .theme
+                    .paint(Role::Detail, "(result of an earlier invocation)"),
+            );
+        }
+        self.check_assertions(&result);
+        self.results.push(result);
+        self.write_intermediate_results()?;
+        Ok(true)
+    }
+
+    /// Export the results of all benchmarks that have been completed so far. We export results
+    /// after each individual benchmark, because we would risk losing them if a later benchmark
+    /// fails.
+    fn write_intermediate_results(&self) -> Result<()> {
+        self.export_manager.write_results(
+            &self.results,
+            &self.skipped,
+            self.reference_index(),
+            self.options.sort_order_exports,
+            true,
+        )
+    }
+
+    /// Check the assertions for a completed benchmark and save its result
+    fn add_result(&mut self, mut result: BenchmarkResult) -> Result<()> {
+        result.iteration = self.iteration;
+        self.check_assertions(&result);
+        self.results.push(result);
+        self.write_intermediate_results()
+    }
+
+    /// Check all `--assert` thresholds for the given result and report the ones that do not hold
+    fn check_assertions(&mut self, result: &BenchmarkResult) {
+        let mut failed = false;
+        for assertion in &self.options.assertions {
+            if let Some(failure) = assertion.check(result, self.options.time_unit) {
+                if self.options.output_style != OutputStyleOption::Disabled {
+                    eprintln!(
+                        "  {}: {}",
+                        self.options.theme.paint(Role::Error, "Assertion failed"),
+                        failure
+                    );
+                }
+                self.failed_assertions
+                    .push((result.command_with_unused_parameters.clone(), failure));
+                failed = true;
+            }
+        }
+        if failed && self.options.output_style != OutputStyleOption::Disabled {
+            eprintln!(" ");
+        }
+    }
+
+    /// Returns an error that lists all assertions that did not hold. This is called after the
+    /// final export, such that the results are available even if an assertion fails.
+    pub fn ensure_assertions_hold(&self) -> Result<()> {
+        if self.failed_assertions.is_empty() {
+            return Ok(());
+        }
+
+        let failures: String = self
+            .failed_assertions
+            .iter()
+            .map(|(name, failure)| format!("\n  {name}: {failure}"))
+            .collect();
+        Err(anyhow!(
+            "{} of the assertions specified via '--assert' failed:{}",
+            self.failed_assertions.len(),
+            failures
+        ))
+    }
+
+    /// The executor of the given benchmark, see `Options::executor_kind_for`
+    fn executor_for<'e>(
+        &self,
+        executors: &'e [Box<dyn Executor + 'e>],
+        cmd: &Command,
+    ) -> &'e dyn Executor {
+        let index = cmd
+            .get_shell()
+            .and_then(|shell| {
+                self.options
+                    .shells
+                    .iter()
+                    .position(|(name, _)| *name == shell)
+            })
+            .unwrap_or(0);
+        &*executors[index]
+    }
+
+    /// Print the result of the shell (or process) spawning time calibration (`--verbose`)
+    fn print_shell_calibration(&self, executor_kind: &ExecutorKind, executor: &dyn Executor) {
+        let (title, spawning_time) = match executor_kind {
+            ExecutorKind::Shell(ref shell) => {
+                let title = format!("Shell calibration ({shell})");
+                let Some(spawning_time) = executor.shell_spawning_time() else {
+                    println!("{title}: disabled via --no-shell-calibration\n");
+                    return;
+                };
+                (title, spawning_time)
+            }
+            ExecutorKind::Raw => {
+                let title = "Spawn calibration (--shell=none)".to_string();
+                let Some(spawning_time) = executor.spawn_calibration() else {
+                    if self.options.spawn_calibration {
+                        println!("{title}: not supported on this platform\n");
+                    } else {
+                        println!("{title}: disabled via --no-spawn-calibration\n");
+                    }
+                    return;
+                };
+                (title, spawning_time)
+            }
+            ExecutorKind::Mock(_) => return,
+        };
+        let unit = self.options.time_unit;
+        println!("{title}:");
+        println!(
+            "  Spawning time:    {} ± {}    [User: {}, System: {}]",
+            format_duration(spawning_time.mean, unit),
+            format_duration(spawning_time.stddev, unit),
+            format_duration(spawning_time.user, unit),
+            format_duration(spawning_time.system, unit)
+        );
+        println!(
+            "  Calibration runs: {} ({})\n",
+            spawning_time.runs,
+            if spawning_time.precise {
+                "precise estimate"
+            } else {
+                "stopped at the maximum number of runs or time"
+            }
+        );
+    }
+
+    /// Time that has been spent on the benchmarks since the given start. The mocked runs do not
+    /// take any actual time, so their (mocked) times are summed up instead.
+    fn elapsed_time(&self, start: Instant) -> Second {
+        if matches!(self.options.executor_kind, ExecutorKind::Mock(_)) {
+            self.results
+                .iter()
+                .map(|result| result.mean * result.runs as Second)
+                .sum()
+        } else {
+            start.elapsed().as_secs_f64()
+        }
+    }
+
+    /// Record the given benchmarks (starting with the given number) as skipped, because the
+    /// `--total-time-budget` has been exhausted
+    fn skip_benchmarks(&mut self, first_number: usize, commands: &[&Command]) -> Result<()> {
+        for (number, cmd) in commands.iter().enumerate() {
+            if self.add_resumed_result(first_number + number, cmd)? {
+                continue;
+            }
+            if self.options.output_style != OutputStyleOption::Disabled {
+                println!(
+                    "{}{}: {} {}",
+                    "Benchmark ".bold(),
+                    (first_number + number + 1).to_string().bold(),
+                    cmd.get_name_with_unused_parameters(),
+                    self.options.theme.paint(
+                        Role::Warning,
+                        "(skipped, the total time budget is exhausted)"
+                    ),
+                );
+            }
+            self.skipped.push(SkippedBenchmark {
+                command: cmd.get_command_line(),
+                name: cmd.get_name(),
+                parameters: cmd
+                    .get_parameters()
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            });
+        }
+        if self.options.output_style != OutputStyleOption::Disabled {
+            println!(" ");
+        }
+
+        self.write_intermediate_results()
+    }
+
     pub fn print_relative_speed_comparison(&self) {
         if self.options.output_style == OutputStyleOption::Disabled {
             return;
         }
 
-        if self.results.len() < 2 {
+        if self.iteration.is_none() {
+            self.print_comparison(&self.results, None);
+            let reference_index = self.reference_index().unwrap_or(0);
+            if let Some(reference) = self.results.get(reference_index) {
+                let others: Vec<&BenchmarkResult> = self
+                    .results
+                    .iter()
+                    .enumerate()
+                    .filter(|&(index, _)| index != reference_index)
+                    .map(|(_, other)| other)
+                    .collect();
+                if others.len() == 1 || self.options.show_side_by_side {
+                    for other in others {
+                        self.print_side_by_side(reference, other);
+                    }
+                }
+            }
+            return;
+        }
+
+        // With `--repeat-suite`, the comparison is shown for every iteration and for the runs of
+        // all iterations together. The results of every iteration are in the same order.
+        let num_benchmarks = self
+            .results
+            .iter()
+            .filter(|r| r.iteration == Some(1))
+            .count();
+        if num_benchmarks < 2 {
             return;
         }
 
+        let num_iterations = self.results.last().and_then(|r| r.iteration).unwrap_or(0);
+        for iteration in 1..=num_iterations {
+            let results: Vec<BenchmarkResult> = self
+                .results
+                .iter()
+                .filter(|r| r.iteration == Some(iteration))
+                .cloned()
+                .collect();
+            self.print_comparison(&results, Some(&format!("iteration {iteration}")));
+            println!();
+        }
+
+        let aggregates: Option<Vec<BenchmarkResult>> = (0..num_benchmarks)
+            .map(|index| {
+                let results: Vec<&BenchmarkResult> = self
+                    .results
+                    .iter()
+                    .skip(index)
+                    .step_by(num_benchmarks)
+                    .collect();
+                BenchmarkResult::aggregate(&results)
+            })
+            .collect();
+        if let Some(aggregates) = aggregates {
+            self.print_comparison(
+                &aggregates,
+                Some(&format!("all {num_iterations} iterations")),
+            );
+        }
+    }
+
+    /// Print the relative speed comparison of the given results. The label (if any) is shown
+    /// in the heading.
+    fn print_comparison(&self, results: &[BenchmarkResult], label: Option<&str>) {
+        if results.len() < 2 {
+            return;
+        }
+
+        let theme = self.options.theme;
         let reference = self
-            .options
-            .reference_command
-            .as_ref()
-            .map(|_| &self.results[0])
-            .unwrap_or_else(|| relative_speed::fastest_of(&self.results));
+            .reference_index()
+            .and_then(|index| results.get(index))
+            .unwrap_or_else(|| {
+                relative_speed::fastest_of(results, self.options.sort_order_speed_comparison)
+            });
 
         if let Some(annotated_results) = relative_speed::compute_with_check_from_reference(
-            &self.results,
+            results,
             reference,
             self.options.sort_order_speed_comparison,
         ) {
+            let geometric_mean = relative_speed::geometric_mean_relative_time(&annotated_results)
+                .filter(|_| self.options.show_geometric_mean || results.len() > 2);
+
+            let summary_format = self.options.summary_format;
+            let delta = |difference: Second| format_duration(difference, self.options.time_unit);
+            let signed_delta = |difference: Second| {
+                format!(
+                    "{}{}",
+                    if difference < 0.0 { "-" } else { "+" },
+                    delta(difference.abs())
+                )
+            };
+            let delta_symbol = if self.options.output_style == OutputStyleOption::Ascii {
+                "delta"
+            } else {
+                "Δ"
+            };
+
             match self.options.sort_order_speed_comparison {
-                SortOrder::MeanTime => {
-                    println!("{}", "Summary".bold());
+                sort_order @ (SortOrder::MeanTime
+                | SortOrder::MedianTime
+                | SortOrder::MaxTime
+                | SortOrder::UserTime) => {
+                    let details: Vec<String> = statistic_name(sort_order)
+                        .map(|statistic| format!("by {statistic}"))
+                        .into_iter()
+                        .chain(label.map(String::from))
+                        .collect();
+                    if details.is_empty() {
+                        println!("{}", "Summary".bold());
+                    } else {
+                        println!("{} ({})", "Summary".bold(), details.join(", "));
+                    }
 
                     let reference = annotated_results.iter().find(|r| r.is_reference).unwrap();
                     let others = annotated_results.iter().filter(|r| !r.is_reference);
 
                     println!(
                         "  {} ran",
-                        reference.result.command_with_unused_parameters.cyan()
+                        theme.paint(
+                            Role::Reference,
+                            &reference.result.command_with_unused_parameters
+                        )
                     );
 
                     for item in others {
                         let stddev = if let Some(stddev) = item.relative_speed_stddev {
-                            format!(" ± {}", format!("{:.2}", stddev).green())
+                            format!(" ± {}", theme.paint(Role::Value, format!("{:.2}", stddev)))
                         } else {
                             "".into()
                         };
-                        let comparator = match item.relative_ordering {
-                            Ordering::Less => format!(
+                        let comparator = match (summary_format, item.relative_ordering) {
+                            (SummaryFormat::Delta, Ordering::Less) => format!(
+                                "    {} slower than",
+                                theme
+                                    .paint(Role::Value, delta(-item.time_difference))
+                                    .bold()
+                            ),
+                            (SummaryFormat::Delta, Ordering::Greater) => format!(
+                                "    {} faster than",
+                                theme.paint(Role::Value, delta(item.time_difference)).bold()
+                            ),
+                            (SummaryFormat::Delta, Ordering::Equal) => "    As fast as".into(),
+                            (_, Ordering::Less) => format!(
                                 "{}{} times slower than",
-                                format!("{:8.2}", item.relative_speed).bold().green(),
+                                theme
+                                    .paint(Role::Value, format!("{:8.2}", item.relative_speed))
+                                    .bold(),
                                 stddev
                             ),
-                            Ordering::Greater => format!(
+                            (_, Ordering::Greater) => format!(
                                 "{}{} times faster than",
-                                format!("{:8.2}", item.relative_speed).bold().green(),
+                                theme
+                                    .paint(Role::Value, format!("{:8.2}", item.relative_speed))
+                                    .bold(),
                                 stddev
                             ),
-                            Ordering::Equal => format!(
+                            (_, Ordering::Equal) => format!(
                                 "    As fast ({}{}) as",
-                                format!("{:.2}", item.relative_speed).bold().green(),
+                                theme
+                                    .paint(Role::Value, format!("{:.2}", item.relative_speed))
+                                    .bold(),
                                 stddev
                             ),
                         };
+                        let difference = if summary_format == SummaryFormat::Both {
+                            format!(" ({delta_symbol} {})", delta(item.time_difference.abs()))
+                        } else {
+                            String::new()
+                        };
                         println!(
-                            "{} {}",
+                            "{} {}{}",
                             comparator,
-                            &item.result.command_with_unused_parameters.magenta()
+                            &theme
+                                .paint(Role::Command, &item.result.command_with_unused_parameters),
+                            difference
                         );
+
+                        // The t-test compares the mean times
+                        match significance::p_value(reference.result, item.result) {
+                            Some(p_value)
+                                if sort_order == SortOrder::MeanTime
+                                    && p_value >= SIGNIFICANCE_LEVEL =>
+                            {
+                                println!(
+                                    "             {}",
+                                    format!(
+                                        "(p = {p_value:.2}: the difference is not statistically \
+                                         significant at α = {SIGNIFICANCE_LEVEL})"
+                                    )
+                                    .dimmed()
+                                )
+                            }
+                            _ => {}
+                        }
                     }
                 }
                 SortOrder::Command => {
-                    println!("{}", "Relative speed comparison".bold());
+                    match label {
+                        Some(label) => {
+                            println!("{} ({})", "Relative speed comparison".bold(), label)
+                        }
+                        None => println!("{}", "Relative speed comparison".bold()),
+                    }
+
+                    for item in &annotated_results {
+                        if summary_format == SummaryFormat::Delta {
+                            let delta = if item.is_reference {
+                                format!("{:>10}", "")
+                            } else {
+                                format!("{:>10}", signed_delta(item.time_difference))
+                            };
+                            println!(
+                                "  {}  {}",
+                                theme.paint(Role::Value, delta).bold(),
+                                &item.result.command_with_unused_parameters,
+                            );
+                            continue;
+                        }
 
-                    for item in annotated_results {
+                        let (relative_time, relative_time_stddev) = item.relative_time();
+                        let difference =
+                            if summary_format == SummaryFormat::Both && !item.is_reference {
+                                format!(" ({delta_symbol} {})", signed_delta(item.time_difference))
+                            } else {
+                                String::new()
+                            };
                         println!(
-                            "  {}{}  {}",
-                            format!("{:10.2}", item.relative_speed).bold().green(),
+                            "  {}{}  {}{}",
+                            theme
+                                .paint(Role::Value, format!("{relative_time:10.2}"))
+                                .bold(),
                             if item.is_reference {
                                 "        ".into()
-                            } else if let Some(stddev) = item.relative_speed_stddev {
-                                format!(" ± {}", format!("{stddev:5.2}").green())
+                            } else if let Some(stddev) = relative_time_stddev {
+                                format!(" ± {}", theme.paint(Role::Value, format!("{stddev:5.2}")))
                             } else {
                                 "        ".into()
                             },
                             &item.result.command_with_unused_parameters,
+                            difference,
                         );
                     }
                 }
             }
+
+            if let Some(geometric_mean) = geometric_mean {
+                let reference = annotated_results.iter().find(|r| r.is_reference).unwrap();
+                println!(
+                    "\n  Geometric mean of the times relative to {}: {}",
+                    theme.paint(
+                        Role::Reference,
+                        &reference.result.command_with_unused_parameters
+                    ),
+                    theme
+                        .paint(Role::Value, format!("{geometric_mean:.2}"))
+                        .bold()
+                );
+            }
+        } else if results.iter().any(|result| result.clamped_runs > 0) {
+            let clamped = results
+                .iter()
+                .filter(|result| result.clamped_runs > 0)
+                .map(|result| {
+                    format!(
+                        "'{}' ({} of {} runs)",
+                        result.command_with_unused_parameters,
+                        result.clamped_runs,
+                        result.num_included_runs()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            eprintln!(
+                "{}: The benchmark comparison could not be computed as some benchmark times are zero. \
+                 The measured times of {clamped} were shorter than the estimated shell (or process) \
+                 spawning time and have been set to zero. Use the --shell=none/-N option to \
+                 benchmark such fast commands without an intermediate shell, or \
+                 --no-spawn-calibration if it is used already.",
+                theme.paint(Role::Error, "Note").bold()
+            );
         } else {
             eprintln!(
                 "{}: The benchmark comparison could not be computed as some benchmark times are zero. \
@@ -152,13 +870,168 @@ impl<'a> Scheduler<'a> {
                  Try to re-run the benchmark on a quiet system. If you did not do so already, try the \
                  --shell=none/-N option. If it does not help either, you command is most likely too fast \
                  to be accurately benchmarked by hyperfine.",
-                 "Note".bold().red()
+                 theme.paint(Role::Error, "Note").bold()
+            );
+        }
+    }
+
+    /// Print the statistics of two benchmarks side by side, together with the relative change
+    /// from the first (the reference) to the second one
+    fn print_side_by_side(&self, first: &BenchmarkResult, second: &BenchmarkResult) {
+        let theme = self.options.theme;
+        let unit = self.options.time_unit;
+
+        println!("\n{}", "Comparison".bold());
+        println!(
+            "  {}: {}",
+            "A".bold(),
+            theme.paint(Role::Reference, &first.command_with_unused_parameters)
+        );
+        println!(
+            "  {}: {}",
+            "B".bold(),
+            theme.paint(Role::Command, &second.command_with_unused_parameters)
+        );
+        println!("  {:<8}{:>12}{:>12}{:>10}", "", "A", "B", "Change");
+        for (statistic, a, b) in [
+            ("Mean", first.mean, second.mean),
+            ("Median", first.median, second.median),
+            ("Min", first.min, second.min),
+            ("Max", first.max, second.max),
+            ("User", first.user, second.user),
+            ("System", first.system, second.system),
+        ] {
+            let change = relative_change(a, b);
+            let change_str = format!(
+                "{:>10}",
+                change.map_or("-".to_string(), |change| format!("{:+.1}%", change * 100.0))
+            );
+            let change_str = match change {
+                Some(change) if change < 0.0 => theme.paint(Role::Faster, change_str),
+                Some(change) if change > 0.0 => theme.paint(Role::Slower, change_str),
+                _ => change_str.normal(),
+            };
+            println!(
+                "  {:<8}{:>12}{:>12}{}",
+                statistic,
+                format_duration(a, unit),
+                format_duration(b, unit),
+                change_str
             );
         }
     }
 
     pub fn final_export(&self) -> Result<()> {
-        self.export_manager
-            .write_results(&self.results, self.options.sort_order_exports, false)
+        self.export_manager.write_results(
+            &self.results,
+            &self.skipped,
+            self.reference_index(),
+            self.options.sort_order_exports,
+            false,
+        )
+    }
+
+    /// The results of all benchmarks that have been performed, including the one of a reference
+    /// command that is not part of the command list
+    pub fn into_results(self) -> Vec<BenchmarkResult> {
+        self.results
+    }
+
+    /// Index of the reference result for relative speed comparisons. A reference command that
+    /// is not part of the command list is benchmarked first.
+    fn reference_index(&self) -> Option<usize> {
+        self.options
+            .reference_command
+            .as_ref()
+            .map(|cmd| self.commands.position_of(cmd).unwrap_or(0))
+    }
+}
+
+/// Relative change from the first to the second time (-0.1 if the second one is 10% shorter),
+/// rounded to zero if it is below 0.05%. Not available if the first time is zero.
+fn relative_change(first: Second, second: Second) -> Option<f64> {
+    (first > 0.0).then(|| second / first - 1.0).map(
+        |change| {
+            if change.abs() < 5e-4 {
+                0.0
+            } else {
+                change
+            }
+        },
+    )
+}
+
+/// Name of the statistic that the summary is based on, unless it is the mean time
+fn statistic_name(sort_order: SortOrder) -> Option<&'static str> {
+    match sort_order {
+        SortOrder::Command | SortOrder::MeanTime => None,
+        SortOrder::MedianTime => Some("median time"),
+        SortOrder::MaxTime => Some("maximum time"),
+        SortOrder::UserTime => Some("user time"),
     }
 }
+
+/// Perform the remaining timing runs of all benchmarks in a random order. Each benchmark is
+/// picked as often as it has remaining runs.
+fn perform_interleaved_runs<'a>(
+    benchmarks: &[Benchmark<'a>],
+    states: &mut [MeasurementState<'a>],
+    options: &Options,
+) -> Result<()> {
+    let mut order: Vec<usize> = states
+        .iter()
+        .enumerate()
+        .flat_map(|(index, state)| std::iter::repeat(index).take(state.remaining_runs()))
+        .collect();
+    order.shuffle(&mut rand::thread_rng());
+
+    let bar = (options.output_style != OutputStyleOption::Disabled && !options.progress_json).then(
+        || {
+            get_progress_bar(
+                order.len() as u64,
+                "Interleaved timing runs",
+                options.output_style,
+            )
+        },
+    );
+    for index in order {
+        benchmarks[index].perform_run(&mut states[index])?;
+        if let Some(bar) = bar.as_ref() {
+            bar.inc(1);
+        }
+    }
+    if let Some(bar) = bar {
+        bar.finish_and_clear();
+    }
+
+    Ok(())
+}
+
+/// Create an executor of the given kind, e.g. for one of several shells
+fn new_executor<'a>(kind: &'a ExecutorKind, options: &'a Options) -> Box<dyn Executor + 'a> {
+    match kind {
+        ExecutorKind::Raw => Box::new(RawExecutor::new(options)),
+        ExecutorKind::Mock(shell) => Box::new(MockExecutor::new(shell.clone())),
+        ExecutorKind::Shell(shell) => Box::new(ShellExecutor::new(shell, options)),
+    }
+}
+
+/// Run the command specified by `--setup-global`. If it fails, the error output of the command is
+/// included in the returned error.
+fn run_global_setup_command(executor: &dyn Executor, command: &Command<'_>) -> Result<()> {
+    let output = executor.run_command_once(command)?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let error_output = "The global setup command terminated with a non-zero exit code. \
+                        Append ' || true' to the command if you are sure that this can be ignored.";
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim_end();
+
+    Err(if stderr.is_empty() {
+        anyhow!("{error_output}")
+    } else {
+        anyhow!("{stderr}").context(format!("{error_output} Error output of the command"))
+    })
+}
@@ -0,0 +1,220 @@
+use statistical::{mean, variance};
+
+use super::benchmark_result::BenchmarkResult;
+use crate::util::units::Scalar;
+
+/// Significance level below which the difference between two benchmarks is considered to be
+/// statistically significant
+pub const SIGNIFICANCE_LEVEL: Scalar = 0.05;
+
+/// Compute the p-value of Welch's t-test for the run times of the two given benchmarks. This is
+/// not available if one of the benchmarks has fewer than two runs.
+pub fn p_value(a: &BenchmarkResult, b: &BenchmarkResult) -> Option<Scalar> {
+    match (a.trimmed_times(), b.trimmed_times()) {
+        (Some(times_a), Some(times_b)) => welch_t_test(&times_a, &times_b),
+        _ => None,
+    }
+}
+
+/// Two-sided p-value of Welch's t-test for the null hypothesis that both samples have the same
+/// mean. Welch's test does not assume equal variances of both samples. Returns `None` if the
+/// test can not be performed, e.g. if there is no variance in the measurements at all.
+pub fn welch_t_test(a: &[Scalar], b: &[Scalar]) -> Option<Scalar> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+
+    let (n_a, n_b) = (a.len() as Scalar, b.len() as Scalar);
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let var_a = variance(a, Some(mean_a)) / n_a;
+    let var_b = variance(b, Some(mean_b)) / n_b;
+
+    let var_sum = var_a + var_b;
+    if var_sum <= 0.0 || !var_sum.is_finite() {
+        return None;
+    }
+
+    let t = (mean_a - mean_b) / var_sum.sqrt();
+
+    // Welch–Satterthwaite equation
+    let df = var_sum.powi(2) / (var_a.powi(2) / (n_a - 1.0) + var_b.powi(2) / (n_b - 1.0));
+
+    Some(student_t_two_sided_p_value(t, df))
+}
+
+/// Probability for |T| ≥ |t| if T follows Student's t-distribution with `df` degrees of freedom
+pub(crate) fn student_t_two_sided_p_value(t: Scalar, df: Scalar) -> Scalar {
+    regularized_incomplete_beta(df / (df + t * t), df / 2.0, 0.5)
+}
+
+/// Critical value t > 0 of Student's t-distribution with `df` degrees of freedom, such that
+/// P(|T| ≥ t) = `alpha`. Computed by bisection of the two-sided p-value.
+pub fn student_t_critical_value(alpha: Scalar, df: Scalar) -> Scalar {
+    let mut upper = 1.0;
+    while student_t_two_sided_p_value(upper, df) > alpha {
+        upper *= 2.0;
+    }
+
+    let mut lower = 0.0;
+    for _ in 0..100 {
+        let middle = (lower + upper) / 2.0;
+        if student_t_two_sided_p_value(middle, df) > alpha {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+    }
+
+    (lower + upper) / 2.0
+}
+
+/// Regularized incomplete beta function I_x(a, b), see "Numerical Recipes", section 6.4
+fn regularized_incomplete_beta(x: Scalar, a: Scalar, b: Scalar) -> Scalar {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+
+    // The continued fraction converges rapidly for x < (a + 1) / (a + b + 2). Otherwise, use the
+    // symmetry relation I_x(a, b) = 1 - I_{1-x}(b, a).
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - ln_front.exp() * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz's method)
+fn beta_continued_fraction(x: Scalar, a: Scalar, b: Scalar) -> Scalar {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: Scalar = 1e-14;
+    const TINY: Scalar = 1e-300;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as Scalar;
+
+        // Even step
+        let numerator = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + numerator * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+
+        // Odd step
+        let numerator = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + numerator * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    h
+}
+
+/// Natural logarithm of the gamma function for x > 0 (Lanczos approximation)
+fn ln_gamma(x: Scalar) -> Scalar {
+    const COEFFICIENTS: [Scalar; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |sum, (i, c)| {
+            sum + c / (x + 1.0 + i as Scalar)
+        });
+
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+#[test]
+fn test_student_t_two_sided_p_value() {
+    use approx::assert_relative_eq;
+
+    // For one degree of freedom, this is the Cauchy distribution
+    assert_relative_eq!(0.5, student_t_two_sided_p_value(1.0, 1.0), epsilon = 1e-9);
+    assert_relative_eq!(
+        0.183_503_419_071_688,
+        student_t_two_sided_p_value(2.0, 2.0),
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(
+        0.027_492_775_039_376,
+        student_t_two_sided_p_value(-2.5, 12.3),
+        epsilon = 1e-9
+    );
+    assert_relative_eq!(1.0, student_t_two_sided_p_value(0.0, 5.0), epsilon = 1e-12);
+}
+
+#[test]
+fn test_student_t_critical_value() {
+    use approx::assert_relative_eq;
+
+    assert_relative_eq!(
+        12.706_204_736,
+        student_t_critical_value(0.05, 1.0),
+        epsilon = 1e-6
+    );
+    assert_relative_eq!(
+        2.228_138_852,
+        student_t_critical_value(0.05, 10.0),
+        epsilon = 1e-6
+    );
+    assert_relative_eq!(
+        2.583_487_185,
+        student_t_critical_value(0.02, 16.0),
+        epsilon = 1e-6
+    );
+    assert_relative_eq!(1.960, student_t_critical_value(0.05, 1e6), epsilon = 1e-3);
+}
+
+#[test]
+fn test_welch_t_test() {
+    use approx::assert_relative_eq;
+
+    // t = -1 with 8 degrees of freedom
+    let p = welch_t_test(&[1.0, 2.0, 3.0, 4.0, 5.0], &[2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    assert_relative_eq!(0.346_593_507_086_853, p, epsilon = 1e-9);
+
+    let p = welch_t_test(&[1.0, 1.1, 0.9, 1.0], &[2.0, 2.1, 1.9, 2.05]).unwrap();
+    assert!(p < SIGNIFICANCE_LEVEL);
+
+    assert_eq!(welch_t_test(&[1.0, 1.0], &[2.0, 2.0]), None);
+    assert_eq!(welch_t_test(&[1.0], &[2.0, 3.0]), None);
+}
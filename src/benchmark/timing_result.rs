@@ -1,4 +1,4 @@
-use crate::util::units::Second;
+use crate::util::units::{MegaHertz, Second};
 
 /// Results from timing a single command
 #[derive(Debug, Default, Copy, Clone)]
@@ -11,4 +11,42 @@ pub struct TimingResult {
 
     /// Time spent in kernel mode
     pub time_system: Second,
+
+    /// Peak resident set size (in bytes)
+    pub memory_usage_byte: u64,
+
+    /// Whether or not the command has been aborted because it exceeded the timeout
+    pub timed_out: bool,
+
+    /// Unix timestamp (in seconds) of the start of the command
+    pub start_timestamp: f64,
+
+    /// Context switches and page faults (not available on all platforms)
+    pub resource_usage: Option<ResourceUsage>,
+
+    /// Mean frequency of the CPUs right before and after the command (only available on Linux)
+    pub cpu_frequency: Option<[MegaHertz; 2]>,
+
+    /// Time that the command has reported itself in its output (`--extract-time`)
+    pub reported_time: Option<Second>,
+
+    /// Whether the wall clock time has been set to zero because it was shorter than the
+    /// estimated shell (or process) spawning time that has been subtracted from it
+    pub clamped: bool,
+}
+
+/// Resource usage statistics of a single command invocation
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Number of times the process gave up the CPU voluntarily (e.g. while waiting for I/O)
+    pub voluntary_context_switches: u64,
+
+    /// Number of times the process was preempted by the scheduler
+    pub involuntary_context_switches: u64,
+
+    /// Number of page faults that required I/O
+    pub major_page_faults: u64,
+
+    /// Number of page faults that could be served without I/O
+    pub minor_page_faults: u64,
 }
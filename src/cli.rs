@@ -23,6 +23,41 @@ fn build_command() -> Command {
         .about("A command-line benchmarking tool.")
         .help_expected(true)
         .max_term_width(80)
+        .subcommand_negates_reqs(true)
+        .disable_help_subcommand(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("compare")
+                .about(
+                    "Compare the results of two JSON exports (see '--export-json'), for example \
+                     before and after a change. Benchmarks are matched by their names, \
+                     parameter values and iterations of the benchmark suite, and Welch's \
+                     t-test is used to decide whether the difference of the mean run times is \
+                     statistically significant.",
+                )
+                .arg(
+                    Arg::new("old")
+                        .required(true)
+                        .value_name("OLD")
+                        .value_hint(ValueHint::FilePath)
+                        .help("The JSON export with the baseline results."),
+                )
+                .arg(
+                    Arg::new("new")
+                        .required(true)
+                        .value_name("NEW")
+                        .value_hint(ValueHint::FilePath)
+                        .help("The JSON export with the results to compare against the baseline."),
+                )
+                .arg(
+                    Arg::new("export-markdown")
+                        .long("export-markdown")
+                        .action(ArgAction::Set)
+                        .value_name("FILE")
+                        .value_hint(ValueHint::FilePath)
+                        .help("Export the comparison as a Markdown table to the given FILE."),
+                ),
+        )
         .arg(
             Arg::new("command")
                 .help("The command to benchmark. This can be the name of an executable, a command \
@@ -30,7 +65,7 @@ fn build_command() -> Command {
                        The latter is only available if the shell is not explicitly disabled via \
                        '--shell=none'. If multiple commands are given, hyperfine will show a \
                        comparison of the respective runtimes.")
-                .required(true)
+                .required_unless_present_any(["command-file", "suite"])
                 .action(ArgAction::Append)
                 .value_hint(ValueHint::CommandString)
                 .value_parser(NonEmptyStringValueParser::new()),
@@ -43,7 +78,13 @@ fn build_command() -> Command {
                 .action(ArgAction::Set)
                 .help(
                     "Perform NUM warmup runs before the actual benchmark. This can be used \
-                     to fill (disk) caches for I/O-heavy programs.",
+                     to fill (disk) caches for I/O-heavy programs. Alternatively, NUM can be \
+                     a duration with a unit suffix (e.g. '2s' or '500ms') to repeat the warmup \
+                     runs until at least this amount of time has passed. Use 'auto' to repeat \
+                     the warmup runs until the run times have stabilized, i.e. until the moving \
+                     average of the last three runs changes by less than 2% (at most 30 runs). \
+                     This is useful for commands that need a while to reach their final \
+                     performance, like programs with a JIT compiler.",
                 ),
         )
         .arg(
@@ -60,7 +101,8 @@ fn build_command() -> Command {
                 .short('M')
                 .action(ArgAction::Set)
                 .value_name("NUM")
-                .help("Perform at most NUM runs for each command. By default, there is no limit."),
+                .help("Perform at most NUM runs for each command. By default, at most 10000 \
+                       runs are performed (or --min-runs, if it is larger)."),
         )
         .arg(
             Arg::new("runs")
@@ -72,18 +114,169 @@ fn build_command() -> Command {
                 .help("Perform exactly NUM runs for each command. If this option is not specified, \
                        hyperfine automatically determines the number of runs."),
         )
+        .arg(
+            Arg::new("runs-per-command")
+                .long("runs-per-command")
+                .conflicts_with_all(["runs", "max-runs", "min-runs"])
+                .action(ArgAction::Set)
+                .value_name("NUM,...")
+                .help("Perform exactly the given number of runs for each command, as a \
+                       comma-separated list with one value per command (in the order of the \
+                       commands, including a --reference command that is not part of the list), \
+                       e.g. '--runs-per-command 100,3'."),
+        )
+        .arg(
+            Arg::new("warmup-per-command")
+                .long("warmup-per-command")
+                .conflicts_with("warmup")
+                .action(ArgAction::Set)
+                .value_name("NUM,...")
+                .help("Perform the given number of warmup runs for each command, as a \
+                       comma-separated list with one value per command (like --runs-per-command)."),
+        )
+        .arg(
+            Arg::new("min-benchmarking-time")
+                .long("min-benchmarking-time")
+                .action(ArgAction::Set)
+                .value_name("TIME")
+                .help("Run each command for at least TIME (default: 3s) when determining the number \
+                       of runs automatically. The number of runs is additionally limited by the \
+                       --min-runs and --max-runs options. A value of 0 results in exactly \
+                       --min-runs runs. TIME is a number of seconds or a duration with a unit \
+                       suffix like '500ms'."),
+        )
+        .arg(
+            Arg::new("deduplicate")
+                .long("deduplicate")
+                .action(ArgAction::SetTrue)
+                .help("Only benchmark the first of several commands that are identical after \
+                       the parameters have been substituted (even if they have different \
+                       names). Without this option, a warning lists the duplicates."),
+        )
+        .arg(
+            Arg::new("interleave")
+                .long("interleave")
+                .action(ArgAction::SetTrue)
+                .help("Perform the timing runs of all commands in a random, interleaved order \
+                       instead of one command after another. This makes the comparison less \
+                       sensitive to changes of the system state over time, like thermal \
+                       throttling. The setup, warmup and initial runs are still performed for \
+                       each command separately, before the interleaved runs."),
+        )
+        .arg(
+            Arg::new("repeat-suite")
+                .long("repeat-suite")
+                .alias("repeat-commands")
+                .action(ArgAction::Set)
+                .value_name("NUM")
+                .help("Perform the whole list of benchmarks NUM times, one iteration after \
+                       another, to see how stable the results of the entire suite are. Every \
+                       iteration yields its own results, which are labeled with the number of \
+                       the iteration in the exports. The relative speed comparison is shown for \
+                       every iteration and for the runs of all iterations together."),
+        )
+        .arg(
+            Arg::new("resume-from")
+                .long("resume-from")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .conflicts_with("repeat-suite")
+                .help("Continue an earlier invocation that has been aborted, with the results \
+                       of its JSON export (see --export-json). Benchmarks whose command line and \
+                       parameter values already have a result in FILE are not performed again. \
+                       Their results are part of the summary and the exports, such that the \
+                       same file can also be used for --export-json."),
+        )
+        .arg(
+            Arg::new("force-rerun")
+                .long("force-rerun")
+                .action(ArgAction::Set)
+                .value_name("PATTERN")
+                .requires("resume-from")
+                .help("Perform the benchmarks whose name or command line matches the regular \
+                       expression PATTERN again, even if they have a result in the file of \
+                       --resume-from."),
+        )
+        .arg(
+            Arg::new("cpu")
+                .long("cpu")
+                .action(ArgAction::Set)
+                .value_name("CPUS")
+                .help("Only run the benchmarked commands on the given CPUs, as a comma-separated \
+                       list of CPU numbers or ranges (like '2', '2,3' or '0-3'). Unlike running \
+                       hyperfine via 'taskset', this sets the CPU affinity of the spawned \
+                       processes only. Supported on Linux and Windows."),
+        )
+        .arg(
+            Arg::new("priority")
+                .long("priority")
+                .action(ArgAction::Set)
+                .value_name("PRIORITY")
+                .value_parser(["high", "normal", "low"])
+                .help("Run the benchmarked commands with the given scheduling priority: 'high', \
+                       'normal' (the priority of hyperfine itself, the default) or 'low'. On Unix, \
+                       the nice value of hyperfine is decreased or increased by 10. A high \
+                       priority usually requires root privileges on Unix. Without them, the \
+                       commands run at normal priority."),
+        )
+        .arg(
+            Arg::new("randomization")
+                .long("randomization")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .help("Control the environment variable HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET, \
+                       whose length is randomized for every command invocation to avoid a bias \
+                       from the memory layout of the benchmarked programs: 'on' (the default), \
+                       'off' to not set the variable at all, or 'seed:N' to make the lengths \
+                       deterministic, such that two invocations of hyperfine with the same seed \
+                       perturb the environment identically."),
+        )
+        .arg(
+            Arg::new("no-load-check")
+                .long("no-load-check")
+                .action(ArgAction::SetTrue)
+                .help("Do not warn if the system load is high before the benchmarks are started. \
+                       By default, hyperfine shows a warning if the load (the 1-minute load \
+                       average on Unix) exceeds half the number of CPU cores, since other \
+                       processes can make the results noisy."),
+        )
+        .arg(
+            Arg::new("no-system-checks")
+                .long("no-system-checks")
+                .action(ArgAction::SetTrue)
+                .help("Do not warn about CPU settings that make the results less stable. By \
+                       default, hyperfine checks on Linux whether the CPU frequency scaling \
+                       governor is not 'performance', whether turbo boost is enabled and whether \
+                       simultaneous multithreading is active. The detected settings are always \
+                       included in the JSON export."),
+        )
         .arg(
             Arg::new("setup")
                 .long("setup")
                 .short('s')
-                .action(ArgAction::Set)
+                .action(ArgAction::Append)
+                .num_args(1)
                 .value_name("CMD")
                 .value_hint(ValueHint::CommandString)
                 .help(
                     "Execute CMD before each set of timing runs. This is useful for \
                      compiling your software with the provided parameters, or to do any \
                      other work that should happen once before a series of benchmark runs, \
-                     not every time as would happen with the --prepare option."
+                     not every time as would happen with the --prepare option.\nLike \
+                     --prepare, the --setup option can be specified once for all commands or \
+                     once for each command."
+                ),
+        )
+        .arg(
+            Arg::new("setup-global")
+                .long("setup-global")
+                .action(ArgAction::Set)
+                .value_name("CMD")
+                .value_hint(ValueHint::CommandString)
+                .help(
+                    "Execute CMD exactly once before all benchmarks. In contrast to --setup, \
+                     this command is not repeated for each benchmark and it can not use \
+                     parameters. If CMD fails, hyperfine aborts and shows its error output."
                 ),
         )
         .arg(
@@ -92,8 +285,10 @@ fn build_command() -> Command {
                 .action(ArgAction::Set)
                 .value_name("CMD")
                 .help(
-                    "The reference command for the relative comparison of results. \
-                    If this is unset, results are compared with the fastest command as reference."
+                    "The reference command for the relative comparison of results. This can \
+                    be the name of one of the benchmarked commands (see --command-name). \
+                    Otherwise, CMD is benchmarked as an additional command. If this is unset, \
+                    results are compared with the fastest command as reference."
                 )
         )
         .arg(
@@ -109,7 +304,54 @@ fn build_command() -> Command {
                      clearing disk caches, for example.\nThe --prepare option can \
                      be specified once for all commands or multiple times, once for \
                      each command. In the latter case, each preparation command will \
-                     be run prior to the corresponding benchmark command.",
+                     be run prior to the corresponding benchmark command. With parameter \
+                     scans, every combination of parameter values counts as a separate \
+                     command (in the order in which they are benchmarked, after a potential \
+                     --reference), and CMD can use the same parameters as the command.",
+                ),
+        )
+        .arg(
+            Arg::new("prepare-mode")
+                .long("prepare-mode")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["per-run", "per-benchmark"])
+                .requires("prepare")
+                .help(
+                    "Specify when the --prepare command is executed:\n  \
+                     * 'per-run' (default): before each warmup and timing run.\n  \
+                     * 'per-benchmark': only once, before the first run of each benchmark. \
+                       This is useful for expensive preparations.",
+                ),
+        )
+        .arg(
+            Arg::new("warmup-mode")
+                .long("warmup-mode")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["after-prepare", "before-prepare", "skip-with-prepare"])
+                .help(
+                    "Specify how the warmup runs interact with the --prepare command:\n  \
+                     * 'after-prepare' (default): the preparation command also runs before \
+                       the warmup runs (or once before the first of them with \
+                       '--prepare-mode per-benchmark').\n  \
+                     * 'before-prepare': the warmup runs are performed without the \
+                       preparation command, which only runs before the timing runs.\n  \
+                     * 'skip-with-prepare': no warmup runs are performed for benchmarks that \
+                       have a preparation command. This is useful if it makes the caches cold \
+                       anyway, since the warmup runs would only fill them again.",
+                ),
+        )
+        .arg(
+            Arg::new("drop-file-caches")
+                .long("drop-file-caches")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Drop the file system caches of the operating system before each timing \
+                     run (after a potential --prepare command), in order to benchmark with cold \
+                     caches. This requires root privileges. If hyperfine does not run as root, \
+                     it uses 'sudo', which might ask for a password once before the benchmarks \
+                     are started. Only supported on Linux and macOS.",
                 ),
         )
         .arg(
@@ -129,18 +371,51 @@ fn build_command() -> Command {
                      command.",
                 ),
         )
+        .arg(
+            Arg::new("validate")
+                .long("validate")
+                .action(ArgAction::Append)
+                .num_args(1)
+                .value_name("CMD")
+                .value_hint(ValueHint::CommandString)
+                .help(
+                    "Execute CMD after each timing run (before a --conclude command) to check \
+                     that the run has produced the correct result, e.g. by computing a checksum \
+                     of its output file. The placeholder '{run}' is replaced by the number of \
+                     the run, and parameter placeholders can be used as well. The time of CMD \
+                     is not measured. If CMD fails, the benchmark is aborted, unless \
+                     --ignore-failure is used. In that case, the run is excluded from the \
+                     statistics and counted as 'validation_failures' in the JSON export (with \
+                     the indices of the runs in 'failed_validations'). Like \
+                     --conclude, this can be specified once for all commands or once for each \
+                     command.",
+                ),
+        )
         .arg(
             Arg::new("cleanup")
                 .long("cleanup")
                 .short('c')
-                .action(ArgAction::Set)
+                .action(ArgAction::Append)
+                .num_args(1)
                 .value_name("CMD")
                 .value_hint(ValueHint::CommandString)
                 .help(
                     "Execute CMD after the completion of all benchmarking \
                      runs for each individual command to be benchmarked. \
                      This is useful if the commands to be benchmarked produce \
-                     artifacts that need to be cleaned up."
+                     artifacts that need to be cleaned up. The cleanup command \
+                     also runs if a benchmark is interrupted with Ctrl-C.\nLike --prepare, \
+                     the --cleanup option can be specified once for all commands or once for \
+                     each command."
+                ),
+        )
+        .arg(
+            Arg::new("no-cleanup-on-interrupt")
+                .long("no-cleanup-on-interrupt")
+                .action(ArgAction::SetTrue)
+                .requires("cleanup")
+                .help(
+                    "Do not run the --cleanup command if a benchmark is interrupted with Ctrl-C."
                 ),
         )
         .arg(
@@ -158,7 +433,12 @@ fn build_command() -> Command {
                      To have the value increase following different patterns, use shell arithmetics.\n\n  \
                      Example: hyperfine -P size 0 3 'sleep $((2**{size}))'\n\n\
                      This performs benchmarks with power of 2 increases: 'sleep 1', 'sleep 2', 'sleep 4', …\n\
-                     The exact syntax may vary depending on your shell and OS."
+                     The exact syntax may vary depending on your shell and OS.\n\n\
+                     The value can be formatted with '{VAR:03}' (zero-padding to a width of 3) \
+                     or '{VAR:.2}' (two decimal places).\n\n  \
+                     Example: hyperfine -P n 1 20 'process input_{n:03}.txt'\n\n\
+                     Placeholders can also be used in the file names of the --export-* options \
+                     to write a separate file for each parameter value."
                 ),
         )
         .arg(
@@ -172,7 +452,35 @@ fn build_command() -> Command {
                     "This argument requires --parameter-scan to be specified as well. \
                      Traverse the range MIN..MAX in steps of DELTA.\n\n  \
                      Example:  hyperfine -P delay 0.3 0.7 -D 0.2 'sleep {delay}'\n\n\
-                     This performs benchmarks for 'sleep 0.3', 'sleep 0.5' and 'sleep 0.7'.",
+                     This performs benchmarks for 'sleep 0.3', 'sleep 0.5' and 'sleep 0.7'.\n\n\
+                     With '--parameter-step-mode multiply' (or a DELTA with an 'x' suffix or \
+                     prefix like '2x' or 'x2'), each value is multiplied by DELTA instead.\n\n  \
+                     Example:  hyperfine -P size 1K 64K -D 2x 'dd bs={size} …'\n\n\
+                     Integers can have a 'K', 'M' or 'G' suffix (binary multiples). This \
+                     performs benchmarks for 'bs=1K', 'bs=2K', …, 'bs=64K'.",
+                ),
+        )
+        .arg(
+            Arg::new("parameter-step-mode")
+                .long("parameter-step-mode")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["add", "multiply"])
+                .requires("parameter-scan")
+                .help(
+                    "Specify how the values of --parameter-scan are generated: 'add' (default) \
+                     adds DELTA to the previous value, 'multiply' multiplies the previous value \
+                     by DELTA.",
+                ),
+        )
+        .arg(
+            Arg::new("parameter-expand-suffix")
+                .long("parameter-expand-suffix")
+                .action(ArgAction::SetTrue)
+                .requires("parameter-scan")
+                .help(
+                    "Substitute the full values of --parameter-scan instead of values with a \
+                     suffix, e.g. '65536' instead of '64K'.",
                 ),
         )
         .arg(
@@ -182,7 +490,7 @@ fn build_command() -> Command {
                 .action(ArgAction::Append)
                 .allow_hyphen_values(true)
                 .value_names(["VAR", "VALUES"])
-                .conflicts_with_all(["parameter-scan", "parameter-step-size"])
+                .conflicts_with_all(["parameter-scan", "parameter-step-size", "parameter-step-mode"])
                 .help(
                     "Perform benchmark runs for each value in the comma-separated list VALUES. \
                      Replaces the string '{VAR}' in each command by the current parameter value\
@@ -192,13 +500,40 @@ fn build_command() -> Command {
                      possible parameter combinations.\n"
                 ),
         )
+        .arg(
+            Arg::new("parameter-mode")
+                .long("parameter-mode")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["product", "zip"])
+                .requires("parameter-list")
+                .help(
+                    "Specify how the values of multiple --parameter-list options are combined:\n  \
+                     * 'product' (default): run benchmarks for all possible combinations.\n  \
+                     * 'zip': combine the i-th values of all lists. The lists need to have \
+                       the same length.\n\n\
+                     Example:  hyperfine --parameter-mode zip -L file a.txt,b.txt -L lines 10,20 \
+                     'test $(wc -l < {file}) -eq {lines}'",
+                ),
+        )
+        .arg(
+            Arg::new("allow-unknown-placeholders")
+                .long("allow-unknown-placeholders")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Do not fail if a parametrized command (or one of the --prepare, --conclude, \
+                     --setup and --cleanup commands) contains a placeholder like '{name}' \
+                     that does not correspond to any parameter. Shell variables like '${name}' \
+                     are not considered. Use this if the braces are not meant as placeholders, \
+                     e.g. in 'awk {print}'.",
+                ),
+        )
         .arg(
             Arg::new("shell")
                 .long("shell")
                 .short('S')
-                .action(ArgAction::Set)
+                .action(ArgAction::Append)
                 .value_name("SHELL")
-                .overrides_with("shell")
                 .value_hint(ValueHint::CommandString)
                 .help("Set the shell to use for executing benchmarked commands. This can be the \
                        name or the path to the shell executable, or a full command line \
@@ -206,7 +541,9 @@ fn build_command() -> Command {
                        the default shell on this platform. Finally, this can also be set to \
                        \"none\" to disable the shell. In this case, commands will be executed \
                        directly. They can still have arguments, but more complex things like \
-                       \"sleep 0.1; sleep 0.2\" are not possible without a shell.")
+                       \"sleep 0.1; sleep 0.2\" are not possible without a shell. If this option \
+                       is given multiple times, every command is benchmarked with each of the \
+                       shells, which are shown as the parameter 'shell' (like 'shell = zsh').")
         )
         .arg(
             Arg::new("no-shell")
@@ -215,6 +552,40 @@ fn build_command() -> Command {
                 .conflicts_with_all(["shell", "debug-mode"])
                 .help("An alias for '--shell=none'.")
         )
+        .arg(
+            Arg::new("shell-mode")
+                .long("shell-mode")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["spawn", "persistent"])
+                .conflicts_with("no-shell")
+                .help(
+                    "Specify how the commands are run through the shell:\n  \
+                     * 'spawn' (default): a new shell is spawned for each run.\n  \
+                     * 'persistent': a single shell is started once and the commands are sent to \
+                       it. Each run is executed in a subshell, which isolates the state of the \
+                       commands, but the subshell still has to be forked. This only works with \
+                       POSIX shells like 'sh' or 'bash', and not in combination with \
+                       '--show-output', '--output', '--input' or '--timeout'. Memory usage is \
+                       not measured. Otherwise, hyperfine falls back to 'spawn' with a warning.",
+                ),
+        )
+        .arg(
+            Arg::new("no-shell-calibration")
+                .long("no-shell-calibration")
+                .action(ArgAction::SetTrue)
+                .help("Do not measure the time it takes to spawn the shell and do not subtract it \
+                       from the measurements. The reported times are then the raw wall clock \
+                       times, including the shell overhead.")
+        )
+        .arg(
+            Arg::new("no-spawn-calibration")
+                .long("no-spawn-calibration")
+                .action(ArgAction::SetTrue)
+                .help("With '--shell=none', do not measure the time it takes to spawn a process \
+                       (by running 'true' repeatedly) and do not subtract it from the \
+                       measurements. The calibration is only performed on Unix.")
+        )
         .arg(
             Arg::new("ignore-failure")
                 .long("ignore-failure")
@@ -222,19 +593,122 @@ fn build_command() -> Command {
                 .short('i')
                 .help("Ignore non-zero exit codes of the benchmarked programs."),
         )
+        .arg(
+            Arg::new("retries")
+                .long("retries")
+                .action(ArgAction::Set)
+                .value_name("NUM")
+                .help("Repeat a failed run (non-zero exit code or timeout) up to NUM times. Only \
+                       the time of the last attempt is included in the statistics. If all \
+                       attempts fail, the run counts as failed."),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Abort individual runs that take longer than DURATION (e.g. '30s', \
+                       '500ms' or '2min'; plain numbers are interpreted as seconds). The process \
+                       and all of its children are killed. Timed-out runs count as failed runs, \
+                       so '--ignore-failure' is required to continue with the benchmark. Their \
+                       exit code is reported as 'null' in the JSON export."),
+        )
+        .arg(
+            Arg::new("total-time-budget")
+                .long("total-time-budget")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Spend at most DURATION (e.g. '10min') on all benchmarks together. The \
+                       remaining time is divided among the remaining benchmarks, which reduces \
+                       their number of runs (but not below --min-runs). Benchmarks that can not \
+                       be started within the budget are skipped and listed in the JSON export."),
+        )
+        .arg(
+            Arg::new("max-benchmark-time")
+                .long("max-benchmark-time")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Stop performing timing runs of a benchmark once they have taken more than \
+                       DURATION (e.g. '2min') in total, even if fewer runs than requested have \
+                       been performed (but not fewer than --min-runs). Such benchmarks are \
+                       marked as 'truncated' in the JSON export."),
+        )
+        .arg(
+            Arg::new("max-benchmark-time-hard")
+                .long("max-benchmark-time-hard")
+                .action(ArgAction::SetTrue)
+                .requires("max-benchmark-time")
+                .help("Also stop before --min-runs have been performed if the \
+                       --max-benchmark-time has been exceeded. A warning is shown in that case."),
+        )
+        .arg(
+            Arg::new("assert")
+                .long("assert")
+                .action(ArgAction::Append)
+                .value_name("EXPR")
+                .help("Check a statistic of each benchmark against a threshold, like \
+                       'mean < 250ms' or 'p95 <= 400ms'. Available statistics are mean, median, \
+                       stddev, min, max, user, system and percentiles like p90. The option can be \
+                       specified multiple times. If an assertion does not hold, hyperfine still \
+                       finishes all benchmarks and exports, but exits with a non-zero exit code."),
+        )
         .arg(
             Arg::new("style")
                 .long("style")
                 .action(ArgAction::Set)
                 .value_name("TYPE")
-                .value_parser(["auto", "basic", "full", "nocolor", "color", "none"])
+                .value_parser(["auto", "basic", "full", "nocolor", "color", "ascii", "none"])
                 .help(
                     "Set output style type (default: auto). Set this to 'basic' to disable output \
                      coloring and interactive elements. Set it to 'full' to enable all effects \
                      even if no interactive terminal was detected. Set this to 'nocolor' to \
                      keep the interactive output without any colors. Set this to 'color' to keep \
-                     the colors without any interactive output. Set this to 'none' to disable all \
-                     the output of the tool.",
+                     the colors without any interactive output. Set this to 'ascii' to keep all \
+                     effects, but only use ASCII characters for the progress bar (which is also \
+                     done if the locale does not use UTF-8). Set this to 'none' to disable all \
+                     the output of the tool. Colors are disabled if the NO_COLOR environment \
+                     variable is set, unless a style is specified explicitly.",
+                ),
+        )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .global(true)
+                .action(ArgAction::Set)
+                .value_name("NAME")
+                .value_parser(["default", "light", "monochrome-bold", "colorblind"])
+                .help(
+                    "Choose the colors of the terminal output: 'default', 'light' (darker colors \
+                     for terminals with a light background), 'monochrome-bold' (no colors, but \
+                     the reference command, the results and the warnings are shown in bold) or \
+                     'colorblind' (blue and yellow instead of red and green). Colors are still \
+                     disabled with '--style=nocolor' or '--style=basic'. The theme also applies \
+                     to the 'compare' subcommand.",
+                ),
+        )
+        .arg(
+            Arg::new("progress-json")
+                .long("progress-json")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Instead of showing a progress bar, write the progress of the benchmarks as \
+                     JSON events to stderr, one per line. This is meant for programs that embed \
+                     hyperfine. Each event has an 'event' field with one of the types \
+                     'benchmark_started', 'warmup_started', 'warmup_run_completed', \
+                     'warmup_finished', 'measurement_started', 'run_completed' and \
+                     'benchmark_finished'.",
+                ),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Print additional information about the measurements, like the mean and \
+                     standard deviation of the shell spawning time that is subtracted from all \
+                     measured times, or the exact program and arguments that are executed for \
+                     each benchmark (including the shell). The shell spawning time is also \
+                     included in the metadata of the JSON export.",
                 ),
         )
         .arg(
@@ -242,7 +716,7 @@ fn build_command() -> Command {
             .long("sort")
             .action(ArgAction::Set)
             .value_name("METHOD")
-            .value_parser(["auto", "command", "mean-time"])
+            .value_parser(["auto", "command", "mean-time", "median-time", "max-time", "user-time"])
             .default_value("auto")
             .hide_default_value(true)
             .help(
@@ -251,19 +725,57 @@ fn build_command() -> Command {
                    * 'auto' (default): the speed comparison will be ordered by time and\n    \
                      the markup tables will be ordered by command (input order).\n  \
                    * 'command': order benchmarks in the way they were specified\n  \
-                   * 'mean-time': order benchmarks by mean runtime\n"
+                   * 'mean-time': order benchmarks by mean runtime\n  \
+                   * 'median-time': order benchmarks by median runtime\n  \
+                   * 'max-time': order benchmarks by maximum (worst-case) runtime\n  \
+                   * 'user-time': order benchmarks by mean user time\n\
+                 When ordering by median, maximum or user time, the relative speeds are \
+                 computed from that statistic as well.\n"
             ),
         )
+        .arg(
+            Arg::new("summary-format")
+                .long("summary-format")
+                .action(ArgAction::Set)
+                .value_name("FORMAT")
+                .value_parser(["ratio", "delta", "both"])
+                .help(
+                    "Specify how the commands are compared in the summary:\n  \
+                     * 'ratio' (default): by the ratio of the run times, like '2.05 ± 0.12 \
+                       times faster'.\n  \
+                     * 'delta': by the absolute difference of the run times, like '412.3 ms \
+                       faster'.\n  \
+                     * 'both': by the ratio, followed by the absolute difference.\n\
+                     The difference is shown in the unit given via '--time-unit'.",
+                ),
+        )
+        .arg(
+            Arg::new("compare")
+                .long("compare")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("repeat-suite")
+                .help(
+                    "Show the mean, median, min, max, user and system times of the first command \
+                     (or the one given via '--reference') side by side with the ones of every \
+                     other command, together with the relative change. This is done by default \
+                     if exactly two commands are benchmarked. It can not be combined with \
+                     '--repeat-suite'.",
+                ),
+        )
         .arg(
             Arg::new("time-unit")
                 .long("time-unit")
                 .short('u')
                 .action(ArgAction::Set)
                 .value_name("UNIT")
-                .value_parser(["microsecond", "millisecond", "second"])
-                .help("Set the time unit to be used. Possible values: microsecond, millisecond, second. \
+                .value_parser(["nanosecond", "microsecond", "millisecond", "second", "minute", "auto-per-row"])
+                .help("Set the time unit to be used. Possible values: nanosecond, microsecond, \
+                       millisecond, second, minute, auto-per-row. \
                        If the option is not given, the time unit is determined automatically. \
-                       This option affects the standard output as well as all export formats except for CSV and JSON."),
+                       This option affects the standard output as well as all export formats except for CSV and JSON. \
+                       With 'auto-per-row', every row of the markup tables is shown in its own unit \
+                       (like '2.1 ms' and '1.5 min'), which is then part of the cells instead of the \
+                       column headers. This is useful for commands with very different run times."),
         )
         .arg(
             Arg::new("export-asciidoc")
@@ -281,9 +793,39 @@ fn build_command() -> Command {
                 .value_name("FILE")
                 .value_hint(ValueHint::FilePath)
                 .help("Export the timing summary statistics as CSV to the given FILE. If you need \
-                       the timing results for each individual run, use the JSON export format. \
+                       the timing results for each individual run, use --export-csv-runs or the JSON export format. \
                        The output time unit is always seconds."),
         )
+        .arg(
+            Arg::new("export-csv-runs")
+                .long("export-csv-runs")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the timings of all individual runs as CSV to the given FILE, with \
+                       one row per run. The output time unit is always seconds."),
+        )
+        .arg(
+            Arg::new("csv-decimal-separator")
+                .long("csv-decimal-separator")
+                .action(ArgAction::Set)
+                .value_name("SEP")
+                .value_parser([".", ","])
+                .default_value(".")
+                .help("Decimal separator for the numbers in the CSV exports (--export-csv and \
+                       --export-csv-runs). If a comma is chosen, as expected by spreadsheet \
+                       applications in many locales, the fields are separated by semicolons."),
+        )
+        .arg(
+            Arg::new("export-html")
+                .long("export-html")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the timing summary statistics as a standalone HTML report (including \
+                       a bar chart of the mean run times) to the given FILE. The output time unit \
+                       can be changed using the --time-unit option."),
+        )
         .arg(
             Arg::new("export-json")
                 .long("export-json")
@@ -293,6 +835,45 @@ fn build_command() -> Command {
                 .help("Export the timing summary statistics and timings of individual runs as JSON to the given FILE. \
                        The output time unit is always seconds"),
         )
+        .arg(
+            Arg::new("no-metadata-host")
+                .long("no-metadata-host")
+                .action(ArgAction::SetTrue)
+                .help("Do not include the host name in the metadata of the JSON export."),
+        )
+        .arg(
+            Arg::new("export-json-lines")
+                .long("export-json-lines")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the timings of individual runs in the JSON Lines format to the given \
+                       FILE. Every run is appended as a single line with a JSON object as soon as \
+                       it has finished, such that no results are lost if hyperfine is interrupted. \
+                       The output time unit is always seconds."),
+        )
+        .arg(
+            Arg::new("export-junit")
+                .long("export-junit")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the results as a JUnit XML report to the given FILE, which CI \
+                       systems like Jenkins or GitLab can render natively. Every benchmark is a \
+                       test case whose time is the mean run time and whose statistics are \
+                       included as its output. A test case fails if one of the --assert \
+                       thresholds or the --junit-fail-above limit does not hold."),
+        )
+        .arg(
+            Arg::new("junit-fail-above")
+                .long("junit-fail-above")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .requires("export-junit")
+                .help("Mark the test cases of the --export-junit report as failed if the mean \
+                       time of the benchmark is above DURATION (like '250ms'). Unlike --assert, \
+                       this does not change the exit code of hyperfine."),
+        )
         .arg(
             Arg::new("export-markdown")
                 .long("export-markdown")
@@ -311,16 +892,261 @@ fn build_command() -> Command {
                 .help("Export the timing summary statistics as an Emacs org-mode table to the given FILE. \
                        The output time unit can be changed using the --time-unit option."),
         )
+        .arg(
+            Arg::new("show-cpu-time-in-exports")
+                .long("show-cpu-time-in-exports")
+                .action(ArgAction::SetTrue)
+                .help("Add columns with the mean user and system times to the tables of the \
+                       AsciiDoc, Markdown, org-mode and plain-text exports."),
+        )
+        .arg(
+            Arg::new("show-runs-in-exports")
+                .long("show-runs-in-exports")
+                .action(ArgAction::SetTrue)
+                .help("Add a column with the number of runs of every benchmark (without the \
+                       warmup runs) to the tables of the AsciiDoc, Markdown, org-mode and \
+                       plain-text exports."),
+        )
+        .arg(
+            Arg::new("export-parameter-columns")
+                .long("export-parameter-columns")
+                .action(ArgAction::SetTrue)
+                .help("Show the parameter values only in their own columns of the AsciiDoc, \
+                       Markdown, org-mode and plain-text exports. If all benchmarks use the \
+                       same command template, the command column contains the template (like \
+                       'make -j {threads}') instead of the substituted commands."),
+        )
+        .arg(
+            Arg::new("export-columns")
+                .long("export-columns")
+                .action(ArgAction::Set)
+                .value_name("COLUMNS")
+                .conflicts_with_all(["show-cpu-time-in-exports", "show-runs-in-exports"])
+                .help("Choose the columns of the tables of the AsciiDoc, Markdown, org-mode and \
+                       plain-text exports, as a comma-separated list of 'command', 'mean', \
+                       'stddev', 'median', 'min', 'max', 'relative', 'user', 'system', 'runs' \
+                       and the names of the parameters, in the order in which they are shown, \
+                       like 'command,median,relative'. The mean time includes the standard \
+                       deviation unless 'stddev' is shown in its own column."),
+        )
+        .arg(
+            Arg::new("max-export-samples")
+                .long("max-export-samples")
+                .action(ArgAction::Set)
+                .value_name("NUM")
+                .help("Export at most NUM run times for each benchmark (default: 10000). If more \
+                       runs have been performed, a uniform sample of them is exported and the \
+                       result is marked with 'times_subsampled'. The statistics are still \
+                       computed over all runs. Use '--max-export-samples 0' to export every \
+                       run time."),
+        )
+        .arg(
+            Arg::new("export-sql")
+                .long("export-sql")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the timing summary statistics and all individual run times as SQL \
+                       statements to the given FILE. The statements create the tables \
+                       'benchmarks' and 'runs' if necessary and insert one row per benchmark \
+                       and per run, respectively. All times are given in seconds."),
+        )
+        .arg(
+            Arg::new("export-toml")
+                .long("export-toml")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the timing summary statistics and timings of individual runs as TOML \
+                       to the given FILE, with a [[results]] table for each benchmark. The output \
+                       time unit is always seconds."),
+        )
+        .arg(
+            Arg::new("export-txt")
+                .long("export-txt")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the timing summary statistics as a plain-text table with aligned \
+                       columns to the given FILE, e.g. for pasting into a terminal or an e-mail. \
+                       The output time unit can be changed using the --time-unit option."),
+        )
+        .arg(
+            Arg::new("export-vega")
+                .long("export-vega")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export a Vega-Lite specification of a chart with a box plot and the \
+                       individual run times of each benchmark to the given FILE. The run times are \
+                       included in the specification, which can be opened in the Vega editor \
+                       (https://vega.github.io/editor). Parametrized benchmarks are shown in \
+                       separate rows for each parameter value. The output time unit can be \
+                       changed using the --time-unit option."),
+        )
         .arg(
             Arg::new("show-output")
                 .long("show-output")
-                .action(ArgAction::SetTrue)
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("prefixed")
+                .value_name("MODE")
+                .value_parser(["prefixed", "raw"])
                 .conflicts_with("style")
                 .help(
                     "Print the stdout and stderr of the benchmark instead of suppressing it. \
                      This will increase the time it takes for benchmarks to run, \
                      so it should only be used for debugging purposes or \
-                     when trying to benchmark output speed.",
+                     when trying to benchmark output speed.\n\
+                     With '--show-output' or '--show-output=prefixed', every line of output \
+                     is marked with the benchmark and the run, like '[cmd 2, run 7/10]', and \
+                     lines on stderr are colored. With '--show-output=raw', the commands write \
+                     to the terminal directly.",
+                ),
+        )
+        .arg(
+            Arg::new("throughput")
+                .long("throughput")
+                .action(ArgAction::Append)
+                .num_args(1)
+                .value_name("SIZE")
+                .help(
+                    "Show the throughput of each benchmark, given the SIZE that is processed by \
+                     a single run. SIZE is a number of bytes like '500MB' or '1.2GiB', or a plain \
+                     number of items. It may contain parameter placeholders like '{size}MB'. The \
+                     option can be specified once for all commands, or once for each command. \
+                     The throughput is included in the JSON and CSV exports.",
+                ),
+        )
+        .arg(
+            Arg::new("show-memory")
+                .long("show-memory")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the peak memory usage (maximum resident set size) of the benchmarked \
+                     command in the terminal output. The memory usage of each individual run is \
+                     always included in the JSON export.",
+                ),
+        )
+        .arg(
+            Arg::new("show-resource-usage")
+                .long("show-resource-usage")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the mean number of context switches and page faults of the benchmarked \
+                     command in the terminal output. The numbers for each individual run are \
+                     always included in the JSON export. Not available on Windows.",
+                ),
+        )
+        .arg(
+            Arg::new("show-cpu-frequency")
+                .long("show-cpu-frequency")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the mean, minimum and maximum CPU frequency that has been observed \
+                     before and after the runs in the terminal output. The frequency is always \
+                     included in the JSON export. Only available on Linux.",
+                ),
+        )
+        .arg(
+            Arg::new("histogram")
+                .long("histogram")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show a histogram of the run times below the statistics of each benchmark. \
+                     The bins are chosen according to the Freedman-Diaconis rule.",
+                ),
+        )
+        .arg(
+            Arg::new("show-live-stats")
+                .long("show-live-stats")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the current mean ± standard deviation, minimum, median and maximum \
+                     of the run times in the progress bar while a benchmark is running, \
+                     instead of just the current mean.",
+                ),
+        )
+        .arg(
+            Arg::new("confidence-interval")
+                .long("confidence-interval")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the 95% confidence interval of the mean run time, based on Student's \
+                     t-distribution. The bounds of the interval are always included in the JSON \
+                     export (ci_lower, ci_upper).",
+                ),
+        )
+        .arg(
+            Arg::new("show-cv")
+                .long("show-cv")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the coefficient of variation (the standard deviation divided by the \
+                     mean) of the run times next to the mean in the terminal output. It is always \
+                     included in the JSON and CSV exports (cv), along with the median absolute \
+                     deviation (mad).",
+                ),
+        )
+        .arg(
+            Arg::new("cv-warning-threshold")
+                .long("cv-warning-threshold")
+                .action(ArgAction::Set)
+                .value_name("PERCENT")
+                .help(
+                    "Show a warning if the coefficient of variation of the run times is above \
+                     the given percentage, like '--cv-warning-threshold 10%'. By default, no such \
+                     warning is shown.",
+                ),
+        )
+        .arg(
+            Arg::new("trim-outliers")
+                .long("trim-outliers")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Exclude statistical outliers (see '--outlier-threshold') from the reported \
+                     statistics and the relative speed comparison. All run times are still \
+                     exported.",
+                ),
+        )
+        .arg(
+            Arg::new("outlier-threshold")
+                .long("outlier-threshold")
+                .action(ArgAction::Set)
+                .value_name("Z")
+                .help(
+                    "Runs with an (absolute) modified Z-score above this threshold are considered \
+                     statistical outliers. They are listed in a warning and in the JSON export \
+                     (outliers, as indices into the run times). Default: 14.8.",
+                ),
+        )
+        .arg(
+            Arg::new("geometric-mean")
+                .long("geometric-mean")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Show the geometric mean of the run times relative to the reference in the \
+                     summary. This is done by default if more than two commands are benchmarked. \
+                     The value is always included in the JSON export \
+                     (summary.geometric_mean_relative_time).",
+                ),
+        )
+        .arg(
+            Arg::new("extract-time")
+                .long("extract-time")
+                .conflicts_with_all(["output", "show-output"])
+                .action(ArgAction::Set)
+                .value_name("REGEX")
+                .help(
+                    "Extract the time that the command reports itself (like 'compiled in \
+                     1.234s') from its output on stdout and stderr after each timing run. The \
+                     first capture group of the regular expression contains the number. An \
+                     optional capture group named 'unit' contains its unit (like 'ms'), \
+                     otherwise the number is a number of seconds. Example: \
+                     --extract-time 'compiled in ([0-9.]+) ?(?P<unit>ms|s)'. The reported \
+                     times are shown below the measured times and exported as 'reported_times' \
+                     in the JSON export. Runs without a match are exported as null.",
                 ),
         )
         .arg(
@@ -343,7 +1169,13 @@ fn build_command() -> Command {
                      \n  \
                        inherit:  Don't redirect the output at all (same as '--show-output').\n\
                      \n  \
-                       <FILE>:   Write the output to the given file.",
+                       <FILE>:   Write the output to the given file.\n\
+                     \n  \
+                       file:<PATH>:  Write the output of every timing run to a separate file. \
+                     <PATH> may contain '{run}' (the number of the run) and parameter placeholders, \
+                     like 'file:out/{threads}-{run}.txt'. The output on stderr is written to a \
+                     file with an additional '.stderr' extension. The files are opened before the \
+                     command is started. The output of warmup runs is discarded.",
                 ),
         )
         .arg(
@@ -360,6 +1192,44 @@ fn build_command() -> Command {
                        \n  \
                          <FILE>:   Read the input from the given file."),
         )
+        .arg(
+            Arg::new("env")
+                .long("env")
+                .action(ArgAction::Append)
+                .value_name("NAME=VALUE")
+                .help(
+                    "Set the environment variable NAME to VALUE for all commands (including \
+                     --prepare, --setup, etc.). This also works with '--shell=none'. The option \
+                     can be specified multiple times. The variables are recorded in the JSON \
+                     export.",
+                ),
+        )
+        .arg(
+            Arg::new("env-file")
+                .long("env-file")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help(
+                    "Read environment variables from FILE, with one NAME=VALUE pair per line. \
+                     Empty lines and lines starting with '#' are ignored. Variables specified \
+                     via --env take precedence.",
+                ),
+        )
+        .arg(
+            Arg::new("working-dir")
+                .long("working-dir")
+                .action(ArgAction::Set)
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .help(
+                    "Execute the benchmarked commands (and their --setup, --prepare, --conclude \
+                     and --cleanup commands) in the directory DIR. Parameter placeholders like \
+                     {dir} are substituted, such that the directory can be varied with \
+                     --parameter-list. The --setup-global command and the shell spawning time \
+                     calibration still run in the current directory.",
+                ),
+        )
         .arg(
             Arg::new("command-name")
                 .long("command-name")
@@ -370,23 +1240,54 @@ fn build_command() -> Command {
                 .help("Give a meaningful name to a command. This can be specified multiple times \
                        if several commands are benchmarked."),
         )
-        // This option is hidden for now, as it is not yet clear yet if we want to 'stabilize' this,
-        // see discussion in https://github.com/sharkdp/hyperfine/issues/527
         .arg(
-            Arg::new("min-benchmarking-time")
-            .long("min-benchmarking-time")
-            .action(ArgAction::Set)
-            .hide(true)
-            .help("Set the minimum time (in seconds) to run benchmarks. Note that the number of \
-                   benchmark runs is additionally influenced by the `--min-runs`, `--max-runs`, and \
-                   `--runs` option.")
+            Arg::new("command-file")
+                .long("command-file")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Read additional commands to benchmark from FILE (use '-' for stdin), \
+                       one command per line. Blank lines and lines starting with '#' are \
+                       ignored. The commands are benchmarked after the ones given on the \
+                       command line and support the same parameter placeholders."),
+        )
+        .arg(
+            Arg::new("suite")
+                .long("suite")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .conflicts_with_all([
+                    "command",
+                    "command-file",
+                    "command-name",
+                    "parameter-scan",
+                    "parameter-list",
+                ])
+                .help("Read the benchmarks from a TOML or JSON suite FILE instead of the command \
+                       line. Every entry of its 'benchmarks' list has a 'command' and (optionally) \
+                       a 'name', 'parameters' (lists of values for each parameter name) and its \
+                       own 'setup', 'prepare', 'conclude', 'cleanup', 'runs' and 'warmup'. The \
+                       corresponding options on the command line are used for the benchmarks \
+                       that do not specify them."),
+        )
+        .arg(
+            Arg::new("command-names-file")
+                .long("command-names-file")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .requires("command-file")
+                .help("Read command names from FILE (use '-' for stdin), one name per line. \
+                       The names are used as if each of them had been passed via \
+                       '--command-name', after the ones given on the command line."),
         )
         .arg(
             Arg::new("debug-mode")
             .long("debug-mode")
             .action(ArgAction::SetTrue)
             .hide(true)
-            .help("Enable debug mode which does not actually run commands, but returns fake times when the command is 'sleep <time>'.")
+            .help("Enable debug mode which does not actually run commands, but returns fake times when the command is 'sleep <time>' (or 'sleep <time> <time>...' for times that are used in turn).")
         )
 }
 
@@ -394,3 +1295,13 @@ fn build_command() -> Command {
 fn verify_app() {
     build_command().debug_assert();
 }
+
+#[test]
+fn theme_applies_to_the_compare_subcommand() {
+    let matches = get_cli_arguments(["hyperfine", "compare", "--theme=light", "a.json", "b.json"]);
+    let compare = matches.subcommand_matches("compare").unwrap();
+    assert_eq!(
+        compare.get_one::<String>("theme").map(String::as_str),
+        Some("light")
+    );
+}
@@ -1,15 +1,22 @@
 use std::collections::BTreeMap;
 use std::fmt;
+use std::io::Read;
+use std::ops::Mul;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use crate::parameter::format::ParameterFormat;
 use crate::parameter::tokenize::tokenize;
 use crate::parameter::ParameterValue;
+use crate::suite::Suite;
 use crate::{
     error::{OptionsError, ParameterScanError},
+    output::format::single_line,
     parameter::{
-        range_step::{Numeric, RangeStep},
+        range_step::{Numeric, RangeMultiply, RangeStep},
         ParameterNameAndValue,
     },
+    util::number::{Number, SuffixedInteger},
 };
 
 use clap::{parser::ValuesRef, ArgMatches};
@@ -17,6 +24,10 @@ use clap::{parser::ValuesRef, ArgMatches};
 use anyhow::{bail, Context, Result};
 use rust_decimal::Decimal;
 
+/// Name of the pseudo-parameter under which the shell of a benchmark is shown and exported, if
+/// `--shell` is given multiple times
+pub const SHELL_PARAMETER: &str = "shell";
+
 /// A command that should be benchmarked.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Command<'a> {
@@ -28,6 +39,13 @@ pub struct Command<'a> {
 
     /// Zero or more parameter values.
     parameters: Vec<ParameterNameAndValue<'a>>,
+
+    /// The directory in which the command is executed (without parameter substitution)
+    working_dir: Option<&'a str>,
+
+    /// The one of several shells with which the command is run. It is not a placeholder, but
+    /// it is shown and exported like a parameter.
+    shell: Option<String>,
 }
 
 impl<'a> Command<'a> {
@@ -36,6 +54,8 @@ impl<'a> Command<'a> {
             name,
             expression,
             parameters: Vec::new(),
+            working_dir: None,
+            shell: None,
         }
     }
 
@@ -48,9 +68,46 @@ impl<'a> Command<'a> {
             name,
             expression,
             parameters: parameters.into_iter().collect(),
+            working_dir: None,
+            shell: None,
         }
     }
 
+    /// Execute the command in the given directory, which may contain parameter placeholders
+    pub fn with_working_dir(mut self, working_dir: Option<&'a str>) -> Command<'a> {
+        self.working_dir = working_dir;
+        self
+    }
+
+    /// Run the command with the given one of several shells, which is shown and exported as the
+    /// `SHELL_PARAMETER`
+    pub fn with_shell(mut self, shell: &str) -> Command<'a> {
+        self.shell = Some(shell.to_string());
+        self
+    }
+
+    /// The `--shell` with which this command is run if several shells are compared
+    pub fn get_shell(&self) -> Option<String> {
+        self.shell.clone()
+    }
+
+    /// The shell as a pseudo-parameter, if several shells are compared
+    fn shell_parameter(&self) -> Option<ParameterNameAndValue<'a>> {
+        self.shell
+            .clone()
+            .map(|shell| (SHELL_PARAMETER, ParameterValue::Text(shell)))
+    }
+
+    /// Create a command like `--prepare` or `--cleanup` that belongs to this benchmark. It uses
+    /// the same parameter values and the same working directory.
+    pub fn related_command<'b>(&self, expression: &'b str) -> Command<'b>
+    where
+        'a: 'b,
+    {
+        Command::new_parametrized(None, expression, self.parameters.iter().cloned())
+            .with_working_dir(self.working_dir)
+    }
+
     pub fn get_name(&self) -> String {
         self.name.map_or_else(
             || self.get_command_line(),
@@ -58,6 +115,8 @@ impl<'a> Command<'a> {
         )
     }
 
+    /// The name, followed by the values of all parameters that do not appear in it. This is
+    /// shown on a single line, so line breaks are replaced by a marker (see `single_line`).
     pub fn get_name_with_unused_parameters(&self) -> String {
         let parameters = self
             .get_unused_parameters()
@@ -71,7 +130,12 @@ impl<'a> Command<'a> {
             format!(" ({parameters})")
         };
 
-        format!("{}{}", self.get_name(), parameters)
+        single_line(&format!("{}{}", self.get_name(), parameters)).into_owned()
+    }
+
+    /// The name (or the command line) without parameter substitution, like `sleep {time}`
+    pub fn get_template(&self) -> String {
+        self.name.unwrap_or(self.expression).to_string()
     }
 
     pub fn get_command_line(&self) -> String {
@@ -87,41 +151,193 @@ impl<'a> Command<'a> {
         if let Some(program_name) = tokens.next() {
             let mut command_builder = std::process::Command::new(program_name);
             command_builder.args(tokens);
+            if let Some(working_dir) = self.get_working_dir() {
+                command_builder.current_dir(working_dir);
+            }
             Ok(command_builder)
         } else {
             bail!("Can not execute empty command")
         }
     }
 
-    pub fn get_parameters(&self) -> &[(&'a str, ParameterValue)] {
-        &self.parameters
+    /// The directory in which the command is executed, with all parameters substituted (`None`
+    /// for the current directory)
+    pub fn get_working_dir(&self) -> Option<String> {
+        self.working_dir
+            .map(|working_dir| self.replace_parameters_in(working_dir))
+    }
+
+    /// The path of the file to which the output of the given timing run is written
+    /// (`--output=file:<path>`). `{run}` and all parameters are substituted in the template.
+    pub fn get_output_path(&self, template: &str, run: usize) -> PathBuf {
+        PathBuf::from(
+            self.replace_parameters_in(template)
+                .replace("{run}", &run.to_string()),
+        )
     }
 
-    pub fn get_unused_parameters(&self) -> impl Iterator<Item = &(&'a str, ParameterValue)> {
+    /// All parameter values, followed by the shell if several shells are compared
+    pub fn get_parameters(&self) -> Vec<ParameterNameAndValue<'a>> {
         self.parameters
             .iter()
-            .filter(move |(parameter, _)| !self.expression.contains(&format!("{{{parameter}}}")))
+            .cloned()
+            .chain(self.shell_parameter())
+            .collect()
+    }
+
+    /// The parameters that do not appear in the command, and the shell (which never does)
+    pub fn get_unused_parameters(&self) -> impl Iterator<Item = ParameterNameAndValue<'a>> + '_ {
+        self.parameters
+            .iter()
+            .filter(move |(parameter, _)| {
+                !self.expression.contains(&format!("{{{parameter}}}"))
+                    && !self.expression.contains(&format!("{{{parameter}:"))
+            })
+            .cloned()
+            .chain(self.shell_parameter())
+    }
+
+    /// Make sure that all format specifications of placeholders like `{n:03}` (in the command
+    /// and its name) are valid for the respective parameter values
+    pub fn validate_parameter_formats(&self) -> Result<(), OptionsError<'static>> {
+        for text in self.name.iter().chain([&self.expression]) {
+            for (param_name, param_value) in &self.parameters {
+                let prefix = format!("{{{param_name}:");
+                for (start, _) in text.match_indices(&prefix) {
+                    let rest = &text[start + prefix.len()..];
+                    let spec = &rest[..rest.find('}').unwrap_or(rest.len())];
+                    let format = spec.parse::<ParameterFormat>().map_err(|_| {
+                        OptionsError::InvalidParameterFormat(param_name.to_string(), spec.into())
+                    })?;
+                    if format.apply(param_value).is_none() {
+                        return Err(OptionsError::NonNumericParameterFormat(
+                            param_name.to_string(),
+                            spec.into(),
+                            param_value.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the placeholders like `{name}` or `{name:03}` in the given text that do not
+    /// belong to any of the parameters of this command. Braces after a `$` are not considered,
+    /// since they are most likely shell variables like `${HOME}`.
+    fn unknown_placeholders<'t>(&self, text: &'t str) -> Vec<&'t str> {
+        let mut unknown = vec![];
+        for (start, _) in text.match_indices('{') {
+            if text[..start].ends_with('$') {
+                continue;
+            }
+            let rest = &text[start + 1..];
+            let length = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let (name, rest) = rest.split_at(length);
+            if name.is_empty() || !(rest.starts_with('}') || rest.starts_with(':')) {
+                continue;
+            }
+            if !self
+                .parameters
+                .iter()
+                .any(|(param_name, _)| *param_name == name)
+                && !unknown.contains(&name)
+            {
+                unknown.push(name);
+            }
+        }
+        unknown
+    }
+
+    /// Make sure that all placeholders in the command, its name, its working directory and the
+    /// given related commands (like `--prepare`) correspond to one of the parameters. Commands
+    /// without any parameters are not checked, since their braces can not be placeholders.
+    pub fn validate_placeholders<'t>(
+        &self,
+        related_commands: impl IntoIterator<Item = &'t str>,
+    ) -> Result<(), OptionsError<'static>>
+    where
+        'a: 't,
+    {
+        if self.parameters.is_empty() {
+            return Ok(());
+        }
+
+        let texts = [Some(self.expression), self.name, self.working_dir];
+        for text in texts.iter().flatten().copied().chain(related_commands) {
+            let unknown = self.unknown_placeholders(text);
+            if !unknown.is_empty() {
+                let list = |names: &mut dyn Iterator<Item = &str>| {
+                    names
+                        .map(|name| format!("'{{{name}}}'"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                return Err(OptionsError::UnknownPlaceholders(
+                    text.to_string(),
+                    list(&mut unknown.into_iter()),
+                    list(&mut self.parameters.iter().map(|(name, _)| *name)),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Make sure that the working directory of the command exists
+    pub fn validate_working_dir(&self) -> Result<(), OptionsError<'static>> {
+        match self.get_working_dir() {
+            Some(working_dir) if !Path::new(&working_dir).is_dir() => {
+                if self.parameters.is_empty() {
+                    Err(OptionsError::WorkingDirectoryNotFound(working_dir))
+                } else {
+                    let parameters = self
+                        .parameters
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    Err(OptionsError::WorkingDirectoryNotFoundForParameters(
+                        working_dir,
+                        parameters,
+                    ))
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// If the given string starts with a placeholder for one of the parameters, like `{n}` or
+    /// `{n:03}`, return its replacement and the length of the placeholder
+    fn replacement_at(&self, s: &str) -> Option<(String, usize)> {
+        let inner = s.strip_prefix('{')?;
+        self.parameters
+            .iter()
+            .find_map(|(param_name, param_value)| {
+                let rest = inner.strip_prefix(param_name)?;
+                if rest.starts_with('}') {
+                    return Some((param_value.to_string(), param_name.len() + 2));
+                }
+                let (spec, _) = rest.strip_prefix(':')?.split_once('}')?;
+                let replacement = spec.parse::<ParameterFormat>().ok()?.apply(param_value)?;
+                Some((replacement, param_name.len() + spec.len() + 3))
+            })
     }
 
     fn replace_parameters_in(&self, original: &str) -> String {
         let mut result = String::new();
-        let mut replacements = BTreeMap::<String, String>::new();
-        for (param_name, param_value) in &self.parameters {
-            replacements.insert(format!("{{{param_name}}}"), param_value.to_string());
-        }
         let mut remaining = original;
         // Manually replace consecutive occurrences to avoid double-replacing: e.g.,
         //
         //     hyperfine -L foo 'a,{bar}' -L bar 'baz,quux' 'echo {foo} {bar}'
         //
         // should not ever run 'echo baz baz'. See `test_get_command_line_nonoverlapping`.
-        'outer: while let Some(head) = remaining.chars().next() {
-            for (k, v) in &replacements {
-                if remaining.starts_with(k.as_str()) {
-                    result.push_str(v);
-                    remaining = &remaining[k.len()..];
-                    continue 'outer;
-                }
+        while let Some(head) = remaining.chars().next() {
+            if let Some((replacement, length)) = self.replacement_at(remaining) {
+                result.push_str(&replacement);
+                remaining = &remaining[length..];
+                continue;
             }
             result.push(head);
             remaining = &remaining[head.len_utf8()..];
@@ -130,15 +346,162 @@ impl<'a> Command<'a> {
     }
 }
 
+/// Commands and command names that have been read from the files given via `--command-file`
+/// and `--command-names-file`. They are appended to the ones given on the command line.
+#[derive(Debug, Default)]
+pub struct CommandFileEntries {
+    commands: Vec<String>,
+    names: Vec<String>,
+}
+
+impl CommandFileEntries {
+    pub fn from_cli_arguments(matches: &ArgMatches) -> Result<CommandFileEntries> {
+        let command_file = matches.get_one::<String>("command-file");
+        let names_file = matches.get_one::<String>("command-names-file");
+
+        if command_file.map(|s| s.as_str()) == Some("-")
+            && names_file.map(|s| s.as_str()) == Some("-")
+        {
+            bail!("Only one of '--command-file' and '--command-names-file' can be read from stdin");
+        }
+
+        Ok(CommandFileEntries {
+            commands: command_file.map_or(Ok(vec![]), |path| Self::read_entries(path))?,
+            names: names_file.map_or(Ok(vec![]), |path| Self::read_entries(path))?,
+        })
+    }
+
+    /// Read the entries of the given file, or of stdin if the path is '-'.
+    fn read_entries(path: &str) -> Result<Vec<String>> {
+        let content = if path == "-" {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .context("Failed to read commands from stdin")?;
+            content
+        } else {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read commands from file '{path}'"))?
+        };
+        Ok(Self::parse_entries(&content))
+    }
+
+    /// Every line is an entry, except for blank lines and lines starting with '#'.
+    fn parse_entries(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.to_string())
+            .collect()
+    }
+}
+
 /// A collection of commands that should be benchmarked
-pub struct Commands<'a>(Vec<Command<'a>>);
+pub struct Commands<'a> {
+    commands: Vec<Command<'a>>,
+
+    /// Groups of indices of commands that are identical, see `duplicates`
+    duplicates: Vec<Vec<usize>>,
+
+    /// Whether all but the first command of each group have been removed
+    deduplicated: bool,
+
+    /// Positions of the commands in the list of all commands after the parameter expansion,
+    /// which only differ from their indices after a `deduplicate` call
+    expanded_indices: Vec<usize>,
+}
+
+impl<'a> From<Vec<Command<'a>>> for Commands<'a> {
+    fn from(commands: Vec<Command<'a>>) -> Self {
+        let duplicates = Self::find_duplicate_commands(&commands);
+        Self {
+            expanded_indices: (0..commands.len()).collect(),
+            commands,
+            duplicates,
+            deduplicated: false,
+        }
+    }
+}
 
 impl<'a> Commands<'a> {
-    pub fn from_cli_arguments(matches: &'a ArgMatches) -> Result<Commands> {
-        let command_names = matches.get_many::<String>("command-name");
+    pub fn from_cli_arguments(
+        matches: &'a ArgMatches,
+        file_entries: &'a CommandFileEntries,
+    ) -> Result<Commands<'a>> {
+        Self::with_cli_arguments(
+            Self::build_from_cli_arguments(matches, file_entries)?,
+            matches,
+        )
+    }
+
+    /// Build the commands from the benchmarks of a suite (`--suite`) instead of the command line
+    pub fn from_suite(matches: &'a ArgMatches, suite: &'a Suite) -> Result<Commands<'a>> {
+        Self::with_cli_arguments(suite.commands(), matches)
+    }
+
+    /// Apply the options of the command line that affect the commands themselves, like
+    /// `--working-dir` and `--deduplicate`
+    fn with_cli_arguments(
+        commands: Vec<Command<'a>>,
+        matches: &'a ArgMatches,
+    ) -> Result<Commands<'a>> {
+        let working_dir = matches.get_one::<String>("working-dir").map(|s| s.as_str());
+        let commands = commands
+            .into_iter()
+            .map(|command| command.with_working_dir(working_dir));
+
+        // With several shells, each command is benchmarked with every one of them
+        let shells: Vec<&String> = matches.get_many("shell").unwrap_or_default().collect();
+        let mut commands = Self::from(if shells.len() > 1 {
+            let commands: Vec<Command> = commands.collect();
+            let uses_shell_parameter = |command: &Command| {
+                command
+                    .parameters
+                    .iter()
+                    .any(|(name, _)| *name == SHELL_PARAMETER)
+            };
+            if commands.iter().any(uses_shell_parameter) {
+                bail!(
+                    "The parameter name '{SHELL_PARAMETER}' is reserved for the shell if \
+                     '--shell' is given multiple times"
+                );
+            }
+            commands
+                .into_iter()
+                .flat_map(|command| {
+                    shells
+                        .iter()
+                        .map(move |shell| command.clone().with_shell(shell))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            commands.collect::<Vec<_>>()
+        });
+        for command in commands.iter() {
+            command.validate_parameter_formats()?;
+            command.validate_working_dir()?;
+        }
+        if matches.get_flag("deduplicate") {
+            commands.deduplicate();
+        }
+        Ok(commands)
+    }
+
+    fn build_from_cli_arguments(
+        matches: &'a ArgMatches,
+        file_entries: &'a CommandFileEntries,
+    ) -> Result<Vec<Command<'a>>> {
+        let command_names = matches
+            .get_many::<String>("command-name")
+            .unwrap_or_default()
+            .chain(file_entries.names.iter())
+            .map(|v| v.as_str())
+            .collect::<Vec<_>>();
         let command_strings = matches
             .get_many::<String>("command")
             .unwrap_or_default()
+            .chain(file_entries.commands.iter())
             .map(|v| v.as_str())
             .collect::<Vec<_>>();
 
@@ -146,16 +509,18 @@ impl<'a> Commands<'a> {
             let step_size = matches
                 .get_one::<String>("parameter-step-size")
                 .map(|s| s.as_str());
-            Ok(Self(Self::get_parameter_scan_commands(
+            let multiplicative = matches
+                .get_one::<String>("parameter-step-mode")
+                .map(|mode| mode == "multiply");
+            Ok(Self::get_parameter_scan_commands(
                 command_names,
                 command_strings,
                 args,
                 step_size,
-            )?))
+                multiplicative,
+                matches.get_flag("parameter-expand-suffix"),
+            )?)
         } else if let Some(args) = matches.get_many::<String>("parameter-list") {
-            let command_names = command_names.map_or(vec![], |names| {
-                names.map(|v| v.as_str()).collect::<Vec<_>>()
-            });
             let args: Vec<_> = args.map(|v| v.as_str()).collect::<Vec<_>>();
             let param_names_and_values: Vec<(&str, Vec<String>)> = args
                 .chunks_exact(2)
@@ -173,16 +538,37 @@ impl<'a> Commands<'a> {
                 }
             }
 
+            // In zip mode, the i-th values of all parameter lists form a single dimension
+            let zip = matches
+                .get_one::<String>("parameter-mode")
+                .is_some_and(|mode| mode == "zip");
+            if zip {
+                let (first_name, first_values) = &param_names_and_values[0];
+                if let Some((name, values)) = param_names_and_values
+                    .iter()
+                    .find(|(_, values)| values.len() != first_values.len())
+                {
+                    return Err(OptionsError::ParameterListLengthMismatch(
+                        first_name.to_string(),
+                        first_values.len(),
+                        name.to_string(),
+                        values.len(),
+                    )
+                    .into());
+                }
+            }
+
             let dimensions: Vec<usize> = std::iter::once(command_strings.len())
                 .chain(
                     param_names_and_values
                         .iter()
-                        .map(|(_, values)| values.len()),
+                        .map(|(_, values)| values.len())
+                        .take(if zip { 1 } else { usize::MAX }),
                 )
                 .collect();
             let param_space_size = dimensions.iter().product();
             if param_space_size == 0 {
-                return Ok(Self(Vec::new()));
+                return Ok(Vec::new());
             }
 
             // `--command-name` should appear exactly once or exactly B times,
@@ -209,7 +595,7 @@ impl<'a> Commands<'a> {
                 let (command_index, params_indices) = index.split_first().unwrap();
                 let parameters: Vec<_> = param_names_and_values
                     .iter()
-                    .zip(params_indices)
+                    .zip(params_indices.iter().cycle())
                     .map(|((name, values), i)| (*name, ParameterValue::Text(values[*i].clone())))
                     .collect();
                 commands.push(Command::new_parametrized(
@@ -230,11 +616,8 @@ impl<'a> Commands<'a> {
                 break 'outer;
             }
 
-            Ok(Self(commands))
+            Ok(commands)
         } else {
-            let command_names = command_names.map_or(vec![], |names| {
-                names.map(|v| v.as_str()).collect::<Vec<_>>()
-            });
             if command_names.len() > command_strings.len() {
                 return Err(OptionsError::TooManyCommandNames(command_strings.len()).into());
             }
@@ -243,16 +626,96 @@ impl<'a> Commands<'a> {
             for (i, s) in command_strings.iter().enumerate() {
                 commands.push(Command::new(command_names.get(i).copied(), s));
             }
-            Ok(Self(commands))
+            Ok(commands)
         }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &Command<'a>> {
-        self.0.iter()
+        self.commands.iter()
     }
 
     pub fn num_commands(&self) -> usize {
-        self.0.len()
+        self.commands.len()
+    }
+
+    /// The commands together with their positions in the list of all commands after the
+    /// parameter expansion, which the per-command options (like `--prepare`) refer to
+    pub fn iter_with_expanded_index(&self) -> impl Iterator<Item = (usize, &Command<'a>)> {
+        self.expanded_indices.iter().copied().zip(&self.commands)
+    }
+
+    /// Number of commands after the parameter expansion, including the ones that have been
+    /// removed by a `deduplicate` call
+    pub fn num_expanded_commands(&self) -> usize {
+        self.commands.len()
+            + self
+                .duplicates
+                .iter()
+                .filter(|_| self.deduplicated)
+                .map(|group| group.len() - 1)
+                .sum::<usize>()
+    }
+
+    /// Groups of indices of commands with identical command lines (and working directories and
+    /// shells), which only differ by their names, if at all. The indices refer to the list of
+    /// commands before a `deduplicate` call.
+    pub fn duplicates(&self) -> &[Vec<usize>] {
+        &self.duplicates
+    }
+
+    /// Whether the duplicate commands have been removed (`--deduplicate`)
+    pub fn is_deduplicated(&self) -> bool {
+        self.deduplicated
+    }
+
+    /// Remove all but the first command of each group of duplicates
+    pub fn deduplicate(&mut self) {
+        if self.deduplicated {
+            return;
+        }
+        let removed: Vec<usize> = self
+            .duplicates
+            .iter()
+            .flat_map(|group| group[1..].iter().copied())
+            .collect();
+        self.commands = std::mem::take(&mut self.commands)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !removed.contains(index))
+            .map(|(_, command)| command)
+            .collect();
+        self.expanded_indices
+            .retain(|index| !removed.contains(index));
+        self.deduplicated = true;
+    }
+
+    fn find_duplicate_commands(commands: &[Command]) -> Vec<Vec<usize>> {
+        let mut groups = BTreeMap::<(String, Option<String>, Option<String>), Vec<usize>>::new();
+        for (index, command) in commands.iter().enumerate() {
+            groups
+                .entry((
+                    command.get_command_line(),
+                    command.get_working_dir(),
+                    command.get_shell(),
+                ))
+                .or_default()
+                .push(index);
+        }
+        let mut duplicates: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        duplicates.sort_unstable();
+        duplicates
+    }
+
+    /// Find the first command whose name (given via `--command-name`) is equal to the given
+    /// string. Command lines are not matched, such that a `--reference` command is still
+    /// benchmarked separately, even if it is also part of the command list.
+    pub fn position_of(&self, name: &str) -> Option<usize> {
+        self.commands
+            .iter()
+            .position(|cmd| cmd.name.is_some() && cmd.get_name() == name)
     }
 
     /// Finds all the strings that appear multiple times in the input iterator, returning them in
@@ -268,16 +731,30 @@ impl<'a> Commands<'a> {
             .collect()
     }
 
-    fn build_parameter_scan_commands<'b, T: Numeric>(
-        param_name: &'b str,
-        param_min: T,
-        param_max: T,
+    /// Values of a parameter scan from `min` to `max`. Each value is obtained by adding `step` to
+    /// the previous one, or by multiplying it with `step` for multiplicative scans.
+    fn parameter_scan_values<T: Numeric + Mul<Output = T>>(
+        min: T,
+        max: T,
         step: T,
+        multiplicative: bool,
+    ) -> Result<Vec<Number>, ParameterScanError> {
+        Ok(if multiplicative {
+            RangeMultiply::new(min, max, step)?
+                .map(Into::into)
+                .collect()
+        } else {
+            RangeStep::new(min, max, step)?.map(Into::into).collect()
+        })
+    }
+
+    fn build_parameter_scan_commands<'b>(
+        param_name: &'b str,
+        param_values: Vec<Number>,
         command_names: Vec<&'b str>,
         command_strings: Vec<&'b str>,
     ) -> Result<Vec<Command<'b>>, ParameterScanError> {
-        let param_range = RangeStep::new(param_min, param_max, step)?;
-        let param_count = param_range.size_hint().1.unwrap();
+        let param_count = param_values.len();
         let command_name_count = command_names.len();
 
         // `--command-name` should appear exactly once or exactly B times,
@@ -291,7 +768,7 @@ impl<'a> Commands<'a> {
 
         let mut i = 0;
         let mut commands = vec![];
-        for value in param_range {
+        for value in param_values {
             for cmd in &command_strings {
                 let name = command_names
                     .get(i)
@@ -300,7 +777,7 @@ impl<'a> Commands<'a> {
                 commands.push(Command::new_parametrized(
                     name,
                     cmd,
-                    vec![(param_name, ParameterValue::Numeric(value.into()))],
+                    vec![(param_name, ParameterValue::Numeric(value))],
                 ));
                 i += 1;
             }
@@ -309,48 +786,72 @@ impl<'a> Commands<'a> {
     }
 
     fn get_parameter_scan_commands<'b>(
-        command_names: Option<ValuesRef<'b, String>>,
+        command_names: Vec<&'b str>,
         command_strings: Vec<&'b str>,
         mut vals: ValuesRef<'b, String>,
         step: Option<&str>,
+        multiplicative: Option<bool>,
+        expand_suffix: bool,
     ) -> Result<Vec<Command<'b>>, ParameterScanError> {
-        let command_names = command_names.map_or(vec![], |names| {
-            names.map(|v| v.as_str()).collect::<Vec<_>>()
-        });
         let param_name = vals.next().unwrap().as_str();
         let param_min = vals.next().unwrap().as_str();
         let param_max = vals.next().unwrap().as_str();
 
-        // attempt to parse as integers
-        if let (Ok(param_min), Ok(param_max), Ok(step)) = (
+        // A step like '2x' (or 'x2') is a shorthand for a multiplicative scan
+        let factor = step.and_then(|s| s.strip_suffix('x').or_else(|| s.strip_prefix('x')));
+        if factor.is_some() && multiplicative == Some(false) {
+            return Err(ParameterScanError::FactorWithAdditiveStepMode);
+        }
+        let multiplicative = multiplicative.unwrap_or(false) || factor.is_some();
+        let step = factor.or(step);
+        if multiplicative && step.is_none() {
+            return Err(ParameterScanError::FactorRequired);
+        }
+
+        let param_values = if [Some(param_min), Some(param_max), step]
+            .iter()
+            .flatten()
+            .any(|value| SuffixedInteger::has_suffix(value))
+        {
+            // integers with a suffix like '64K'
+            Self::parameter_scan_values(
+                param_min.parse::<SuffixedInteger>()?,
+                param_max.parse::<SuffixedInteger>()?,
+                step.unwrap_or("1").parse::<SuffixedInteger>()?,
+                multiplicative,
+            )?
+        } else if let (Ok(param_min), Ok(param_max), Ok(step)) = (
+            // attempt to parse as integers
             param_min.parse::<i32>(),
             param_max.parse::<i32>(),
             step.unwrap_or("1").parse::<i32>(),
         ) {
-            return Self::build_parameter_scan_commands(
-                param_name,
-                param_min,
-                param_max,
-                step,
-                command_names,
-                command_strings,
-            );
-        }
+            Self::parameter_scan_values(param_min, param_max, step, multiplicative)?
+        } else {
+            // try parsing them as decimals
+            let param_min = Decimal::from_str(param_min)?;
+            let param_max = Decimal::from_str(param_max)?;
+
+            if step.is_none() {
+                return Err(ParameterScanError::StepRequired);
+            }
 
-        // try parsing them as decimals
-        let param_min = Decimal::from_str(param_min)?;
-        let param_max = Decimal::from_str(param_max)?;
+            let step = Decimal::from_str(step.unwrap())?;
+            Self::parameter_scan_values(param_min, param_max, step, multiplicative)?
+        };
 
-        if step.is_none() {
-            return Err(ParameterScanError::StepRequired);
-        }
+        let param_values = if expand_suffix {
+            param_values
+                .into_iter()
+                .map(Number::expand_suffix)
+                .collect()
+        } else {
+            param_values
+        };
 
-        let step = Decimal::from_str(step.unwrap())?;
         Self::build_parameter_scan_commands(
             param_name,
-            param_min,
-            param_max,
-            step,
+            param_values,
             command_names,
             command_strings,
         )
@@ -383,6 +884,49 @@ fn test_get_parameterized_command_name() {
     assert_eq!(cmd.get_name(), "name-quux-baz");
 }
 
+#[test]
+fn test_validate_placeholders() {
+    let cmd = Command::new_parametrized(
+        None,
+        "make -j{threads} {target:03}",
+        vec![
+            ("threads", ParameterValue::Text("4".into())),
+            ("target", ParameterValue::Text("1".into())),
+        ],
+    );
+    assert!(cmd
+        .validate_placeholders(["echo {threads}", "awk '{ print }'", "echo ${HOME}"])
+        .is_ok());
+
+    let error = cmd
+        .validate_placeholders(["rm -rf build-{thread}-{x:02}-{thread}"])
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unknown parameter placeholder(s) '{thread}', '{x}' in 'rm -rf build-{thread}-{x:02}-{thread}'. \
+         The known parameters are: '{threads}', '{target}'. Use '--allow-unknown-placeholders' if \
+         the braces are not meant as placeholders"
+    );
+
+    // Commands without parameters are not checked
+    assert!(Command::new(None, "awk '{print}'")
+        .validate_placeholders([])
+        .is_ok());
+}
+
+#[test]
+fn test_get_output_path() {
+    let cmd = Command::new_parametrized(
+        None,
+        "gzip -{level} data",
+        vec![("level", ParameterValue::Text("9".into()))],
+    );
+    assert_eq!(
+        cmd.get_output_path("out/level-{level}/{run}.txt", 3),
+        PathBuf::from("out/level-9/3.txt")
+    );
+}
+
 impl<'a> fmt::Display for Command<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.get_command_line())
@@ -404,7 +948,10 @@ fn test_build_commands_cross_product() {
         "echo {par1} {par2}",
         "printf '%s\n' {par1} {par2}",
     ]);
-    let result = Commands::from_cli_arguments(&matches).unwrap().0;
+    let file_entries = CommandFileEntries::default();
+    let result = Commands::from_cli_arguments(&matches, &file_entries)
+        .unwrap()
+        .commands;
 
     // Iteration order: command list first, then parameters in listed order (here, "par1" before
     // "par2", which is distinct from their sorted order), with parameter values in listed order.
@@ -427,6 +974,51 @@ fn test_build_commands_cross_product() {
     assert_eq!(result, expected);
 }
 
+#[test]
+fn test_build_zipped_parameter_list_commands() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--parameter-mode=zip",
+        "-L",
+        "file",
+        "a.txt,b.txt",
+        "-L",
+        "lines",
+        "10,20",
+        "wc -l {file} {lines}",
+    ]);
+    let file_entries = CommandFileEntries::default();
+    let commands = Commands::from_cli_arguments(&matches, &file_entries)
+        .unwrap()
+        .commands;
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[0].get_command_line(), "wc -l a.txt 10");
+    assert_eq!(commands[1].get_command_line(), "wc -l b.txt 20");
+    assert_eq!(commands[1].get_parameters().len(), 2);
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--parameter-mode=zip",
+        "-L",
+        "file",
+        "a.txt,b.txt",
+        "-L",
+        "lines",
+        "10,20,30",
+        "wc -l {file} {lines}",
+    ]);
+    let error = Commands::from_cli_arguments(&matches, &file_entries)
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "The parameter lists for 'file' (2 values) and 'lines' (3 values) have different \
+         lengths. All lists need to have the same length for '--parameter-mode=zip'"
+    );
+}
+
 #[test]
 fn test_build_parameter_list_commands() {
     use crate::cli::get_cli_arguments;
@@ -440,7 +1032,10 @@ fn test_build_parameter_list_commands() {
         "--command-name",
         "name-{foo}",
     ]);
-    let commands = Commands::from_cli_arguments(&matches).unwrap().0;
+    let file_entries = CommandFileEntries::default();
+    let commands = Commands::from_cli_arguments(&matches, &file_entries)
+        .unwrap()
+        .commands;
     assert_eq!(commands.len(), 2);
     assert_eq!(commands[0].get_name(), "name-1");
     assert_eq!(commands[1].get_name(), "name-2");
@@ -448,6 +1043,45 @@ fn test_build_parameter_list_commands() {
     assert_eq!(commands[1].get_command_line(), "echo 2");
 }
 
+#[test]
+fn test_parse_command_file_entries() {
+    let entries = CommandFileEntries::parse_entries(
+        "# variants of the tool\nsleep 0.1\n\n   \n  echo {foo}  \r\n#sleep 0.2\nnot # a comment\n",
+    );
+    assert_eq!(entries, vec!["sleep 0.1", "echo {foo}", "not # a comment"]);
+}
+
+#[test]
+fn test_build_commands_with_file_entries() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec!["hyperfine", "echo a", "--command-name", "first"]);
+    let file_entries = CommandFileEntries {
+        commands: vec!["echo b".into(), "echo c".into()],
+        names: vec!["second".into()],
+    };
+    let commands = Commands::from_cli_arguments(&matches, &file_entries)
+        .unwrap()
+        .commands;
+    assert_eq!(commands.len(), 3);
+    assert_eq!(commands[0].get_name(), "first");
+    assert_eq!(commands[1].get_name(), "second");
+    assert_eq!(commands[1].get_command_line(), "echo b");
+    assert_eq!(commands[2].get_name(), "echo c");
+
+    let matches = get_cli_arguments(vec!["hyperfine", "echo a", "-L", "foo", "1,2"]);
+    let file_entries = CommandFileEntries {
+        commands: vec!["echo {foo}".into()],
+        names: vec![],
+    };
+    let commands = Commands::from_cli_arguments(&matches, &file_entries)
+        .unwrap()
+        .commands;
+    assert_eq!(commands.len(), 4);
+    assert_eq!(commands[1].get_command_line(), "echo 1");
+    assert_eq!(commands[3].get_command_line(), "echo 2");
+}
+
 #[test]
 fn test_build_parameter_scan_commands() {
     use crate::cli::get_cli_arguments;
@@ -463,7 +1097,10 @@ fn test_build_parameter_scan_commands() {
         "--command-name",
         "name-{val}",
     ]);
-    let commands = Commands::from_cli_arguments(&matches).unwrap().0;
+    let file_entries = CommandFileEntries::default();
+    let commands = Commands::from_cli_arguments(&matches, &file_entries)
+        .unwrap()
+        .commands;
     assert_eq!(commands.len(), 2);
     assert_eq!(commands[0].get_name(), "name-1");
     assert_eq!(commands[1].get_name(), "name-2");
@@ -475,9 +1112,7 @@ fn test_build_parameter_scan_commands() {
 fn test_parameter_scan_commands_int() {
     let commands = Commands::build_parameter_scan_commands(
         "val",
-        1i32,
-        7i32,
-        3i32,
+        Commands::parameter_scan_values(1i32, 7i32, 3i32, false).unwrap(),
         vec![],
         vec!["echo {val}"],
     )
@@ -495,9 +1130,7 @@ fn test_parameter_scan_commands_decimal() {
 
     let commands = Commands::build_parameter_scan_commands(
         "val",
-        param_min,
-        param_max,
-        step,
+        Commands::parameter_scan_values(param_min, param_max, step, false).unwrap(),
         vec![],
         vec!["echo {val}"],
     )
@@ -507,13 +1140,99 @@ fn test_parameter_scan_commands_decimal() {
     assert_eq!(commands[3].get_command_line(), "echo 0.99");
 }
 
+#[test]
+fn test_multiplicative_parameter_scan_commands() {
+    use crate::cli::get_cli_arguments;
+    let file_entries = CommandFileEntries::default();
+    let command_lines = |args: &[&str]| {
+        let matches = get_cli_arguments(
+            ["hyperfine", "dd bs={size}", "--parameter-scan", "size"]
+                .iter()
+                .chain(args),
+        );
+        Commands::from_cli_arguments(&matches, &file_entries).map(|commands| {
+            commands
+                .iter()
+                .map(|c| c.get_command_line())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(
+        command_lines(&["1K", "64K", "-D", "2x"]).unwrap(),
+        vec![
+            "dd bs=1K",
+            "dd bs=2K",
+            "dd bs=4K",
+            "dd bs=8K",
+            "dd bs=16K",
+            "dd bs=32K",
+            "dd bs=64K"
+        ]
+    );
+    assert_eq!(
+        command_lines(&["1", "64", "-D", "x2"]).unwrap(),
+        vec!["dd bs=1", "dd bs=2", "dd bs=4", "dd bs=8", "dd bs=16", "dd bs=32", "dd bs=64"]
+    );
+    assert_eq!(
+        command_lines(&["1", "100", "-D", "x3"]).unwrap(),
+        vec!["dd bs=1", "dd bs=3", "dd bs=9", "dd bs=27", "dd bs=81"]
+    );
+    assert_eq!(
+        command_lines(&["0.5", "5", "-D", "x2"]).unwrap(),
+        vec!["dd bs=0.5", "dd bs=1.0", "dd bs=2.0", "dd bs=4.0"]
+    );
+    assert_eq!(
+        command_lines(&["1", "16", "-D", "4", "--parameter-step-mode", "multiply"]).unwrap(),
+        vec!["dd bs=1", "dd bs=4", "dd bs=16"]
+    );
+    assert_eq!(
+        command_lines(&["512K", "2M", "-D", "2x", "--parameter-expand-suffix"]).unwrap(),
+        vec!["dd bs=524288", "dd bs=1048576", "dd bs=2097152"]
+    );
+    assert_eq!(
+        command_lines(&["1K", "3K", "-D", "1K"]).unwrap(),
+        vec!["dd bs=1K", "dd bs=2K", "dd bs=3K"]
+    );
+
+    assert!(matches!(
+        command_lines(&["1", "64", "-D", "x0.5"])
+            .unwrap_err()
+            .downcast_ref::<ParameterScanError>(),
+        Some(ParameterScanError::InvalidFactor)
+    ));
+    assert!(matches!(
+        command_lines(&["1K", "64K", "-D", "1x"])
+            .unwrap_err()
+            .downcast_ref::<ParameterScanError>(),
+        Some(ParameterScanError::InvalidFactor)
+    ));
+    assert!(matches!(
+        command_lines(&["0", "64", "-D", "2x"])
+            .unwrap_err()
+            .downcast_ref::<ParameterScanError>(),
+        Some(ParameterScanError::NonPositiveStart)
+    ));
+    assert!(matches!(
+        command_lines(&["1", "64", "--parameter-step-mode", "multiply"])
+            .unwrap_err()
+            .downcast_ref::<ParameterScanError>(),
+        Some(ParameterScanError::FactorRequired)
+    ));
+    assert!(matches!(
+        command_lines(&["1", "64", "-D", "2x", "--parameter-step-mode", "add"])
+            .unwrap_err()
+            .downcast_ref::<ParameterScanError>(),
+        Some(ParameterScanError::FactorWithAdditiveStepMode)
+    ));
+    assert!(command_lines(&["1Q", "64K"]).is_err());
+}
+
 #[test]
 fn test_parameter_scan_commands_names() {
     let commands = Commands::build_parameter_scan_commands(
         "val",
-        1i32,
-        3i32,
-        1i32,
+        Commands::parameter_scan_values(1i32, 3i32, 1i32, false).unwrap(),
         vec!["name-{val}"],
         vec!["echo {val}"],
     )
@@ -526,13 +1245,204 @@ fn test_parameter_scan_commands_names() {
     assert_eq!(command_names, vec!["name-1", "name-2", "name-3"]);
 }
 
+#[test]
+fn test_parameter_formats() {
+    use crate::cli::get_cli_arguments;
+    let file_entries = CommandFileEntries::default();
+    let commands = |args: &[&str]| {
+        let matches = get_cli_arguments(["hyperfine"].iter().chain(args));
+        Commands::from_cli_arguments(&matches, &file_entries).map(|commands| {
+            commands
+                .iter()
+                .map(|c| (c.get_name(), c.get_command_line()))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(
+        commands(&[
+            "-P",
+            "n",
+            "9",
+            "10",
+            "-n",
+            "run {n}",
+            "cat input_{n:03}.txt"
+        ])
+        .unwrap(),
+        vec![
+            ("run 9".into(), "cat input_009.txt".into()),
+            ("run 10".into(), "cat input_010.txt".into())
+        ]
+    );
+    assert_eq!(
+        commands(&[
+            "-P",
+            "x",
+            "0",
+            "1",
+            "-D",
+            "0.5",
+            "-n",
+            "{x:.2}",
+            "sleep {x:05.1}"
+        ])
+        .unwrap(),
+        vec![
+            ("0.00".into(), "sleep 000.0".into()),
+            ("0.50".into(), "sleep 000.5".into()),
+            ("1.00".into(), "sleep 001.0".into())
+        ]
+    );
+    assert_eq!(
+        commands(&["-L", "n", "1,22", "echo {n:4}"]).unwrap(),
+        vec![
+            ("echo    1".into(), "echo    1".into()),
+            ("echo   22".into(), "echo   22".into())
+        ]
+    );
+
+    let error = commands(&["-P", "n", "1", "3", "echo {n:x}"]).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<OptionsError>(),
+        Some(OptionsError::InvalidParameterFormat(name, spec)) if name == "n" && spec == "x"
+    ));
+
+    let error = commands(&["-L", "n", "1,a", "echo {n:.1}"]).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<OptionsError>(),
+        Some(OptionsError::NonNumericParameterFormat(_, _, value)) if value == "a"
+    ));
+}
+
+#[test]
+fn test_working_dir() {
+    use crate::cli::get_cli_arguments;
+    let file_entries = CommandFileEntries::default();
+    let tempdir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tempdir.path().join("a")).unwrap();
+    let template = format!("{}/{{dir}}", tempdir.path().display());
+
+    let matches = get_cli_arguments(
+        [
+            "hyperfine",
+            "-L",
+            "dir",
+            "a",
+            "--working-dir",
+            &template,
+            "ls",
+        ]
+        .iter(),
+    );
+    let commands = Commands::from_cli_arguments(&matches, &file_entries).unwrap();
+    let command = commands.iter().next().unwrap();
+    let working_dir = format!("{}/a", tempdir.path().display());
+    assert_eq!(command.get_working_dir(), Some(working_dir.clone()));
+    assert_eq!(
+        command.related_command("make clean").get_working_dir(),
+        Some(working_dir)
+    );
+    assert_eq!(Command::new(None, "ls").get_working_dir(), None);
+
+    let matches = get_cli_arguments(
+        [
+            "hyperfine",
+            "-L",
+            "dir",
+            "a,b",
+            "--working-dir",
+            &template,
+            "ls",
+        ]
+        .iter(),
+    );
+    let error = Commands::from_cli_arguments(&matches, &file_entries)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error.downcast_ref::<OptionsError>(),
+        Some(OptionsError::WorkingDirectoryNotFoundForParameters(_, parameters))
+            if parameters == "dir = b"
+    ));
+}
+
+#[test]
+fn test_commands_with_several_shells() {
+    use crate::cli::get_cli_arguments;
+    let file_entries = CommandFileEntries::default();
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--shell",
+        "bash",
+        "--shell",
+        "zsh",
+        "echo a",
+        "echo b",
+    ]);
+    let commands = Commands::from_cli_arguments(&matches, &file_entries).unwrap();
+    let names: Vec<String> = commands
+        .iter()
+        .map(|c| c.get_name_with_unused_parameters())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "echo a (shell = bash)",
+            "echo a (shell = zsh)",
+            "echo b (shell = bash)",
+            "echo b (shell = zsh)"
+        ]
+    );
+    assert_eq!(
+        commands.iter().nth(1).unwrap().get_shell().as_deref(),
+        Some("zsh")
+    );
+    assert!(commands.duplicates().is_empty());
+
+    // The shell is not a placeholder
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--shell",
+        "bash",
+        "--shell",
+        "zsh",
+        "echo '{print}' {shell}",
+    ]);
+    let commands = Commands::from_cli_arguments(&matches, &file_entries).unwrap();
+    let command = commands.iter().next().unwrap();
+    assert!(command.validate_placeholders([]).is_ok());
+    assert_eq!(command.get_command_line(), "echo '{print}' {shell}");
+    assert_eq!(
+        command.get_parameters(),
+        [(SHELL_PARAMETER, ParameterValue::Text("bash".into()))]
+    );
+
+    // A single shell is not a parameter
+    let matches = get_cli_arguments(vec!["hyperfine", "--shell", "bash", "echo a"]);
+    let commands = Commands::from_cli_arguments(&matches, &file_entries).unwrap();
+    assert_eq!(commands.iter().next().unwrap().get_shell(), None);
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "-S",
+        "bash",
+        "-S",
+        "zsh",
+        "-L",
+        "shell",
+        "a,b",
+        "echo {shell}",
+    ]);
+    assert!(Commands::from_cli_arguments(&matches, &file_entries).is_err());
+}
+
 #[test]
 fn test_get_specified_command_names() {
     let commands = Commands::build_parameter_scan_commands(
         "val",
-        1i32,
-        3i32,
-        1i32,
+        Commands::parameter_scan_values(1i32, 3i32, 1i32, false).unwrap(),
         vec!["name-a", "name-b", "name-c"],
         vec!["echo {val}"],
     )
@@ -549,9 +1459,7 @@ fn test_get_specified_command_names() {
 fn test_different_command_name_count_with_parameters() {
     let result = Commands::build_parameter_scan_commands(
         "val",
-        1i32,
-        3i32,
-        1i32,
+        Commands::parameter_scan_values(1i32, 3i32, 1i32, false).unwrap(),
         vec!["name-1", "name-2"],
         vec!["echo {val}"],
     );
@@ -560,3 +1468,40 @@ fn test_different_command_name_count_with_parameters() {
         ParameterScanError::UnexpectedCommandNameCount(2, 3)
     ));
 }
+
+#[test]
+fn test_find_duplicates_and_deduplicate() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "-n",
+        "first",
+        "-n",
+        "second",
+        "echo a",
+        "echo a",
+        "echo b",
+        "echo {x}",
+        "echo b",
+    ]);
+    let file_entries = CommandFileEntries::default();
+    let mut commands = Commands::from_cli_arguments(&matches, &file_entries).unwrap();
+    assert_eq!(commands.duplicates(), [vec![0, 1], vec![2, 4]]);
+    assert!(!commands.is_deduplicated());
+
+    commands.deduplicate();
+    let command_lines: Vec<String> = commands.iter().map(|c| c.get_command_line()).collect();
+    assert_eq!(command_lines, ["echo a", "echo b", "echo {x}"]);
+    assert_eq!(commands.iter().next().unwrap().get_name(), "first");
+    assert_eq!(commands.duplicates(), [vec![0, 1], vec![2, 4]]);
+    assert!(commands.is_deduplicated());
+
+    // The per-command options still refer to the commands before the deduplication
+    let expanded_indices: Vec<usize> = commands
+        .iter_with_expanded_index()
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(expanded_indices, [0, 2, 3]);
+    assert_eq!(commands.num_expanded_commands(), 5);
+}
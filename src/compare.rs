@@ -0,0 +1,468 @@
+//! The `hyperfine compare OLD NEW` subcommand, which compares the results of two JSON exports
+//! (see `--export-json`), for example before and after a change.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use clap::ArgMatches;
+use colored::*;
+use serde::Deserialize;
+
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::benchmark::significance::{self, SIGNIFICANCE_LEVEL};
+use crate::export::{Alignment, MarkdownExporter, MarkupExporter};
+use crate::output::format::{format_duration, format_duration_unit, format_duration_value};
+use crate::output::theme::{Role, Theme};
+use crate::util::units::{Scalar, Unit};
+
+use anyhow::{bail, Context, Result};
+
+/// The part of the JSON export that is needed for a comparison
+#[derive(Deserialize)]
+pub(crate) struct JsonExport {
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// How the mean run time of a benchmark has changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Faster,
+    Slower,
+
+    /// The difference is not statistically significant (or can not be tested, e.g. because
+    /// there are fewer than two runs)
+    Unchanged,
+}
+
+impl Verdict {
+    fn description(self) -> &'static str {
+        match self {
+            Verdict::Faster => "faster",
+            Verdict::Slower => "slower",
+            Verdict::Unchanged => "unchanged",
+        }
+    }
+
+    fn colorize(self, text: &str, theme: Theme) -> ColoredString {
+        match self {
+            Verdict::Faster => theme.paint(Role::Faster, text),
+            Verdict::Slower => theme.paint(Role::Slower, text),
+            Verdict::Unchanged => text.normal(),
+        }
+    }
+}
+
+/// The results of the same benchmark in both exports
+#[derive(Debug)]
+pub struct Comparison<'a> {
+    pub name: &'a str,
+
+    /// Iteration of the benchmark suite (with `--repeat-suite`)
+    pub iteration: Option<usize>,
+    pub old: &'a BenchmarkResult,
+    pub new: &'a BenchmarkResult,
+
+    /// p-value of Welch's t-test for the run times of both results
+    pub p_value: Option<Scalar>,
+}
+
+impl Comparison<'_> {
+    /// Relative change of the mean run time (-0.1 if the new result is 10% faster). Not
+    /// available if the old mean time is zero.
+    pub fn relative_change(&self) -> Option<Scalar> {
+        (self.old.mean > 0.0).then(|| self.new.mean / self.old.mean - 1.0)
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        match self.p_value {
+            Some(p) if p < SIGNIFICANCE_LEVEL && self.new.mean < self.old.mean => Verdict::Faster,
+            Some(p) if p < SIGNIFICANCE_LEVEL && self.new.mean > self.old.mean => Verdict::Slower,
+            _ => Verdict::Unchanged,
+        }
+    }
+}
+
+/// Comparison of all benchmarks of two exports
+#[derive(Debug)]
+pub struct ComparisonReport<'a> {
+    /// Benchmarks that are contained in both exports, in the order of the old export
+    pub comparisons: Vec<Comparison<'a>>,
+
+    /// Benchmarks that are only contained in the old export
+    pub only_old: Vec<&'a BenchmarkResult>,
+
+    /// Benchmarks that are only contained in the new export
+    pub only_new: Vec<&'a BenchmarkResult>,
+}
+
+fn benchmark_name(result: &BenchmarkResult) -> &str {
+    if result.name.is_empty() {
+        &result.command
+    } else {
+        &result.name
+    }
+}
+
+/// Benchmarks are matched by their name (as specified via `--command-name`, or the command
+/// line), their parameter values and the iteration of the benchmark suite (`--repeat-suite`)
+type BenchmarkKey<'a> = (&'a str, &'a BTreeMap<String, String>, Option<usize>);
+
+fn benchmark_key(result: &BenchmarkResult) -> BenchmarkKey<'_> {
+    (benchmark_name(result), &result.parameters, result.iteration)
+}
+
+/// The name of a benchmark in the report, including the iteration of the benchmark suite
+fn benchmark_label(name: &str, iteration: Option<usize>) -> String {
+    match iteration {
+        Some(iteration) => format!("{name} (iteration {iteration})"),
+        None => name.to_string(),
+    }
+}
+
+/// Index the results of an export by their keys. Fails if several results have the same key,
+/// since it would not be clear which one to compare.
+fn results_by_key<'a>(
+    results: &'a [BenchmarkResult],
+    export: &str,
+) -> Result<HashMap<BenchmarkKey<'a>, &'a BenchmarkResult>> {
+    let mut results_by_key = HashMap::new();
+    for result in results {
+        if results_by_key
+            .insert(benchmark_key(result), result)
+            .is_some()
+        {
+            bail!(
+                "The {export} export contains several results for the benchmark '{}'. Use \
+                 '--command-name' to give them distinct names.",
+                benchmark_label(benchmark_name(result), result.iteration)
+            );
+        }
+    }
+    Ok(results_by_key)
+}
+
+/// Match the benchmarks of both exports and compare their run times
+pub fn compare<'a>(
+    old: &'a [BenchmarkResult],
+    new: &'a [BenchmarkResult],
+) -> Result<ComparisonReport<'a>> {
+    results_by_key(old, "old")?;
+    let new_by_key = results_by_key(new, "new")?;
+
+    let mut comparisons = vec![];
+    let mut only_old = vec![];
+    for old_result in old {
+        match new_by_key.get(&benchmark_key(old_result)) {
+            Some(new_result) => comparisons.push(Comparison {
+                name: benchmark_name(old_result),
+                iteration: old_result.iteration,
+                old: old_result,
+                new: new_result,
+                p_value: significance::p_value(old_result, new_result),
+            }),
+            None => only_old.push(old_result),
+        }
+    }
+
+    let only_new = new
+        .iter()
+        .filter(|result| {
+            comparisons
+                .iter()
+                .all(|comparison| benchmark_key(comparison.new) != benchmark_key(result))
+        })
+        .collect();
+
+    Ok(ComparisonReport {
+        comparisons,
+        only_old,
+        only_new,
+    })
+}
+
+/// Read the results from a JSON export
+fn load_results(path: &str) -> Result<Vec<BenchmarkResult>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read the file '{path}'"))?;
+    let export: JsonExport = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse the JSON export '{path}'"))?;
+    Ok(export.results)
+}
+
+/// Mean ± standard deviation, with a unit that fits the mean
+fn format_mean(result: &BenchmarkResult) -> String {
+    let (mean, unit) = format_duration_unit(result.mean, None);
+    match result.stddev {
+        Some(stddev) => format!("{mean} ± {}", format_duration(stddev, Some(unit))),
+        None => mean,
+    }
+}
+
+/// Mean ± standard deviation in the given unit, without the name of the unit
+fn format_mean_value(result: &BenchmarkResult, unit: Unit) -> String {
+    let mean = format_duration_value(result.mean, Some(unit)).0;
+    match result.stddev {
+        Some(stddev) => format!("{mean} ± {}", format_duration_value(stddev, Some(unit)).0),
+        None => mean,
+    }
+}
+
+fn format_change(comparison: &Comparison) -> String {
+    comparison
+        .relative_change()
+        .map_or_else(|| "–".into(), |change| format!("{:+.1}%", change * 100.0))
+}
+
+fn format_p_value(comparison: &Comparison) -> String {
+    comparison
+        .p_value
+        .map_or_else(|| "–".into(), |p| format!("{p:.3}"))
+}
+
+/// Print the comparison as a table in the terminal
+fn print_report(report: &ComparisonReport, old_path: &str, new_path: &str, theme: Theme) {
+    let header = ["Benchmark", "Old", "New", "Change", "p-value", ""];
+    let rows: Vec<[String; 6]> = report
+        .comparisons
+        .iter()
+        .map(|comparison| {
+            [
+                benchmark_label(comparison.name, comparison.iteration),
+                format_mean(comparison.old),
+                format_mean(comparison.new),
+                format_change(comparison),
+                format_p_value(comparison),
+                comparison.verdict().description().to_string(),
+            ]
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let pad = |text: &str, column: usize| {
+        let padding = " ".repeat(widths[column] - text.chars().count());
+        if column == 0 {
+            format!("{text}{padding}")
+        } else {
+            format!("{padding}{text}")
+        }
+    };
+
+    if !rows.is_empty() {
+        let header: Vec<String> = (0..header.len() - 1)
+            .map(|column| pad(header[column], column))
+            .collect();
+        println!("{}", header.join("  ").trim_end().bold());
+
+        for (comparison, row) in report.comparisons.iter().zip(&rows) {
+            let verdict = comparison.verdict();
+            println!(
+                "{}  {}  {}  {}  {}  {}",
+                pad(&row[0], 0),
+                pad(&row[1], 1),
+                pad(&row[2], 2),
+                verdict.colorize(&pad(&row[3], 3), theme),
+                pad(&row[4], 4),
+                verdict.colorize(&row[5], theme),
+            );
+        }
+    }
+
+    for (results, path) in [(&report.only_old, old_path), (&report.only_new, new_path)] {
+        if !results.is_empty() {
+            println!("\nOnly in '{path}':");
+            for result in results.iter() {
+                println!(
+                    "  {}",
+                    benchmark_label(benchmark_name(result), result.iteration)
+                );
+            }
+        }
+    }
+}
+
+/// Format the comparison as a Markdown table
+fn markdown_report(report: &ComparisonReport, old_path: &str, new_path: &str) -> String {
+    let exporter = MarkdownExporter::default();
+
+    let unit = report
+        .comparisons
+        .first()
+        .map_or(Unit::Second, |comparison| {
+            format_duration_value(comparison.old.mean, None).1
+        });
+    let notation = format!("[{}]", unit.short_name());
+
+    let alignments = [
+        Alignment::Left,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Left,
+    ];
+    let mut table = exporter.table_row(&[
+        "Benchmark",
+        &format!("Old {notation}"),
+        &format!("New {notation}"),
+        "Change",
+        "p-value",
+        "Result",
+    ]);
+    table.push_str(&exporter.table_divider(&alignments));
+
+    for comparison in &report.comparisons {
+        table.push_str(&exporter.table_row(&[
+            &exporter.command(&benchmark_label(comparison.name, comparison.iteration)),
+            &format_mean_value(comparison.old, unit),
+            &format_mean_value(comparison.new, unit),
+            &format_change(comparison),
+            &format_p_value(comparison),
+            comparison.verdict().description(),
+        ]));
+    }
+
+    for (results, path) in [(&report.only_old, old_path), (&report.only_new, new_path)] {
+        if !results.is_empty() {
+            table.push_str(&format!("\nOnly in `{path}`:\n\n"));
+            for result in results.iter() {
+                table.push_str(&format!(
+                    "- {}\n",
+                    exporter.command(&benchmark_label(benchmark_name(result), result.iteration))
+                ));
+            }
+        }
+    }
+
+    table
+}
+
+/// Run the `compare` subcommand
+pub fn run(matches: &ArgMatches) -> Result<()> {
+    let old_path = matches.get_one::<String>("old").unwrap();
+    let new_path = matches.get_one::<String>("new").unwrap();
+
+    let old = load_results(old_path)?;
+    let new = load_results(new_path)?;
+    let report = compare(&old, &new)?;
+
+    let theme = matches
+        .get_one::<String>("theme")
+        .and_then(|theme| Theme::from_name(theme))
+        .unwrap_or_default();
+    print_report(&report, old_path, new_path, theme);
+
+    if let Some(path) = matches.get_one::<String>("export-markdown") {
+        fs::write(path, markdown_report(&report, old_path, new_path))
+            .with_context(|| format!("Failed to export the comparison to '{path}'"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn result_with_times(name: &str, times: &[Scalar]) -> BenchmarkResult {
+    let mean = statistical::mean(times);
+    BenchmarkResult {
+        command: format!("command {name}"),
+        name: name.to_string(),
+        mean,
+        stddev: Some(statistical::standard_deviation(times, Some(mean))),
+        times: Some(times.to_vec()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_compare() {
+    let old = vec![
+        result_with_times("a", &[1.0, 1.1, 0.9, 1.0]),
+        result_with_times("b", &[1.0, 1.1, 0.9, 1.0]),
+        result_with_times("c", &[1.0, 1.1, 0.9, 1.0]),
+        result_with_times("removed", &[1.0, 1.1]),
+    ];
+    let new = vec![
+        result_with_times("added", &[1.0, 1.1]),
+        result_with_times("c", &[1.0, 1.2, 0.9, 1.0]),
+        result_with_times("b", &[2.0, 2.1, 1.9, 2.0]),
+        result_with_times("a", &[0.5, 0.6, 0.4, 0.5]),
+    ];
+
+    let report = compare(&old, &new).unwrap();
+
+    let verdicts: Vec<(&str, Verdict)> = report
+        .comparisons
+        .iter()
+        .map(|comparison| (comparison.name, comparison.verdict()))
+        .collect();
+    assert_eq!(
+        verdicts,
+        [
+            ("a", Verdict::Faster),
+            ("b", Verdict::Slower),
+            ("c", Verdict::Unchanged)
+        ]
+    );
+    approx::assert_relative_eq!(report.comparisons[0].relative_change().unwrap(), -0.5);
+
+    assert_eq!(report.only_old.len(), 1);
+    assert_eq!(report.only_old[0].name, "removed");
+    assert_eq!(report.only_new.len(), 1);
+    assert_eq!(report.only_new[0].name, "added");
+}
+
+#[test]
+fn test_compare_repeated_benchmarks() {
+    let with_iteration = |times: &[Scalar], iteration| BenchmarkResult {
+        iteration: Some(iteration),
+        ..result_with_times("a", times)
+    };
+    let old = vec![
+        with_iteration(&[1.0, 1.1, 0.9, 1.0], 1),
+        with_iteration(&[1.0, 1.1, 0.9, 1.0], 2),
+    ];
+    let new = vec![
+        with_iteration(&[2.0, 2.1, 1.9, 2.0], 1),
+        with_iteration(&[0.5, 0.6, 0.4, 0.5], 2),
+    ];
+
+    let report = compare(&old, &new).unwrap();
+    let verdicts: Vec<(Option<usize>, Verdict)> = report
+        .comparisons
+        .iter()
+        .map(|comparison| (comparison.iteration, comparison.verdict()))
+        .collect();
+    assert_eq!(
+        verdicts,
+        [(Some(1), Verdict::Slower), (Some(2), Verdict::Faster)]
+    );
+    assert!(report.only_old.is_empty() && report.only_new.is_empty());
+
+    // Results with the same name, parameters and iteration can not be matched
+    let duplicates = vec![
+        result_with_times("a", &[1.0, 1.1]),
+        result_with_times("a", &[2.0, 2.1]),
+    ];
+    assert!(compare(&duplicates, &old).is_err());
+    assert!(compare(&old, &duplicates).is_err());
+}
+
+#[test]
+fn test_markdown_report() {
+    let old = vec![result_with_times("a|b", &[1.0, 1.0])];
+    let new = vec![result_with_times("a|b", &[1.5, 1.5])];
+
+    assert_eq!(
+        markdown_report(&compare(&old, &new).unwrap(), "old.json", "new.json"),
+        "| Benchmark | Old [s] | New [s] | Change | p-value | Result |\n\
+         |:---|---:|---:|---:|---:|:---|\n\
+         | `a\\|b` | 1.000 ± 0.000 | 1.500 ± 0.000 | +50.0% | – | unchanged |\n"
+    );
+}
@@ -0,0 +1,114 @@
+//! A module for detecting a systematic drift of the run times over the course of a benchmark,
+//! as caused by thermal throttling or caching effects, for example.
+
+use crate::benchmark::significance::{student_t_two_sided_p_value, SIGNIFICANCE_LEVEL};
+use crate::util::units::{Scalar, Second};
+
+/// Minimum change of the run time over the course of a benchmark (relative to the mean run time)
+/// that is reported as a drift
+pub const DRIFT_THRESHOLD: Scalar = 0.1;
+
+/// Minimum number of runs that are needed to detect a drift
+const MIN_RUNS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    /// Slope of a linear fit of the run times against the run index (in seconds per run)
+    pub slope: Scalar,
+
+    /// Change of the run time over the course of the benchmark according to the linear fit,
+    /// relative to the mean run time
+    pub relative_change: Scalar,
+
+    /// p-value of a t-test for the null hypothesis that there is no drift (the slope is zero)
+    pub p_value: Scalar,
+}
+
+impl Drift {
+    /// Whether or not the drift is large enough and statistically significant
+    pub fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE_LEVEL && self.relative_change.abs() >= DRIFT_THRESHOLD
+    }
+}
+
+/// Fit a straight line (simple linear regression) to the run times, as a function of the run
+/// index. Returns `None` for benchmarks with too few runs.
+pub fn linear_drift(times: &[Second]) -> Option<Drift> {
+    let n = times.len();
+    if n < MIN_RUNS {
+        return None;
+    }
+
+    let x_mean = (n - 1) as Scalar / 2.0;
+    let y_mean = times.iter().sum::<Scalar>() / n as Scalar;
+    if y_mean <= 0.0 {
+        return None;
+    }
+
+    let (sxx, sxy) = times
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(sxx, sxy), (i, y)| {
+            let dx = i as Scalar - x_mean;
+            (sxx + dx * dx, sxy + dx * (y - y_mean))
+        });
+
+    let slope = sxy / sxx;
+    let intercept = y_mean - slope * x_mean;
+
+    let residuals: Scalar = times
+        .iter()
+        .enumerate()
+        .map(|(i, y)| (y - (intercept + slope * i as Scalar)).powi(2))
+        .sum();
+    let standard_error = (residuals / (n - 2) as Scalar / sxx).sqrt();
+
+    let p_value = if standard_error > 0.0 {
+        student_t_two_sided_p_value(slope / standard_error, (n - 2) as Scalar)
+    } else if slope == 0.0 {
+        1.0
+    } else {
+        0.0
+    };
+
+    Some(Drift {
+        slope,
+        relative_change: slope * (n - 1) as Scalar / y_mean,
+        p_value,
+    })
+}
+
+#[test]
+fn test_linear_drift() {
+    use approx::assert_relative_eq;
+
+    // Should not detect a drift in small samples
+    assert_eq!(None, linear_drift(&[1.0, 2.0, 3.0, 4.0]));
+
+    // No drift for constant run times
+    let drift = linear_drift(&[1.0, 1.0, 1.0, 1.0, 1.0]).unwrap();
+    assert_eq!(0.0, drift.slope);
+    assert!(!drift.is_significant());
+
+    // Perfectly linear increase from 1.0 s to 1.4 s
+    let drift = linear_drift(&[1.0, 1.1, 1.2, 1.3, 1.4]).unwrap();
+    assert_relative_eq!(0.1, drift.slope, epsilon = 1e-12);
+    assert_relative_eq!(0.4 / 1.2, drift.relative_change, epsilon = 1e-12);
+    assert!(drift.p_value < 1e-6);
+    assert!(drift.is_significant());
+
+    // Noisy measurements without a trend
+    let drift = linear_drift(&[1.02, 0.97, 1.01, 0.99, 1.03, 0.98, 1.0, 1.01, 0.97, 1.02]).unwrap();
+    assert!(drift.p_value > SIGNIFICANCE_LEVEL);
+    assert!(!drift.is_significant());
+
+    // Run times that slow down over the course of the benchmark (throttling)
+    let drift = linear_drift(&[1.0, 1.01, 0.99, 1.05, 1.08, 1.1, 1.12, 1.18, 1.2, 1.25]).unwrap();
+    assert!(drift.slope > 0.0);
+    assert!(drift.is_significant());
+
+    // A significant, but tiny drift is not reported
+    let drift = linear_drift(&[1.0, 1.001, 1.002, 1.003, 1.004, 1.005]).unwrap();
+    assert!(drift.p_value < SIGNIFICANCE_LEVEL);
+    assert!(!drift.is_significant());
+}
@@ -3,6 +3,11 @@ use std::num::{self, ParseFloatError, ParseIntError};
 use rust_decimal::Error as DecimalError;
 use thiserror::Error;
 
+/// Returned if hyperfine has been interrupted with Ctrl-C during a benchmark
+#[derive(Debug, Error)]
+#[error("The benchmarks have been interrupted")]
+pub struct InterruptedError;
+
 #[derive(Debug, Error)]
 pub enum ParameterScanError {
     #[error("Error while parsing parameter scan arguments ({0})")]
@@ -15,8 +20,16 @@ pub enum ParameterScanError {
     TooLarge,
     #[error("Zero is not a valid parameter step")]
     ZeroStep,
+    #[error("The factor of a multiplicative parameter scan needs to be larger than 1")]
+    InvalidFactor,
+    #[error("A multiplicative parameter scan needs to start at a positive value")]
+    NonPositiveStart,
     #[error("A step size is required when the range bounds are floating point numbers. The step size can be specified with the '-D/--parameter-step-size <DELTA>' parameter")]
     StepRequired,
+    #[error("A factor is required for a multiplicative parameter scan. The factor can be specified with the '-D/--parameter-step-size <FACTOR>' parameter")]
+    FactorRequired,
+    #[error("A step size with an 'x' (like '2x') denotes a factor, which can not be used with '--parameter-step-mode add'")]
+    FactorWithAdditiveStepMode,
     #[error("'--command-name' has been specified {0} times. It has to appear exactly once, or exactly {1} times (number of benchmarks)")]
     UnexpectedCommandNameCount(usize, usize),
 }
@@ -43,16 +56,64 @@ pub enum OptionsError<'a> {
     TooManyCommandNames(usize),
     #[error("'--command-name' has been specified {0} times. It has to appear exactly once, or exactly {1} times (number of benchmarks)")]
     UnexpectedCommandNameCount(usize, usize),
+    #[error("The parameter lists for '{0}' ({1} values) and '{2}' ({3} values) have different lengths. All lists need to have the same length for '--parameter-mode=zip'")]
+    ParameterListLengthMismatch(String, usize, String, usize),
     #[error("Could not read numeric integer argument to '--{0}': {1}")]
     IntParsingError(&'a str, ParseIntError),
     #[error("Could not read numeric floating point argument to '--{0}': {1}")]
     FloatParsingError(&'a str, ParseFloatError),
+    #[error("Could not read duration argument to '--{0}': '{1}'. Use a number with an optional unit suffix like '30s' or '500ms'")]
+    DurationParsingError(&'a str, String),
     #[error("An empty command has been specified for the '--shell <command>' option")]
     EmptyShell,
     #[error("Failed to parse '--shell <command>' expression as command line: {0}")]
     ShellParseError(shell_words::ParseError),
     #[error("Unknown output policy '{0}'. Use './{0}' to output to a file named '{0}'.")]
     UnknownOutputPolicy(String),
+    #[error("An empty path has been specified for '--output=file:<path>'")]
+    EmptyOutputPath,
     #[error("The file '{0}' specified as '--input' does not exist")]
     StdinDataFileDoesNotExist(String),
+    #[error("Invalid environment variable '{0}' for '--env'. Use the form 'NAME=VALUE'")]
+    InvalidEnvironmentVariable(String),
+    #[error("Could not read the file '{0}' specified as '--env-file': {1}")]
+    EnvironmentFileReadError(String, std::io::Error),
+    #[error(
+        "Line {1} of the file '{0}' specified as '--env-file' is not of the form 'NAME=VALUE'"
+    )]
+    InvalidEnvironmentFileLine(String, usize),
+    #[error("Invalid format '{1}' for the parameter '{0}'. Use e.g. '{{{0}:03}}' for zero-padding to a width of three or '{{{0}:.2}}' for two decimal places")]
+    InvalidParameterFormat(String, String),
+    #[error("The format '{1}' for the parameter '{0}' requires a numeric value, but the parameter has the value '{2}'")]
+    NonNumericParameterFormat(String, String, String),
+    #[error("The working directory '{0}' specified via '--working-dir' does not exist")]
+    WorkingDirectoryNotFound(String),
+    #[error("The working directory '{0}' specified via '--working-dir' does not exist (for the parameter values {1})")]
+    WorkingDirectoryNotFoundForParameters(String, String),
+    #[error("Invalid assertion '{0}' for '--assert'. Use the form 'STATISTIC OPERATOR DURATION', for example 'mean < 250ms' or 'p95 <= 1s'")]
+    InvalidAssertion(String),
+    #[error("Invalid list of CPUs '{0}' for '--cpu'. Use a comma-separated list of CPU numbers or ranges, like '2,3' or '0-3'")]
+    InvalidCpuList(String),
+    #[error("Unknown parameter placeholder(s) {1} in '{0}'. The known parameters are: {2}. Use '--allow-unknown-placeholders' if the braces are not meant as placeholders")]
+    UnknownPlaceholders(String, String, String),
+    #[error("The argument to '--outlier-threshold' needs to be a positive number")]
+    NonPositiveOutlierThreshold,
+    #[error("The argument to '--cv-warning-threshold' needs to be a positive percentage, like '10' or '10%'")]
+    NonPositiveCvWarningThreshold,
+    #[error("Invalid size '{0}' for '--throughput'. Use a positive number of bytes with an optional prefix like '500MB' or '1.2GiB', or a plain number of items")]
+    InvalidThroughputSize(String),
+    #[error("The argument to '--timeout' needs to be a positive duration")]
+    NonPositiveTimeout,
+    #[error("The argument to '--repeat-suite' needs to be at least 1")]
+    ZeroSuiteRepetitions,
+    #[error("Invalid pattern '{0}' for '--extract-time': {1}")]
+    InvalidTimeExtraction(String, String),
+    #[error("Could not read the results '{0}' specified as '--resume-from': {1}")]
+    ResumeFileReadError(String, String),
+    #[error("Invalid pattern '{0}' for '--force-rerun': {1}")]
+    InvalidForceRerunPattern(String, String),
+    #[error("Invalid argument '{0}' for '--randomization'. Use 'on', 'off' or 'seed:N' with a non-negative integer N")]
+    InvalidRandomization(String),
+    #[error("Unknown column '{0}' for '--export-columns'. The available columns are: {1}")]
+    UnknownExportColumn(String, String),
 }
@@ -5,9 +5,9 @@ use crate::export::markup::MarkupExporter;
 pub struct AsciidocExporter {}
 
 impl MarkupExporter for AsciidocExporter {
-    fn table_header(&self, cell_aligmnents: &[Alignment]) -> String {
+    fn table_header(&self, cell_aligmnents: &[Alignment], title: &str) -> String {
         format!(
-            "[cols=\"{}\"]\n|===",
+            ".{title}\n[cols=\"{}\"]\n|===",
             cell_aligmnents
                 .iter()
                 .map(|a| match a {
@@ -32,7 +32,7 @@ impl MarkupExporter for AsciidocExporter {
     }
 
     fn command(&self, cmd: &str) -> String {
-        format!("`{cmd}`")
+        format!("`{}`", cmd.replace('|', "\\|"))
     }
 }
 
@@ -60,8 +60,8 @@ fn test_asciidoc_exporter_table_header() {
         Alignment::Right,
     ];
 
-    let actual = exporter.table_header(&cells_alignment);
-    let expect = "[cols=\"<,>,>,>,>\"]\n|===";
+    let actual = exporter.table_header(&cells_alignment, "Benchmark results");
+    let expect = ".Benchmark results\n[cols=\"<,>,>,>,>\"]\n|===";
 
     assert_eq!(expect, actual);
 }
@@ -71,7 +71,16 @@ fn test_asciidoc_exporter_table_header() {
 #[cfg(test)]
 fn cfg_test_table_header(unit_short_name: &str) -> String {
     format!(
-        "[cols=\"<,>,>,>,>\"]\n|===\n| Command \n| Mean [{unit_short_name}] \n| Min [{unit_short_name}] \n| Max [{unit_short_name}] \n| Relative \n"
+        ".Benchmark results\n[cols=\"<,>,>,>,>\"]\n|===\n| Command \n| Mean [{unit_short_name}] \n| Min [{unit_short_name}] \n| Max [{unit_short_name}] \n| Relative \n"
+    )
+}
+
+/// Same as `cfg_test_table_header`, with additional columns for the parameters
+/// `bar` and `foo` of the test results.
+#[cfg(test)]
+fn cfg_test_table_header_with_parameters(unit_short_name: &str) -> String {
+    format!(
+        ".Benchmark results\n[cols=\"<,>,>,>,>,>,>\"]\n|===\n| Command \n| bar \n| foo \n| Mean [{unit_short_name}] \n| Min [{unit_short_name}] \n| Max [{unit_short_name}] \n| Relative \n"
     )
 }
 
@@ -96,7 +105,9 @@ fn test_asciidoc_format_s() {
     let results = vec![
         BenchmarkResult {
             command: String::from("FOO=1 BAR=2 command | 1"),
+            name: String::from("FOO=1 BAR=2 command | 1"),
             command_with_unused_parameters: String::from("FOO=1 BAR=2 command | 1"),
+            command_template: String::from("FOO=1 BAR=2 command | 1"),
             mean: 1.0,
             stddev: Some(2.0),
             median: 1.0,
@@ -104,6 +115,7 @@ fn test_asciidoc_format_s() {
             system: 4.0,
             min: 5.0,
             max: 6.0,
+            runs: 3,
             times: Some(vec![7.0, 8.0, 9.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
             parameters: {
@@ -112,10 +124,13 @@ fn test_asciidoc_format_s() {
                 params.insert("bar".into(), "2".into());
                 params
             },
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("FOO=1 BAR=7 command | 2"),
+            name: String::from("FOO=1 BAR=7 command | 2"),
             command_with_unused_parameters: String::from("FOO=1 BAR=7 command | 2"),
+            command_template: String::from("FOO=1 BAR=7 command | 2"),
             mean: 11.0,
             stddev: Some(12.0),
             median: 11.0,
@@ -123,6 +138,7 @@ fn test_asciidoc_format_s() {
             system: 14.0,
             min: 15.0,
             max: 16.0,
+            runs: 3,
             times: Some(vec![17.0, 18.0, 19.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
             parameters: {
@@ -131,31 +147,36 @@ fn test_asciidoc_format_s() {
                 params.insert("bar".into(), "7".into());
                 params
             },
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&results, Some(Unit::Second), SortOrder::Command)
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
     let expect = format!(
         "{}
 | `FOO=1 BAR=2 command \\| 1` 
+| `2` 
+| `1` 
 | 1.000 ± 2.000 
 | 5.000 
 | 6.000 
 | 1.00 
 
 | `FOO=1 BAR=7 command \\| 2` 
+| `7` 
+| `1` 
 | 11.000 ± 12.000 
 | 15.000 
 | 16.000 
 | 11.00 ± 25.06 
 |===
 ",
-        cfg_test_table_header("s")
+        cfg_test_table_header_with_parameters("s")
     );
 
     assert_eq!(expect, actual);
@@ -171,7 +192,9 @@ fn test_asciidoc_format_ms() {
     let results = vec![
         BenchmarkResult {
             command: String::from("FOO=1 BAR=7 command | 2"),
+            name: String::from("FOO=1 BAR=7 command | 2"),
             command_with_unused_parameters: String::from("FOO=1 BAR=7 command | 2"),
+            command_template: String::from("FOO=1 BAR=7 command | 2"),
             mean: 0.011,
             stddev: Some(0.012),
             median: 0.011,
@@ -179,6 +202,7 @@ fn test_asciidoc_format_ms() {
             system: 0.014,
             min: 0.015,
             max: 0.016,
+            runs: 3,
             times: Some(vec![0.017, 0.018, 0.019]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
             parameters: {
@@ -187,10 +211,13 @@ fn test_asciidoc_format_ms() {
                 params.insert("bar".into(), "7".into());
                 params
             },
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("FOO=1 BAR=2 command | 1"),
+            name: String::from("FOO=1 BAR=2 command | 1"),
             command_with_unused_parameters: String::from("FOO=1 BAR=2 command | 1"),
+            command_template: String::from("FOO=1 BAR=2 command | 1"),
             mean: 1.0,
             stddev: Some(2.0),
             median: 1.0,
@@ -198,6 +225,7 @@ fn test_asciidoc_format_ms() {
             system: 4.0,
             min: 5.0,
             max: 6.0,
+            runs: 3,
             times: Some(vec![7.0, 8.0, 9.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
             parameters: {
@@ -206,32 +234,79 @@ fn test_asciidoc_format_ms() {
                 params.insert("bar".into(), "2".into());
                 params
             },
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&results, Some(Unit::MilliSecond), SortOrder::Command)
+            .serialize(&results, None, Some(Unit::MilliSecond), SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
     let expect = format!(
         "{}
 | `FOO=1 BAR=7 command \\| 2` 
+| `7` 
+| `1` 
 | 11.0 ± 12.0 
 | 15.0 
 | 16.0 
 | 1.00 
 
 | `FOO=1 BAR=2 command \\| 1` 
+| `2` 
+| `1` 
 | 1000.0 ± 2000.0 
 | 5000.0 
 | 6000.0 
 | 90.91 ± 207.11 
 |===
 ",
-        cfg_test_table_header("ms")
+        cfg_test_table_header_with_parameters("ms")
     );
 
     assert_eq!(expect, actual);
 }
+
+/// Integration test for the nanosecond and minute units
+#[test]
+fn test_asciidoc_format_ns_and_min() {
+    let exporter = AsciidocExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("make"),
+        command_with_unused_parameters: String::from("make"),
+        mean: 90.0,
+        min: 60.0,
+        max: 120.0,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(actual.starts_with(&cfg_test_table_header("min")));
+    assert!(actual.contains("| `make` \n| 1.500 \n| 1.000 \n| 2.000 \n"));
+
+    let results = vec![BenchmarkResult {
+        command: String::from("true"),
+        command_with_unused_parameters: String::from("true"),
+        mean: 0.000_000_45,
+        min: 0.000_000_42,
+        max: 0.000_000_51,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::NanoSecond), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(actual.starts_with(&cfg_test_table_header("ns")));
+    assert!(actual.contains("| `true` \n| 450 \n| 420 \n| 510 \n"));
+}
@@ -1,34 +1,107 @@
 use std::borrow::Cow;
+use std::collections::BTreeSet;
 
 use csv::WriterBuilder;
 
 use super::Exporter;
 use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::benchmark::relative_speed;
 use crate::options::SortOrder;
 use crate::util::units::Unit;
 
 use anyhow::Result;
 
+/// Character that separates the integer part of numbers from the fractional part
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalSeparator {
+    #[default]
+    Point,
+
+    /// Used by spreadsheet applications in many locales. The fields are separated by semicolons
+    /// instead of commas in this case.
+    Comma,
+}
+
+impl DecimalSeparator {
+    fn format(self, value: f64) -> String {
+        match self {
+            DecimalSeparator::Point => value.to_string(),
+            DecimalSeparator::Comma => value.to_string().replace('.', ","),
+        }
+    }
+
+    fn writer(self) -> csv::Writer<Vec<u8>> {
+        let delimiter = match self {
+            DecimalSeparator::Point => b',',
+            DecimalSeparator::Comma => b';',
+        };
+        WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(vec![])
+    }
+}
+
 #[derive(Default)]
-pub struct CsvExporter {}
+pub struct CsvExporter {
+    decimal_separator: DecimalSeparator,
+}
+
+impl CsvExporter {
+    pub fn new(decimal_separator: DecimalSeparator) -> Self {
+        CsvExporter { decimal_separator }
+    }
+}
 
 impl Exporter for CsvExporter {
     fn serialize(
         &self,
         results: &[BenchmarkResult],
+        reference: Option<usize>,
         _unit: Option<Unit>,
-        _sort_order: SortOrder,
+        sort_order: SortOrder,
     ) -> Result<Vec<u8>> {
-        let mut writer = WriterBuilder::new().from_writer(vec![]);
+        let mut writer = self.decimal_separator.writer();
+
+        // The throughput columns are only included if throughputs have been computed (in bytes or
+        // items per second, see `--throughput`)
+        let with_throughput = results.iter().any(|result| result.throughput.is_some());
+
+        // The iteration column is only included if the suite has been repeated (`--repeat-suite`)
+        let with_iteration = results.iter().any(|result| result.iteration.is_some());
 
         {
             let mut headers: Vec<Cow<[u8]>> = [
                 // The list of times and exit codes cannot be exported to the CSV file - omit them.
-                "command", "mean", "stddev", "median", "user", "system", "min", "max",
+                "command",
+                "mean",
+                "stddev",
+                "median",
+                "user",
+                "system",
+                "min",
+                "max",
+                "relative",
+                "relative_stddev",
+                "mad",
+                "cv",
+                "runs",
             ]
             .iter()
             .map(|x| Cow::Borrowed(x.as_bytes()))
             .collect();
+            if with_throughput {
+                for header in [
+                    "throughput",
+                    "throughput_stddev",
+                    "throughput_min",
+                    "throughput_max",
+                ] {
+                    headers.push(Cow::Borrowed(header.as_bytes()));
+                }
+            }
+            if with_iteration {
+                headers.push(Cow::Borrowed(b"iteration"));
+            }
             if let Some(res) = results.first() {
                 for param_name in res.parameters.keys() {
                     headers.push(Cow::Owned(format!("parameter_{param_name}").into_bytes()));
@@ -37,8 +110,27 @@ impl Exporter for CsvExporter {
             writer.write_record(headers)?;
         }
 
-        for res in results {
-            let mut fields = vec![Cow::Borrowed(res.command.as_bytes())];
+        // The relative speeds can not be computed if the compared times are zero. All rows are
+        // still exported in that case, with empty relative speed columns.
+        let (entries, comparable) = if results.is_empty() {
+            (vec![], false)
+        } else {
+            match relative_speed::compute_with_check_from_reference(
+                results,
+                relative_speed::reference_of(results, reference, sort_order),
+                sort_order,
+            ) {
+                Some(entries) => (entries, true),
+                None => (
+                    relative_speed::compute(results, reference, sort_order),
+                    false,
+                ),
+            }
+        };
+
+        for entry in entries {
+            let res = entry.result;
+            let mut fields = vec![Cow::Borrowed(res.name.as_bytes())];
             for f in &[
                 res.mean,
                 res.stddev.unwrap_or(0.0),
@@ -48,7 +140,45 @@ impl Exporter for CsvExporter {
                 res.min,
                 res.max,
             ] {
-                fields.push(Cow::Owned(f.to_string().into_bytes()))
+                fields.push(Cow::Owned(self.decimal_separator.format(*f).into_bytes()))
+            }
+            // The relative speed is only meaningful if there is something to compare to
+            let (relative, relative_stddev) = if comparable && results.len() > 1 {
+                let (relative, relative_stddev) = entry.relative_time();
+                (
+                    Some(relative),
+                    relative_stddev.filter(|_| !entry.is_reference),
+                )
+            } else {
+                (None, None)
+            };
+            for f in [relative, relative_stddev, Some(res.mad), res.cv] {
+                fields.push(Cow::Owned(
+                    f.map_or(String::new(), |f| self.decimal_separator.format(f))
+                        .into_bytes(),
+                ))
+            }
+            fields.push(Cow::Owned(res.runs.to_string().into_bytes()));
+            if with_throughput {
+                let throughput = res.throughput.as_ref();
+                for f in [
+                    throughput.map(|t| t.mean),
+                    throughput.and_then(|t| t.stddev),
+                    throughput.map(|t| t.min),
+                    throughput.map(|t| t.max),
+                ] {
+                    fields.push(Cow::Owned(
+                        f.map_or(String::new(), |f| self.decimal_separator.format(f))
+                            .into_bytes(),
+                    ))
+                }
+            }
+            if with_iteration {
+                fields.push(Cow::Owned(
+                    res.iteration
+                        .map_or(String::new(), |i| i.to_string())
+                        .into_bytes(),
+                ));
             }
             for v in res.parameters.values() {
                 fields.push(Cow::Borrowed(v.as_bytes()))
@@ -60,6 +190,79 @@ impl Exporter for CsvExporter {
     }
 }
 
+/// Exports one row per individual run instead of summary statistics
+#[derive(Default)]
+pub struct CsvRunsExporter {
+    decimal_separator: DecimalSeparator,
+}
+
+impl CsvRunsExporter {
+    pub fn new(decimal_separator: DecimalSeparator) -> Self {
+        CsvRunsExporter { decimal_separator }
+    }
+}
+
+impl Exporter for CsvRunsExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _reference: Option<usize>,
+        _unit: Option<Unit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let mut writer = self.decimal_separator.writer();
+
+        // Commands do not necessarily share the same parameters, so we use the union of all
+        // parameter names and leave the cells empty for missing parameters.
+        let param_names: BTreeSet<&String> = results
+            .iter()
+            .flat_map(|res| res.parameters.keys())
+            .collect();
+
+        {
+            let mut headers: Vec<String> =
+                ["command", "run", "time", "user", "system", "exit_code"]
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect();
+            for param_name in &param_names {
+                headers.push(format!("parameter_{param_name}"));
+            }
+            writer.write_record(headers)?;
+        }
+
+        let value_at =
+            |values: &Option<Vec<f64>>, i: usize| values.as_ref().and_then(|v| v.get(i).copied());
+
+        for res in results {
+            for (i, time) in res.times.iter().flatten().enumerate() {
+                let run = res.run_index(i);
+                let mut fields = vec![
+                    res.name.clone(),
+                    (run + 1).to_string(),
+                    self.decimal_separator.format(*time),
+                ];
+                for value in [value_at(&res.user_times, i), value_at(&res.system_times, i)] {
+                    fields.push(value.map_or(String::new(), |v| self.decimal_separator.format(v)));
+                }
+                fields.push(
+                    res.exit_codes
+                        .get(run)
+                        .copied()
+                        .flatten()
+                        .map_or(String::new(), |c| c.to_string()),
+                );
+                for param_name in &param_names {
+                    fields.push(res.parameters.get(*param_name).cloned().unwrap_or_default());
+                }
+                writer.write_record(fields)?;
+            }
+        }
+
+        Ok(writer.into_inner()?)
+    }
+}
+
 #[test]
 fn test_csv() {
     use std::collections::BTreeMap;
@@ -68,7 +271,9 @@ fn test_csv() {
     let results = vec![
         BenchmarkResult {
             command: String::from("FOO=one BAR=two command | 1"),
+            name: String::from("FOO=one BAR=two command | 1"),
             command_with_unused_parameters: String::from("FOO=one BAR=two command | 1"),
+            command_template: String::from("FOO=one BAR=two command | 1"),
             mean: 1.0,
             stddev: Some(2.0),
             median: 1.0,
@@ -76,6 +281,7 @@ fn test_csv() {
             system: 4.0,
             min: 5.0,
             max: 6.0,
+            runs: 3,
             times: Some(vec![7.0, 8.0, 9.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
             parameters: {
@@ -84,10 +290,13 @@ fn test_csv() {
                 params.insert("bar".into(), "two".into());
                 params
             },
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("FOO=one BAR=seven command | 2"),
+            name: String::from("FOO=one BAR=seven command | 2"),
             command_with_unused_parameters: String::from("FOO=one BAR=seven command | 2"),
+            command_template: String::from("FOO=one BAR=seven command | 2"),
             mean: 11.0,
             stddev: Some(12.0),
             median: 11.0,
@@ -95,6 +304,7 @@ fn test_csv() {
             system: 14.0,
             min: 15.0,
             max: 16.5,
+            runs: 3,
             times: Some(vec![17.0, 18.0, 19.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
             parameters: {
@@ -103,20 +313,201 @@ fn test_csv() {
                 params.insert("bar".into(), "seven".into());
                 params
             },
+            ..Default::default()
         },
     ];
     let exps: String = String::from(
-        "command,mean,stddev,median,user,system,min,max,parameter_bar,parameter_foo\n\
-        FOO=one BAR=two command | 1,1,2,1,3,4,5,6,two,one\n\
-        FOO=one BAR=seven command | 2,11,12,11,13,14,15,16.5,seven,one\n\
+        "command,mean,stddev,median,user,system,min,max,relative,relative_stddev,mad,cv,runs,parameter_bar,parameter_foo\n\
+        FOO=one BAR=two command | 1,1,2,1,3,4,5,6,1,,0,,3,two,one\n\
+        FOO=one BAR=seven command | 2,11,12,11,13,14,15,16.5,11,25.059928172283335,0,,3,seven,one\n\
         ",
     );
     let gens = String::from_utf8(
         exporter
-            .serialize(&results, Some(Unit::Second), SortOrder::Command)
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
 
     assert_eq!(exps, gens);
 }
+
+#[test]
+fn test_csv_relative_speed() {
+    let exporter = CsvExporter::default();
+    let result = |name: &str, mean| BenchmarkResult {
+        command: name.into(),
+        name: name.into(),
+        mean,
+        stddev: Some(0.1),
+        median: mean,
+        ..Default::default()
+    };
+    let serialize = |results: &[BenchmarkResult], reference, sort_order| {
+        String::from_utf8(
+            exporter
+                .serialize(results, reference, None, sort_order)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let results = [result("slow", 2.0), result("fast", 1.0)];
+    assert_eq!(
+        serialize(&results, None, SortOrder::MeanTime),
+        "command,mean,stddev,median,user,system,min,max,relative,relative_stddev,mad,cv,runs\n\
+         fast,1,0.1,1,0,0,0,0,1,,0,,0\n\
+         slow,2,0.1,2,0,0,0,0,2,0.223606797749979,0,,0\n"
+    );
+    assert_eq!(
+        serialize(&results, Some(0), SortOrder::Command),
+        "command,mean,stddev,median,user,system,min,max,relative,relative_stddev,mad,cv,runs\n\
+         slow,2,0.1,2,0,0,0,0,1,,0,,0\n\
+         fast,1,0.1,1,0,0,0,0,0.5,0.05590169943749475,0,,0\n"
+    );
+
+    // The columns are present, but empty if there is nothing to compare to
+    assert_eq!(
+        serialize(&results[..1], None, SortOrder::Command),
+        "command,mean,stddev,median,user,system,min,max,relative,relative_stddev,mad,cv,runs\n\
+         slow,2,0.1,2,0,0,0,0,,,0,,0\n"
+    );
+
+    // ... and if the compared times are zero
+    let results = [result("slow", 2.0), result("zero", 0.0)];
+    assert_eq!(
+        serialize(&results, Some(0), SortOrder::Command),
+        "command,mean,stddev,median,user,system,min,max,relative,relative_stddev,mad,cv,runs\n\
+         slow,2,0.1,2,0,0,0,0,,,0,,0\n\
+         zero,0,0.1,0,0,0,0,0,,,0,,0\n"
+    );
+}
+
+#[test]
+fn test_csv_throughput() {
+    use crate::benchmark::benchmark_result::Throughput;
+    use crate::util::units::SizeUnit;
+
+    let exporter = CsvExporter::default();
+    let results = vec![BenchmarkResult {
+        command: "gzip".into(),
+        name: "gzip".into(),
+        mean: 2.0,
+        stddev: Some(0.5),
+        median: 2.0,
+        mad: 0.5,
+        cv: Some(0.25),
+        min: 1.0,
+        max: 4.0,
+        throughput: Throughput::compute(100.0, SizeUnit::Bytes, 2.0, Some(0.5), 1.0, 4.0),
+        ..Default::default()
+    }];
+
+    assert_eq!(
+        String::from_utf8(
+            exporter
+                .serialize(&results, None, None, SortOrder::Command)
+                .unwrap()
+        )
+        .unwrap(),
+        "command,mean,stddev,median,user,system,min,max,relative,relative_stddev,mad,cv,runs,\
+         throughput,throughput_stddev,throughput_min,throughput_max\n\
+         gzip,2,0.5,2,0,0,1,4,,,0.5,0.25,0,50,12.5,25,100\n"
+    );
+}
+
+#[test]
+fn test_csv_runs() {
+    use std::collections::BTreeMap;
+    let exporter = CsvRunsExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
+            times: Some(vec![0.1, 0.2]),
+            user_times: Some(vec![0.01, 0.02]),
+            system_times: Some(vec![0.03, 0.04]),
+            exit_codes: vec![Some(0), None],
+            parameters: BTreeMap::from([(String::from("time"), String::from("0.1"))]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("echo \"a, b\""),
+            name: String::from("echo \"a, b\""),
+            times: Some(vec![1.5]),
+            exit_codes: vec![Some(1)],
+            parameters: BTreeMap::from([(String::from("args"), String::from("a, b"))]),
+            ..Default::default()
+        },
+    ];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        actual,
+        "command,run,time,user,system,exit_code,parameter_args,parameter_time\n\
+         sleep 0.1,1,0.1,0.01,0.03,0,,0.1\n\
+         sleep 0.1,2,0.2,0.02,0.04,,,0.1\n\
+         \"echo \"\"a, b\"\"\",1,1.5,,,1,\"a, b\",\n"
+    );
+}
+
+#[test]
+fn test_csv_decimal_comma() {
+    let results = vec![
+        BenchmarkResult {
+            name: String::from("slow"),
+            mean: 2.5,
+            stddev: Some(0.1),
+            median: 2.5,
+            runs: 2,
+            times: Some(vec![2.4, 2.6]),
+            exit_codes: vec![Some(0), Some(0)],
+            ..Default::default()
+        },
+        BenchmarkResult {
+            name: String::from("fast; small"),
+            mean: 1.25,
+            median: 1.25,
+            runs: 1,
+            times: Some(vec![1.25]),
+            exit_codes: vec![Some(0)],
+            ..Default::default()
+        },
+    ];
+    let serialize = |exporter: &dyn Exporter| {
+        String::from_utf8(
+            exporter
+                .serialize(&results, Some(0), None, SortOrder::Command)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        serialize(&CsvExporter::new(DecimalSeparator::Comma)),
+        "command;mean;stddev;median;user;system;min;max;relative;relative_stddev;mad;cv;runs\n\
+         slow;2,5;0,1;2,5;0;0;0;0;1;;0;;2\n\
+         \"fast; small\";1,25;0;1,25;0;0;0;0;0,5;;0;;1\n"
+    );
+    assert_eq!(
+        serialize(&CsvRunsExporter::new(DecimalSeparator::Comma)),
+        "command;run;time;user;system;exit_code\n\
+         slow;1;2,4;;;0\n\
+         slow;2;2,6;;;0\n\
+         \"fast; small\";1;1,25;;;0\n"
+    );
+    assert_eq!(
+        serialize(&CsvRunsExporter::new(DecimalSeparator::Point)),
+        "command,run,time,user,system,exit_code\n\
+         slow,1,2.4,,,0\n\
+         slow,2,2.6,,,0\n\
+         fast; small,1,1.25,,,0\n"
+    );
+}
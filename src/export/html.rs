@@ -0,0 +1,337 @@
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::benchmark::relative_speed::{self, BenchmarkResultWithRelativeSpeed};
+use crate::options::SortOrder;
+use crate::output::format::{format_duration_value, single_line};
+use crate::util::units::Unit;
+
+use super::markup::determine_unit_from_results;
+use super::Exporter;
+
+use anyhow::Result;
+
+/// Width of the area (in pixels) that is used for the bars in the chart
+const CHART_BAR_AREA_WIDTH: f64 = 400.0;
+
+/// Width of the area (in pixels) that is reserved for the command labels in the chart
+const CHART_LABEL_WIDTH: usize = 300;
+
+/// Height of a single bar (including spacing) in the chart
+const CHART_ROW_HEIGHT: usize = 30;
+
+#[derive(Default)]
+pub struct HtmlExporter {}
+
+impl Exporter for HtmlExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        reference: Option<usize>,
+        unit: Option<Unit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let unit = unit.unwrap_or_else(|| determine_unit_from_results(results));
+        let entries = relative_speed::compute(results, reference, sort_order);
+
+        let mut html = String::from(
+            "<!DOCTYPE html>\n\
+             <html>\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>Benchmark results</title>\n\
+             <style>\n\
+             body { font-family: sans-serif; }\n\
+             table { border-collapse: collapse; }\n\
+             th, td { padding: 4px 12px; border-bottom: 1px solid #ddd; }\n\
+             td.num { text-align: right; }\n\
+             </style>\n\
+             </head>\n\
+             <body>\n",
+        );
+        html.push_str(&table(&entries, unit));
+        html.push_str(&bar_chart(&entries, unit));
+        html.push_str("</body>\n</html>\n");
+
+        Ok(html.into_bytes())
+    }
+}
+
+/// Produce the results table with the same columns as the markup exporters
+fn table(entries: &[BenchmarkResultWithRelativeSpeed], unit: Unit) -> String {
+    let notation = format!("[{}]", unit.short_name());
+
+    let mut table = String::from("<table>\n");
+    table.push_str(&format!(
+        "<tr><th>Command</th><th>Mean {notation}</th><th>Min {notation}</th>\
+         <th>Max {notation}</th><th>Relative</th></tr>\n"
+    ));
+
+    for entry in entries {
+        let measurement = &entry.result;
+        let mean_str = format_duration_value(measurement.mean, Some(unit)).0;
+        let stddev_str = if let Some(stddev) = measurement.stddev {
+            format!(" ± {}", format_duration_value(stddev, Some(unit)).0)
+        } else {
+            "".into()
+        };
+        let min_str = format_duration_value(measurement.min, Some(unit)).0;
+        let max_str = format_duration_value(measurement.max, Some(unit)).0;
+        let (rel_time, rel_stddev) = entry.relative_time();
+        let rel_str = format!("{rel_time:.2}");
+        let rel_stddev_str = if entry.is_reference {
+            "".into()
+        } else if let Some(stddev) = rel_stddev {
+            format!(" ± {stddev:.2}")
+        } else {
+            "".into()
+        };
+
+        table.push_str(&format!(
+            "<tr><td><code>{}</code></td><td class=\"num\">{mean_str}{stddev_str}</td>\
+             <td class=\"num\">{min_str}</td><td class=\"num\">{max_str}</td>\
+             <td class=\"num\">{rel_str}{rel_stddev_str}</td></tr>\n",
+            escape_html(&single_line(&measurement.command_with_unused_parameters)),
+        ));
+    }
+
+    table.push_str("</table>\n");
+    table
+}
+
+/// Produce an inline SVG bar chart of the mean run times
+fn bar_chart(entries: &[BenchmarkResultWithRelativeSpeed], unit: Unit) -> String {
+    let max_mean = entries.iter().map(|e| e.result.mean).fold(0.0, f64::max);
+
+    let width = CHART_LABEL_WIDTH + CHART_BAR_AREA_WIDTH as usize + 100;
+    let height = CHART_ROW_HEIGHT * entries.len();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         font-family=\"sans-serif\" font-size=\"12\">\n"
+    );
+
+    for (i, entry) in entries.iter().enumerate() {
+        let y = i * CHART_ROW_HEIGHT;
+        let text_y = y + CHART_ROW_HEIGHT / 2 + 4;
+        let bar_width = if max_mean > 0.0 {
+            entry.result.mean / max_mean * CHART_BAR_AREA_WIDTH
+        } else {
+            0.0
+        };
+        let (mean_str, _) = format_duration_value(entry.result.mean, Some(unit));
+
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{text_y}\" text-anchor=\"end\">{}</text>\n",
+            CHART_LABEL_WIDTH - 10,
+            escape_html(&single_line(&entry.result.command_with_unused_parameters)),
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{CHART_LABEL_WIDTH}\" y=\"{}\" width=\"{bar_width:.1}\" height=\"{}\" \
+             fill=\"#4c72b0\"/>\n",
+            y + 5,
+            CHART_ROW_HEIGHT - 10,
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{text_y}\">{mean_str} {}</text>\n",
+            CHART_LABEL_WIDTH as f64 + bar_width + 5.0,
+            escape_html(&unit.short_name()),
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape characters that have a special meaning in HTML
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(escape_html("echo foo"), "echo foo");
+    assert_eq!(
+        escape_html("test a < b && echo \"x\" > 'y'"),
+        "test a &lt; b &amp;&amp; echo &quot;x&quot; &gt; &#39;y&#39;"
+    );
+}
+
+#[test]
+fn test_html_format_ms() {
+    let exporter = HtmlExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
+            command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
+            mean: 0.1057,
+            stddev: Some(0.0016),
+            median: 0.1057,
+            user: 0.0009,
+            system: 0.0011,
+            min: 0.1023,
+            max: 0.1080,
+            runs: 3,
+            times: Some(vec![0.1, 0.1, 0.1]),
+            exit_codes: vec![Some(0), Some(0), Some(0)],
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("test 1 < 2"),
+            name: String::from("test 1 < 2"),
+            command_with_unused_parameters: String::from("test 1 < 2"),
+            command_template: String::from("test 1 < 2"),
+            mean: 0.2114,
+            stddev: Some(0.0032),
+            median: 0.2114,
+            user: 0.0009,
+            system: 0.0012,
+            min: 0.2046,
+            max: 0.2160,
+            runs: 3,
+            times: Some(vec![0.2, 0.2, 0.2]),
+            exit_codes: vec![Some(0), Some(0), Some(0)],
+            ..Default::default()
+        },
+    ];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::MeanTime)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert!(actual.starts_with("<!DOCTYPE html>\n"));
+    assert!(actual.contains(
+        "<tr><th>Command</th><th>Mean [ms]</th><th>Min [ms]</th><th>Max [ms]</th><th>Relative</th></tr>\n"
+    ));
+    assert!(actual.contains(
+        "<tr><td><code>sleep 0.1</code></td><td class=\"num\">105.7 ± 1.6</td>\
+         <td class=\"num\">102.3</td><td class=\"num\">108.0</td><td class=\"num\">1.00</td></tr>\n"
+    ));
+    assert!(actual.contains(
+        "<tr><td><code>test 1 &lt; 2</code></td><td class=\"num\">211.4 ± 3.2</td>\
+         <td class=\"num\">204.6</td><td class=\"num\">216.0</td><td class=\"num\">2.00 ± 0.04</td></tr>\n"
+    ));
+    assert!(!actual.contains("test 1 < 2"));
+
+    // The slowest command spans the full width of the chart
+    assert!(actual.contains("<svg "));
+    assert!(actual.contains("width=\"400.0\""));
+    assert!(actual.contains("width=\"200.0\""));
+    assert!(actual.contains(">211.4 ms</text>"));
+    assert!(actual.ends_with("</svg>\n</body>\n</html>\n"));
+}
+
+#[test]
+fn test_html_format_sort_order_and_unit() {
+    let exporter = HtmlExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
+            command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
+            mean: 2.0,
+            median: 2.0,
+            min: 2.0,
+            max: 2.0,
+            runs: 1,
+            times: Some(vec![2.0]),
+            exit_codes: vec![Some(0)],
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("sleep 1"),
+            name: String::from("sleep 1"),
+            command_with_unused_parameters: String::from("sleep 1"),
+            command_template: String::from("sleep 1"),
+            mean: 1.0,
+            median: 1.0,
+            min: 1.0,
+            max: 1.0,
+            runs: 1,
+            times: Some(vec![1.0]),
+            exit_codes: vec![Some(0)],
+            ..Default::default()
+        },
+    ];
+
+    let by_command = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(by_command.contains("<th>Mean [s]</th>"));
+    assert!(by_command.find("sleep 2").unwrap() < by_command.find("sleep 1").unwrap());
+
+    let by_mean_time = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::Second), SortOrder::MeanTime)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(by_mean_time.find("sleep 1").unwrap() < by_mean_time.find("sleep 2").unwrap());
+}
+
+#[test]
+fn test_html_format_ns_and_min() {
+    let exporter = HtmlExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("make"),
+        command_with_unused_parameters: String::from("make"),
+        mean: 90.0,
+        min: 60.0,
+        max: 120.0,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(actual.contains("<th>Mean [min]</th>"));
+    assert!(actual.contains(
+        "<td class=\"num\">1.500</td><td class=\"num\">1.000</td><td class=\"num\">2.000</td>"
+    ));
+    assert!(actual.contains(">1.500 min</text>"));
+
+    let results = vec![BenchmarkResult {
+        command: String::from("true"),
+        command_with_unused_parameters: String::from("true"),
+        mean: 0.000_000_45,
+        min: 0.000_000_42,
+        max: 0.000_000_51,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::NanoSecond), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(actual.contains("<th>Mean [ns]</th>"));
+    assert!(actual.contains(
+        "<td class=\"num\">450</td><td class=\"num\">420</td><td class=\"num\">510</td>"
+    ));
+    assert!(actual.contains(">450 ns</text>"));
+}
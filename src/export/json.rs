@@ -1,33 +1,472 @@
+use std::collections::BTreeMap;
+
 use serde::*;
 use serde_json::to_vec_pretty;
 
+use super::metadata::RunMetadata;
 use super::Exporter;
-use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::benchmark::benchmark_result::{
+    BenchmarkResult, Percentiles, ShellSpawningTime, SkippedBenchmark,
+};
+use crate::benchmark::{relative_speed, significance};
+use crate::command::SHELL_PARAMETER;
+use crate::drift_detection::linear_drift;
 use crate::options::SortOrder;
-use crate::util::units::Unit;
+use crate::util::units::{Scalar, Unit};
 
 use anyhow::Result;
 
+#[derive(Serialize, Debug)]
+struct ExportedResult {
+    /// The result with at most `--max-export-samples` run times
+    #[serde(flatten)]
+    result: BenchmarkResult,
+
+    /// Percentiles of the run times, computed from the individual measurements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percentiles: Option<Percentiles>,
+
+    /// Bounds of the 95% confidence interval for the mean run time (null for a single run)
+    ci_lower: Option<Scalar>,
+    ci_upper: Option<Scalar>,
+
+    /// p-value of Welch's t-test for the difference to the reference benchmark
+    #[serde(skip_serializing_if = "Option::is_none")]
+    p_value: Option<Scalar>,
+
+    /// Slope of a linear fit of the run times against the run index (in seconds per run)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drift_slope: Option<Scalar>,
+
+    /// Number of runs for each distinct exit code ("none" for runs without an exit code)
+    exit_code_summary: BTreeMap<String, usize>,
+}
+
+/// Aggregated statistics over all benchmarks
+#[derive(Serialize, Debug)]
+struct ExportedSummary {
+    /// Geometric mean of the mean run times relative to the reference (null for a single
+    /// benchmark or if some mean times are zero)
+    geometric_mean_relative_time: Option<Scalar>,
+}
+
+/// Information about how the measurements have been performed
+#[derive(Serialize, Debug)]
+struct ExportedMetadata<'a> {
+    /// Version, options and host of the hyperfine invocation
+    #[serde(flatten)]
+    run: Option<&'a RunMetadata>,
+
+    /// Result of the calibration runs, i.e. the shell spawning time that has been subtracted from
+    /// the measurements (null if the commands have not been run through a shell or with
+    /// `--no-shell-calibration`)
+    shell_calibration: Option<ShellSpawningTime>,
+
+    /// Same as `shell_calibration`, kept for existing consumers of the export format
+    shell_spawning_time: Option<ShellSpawningTime>,
+
+    /// Result of the calibration runs with `--shell=none`, i.e. the process spawning time that
+    /// has been subtracted from the measurements (null if the commands have been run through a
+    /// shell or with `--no-spawn-calibration`)
+    spawn_calibration: Option<ShellSpawningTime>,
+
+    /// Result of the calibration runs of every shell, by the names given via `--shell`, if
+    /// several shells are compared. `shell_calibration` and `spawn_calibration` only refer to
+    /// the first of them in this case.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    shell_calibrations: BTreeMap<&'a str, ShellSpawningTime>,
+}
+
 #[derive(Serialize, Debug)]
 struct HyperfineSummary<'a> {
-    results: &'a [BenchmarkResult],
+    results: Vec<ExportedResult>,
+    summary: ExportedSummary,
+    metadata: ExportedMetadata<'a>,
+
+    /// Benchmarks that have not been performed because the `--total-time-budget` was exhausted
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    skipped: &'a [SkippedBenchmark],
 }
 
 #[derive(Default)]
-pub struct JsonExporter {}
+pub struct JsonExporter {
+    metadata: Option<RunMetadata>,
+
+    /// Maximum number of exported run times per benchmark (`--max-export-samples`)
+    max_samples: Option<usize>,
+}
+
+impl JsonExporter {
+    pub fn new(metadata: Option<RunMetadata>, max_samples: Option<usize>) -> Self {
+        JsonExporter {
+            metadata,
+            max_samples,
+        }
+    }
+}
 
 impl Exporter for JsonExporter {
     fn serialize(
         &self,
         results: &[BenchmarkResult],
+        reference: Option<usize>,
+        unit: Option<Unit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        self.serialize_with_skipped(results, &[], reference, unit, sort_order)
+    }
+
+    fn serialize_with_skipped(
+        &self,
+        results: &[BenchmarkResult],
+        skipped: &[SkippedBenchmark],
+        reference: Option<usize>,
         _unit: Option<Unit>,
         _sort_order: SortOrder,
     ) -> Result<Vec<u8>> {
-        let mut output = to_vec_pretty(&HyperfineSummary { results });
+        // The t-test compares mean times, so the fastest command by mean is the default reference
+        let reference = results
+            .first()
+            .map(|_| relative_speed::reference_of(results, reference, SortOrder::MeanTime));
+        let summary = ExportedSummary {
+            geometric_mean_relative_time: reference
+                .and_then(|reference| {
+                    relative_speed::compute_with_check_from_reference(
+                        results,
+                        reference,
+                        SortOrder::MeanTime,
+                    )
+                })
+                .and_then(|annotated_results| {
+                    relative_speed::geometric_mean_relative_time(&annotated_results)
+                }),
+        };
+        let shell_spawning_time = results.iter().find_map(|result| result.shell_spawning_time);
+        let several_shells = self
+            .metadata
+            .as_ref()
+            .is_some_and(|metadata| !metadata.options.shells.is_empty());
+        let shell_calibrations = results
+            .iter()
+            .filter(|_| several_shells)
+            .filter_map(|result| {
+                let shell = result.parameters.get(SHELL_PARAMETER)?;
+                let calibration = result.shell_spawning_time.or(result.spawn_calibration)?;
+                Some((shell.as_str(), calibration))
+            })
+            .collect();
+        let metadata = ExportedMetadata {
+            run: self.metadata.as_ref(),
+            shell_calibration: shell_spawning_time,
+            shell_spawning_time,
+            spawn_calibration: results.iter().find_map(|result| result.spawn_calibration),
+            shell_calibrations,
+        };
+        let results = results
+            .iter()
+            .map(|result| ExportedResult {
+                result: result.subsampled(self.max_samples),
+                percentiles: result.percentiles(),
+                ci_lower: result.confidence_interval().map(|(lower, _)| lower),
+                ci_upper: result.confidence_interval().map(|(_, upper)| upper),
+                p_value: reference
+                    .filter(|&reference| !std::ptr::eq(reference, result))
+                    .and_then(|reference| significance::p_value(reference, result)),
+                // The slope is given per run, also if the run times have been subsampled
+                drift_slope: result.times.as_ref().and_then(|times| {
+                    let slope = linear_drift(times)?.slope;
+                    Some(slope * times.len() as Scalar / result.num_runs()? as Scalar)
+                }),
+                exit_code_summary: result.exit_code_summary(),
+            })
+            .collect();
+
+        let mut output = to_vec_pretty(&HyperfineSummary {
+            results,
+            summary,
+            metadata,
+            skipped,
+        });
         if let Ok(ref mut content) = output {
             content.push(b'\n');
         }
 
         Ok(output?)
     }
+
+    fn uses_all_runs(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_json_export_contains_percentiles() {
+    use approx::assert_relative_eq;
+
+    let exporter = JsonExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("sleep 0.1"),
+        mean: 0.2,
+        times: Some(vec![0.1, 0.2, 0.3]),
+        exit_codes: vec![Some(0), Some(0), Some(0)],
+        ..Default::default()
+    }];
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let result = &json["results"][0];
+
+    assert_eq!(result["command"], "sleep 0.1");
+    assert_eq!(result["mean"], 0.2);
+    assert_relative_eq!(0.15, result["percentiles"]["p25"].as_f64().unwrap());
+    assert_relative_eq!(0.298, result["percentiles"]["p99"].as_f64().unwrap());
+}
+
+#[test]
+fn test_json_export_contains_p_values() {
+    let exporter = JsonExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("fast"),
+            mean: 1.0,
+            times: Some(vec![0.9, 1.0, 1.1]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("slow"),
+            mean: 2.0,
+            times: Some(vec![1.9, 2.0, 2.1]),
+            ..Default::default()
+        },
+    ];
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    // The fastest benchmark is the reference
+    assert!(json["results"][0].get("p_value").is_none());
+    assert!(json["results"][1]["p_value"].as_f64().unwrap() < 0.01);
+
+    let output = exporter
+        .serialize(&results, Some(1), None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(json["results"][0]["p_value"].as_f64().unwrap() < 0.01);
+    assert!(json["results"][1].get("p_value").is_none());
+}
+
+#[test]
+fn test_json_export_contains_drift_slope() {
+    use approx::assert_relative_eq;
+
+    let exporter = JsonExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("throttled"),
+            mean: 1.2,
+            times: Some(vec![1.0, 1.1, 1.2, 1.3, 1.4]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("few runs"),
+            mean: 1.0,
+            times: Some(vec![1.0, 1.0]),
+            ..Default::default()
+        },
+    ];
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_relative_eq!(
+        0.1,
+        json["results"][0]["drift_slope"].as_f64().unwrap(),
+        epsilon = 1e-12
+    );
+    assert!(json["results"][1].get("drift_slope").is_none());
+}
+
+#[test]
+fn test_json_export_contains_exit_code_summary() {
+    let exporter = JsonExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("flaky"),
+        mean: 1.0,
+        times: Some(vec![1.0, 1.0, 1.0, 1.0]),
+        exit_codes: vec![Some(0), Some(1), None, Some(0)],
+        ..Default::default()
+    }];
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(
+        json["results"][0]["exit_code_summary"],
+        serde_json::json!({"0": 2, "1": 1, "none": 1})
+    );
+}
+
+#[test]
+fn test_json_export_contains_confidence_interval() {
+    use approx::assert_relative_eq;
+
+    let exporter = JsonExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("sleep 0.1"),
+            mean: 0.2,
+            stddev: Some(0.1),
+            times: Some(vec![0.1, 0.2, 0.3]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("single run"),
+            mean: 0.2,
+            stddev: None,
+            times: Some(vec![0.2]),
+            ..Default::default()
+        },
+    ];
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    // t = 4.303 for two degrees of freedom
+    let half_width = 4.302_652_730 * 0.1 / 3f64.sqrt();
+    let result = &json["results"][0];
+    assert_relative_eq!(
+        0.2 - half_width,
+        result["ci_lower"].as_f64().unwrap(),
+        epsilon = 1e-6
+    );
+    assert_relative_eq!(
+        0.2 + half_width,
+        result["ci_upper"].as_f64().unwrap(),
+        epsilon = 1e-6
+    );
+
+    let result = &json["results"][1];
+    assert!(result["ci_lower"].is_null() && result.get("ci_lower").is_some());
+    assert!(result["ci_upper"].is_null() && result.get("ci_upper").is_some());
+}
+
+#[test]
+fn test_json_export_contains_geometric_mean() {
+    use approx::assert_relative_eq;
+
+    let exporter = JsonExporter::default();
+    let result = |command: &str, mean| BenchmarkResult {
+        command: command.into(),
+        mean,
+        ..Default::default()
+    };
+
+    let results = vec![result("a", 1.0), result("b", 2.0), result("c", 8.0)];
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_relative_eq!(
+        4.0,
+        json["summary"]["geometric_mean_relative_time"]
+            .as_f64()
+            .unwrap()
+    );
+
+    // Degenerate cases
+    for results in [
+        vec![result("a", 1.0)],
+        vec![result("a", 0.0), result("b", 1.0)],
+    ] {
+        let output = exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert!(json["summary"]["geometric_mean_relative_time"].is_null());
+    }
+}
+
+#[test]
+fn test_json_export_contains_shell_spawning_time() {
+    let exporter = JsonExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: "sleep 0.1".into(),
+        shell_spawning_time: Some(ShellSpawningTime {
+            mean: 0.002,
+            stddev: 0.0005,
+            user: 0.0005,
+            system: 0.001,
+            runs: 50,
+            precise: true,
+        }),
+        ..Default::default()
+    }];
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let shell_calibration = &json["metadata"]["shell_calibration"];
+    assert_eq!(shell_calibration["mean"], 0.002);
+    assert_eq!(shell_calibration["stddev"], 0.0005);
+    assert_eq!(shell_calibration["user"], 0.0005);
+    assert_eq!(shell_calibration["system"], 0.001);
+    assert_eq!(shell_calibration["runs"], 50);
+    assert_eq!(shell_calibration["precise"], true);
+    assert_eq!(&json["metadata"]["shell_spawning_time"], shell_calibration);
+    assert!(json["results"][0].get("shell_spawning_time").is_none());
+
+    let results = vec![BenchmarkResult::default()];
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(json["metadata"]["shell_calibration"].is_null());
+    assert!(json["metadata"]["shell_spawning_time"].is_null());
+    assert!(json["metadata"]["spawn_calibration"].is_null());
+}
+
+#[test]
+fn test_json_export_lists_skipped_benchmarks() {
+    let exporter = JsonExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: "sleep 0.1".into(),
+        ..Default::default()
+    }];
+    let skipped = vec![SkippedBenchmark {
+        command: "sleep 0.2".into(),
+        name: "slow".into(),
+        ..Default::default()
+    }];
+    let output = exporter
+        .serialize_with_skipped(&results, &skipped, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        json["skipped"],
+        serde_json::json!([{"command": "sleep 0.2", "name": "slow"}])
+    );
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert!(json.get("skipped").is_none());
 }
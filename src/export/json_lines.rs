@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use serde::*;
+use serde_json::to_vec;
+
+use crate::util::units::Second;
+
+use anyhow::Result;
+
+/// A single benchmark run, as soon as it has been completed
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ExportedRun {
+    /// The full command line of the benchmarked program
+    pub command: String,
+
+    /// The name of the benchmark, as specified via `--command-name` (defaults to the command line)
+    pub name: String,
+
+    /// Index of the run within its benchmark (starting at 1), not counting warmup runs
+    pub run: usize,
+
+    /// Wall clock time of the run
+    pub time: Second,
+
+    /// Time spent in user mode
+    pub user: Second,
+
+    /// Time spent in kernel mode
+    pub system: Second,
+
+    /// Exit code of the run, if it was not aborted or terminated by a signal
+    pub exit_code: Option<i32>,
+
+    /// Parameter values for this benchmark
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, String>,
+}
+
+/// Serializes each run as a separate JSON object on a single line, such that a file that is
+/// appended to after every run always contains valid JSON Lines.
+#[derive(Default)]
+pub struct JsonLinesExporter {}
+
+impl JsonLinesExporter {
+    pub fn serialize(&self, run: &ExportedRun) -> Result<Vec<u8>> {
+        let mut line = to_vec(run)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+#[test]
+fn test_json_lines_format() {
+    let exporter = JsonLinesExporter::default();
+
+    let run = ExportedRun {
+        command: String::from("sleep 0.1"),
+        name: String::from("short"),
+        run: 3,
+        time: 0.1,
+        user: 0.01,
+        system: 0.02,
+        exit_code: Some(0),
+        parameters: BTreeMap::new(),
+    };
+    assert_eq!(
+        String::from_utf8(exporter.serialize(&run).unwrap()).unwrap(),
+        "{\"command\":\"sleep 0.1\",\"name\":\"short\",\"run\":3,\"time\":0.1,\
+         \"user\":0.01,\"system\":0.02,\"exit_code\":0}\n"
+    );
+
+    let run = ExportedRun {
+        command: String::from("sleep\n{t}"),
+        exit_code: None,
+        parameters: BTreeMap::from([(String::from("t"), String::from("1"))]),
+        ..run
+    };
+    assert_eq!(
+        String::from_utf8(exporter.serialize(&run).unwrap()).unwrap(),
+        "{\"command\":\"sleep\\n{t}\",\"name\":\"short\",\"run\":3,\"time\":0.1,\
+         \"user\":0.01,\"system\":0.02,\"exit_code\":null,\"parameters\":{\"t\":\"1\"}}\n"
+    );
+}
@@ -0,0 +1,252 @@
+//! JUnit XML report, which CI systems like Jenkins or GitLab can render natively. Every benchmark
+//! is a test case whose time is the mean run time. It fails if one of the `--assert` thresholds
+//! (or the `--junit-fail-above` limit) does not hold.
+
+use crate::benchmark::assertions::Assertion;
+use crate::benchmark::benchmark_result::{BenchmarkResult, SkippedBenchmark};
+use crate::options::SortOrder;
+use crate::output::format::format_duration;
+use crate::util::units::{Second, Unit};
+
+use super::Exporter;
+
+use anyhow::Result;
+
+/// Name of the test suite and class name of the test cases
+const SUITE_NAME: &str = "hyperfine";
+
+#[derive(Default)]
+pub struct JunitExporter {
+    /// Test cases fail if one of these assertions does not hold for the benchmark
+    assertions: Vec<Assertion>,
+}
+
+impl JunitExporter {
+    pub fn new(assertions: Vec<Assertion>) -> Self {
+        JunitExporter { assertions }
+    }
+
+    fn testcase(&self, result: &BenchmarkResult, unit: Option<Unit>) -> String {
+        let failures: Vec<String> = self
+            .assertions
+            .iter()
+            .filter_map(|assertion| assertion.check(result, unit))
+            .collect();
+
+        let mut xml = format!(
+            "    <testcase name=\"{}\" classname=\"{SUITE_NAME}\" time=\"{}\">\n",
+            escape_xml(&result.command_with_unused_parameters),
+            format_seconds(result.mean)
+        );
+        if !failures.is_empty() {
+            xml.push_str(&format!(
+                "      <failure message=\"{}\" type=\"assertion\">{}</failure>\n",
+                escape_xml(&format!("Assertion failed: {}", failures.join(", "))),
+                escape_xml(&failures.join("\n"))
+            ));
+        }
+        xml.push_str(&format!(
+            "      <system-out>{}</system-out>\n",
+            escape_xml(&statistics(result, unit))
+        ));
+        xml.push_str("    </testcase>\n");
+        xml
+    }
+
+    fn failures(&self, results: &[BenchmarkResult]) -> usize {
+        results
+            .iter()
+            .filter(|result| {
+                self.assertions
+                    .iter()
+                    .any(|assertion| assertion.check(result, None).is_some())
+            })
+            .count()
+    }
+}
+
+impl Exporter for JunitExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        reference: Option<usize>,
+        unit: Option<Unit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        self.serialize_with_skipped(results, &[], reference, unit, sort_order)
+    }
+
+    /// Benchmarks that have been skipped because the `--total-time-budget` was exhausted are
+    /// reported as skipped test cases
+    fn serialize_with_skipped(
+        &self,
+        results: &[BenchmarkResult],
+        skipped: &[SkippedBenchmark],
+        _reference: Option<usize>,
+        unit: Option<Unit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let tests = results.len() + skipped.len();
+        let failures = self.failures(results);
+        let time = format_seconds(results.iter().map(|result| result.mean).sum());
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"{SUITE_NAME}\" tests=\"{tests}\" failures=\"{failures}\" \
+             time=\"{time}\">\n"
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{SUITE_NAME}\" tests=\"{tests}\" failures=\"{failures}\" \
+             errors=\"0\" skipped=\"{}\" time=\"{time}\">\n",
+            skipped.len()
+        ));
+        for result in results {
+            xml.push_str(&self.testcase(result, unit));
+        }
+        for benchmark in skipped {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{SUITE_NAME}\" time=\"0\">\n      \
+                 <skipped message=\"The total time budget has been exhausted\"/>\n    \
+                 </testcase>\n",
+                escape_xml(&benchmark.name)
+            ));
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+
+        Ok(xml.into_bytes())
+    }
+
+    /// The assertions (like `p99 < 10ms`) are checked for all runs, no run times are exported
+    fn uses_all_runs(&self) -> bool {
+        true
+    }
+}
+
+/// Times in JUnit reports are given in seconds
+fn format_seconds(time: Second) -> String {
+    format!("{time:.6}")
+}
+
+/// The statistics of a benchmark in the format of the terminal output, for the `<system-out>`
+/// block of its test case. Like in the terminal, all times are shown in the unit of the mean
+/// time unless a unit is given.
+fn statistics(result: &BenchmarkResult, unit: Option<Unit>) -> String {
+    let unit = unit.unwrap_or_else(|| Unit::for_duration(result.mean));
+    let duration = |time| format_duration(time, Some(unit));
+    let mut lines = vec![
+        format!("Command: {}", result.command),
+        match result.stddev {
+            Some(stddev) => format!("Mean:    {} ± {}", duration(result.mean), duration(stddev)),
+            None => format!("Mean:    {}", duration(result.mean)),
+        },
+        format!("Median:  {}", duration(result.median)),
+        format!("Min:     {}", duration(result.min)),
+        format!("Max:     {}", duration(result.max)),
+        format!("User:    {}", duration(result.user)),
+        format!("System:  {}", duration(result.system)),
+        format!("Runs:    {}", result.runs),
+    ];
+    lines.extend(
+        result
+            .parameters
+            .iter()
+            .map(|(name, value)| format!("Parameter {name}: {value}")),
+    );
+    lines.join("\n")
+}
+
+/// Escape the given text for use in XML attributes and elements. Control characters other than
+/// tabs and line breaks are not allowed in XML 1.0, so they are replaced.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn test_escape_xml() {
+    assert_eq!(escape_xml("sleep 1"), "sleep 1");
+    assert_eq!(
+        escape_xml("test a < b && echo \"x\" > 'y'"),
+        "test a &lt; b &amp;&amp; echo &quot;x&quot; &gt; &apos;y&apos;"
+    );
+    assert_eq!(escape_xml("a\tb\nc\u{1b}[0m"), "a\tb\nc\u{fffd}[0m");
+}
+
+#[test]
+fn test_junit_format() {
+    let result = |command: &str, mean| BenchmarkResult {
+        command: command.to_string(),
+        command_with_unused_parameters: command.to_string(),
+        mean,
+        median: mean,
+        min: mean,
+        max: mean,
+        runs: 3,
+        ..Default::default()
+    };
+    let results = vec![
+        result("echo \"a < b\" && true", 0.1),
+        result("sleep 0.5", 0.5),
+    ];
+    let skipped = vec![SkippedBenchmark {
+        command: "sleep 10".into(),
+        name: "sleep 10".into(),
+        ..Default::default()
+    }];
+    let exporter = JunitExporter::new(vec![Assertion::parse("mean < 250ms").unwrap()]);
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize_with_skipped(&results, &skipped, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        actual,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"hyperfine\" tests=\"3\" failures=\"1\" time=\"0.600000\">\n  \
+         <testsuite name=\"hyperfine\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" \
+         time=\"0.600000\">\n    \
+         <testcase name=\"echo &quot;a &lt; b&quot; &amp;&amp; true\" classname=\"hyperfine\" \
+         time=\"0.100000\">\n      \
+         <system-out>Command: echo &quot;a &lt; b&quot; &amp;&amp; true\n\
+         Mean:    100.0 ms\n\
+         Median:  100.0 ms\n\
+         Min:     100.0 ms\n\
+         Max:     100.0 ms\n\
+         User:    0.0 ms\n\
+         System:  0.0 ms\n\
+         Runs:    3</system-out>\n    \
+         </testcase>\n    \
+         <testcase name=\"sleep 0.5\" classname=\"hyperfine\" time=\"0.500000\">\n      \
+         <failure message=\"Assertion failed: mean &lt; 250ms (actual value: 500.0 ms)\" \
+         type=\"assertion\">mean &lt; 250ms (actual value: 500.0 ms)</failure>\n      \
+         <system-out>Command: sleep 0.5\n\
+         Mean:    500.0 ms\n\
+         Median:  500.0 ms\n\
+         Min:     500.0 ms\n\
+         Max:     500.0 ms\n\
+         User:    0.0 ms\n\
+         System:  0.0 ms\n\
+         Runs:    3</system-out>\n    \
+         </testcase>\n    \
+         <testcase name=\"sleep 10\" classname=\"hyperfine\" time=\"0\">\n      \
+         <skipped message=\"The total time budget has been exhausted\"/>\n    \
+         </testcase>\n  \
+         </testsuite>\n\
+         </testsuites>\n"
+    );
+}
@@ -24,7 +24,7 @@ impl MarkupExporter for MarkdownExporter {
     }
 
     fn command(&self, cmd: &str) -> String {
-        format!("`{cmd}`")
+        format!("`{}`", cmd.replace('|', "\\|"))
     }
 }
 
@@ -67,13 +67,15 @@ fn cfg_test_table_header(unit_short_name: String) -> String {
 fn test_markdown_format_ms() {
     use super::Exporter;
     use crate::benchmark::benchmark_result::BenchmarkResult;
-    use std::collections::BTreeMap;
+
     let exporter = MarkdownExporter::default();
 
     let timing_results = vec![
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -81,13 +83,16 @@ fn test_markdown_format_ms() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -95,15 +100,16 @@ fn test_markdown_format_ms() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&timing_results, None, SortOrder::Command)
+            .serialize(&timing_results, None, None, SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
@@ -124,13 +130,15 @@ fn test_markdown_format_ms() {
 fn test_markdown_format_s() {
     use super::Exporter;
     use crate::benchmark::benchmark_result::BenchmarkResult;
-    use std::collections::BTreeMap;
+
     let exporter = MarkdownExporter::default();
 
     let timing_results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -138,13 +146,16 @@ fn test_markdown_format_s() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -152,15 +163,16 @@ fn test_markdown_format_s() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&timing_results, None, SortOrder::Command)
+            .serialize(&timing_results, None, None, SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
@@ -181,13 +193,15 @@ fn test_markdown_format_time_unit_s() {
     use super::Exporter;
     use crate::benchmark::benchmark_result::BenchmarkResult;
     use crate::util::units::Unit;
-    use std::collections::BTreeMap;
+
     let exporter = MarkdownExporter::default();
 
     let timing_results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -195,13 +209,16 @@ fn test_markdown_format_time_unit_s() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -209,16 +226,22 @@ fn test_markdown_format_time_unit_s() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
 
     {
         let actual = String::from_utf8(
             exporter
-                .serialize(&timing_results, Some(Unit::Second), SortOrder::Command)
+                .serialize(
+                    &timing_results,
+                    None,
+                    Some(Unit::Second),
+                    SortOrder::Command,
+                )
                 .unwrap(),
         )
         .unwrap();
@@ -236,7 +259,12 @@ fn test_markdown_format_time_unit_s() {
     {
         let actual = String::from_utf8(
             exporter
-                .serialize(&timing_results, Some(Unit::Second), SortOrder::MeanTime)
+                .serialize(
+                    &timing_results,
+                    None,
+                    Some(Unit::Second),
+                    SortOrder::MeanTime,
+                )
                 .unwrap(),
         )
         .unwrap();
@@ -259,13 +287,15 @@ fn test_markdown_format_time_unit_ms() {
     use super::Exporter;
     use crate::benchmark::benchmark_result::BenchmarkResult;
     use crate::util::units::Unit;
-    use std::collections::BTreeMap;
+
     let exporter = MarkdownExporter::default();
 
     let timing_results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -273,13 +303,16 @@ fn test_markdown_format_time_unit_ms() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -287,15 +320,21 @@ fn test_markdown_format_time_unit_ms() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&timing_results, Some(Unit::MilliSecond), SortOrder::Command)
+            .serialize(
+                &timing_results,
+                None,
+                Some(Unit::MilliSecond),
+                SortOrder::Command,
+            )
             .unwrap(),
     )
     .unwrap();
@@ -309,3 +348,63 @@ fn test_markdown_format_time_unit_ms() {
 
     assert_eq!(expect, actual);
 }
+
+/// Ensure that nanoseconds can be chosen explicitly and that minutes are chosen automatically
+/// for very long benchmarks.
+#[test]
+fn test_markdown_format_time_unit_ns_and_min() {
+    use super::Exporter;
+    use crate::benchmark::benchmark_result::BenchmarkResult;
+    use crate::util::units::Unit;
+    let exporter = MarkdownExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("make"),
+        command_with_unused_parameters: String::from("make"),
+        mean: 90.0,
+        stddev: Some(3.0),
+        median: 90.0,
+        min: 60.0,
+        max: 120.0,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    let expect = format!(
+        "{}\
+| `make` | 1.500 ± 0.050 | 1.000 | 2.000 | 1.00 |
+",
+        cfg_test_table_header("min".to_string())
+    );
+    assert_eq!(expect, actual);
+
+    let results = vec![BenchmarkResult {
+        command: String::from("true"),
+        command_with_unused_parameters: String::from("true"),
+        mean: 0.000_000_45,
+        stddev: Some(0.000_000_01),
+        median: 0.000_000_45,
+        min: 0.000_000_42,
+        max: 0.000_000_51,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::NanoSecond), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    let expect = format!(
+        "{}\
+| `true` | 450 ± 10 | 420 | 510 | 1.00 |
+",
+        cfg_test_table_header("ns".to_string())
+    );
+    assert_eq!(expect, actual);
+}
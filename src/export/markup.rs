@@ -1,90 +1,407 @@
+use std::collections::BTreeSet;
+
 use crate::benchmark::relative_speed::BenchmarkResultWithRelativeSpeed;
 use crate::benchmark::{benchmark_result::BenchmarkResult, relative_speed};
+use crate::error::OptionsError;
 use crate::options::SortOrder;
-use crate::output::format::format_duration_value;
-use crate::util::units::Unit;
+use crate::output::format::{format_duration_value, single_line};
+use crate::util::units::{Second, Unit};
 
 use super::Exporter;
 use anyhow::Result;
 
+/// Title of the tables, for formats that support titles
+pub const DEFAULT_TABLE_TITLE: &str = "Benchmark results";
+
 pub enum Alignment {
     Left,
     Right,
 }
 
+/// Settings for the table of results
+pub struct TableOptions<'a> {
+    /// Title of the table (only emitted by formats that support titles)
+    pub title: &'a str,
+
+    /// Whether to show the mean user and system times in additional columns (in the same unit
+    /// as the wall clock times)
+    pub show_cpu_time: bool,
+
+    /// Whether to show the number of runs of every benchmark in an additional column
+    pub show_runs: bool,
+
+    /// Whether the parameter values are only shown in their own columns. The command column
+    /// then contains the command template if it is the same for all benchmarks.
+    pub parameter_columns: bool,
+
+    /// Whether every row is shown in its own unit (chosen for its mean time), which is then
+    /// part of the cells instead of the column headers
+    pub unit_per_row: bool,
+
+    /// The columns of the table (`--export-columns`). By default, these are the command, the
+    /// parameters, the mean, min and max times and the relative speed, with the CPU times and
+    /// the number of runs if `show_cpu_time` and `show_runs` are set.
+    pub columns: Option<&'a [Column]>,
+}
+
+impl Default for TableOptions<'_> {
+    fn default() -> Self {
+        TableOptions {
+            title: DEFAULT_TABLE_TITLE,
+            show_cpu_time: false,
+            show_runs: false,
+            parameter_columns: false,
+            unit_per_row: false,
+            columns: None,
+        }
+    }
+}
+
+/// Names of all parameters that appear in the given results, in alphabetical order. Each of them
+/// is shown in a separate column.
+fn parameter_names<'a>(entries: &[BenchmarkResultWithRelativeSpeed<'a>]) -> Vec<&'a str> {
+    entries
+        .iter()
+        .flat_map(|entry| entry.result.parameters.keys())
+        .map(String::as_str)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// The command template that is shared by all given results, if they have been run with
+/// parameters
+fn common_template<'a>(entries: &[BenchmarkResultWithRelativeSpeed<'a>]) -> Option<&'a str> {
+    let template = entries.first()?.result.command_template.as_str();
+    let is_common = !template.is_empty()
+        && entries.iter().all(|entry| {
+            entry.result.command_template == template && !entry.result.parameters.is_empty()
+        });
+    is_common.then_some(template)
+}
+
+/// Numbers are right-aligned, all other parameter values are left-aligned
+fn parameter_alignment(entries: &[BenchmarkResultWithRelativeSpeed], name: &str) -> Alignment {
+    let is_numeric = entries
+        .iter()
+        .filter_map(|entry| entry.result.parameters.get(name))
+        .all(|value| value.trim().parse::<f64>().is_ok());
+    if is_numeric {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    }
+}
+
+/// A column of the tables of results, as given via `--export-columns`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Command,
+
+    /// The mean time, followed by the standard deviation unless it has its own column
+    Mean,
+    Stddev,
+    Median,
+    Min,
+    Max,
+    Relative,
+    User,
+    System,
+    Runs,
+
+    /// The values of the parameter with the given name
+    Parameter(String),
+}
+
+impl Column {
+    /// Names of all columns except for the parameters
+    pub const NAMES: &'static [&'static str] = &[
+        "command", "mean", "stddev", "median", "min", "max", "relative", "user", "system", "runs",
+    ];
+
+    /// Parse a comma-separated list of column names. Other names than the `NAMES` have to be
+    /// one of the given parameters.
+    pub fn parse_list(
+        list: &str,
+        parameter_names: &[&str],
+    ) -> Result<Vec<Column>, OptionsError<'static>> {
+        list.split(',')
+            .map(str::trim)
+            .map(|name| match name {
+                "command" => Ok(Column::Command),
+                "mean" => Ok(Column::Mean),
+                "stddev" => Ok(Column::Stddev),
+                "median" => Ok(Column::Median),
+                "min" => Ok(Column::Min),
+                "max" => Ok(Column::Max),
+                "relative" => Ok(Column::Relative),
+                "user" => Ok(Column::User),
+                "system" => Ok(Column::System),
+                "runs" => Ok(Column::Runs),
+                _ if parameter_names.contains(&name) => Ok(Column::Parameter(name.to_string())),
+                _ => Err(OptionsError::UnknownExportColumn(
+                    name.to_string(),
+                    Column::NAMES
+                        .iter()
+                        .chain(parameter_names)
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )),
+            })
+            .collect()
+    }
+}
+
+/// The columns of the tables if no `--export-columns` have been given
+fn default_columns(parameter_names: &[&str], options: &TableOptions) -> Vec<Column> {
+    let mut columns = vec![Column::Command];
+    columns.extend(
+        parameter_names
+            .iter()
+            .map(|name| Column::Parameter(name.to_string())),
+    );
+    columns.extend([Column::Mean, Column::Min, Column::Max]);
+    if options.show_cpu_time {
+        columns.extend([Column::User, Column::System]);
+    }
+    if options.show_runs {
+        columns.push(Column::Runs);
+    }
+    columns.push(Column::Relative);
+    columns
+}
+
+/// The unit in which the times of a row are shown, and the suffix of the cells (if every row
+/// has its own unit)
+struct RowUnit {
+    unit: Unit,
+    suffix: String,
+}
+
+type CellFormatter<'a> = Box<dyn Fn(&BenchmarkResultWithRelativeSpeed, &RowUnit) -> String + 'a>;
+
+/// How a column is emitted: its header, the alignment of its cells and how they are computed
+struct ColumnDescriptor<'a> {
+    header: String,
+    alignment: Alignment,
+
+    /// Whether the cells contain commands or parameter values, which are shown on a single
+    /// line and escaped by the markup exporter (see `MarkupExporter::command`)
+    is_text: bool,
+
+    cell: CellFormatter<'a>,
+}
+
+fn column_descriptor<'a>(
+    column: &'a Column,
+    columns: &[Column],
+    entries: &[BenchmarkResultWithRelativeSpeed<'a>],
+    unit: Unit,
+    options: &TableOptions,
+) -> ColumnDescriptor<'a> {
+    let notation = if options.unit_per_row {
+        String::new()
+    } else {
+        format!(" [{}]", unit.short_name())
+    };
+    let duration = |value, row: &RowUnit| {
+        format!(
+            "{}{}",
+            format_duration_value(value, Some(row.unit)).0,
+            row.suffix
+        )
+    };
+    let time_column = |name: &str, value: fn(&BenchmarkResult) -> Second| ColumnDescriptor {
+        header: format!("{name}{notation}"),
+        alignment: Alignment::Right,
+        is_text: false,
+        cell: Box::new(move |entry, row| duration(value(entry.result), row)),
+    };
+
+    match column {
+        Column::Command => {
+            let template = common_template(entries).filter(|_| options.parameter_columns);
+            let parameter_columns = options.parameter_columns;
+            ColumnDescriptor {
+                header: "Command".to_string(),
+                alignment: Alignment::Left,
+                is_text: true,
+                cell: Box::new(move |entry, _| {
+                    match template {
+                        Some(template) => template,
+                        None if parameter_columns => &entry.result.name,
+                        None => &entry.result.command_with_unused_parameters,
+                    }
+                    .to_string()
+                }),
+            }
+        }
+        Column::Mean => {
+            let show_stddev = !columns.contains(&Column::Stddev);
+            ColumnDescriptor {
+                header: format!("Mean{notation}"),
+                alignment: Alignment::Right,
+                is_text: false,
+                cell: Box::new(move |entry, row| {
+                    let mean = format_duration_value(entry.result.mean, Some(row.unit)).0;
+                    let stddev = match entry.result.stddev.filter(|_| show_stddev) {
+                        Some(stddev) => {
+                            format!(" ± {}", format_duration_value(stddev, Some(row.unit)).0)
+                        }
+                        None => "".into(),
+                    };
+                    format!("{mean}{stddev}{}", row.suffix)
+                }),
+            }
+        }
+        Column::Stddev => ColumnDescriptor {
+            header: format!("Stddev{notation}"),
+            alignment: Alignment::Right,
+            is_text: false,
+            cell: Box::new(move |entry, row| {
+                entry
+                    .result
+                    .stddev
+                    .map_or(String::new(), |stddev| duration(stddev, row))
+            }),
+        },
+        Column::Median => time_column("Median", |result| result.median),
+        Column::Min => time_column("Min", |result| result.min),
+        Column::Max => time_column("Max", |result| result.max),
+        Column::User => time_column("User", |result| result.user),
+        Column::System => time_column("System", |result| result.system),
+        Column::Runs => ColumnDescriptor {
+            header: "Runs".to_string(),
+            alignment: Alignment::Right,
+            is_text: false,
+            cell: Box::new(|entry, _| entry.result.runs.to_string()),
+        },
+        Column::Relative => ColumnDescriptor {
+            header: "Relative".to_string(),
+            alignment: Alignment::Right,
+            is_text: false,
+            cell: Box::new(|entry, _| {
+                let (rel_time, rel_stddev) = entry.relative_time();
+                let rel_stddev_str = if entry.is_reference {
+                    "".into()
+                } else if let Some(stddev) = rel_stddev {
+                    format!(" ± {stddev:.2}")
+                } else {
+                    "".into()
+                };
+                format!("{rel_time:.2}{rel_stddev_str}")
+            }),
+        },
+        Column::Parameter(name) => ColumnDescriptor {
+            header: name.clone(),
+            alignment: parameter_alignment(entries, name),
+            is_text: true,
+            cell: Box::new(move |entry, _| {
+                entry
+                    .result
+                    .parameters
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_default()
+            }),
+        },
+    }
+}
+
+/// The cells of a table of results, before they are emitted in a specific format
+pub struct Table {
+    pub alignments: Vec<Alignment>,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Borrow the cells of a row
+pub(super) fn cells(row: &[String]) -> Vec<&str> {
+    row.iter().map(|s| s.as_str()).collect()
+}
+
 pub trait MarkupExporter {
-    fn table_results(&self, entries: &[BenchmarkResultWithRelativeSpeed], unit: Unit) -> String {
-        // prepare table header strings
-        let notation = format!("[{}]", unit.short_name());
-
-        // prepare table cells alignment
-        let cells_alignment = [
-            Alignment::Left,
-            Alignment::Right,
-            Alignment::Right,
-            Alignment::Right,
-            Alignment::Right,
-        ];
-
-        // emit table header format
-        let mut table = self.table_header(&cells_alignment);
-
-        // emit table header data
-        table.push_str(&self.table_row(&[
-            "Command",
-            &format!("Mean {notation}"),
-            &format!("Min {notation}"),
-            &format!("Max {notation}"),
-            "Relative",
-        ]));
-
-        // emit horizontal line
-        table.push_str(&self.table_divider(&cells_alignment));
-
-        for entry in entries {
-            let measurement = &entry.result;
-            // prepare data row strings
-            let cmd_str = measurement
-                .command_with_unused_parameters
-                .replace('|', "\\|");
-            let mean_str = format_duration_value(measurement.mean, Some(unit)).0;
-            let stddev_str = if let Some(stddev) = measurement.stddev {
-                format!(" ± {}", format_duration_value(stddev, Some(unit)).0)
-            } else {
-                "".into()
-            };
-            let min_str = format_duration_value(measurement.min, Some(unit)).0;
-            let max_str = format_duration_value(measurement.max, Some(unit)).0;
-            let rel_str = format!("{:.2}", entry.relative_speed);
-            let rel_stddev_str = if entry.is_reference {
-                "".into()
-            } else if let Some(stddev) = entry.relative_speed_stddev {
-                format!(" ± {stddev:.2}")
-            } else {
-                "".into()
-            };
-
-            // prepare table row entries
-            table.push_str(&self.table_row(&[
-                &self.command(&cmd_str),
-                &format!("{mean_str}{stddev_str}"),
-                &min_str,
-                &max_str,
-                &format!("{rel_str}{rel_stddev_str}"),
-            ]))
+    /// Build the table of results, with one column for each parameter
+    fn table_results(
+        &self,
+        entries: &[BenchmarkResultWithRelativeSpeed],
+        unit: Unit,
+        options: &TableOptions,
+    ) -> String {
+        let table = self.table_cells(entries, unit, options);
+
+        let mut output = self.table_header(&table.alignments, options.title);
+        output.push_str(&self.table_row(&cells(&table.header)));
+        output.push_str(&self.table_divider(&table.alignments));
+        for row in &table.rows {
+            output.push_str(&self.table_row(&cells(row)));
         }
+        output.push_str(&self.table_footer(&table.alignments));
 
-        // emit table footer format
-        table.push_str(&self.table_footer(&cells_alignment));
+        output
+    }
 
-        table
+    /// Compute the cells of the table of results. Commands and parameter values are shown on a
+    /// single line and formatted with `command`.
+    fn table_cells(
+        &self,
+        entries: &[BenchmarkResultWithRelativeSpeed],
+        unit: Unit,
+        options: &TableOptions,
+    ) -> Table {
+        let parameter_names = parameter_names(entries);
+        let columns = match options.columns {
+            Some(columns) => columns.to_vec(),
+            None => default_columns(&parameter_names, options),
+        };
+        let descriptors: Vec<ColumnDescriptor> = columns
+            .iter()
+            .map(|column| column_descriptor(column, &columns, entries, unit, options))
+            .collect();
+
+        let rows = entries
+            .iter()
+            .map(|entry| {
+                let unit = if options.unit_per_row {
+                    let unit = Unit::for_duration(entry.result.mean);
+                    RowUnit {
+                        unit,
+                        suffix: format!(" {}", unit.short_name()),
+                    }
+                } else {
+                    RowUnit {
+                        unit,
+                        suffix: String::new(),
+                    }
+                };
+                descriptors
+                    .iter()
+                    .map(|descriptor| {
+                        let cell = (descriptor.cell)(entry, &unit);
+                        if descriptor.is_text && !cell.is_empty() {
+                            self.command(&single_line(&cell))
+                        } else {
+                            cell
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Table {
+            header: descriptors.iter().map(|d| d.header.clone()).collect(),
+            alignments: descriptors.into_iter().map(|d| d.alignment).collect(),
+            rows,
+        }
     }
 
     fn table_row(&self, cells: &[&str]) -> String;
 
     fn table_divider(&self, cell_aligmnents: &[Alignment]) -> String;
 
-    fn table_header(&self, _cell_aligmnents: &[Alignment]) -> String {
+    /// Start the table. Formats that support it emit the given title.
+    fn table_header(&self, _cell_aligmnents: &[Alignment], _title: &str) -> String {
         "".to_string()
     }
 
@@ -92,10 +409,12 @@ pub trait MarkupExporter {
         "".to_string()
     }
 
-    fn command(&self, size: &str) -> String;
+    /// Format a command (or a parameter value) for a table cell, escaping characters that have
+    /// a special meaning in the markup language (like the cell separator '|')
+    fn command(&self, cmd: &str) -> String;
 }
 
-fn determine_unit_from_results(results: &[BenchmarkResult]) -> Unit {
+pub(super) fn determine_unit_from_results(results: &[BenchmarkResult]) -> Unit {
     if let Some(first_result) = results.first() {
         // Use the first BenchmarkResult entry to determine the unit for all entries.
         format_duration_value(first_result.mean, None).1
@@ -105,29 +424,100 @@ fn determine_unit_from_results(results: &[BenchmarkResult]) -> Unit {
     }
 }
 
+/// The given unit, or the one that is determined from the results if it is unset
+fn resolve_unit(unit: Option<Unit>, results: &[BenchmarkResult]) -> Unit {
+    unit.unwrap_or_else(|| determine_unit_from_results(results))
+}
+
+fn serialize_table(
+    exporter: &impl MarkupExporter,
+    results: &[BenchmarkResult],
+    reference: Option<usize>,
+    unit: Option<Unit>,
+    sort_order: SortOrder,
+    options: &TableOptions,
+) -> Vec<u8> {
+    let unit = resolve_unit(unit, results);
+    let entries = relative_speed::compute(results, reference, sort_order);
+
+    let table = exporter.table_results(&entries, unit, options);
+    table.as_bytes().to_vec()
+}
+
 impl<T: MarkupExporter> Exporter for T {
     fn serialize(
         &self,
         results: &[BenchmarkResult],
+        reference: Option<usize>,
         unit: Option<Unit>,
         sort_order: SortOrder,
     ) -> Result<Vec<u8>> {
-        let unit = unit.unwrap_or_else(|| determine_unit_from_results(results));
-        let entries = relative_speed::compute(results, sort_order);
+        Ok(serialize_table(
+            self,
+            results,
+            reference,
+            unit,
+            sort_order,
+            &TableOptions::default(),
+        ))
+    }
+}
+
+/// Changes the layout of the tables of the wrapped markup exporter
+pub struct WithTableOptions<T> {
+    pub exporter: T,
+
+    /// Add columns with the user and system times (`--show-cpu-time-in-exports`)
+    pub show_cpu_time: bool,
+
+    /// Add a column with the number of runs (`--show-runs-in-exports`)
+    pub show_runs: bool,
+
+    /// Show the parameter values only in their own columns (`--export-parameter-columns`)
+    pub parameter_columns: bool,
 
-        let table = self.table_results(&entries, unit);
-        Ok(table.as_bytes().to_vec())
+    /// Show every row in its own unit (`--time-unit auto-per-row`)
+    pub unit_per_row: bool,
+
+    /// Show only the given columns, in this order (`--export-columns`)
+    pub columns: Option<Vec<Column>>,
+}
+
+impl<T: MarkupExporter> Exporter for WithTableOptions<T> {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        reference: Option<usize>,
+        unit: Option<Unit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        Ok(serialize_table(
+            &self.exporter,
+            results,
+            reference,
+            unit,
+            sort_order,
+            &TableOptions {
+                show_cpu_time: self.show_cpu_time,
+                show_runs: self.show_runs,
+                parameter_columns: self.parameter_columns,
+                unit_per_row: self.unit_per_row,
+                columns: self.columns.as_deref(),
+                ..Default::default()
+            },
+        ))
     }
 }
 
 /// Check unit resolving for timing results and given unit 's'
 #[test]
 fn test_determine_unit_from_results_unit_given_s() {
-    use std::collections::BTreeMap;
     let results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -135,13 +525,16 @@ fn test_determine_unit_from_results_unit_given_s() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -149,14 +542,15 @@ fn test_determine_unit_from_results_unit_given_s() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
     let unit = Some(Unit::Second);
 
-    let markup_actual = unit.unwrap_or_else(|| determine_unit_from_results(&results));
+    let markup_actual = resolve_unit(unit, &results);
     let markup_expected = Unit::Second;
 
     assert_eq!(markup_expected, markup_actual);
@@ -165,11 +559,12 @@ fn test_determine_unit_from_results_unit_given_s() {
 /// Check unit resolving for timing results and given unit 'ms'
 #[test]
 fn test_determine_unit_from_results_unit_given_ms() {
-    use std::collections::BTreeMap;
     let results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -177,13 +572,16 @@ fn test_determine_unit_from_results_unit_given_ms() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -191,14 +589,15 @@ fn test_determine_unit_from_results_unit_given_ms() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
     let unit = Some(Unit::MilliSecond);
 
-    let markup_actual = unit.unwrap_or_else(|| determine_unit_from_results(&results));
+    let markup_actual = resolve_unit(unit, &results);
     let markup_expected = Unit::MilliSecond;
 
     assert_eq!(markup_expected, markup_actual);
@@ -207,11 +606,12 @@ fn test_determine_unit_from_results_unit_given_ms() {
 /// Check unit resolving for timing results using the first result entry as 's'
 #[test]
 fn test_determine_unit_from_results_unit_first_s() {
-    use std::collections::BTreeMap;
     let results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -219,13 +619,16 @@ fn test_determine_unit_from_results_unit_first_s() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -233,14 +636,15 @@ fn test_determine_unit_from_results_unit_first_s() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
     let unit = None;
 
-    let markup_actual = unit.unwrap_or_else(|| determine_unit_from_results(&results));
+    let markup_actual = resolve_unit(unit, &results);
     let markup_expected = Unit::Second;
 
     assert_eq!(markup_expected, markup_actual);
@@ -249,11 +653,12 @@ fn test_determine_unit_from_results_unit_first_s() {
 /// Check unit resolving for timing results using the first result entry as 'ms'
 #[test]
 fn test_determine_unit_from_results_unit_first_ms() {
-    use std::collections::BTreeMap;
     let results = vec![
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -261,13 +666,16 @@ fn test_determine_unit_from_results_unit_first_ms() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -275,14 +683,15 @@ fn test_determine_unit_from_results_unit_first_ms() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
     let unit = None;
 
-    let markup_actual = unit.unwrap_or_else(|| determine_unit_from_results(&results));
+    let markup_actual = resolve_unit(unit, &results);
     let markup_expected = Unit::MilliSecond;
 
     assert_eq!(markup_expected, markup_actual);
@@ -294,8 +703,338 @@ fn test_determine_unit_from_results_unit_default_s() {
     let results: Vec<BenchmarkResult> = vec![];
     let unit = None;
 
-    let markup_actual = unit.unwrap_or_else(|| determine_unit_from_results(&results));
+    let markup_actual = resolve_unit(unit, &results);
     let markup_expected = Unit::Second;
 
     assert_eq!(markup_expected, markup_actual);
 }
+
+#[test]
+fn test_markup_export_with_cpu_time() {
+    use crate::export::asciidoc::AsciidocExporter;
+    use crate::export::markdown::MarkdownExporter;
+
+    let results = vec![
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("fast"),
+            mean: 0.1,
+            stddev: Some(0.01),
+            user: 0.05,
+            system: 0.02,
+            min: 0.09,
+            max: 0.11,
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("slow"),
+            mean: 0.2,
+            user: 0.15,
+            system: 0.0025,
+            min: 0.2,
+            max: 0.2,
+            ..Default::default()
+        },
+    ];
+    fn with_cpu_time<T>(exporter: T) -> WithTableOptions<T> {
+        WithTableOptions {
+            exporter,
+            show_cpu_time: true,
+            show_runs: false,
+            parameter_columns: false,
+            unit_per_row: false,
+            columns: None,
+        }
+    }
+    let serialize = |exporter: &dyn Exporter, unit| {
+        String::from_utf8(
+            exporter
+                .serialize(&results, None, unit, SortOrder::Command)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        serialize(&with_cpu_time(MarkdownExporter::default()), None),
+        "| Command | Mean [ms] | Min [ms] | Max [ms] | User [ms] | System [ms] | Relative |\n\
+         |:---|---:|---:|---:|---:|---:|---:|\n\
+         | `fast` | 100.0 ± 10.0 | 90.0 | 110.0 | 50.0 | 20.0 | 1.00 |\n\
+         | `slow` | 200.0 | 200.0 | 200.0 | 150.0 | 2.5 | 2.00 |\n"
+    );
+    assert!(serialize(
+        &with_cpu_time(AsciidocExporter::default()),
+        Some(Unit::Second)
+    )
+    .starts_with(
+        ".Benchmark results\n[cols=\"<,>,>,>,>,>,>\"]\n|===\n\
+                      | Command \n| Mean [s] \n| Min [s] \n| Max [s] \n| User [s] \n| System [s] \n\
+                      | Relative \n"
+    ));
+
+    // The default layout does not contain the CPU times
+    assert!(!serialize(&MarkdownExporter::default(), None).contains("User"));
+}
+
+#[test]
+fn test_markup_export_with_unit_per_row() {
+    use crate::export::markdown::MarkdownExporter;
+
+    let results = vec![
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("fast"),
+            mean: 0.0021,
+            stddev: Some(0.0001),
+            user: 0.001,
+            system: 0.0005,
+            min: 0.002,
+            max: 0.0022,
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("slow"),
+            mean: 90.0,
+            user: 85.0,
+            system: 1.5,
+            min: 90.0,
+            max: 90.0,
+            ..Default::default()
+        },
+    ];
+    let exporter = WithTableOptions {
+        exporter: MarkdownExporter::default(),
+        show_cpu_time: true,
+        show_runs: false,
+        parameter_columns: false,
+        unit_per_row: true,
+        columns: None,
+    };
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        actual,
+        "| Command | Mean | Min | Max | User | System | Relative |\n\
+         |:---|---:|---:|---:|---:|---:|---:|\n\
+         | `fast` | 2.1 ± 0.1 ms | 2.0 ms | 2.2 ms | 1.0 ms | 0.5 ms | 1.00 |\n\
+         | `slow` | 1.500 min | 1.500 min | 1.500 min | 1.417 min | 0.025 min | 42857.14 |\n"
+    );
+}
+
+#[test]
+fn test_markup_export_with_runs() {
+    use crate::export::markdown::MarkdownExporter;
+
+    let results = vec![
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("fast"),
+            mean: 0.1,
+            min: 0.1,
+            max: 0.1,
+            runs: 30,
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("slow"),
+            mean: 0.2,
+            min: 0.2,
+            max: 0.2,
+            runs: 1,
+            ..Default::default()
+        },
+    ];
+    let exporter = WithTableOptions {
+        exporter: MarkdownExporter::default(),
+        show_cpu_time: false,
+        show_runs: true,
+        parameter_columns: false,
+        unit_per_row: false,
+        columns: None,
+    };
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        actual,
+        "| Command | Mean [ms] | Min [ms] | Max [ms] | Runs | Relative |\n\
+         |:---|---:|---:|---:|---:|---:|\n\
+         | `fast` | 100.0 | 100.0 | 100.0 | 30 | 1.00 |\n\
+         | `slow` | 200.0 | 200.0 | 200.0 | 1 | 2.00 |\n"
+    );
+}
+
+#[test]
+fn test_markup_export_with_columns() {
+    use crate::export::markdown::MarkdownExporter;
+
+    let result = |command: &str, threads: &str, mean, median| BenchmarkResult {
+        command_with_unused_parameters: String::from(command),
+        mean,
+        stddev: Some(0.01),
+        median,
+        parameters: [("threads".to_string(), threads.to_string())].into(),
+        ..Default::default()
+    };
+    let results = vec![
+        result("make -j 1", "1", 0.2, 0.19),
+        result("make -j 8", "8", 0.1, 0.09),
+    ];
+    let serialize = |columns: &str| {
+        let exporter = WithTableOptions {
+            exporter: MarkdownExporter::default(),
+            show_cpu_time: false,
+            show_runs: false,
+            parameter_columns: false,
+            unit_per_row: false,
+            columns: Some(Column::parse_list(columns, &["threads"]).unwrap()),
+        };
+        String::from_utf8(
+            exporter
+                .serialize(&results, None, None, SortOrder::Command)
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        serialize("threads,median,relative"),
+        "| threads | Median [ms] | Relative |\n\
+         |---:|---:|---:|\n\
+         | `1` | 190.0 | 2.00 ± 0.22 |\n\
+         | `8` | 90.0 | 1.00 |\n"
+    );
+    assert_eq!(
+        serialize("command, mean, stddev"),
+        "| Command | Mean [ms] | Stddev [ms] |\n\
+         |:---|---:|---:|\n\
+         | `make -j 1` | 200.0 | 10.0 |\n\
+         | `make -j 8` | 100.0 | 10.0 |\n"
+    );
+
+    assert!(matches!(
+        Column::parse_list("command,p95", &["threads"]),
+        Err(OptionsError::UnknownExportColumn(name, _)) if name == "p95"
+    ));
+}
+
+#[test]
+fn test_markup_export_with_parameter_columns() {
+    use std::collections::BTreeMap;
+
+    use crate::export::markdown::MarkdownExporter;
+
+    let parameters = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let results = vec![
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("gzip -1 a.txt"),
+            mean: 0.1,
+            min: 0.1,
+            max: 0.1,
+            parameters: parameters(&[("level", "1"), ("file", "a.txt")]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("gzip -9 b|c"),
+            mean: 0.2,
+            min: 0.2,
+            max: 0.2,
+            parameters: parameters(&[("level", "9"), ("file", "b|c")]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command_with_unused_parameters: String::from("gzip a.txt"),
+            mean: 0.3,
+            min: 0.3,
+            max: 0.3,
+            parameters: parameters(&[("file", "a.txt")]),
+            ..Default::default()
+        },
+    ];
+
+    let actual = String::from_utf8(
+        MarkdownExporter::default()
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        actual,
+        "| Command | file | level | Mean [s] | Min [s] | Max [s] | Relative |\n\
+         |:---|:---|---:|---:|---:|---:|---:|\n\
+         | `gzip -1 a.txt` | `a.txt` | `1` | 0.100 | 0.100 | 0.100 | 1.00 |\n\
+         | `gzip -9 b\\|c` | `b\\|c` | `9` | 0.200 | 0.200 | 0.200 | 2.00 |\n\
+         | `gzip a.txt` | `a.txt` |  | 0.300 | 0.300 | 0.300 | 3.00 |\n"
+    );
+}
+
+#[test]
+fn test_markup_export_with_command_template() {
+    use std::collections::BTreeMap;
+
+    use crate::export::markdown::MarkdownExporter;
+
+    let result = |threads: &str, mean| BenchmarkResult {
+        command_with_unused_parameters: format!("make -j {threads}"),
+        name: format!("make -j {threads}"),
+        command_template: String::from("make -j {threads}"),
+        mean,
+        min: mean,
+        max: mean,
+        parameters: BTreeMap::from([("threads".to_string(), threads.to_string())]),
+        ..Default::default()
+    };
+    let serialize = |results: &[BenchmarkResult]| {
+        String::from_utf8(
+            WithTableOptions {
+                exporter: MarkdownExporter::default(),
+                show_cpu_time: false,
+                show_runs: false,
+                parameter_columns: true,
+                unit_per_row: false,
+                columns: None,
+            }
+            .serialize(results, None, Some(Unit::Second), SortOrder::Command)
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        serialize(&[result("1", 2.0), result("8", 0.5)]),
+        "| Command | threads | Mean [s] | Min [s] | Max [s] | Relative |\n\
+         |:---|---:|---:|---:|---:|---:|\n\
+         | `make -j {threads}` | `1` | 2.000 | 2.000 | 2.000 | 4.00 |\n\
+         | `make -j {threads}` | `8` | 0.500 | 0.500 | 0.500 | 1.00 |\n"
+    );
+
+    // Without a common template, the commands are shown without the unused parameters
+    let other = BenchmarkResult {
+        command_with_unused_parameters: String::from("ninja (threads = 4)"),
+        name: String::from("ninja"),
+        command_template: String::from("ninja"),
+        mean: 1.0,
+        min: 1.0,
+        max: 1.0,
+        ..Default::default()
+    };
+    assert_eq!(
+        serialize(&[result("1", 2.0), other]),
+        "| Command | threads | Mean [s] | Min [s] | Max [s] | Relative |\n\
+         |:---|---:|---:|---:|---:|---:|\n\
+         | `make -j 1` | `1` | 2.000 | 2.000 | 2.000 | 2.00 |\n\
+         | `ninja` |  | 1.000 | 1.000 | 1.000 | 1.00 |\n"
+    );
+}
@@ -0,0 +1,347 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::*;
+
+use crate::benchmark::persistent_shell;
+use crate::command::Commands;
+use crate::options::{ExecutorKind, Options, Randomization, ShellMode};
+use crate::timer::priority;
+use crate::util::cpu_settings::{self, CpuSettings};
+use crate::util::system_load::{self, SystemLoad};
+use crate::util::units::Second;
+
+/// Information about the hyperfine invocation that produced an export. The keys of this struct
+/// are part of the JSON export format and should not be renamed or removed.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RunMetadata {
+    /// Version of hyperfine
+    pub hyperfine_version: String,
+
+    /// Full command line of the hyperfine invocation
+    pub command_line: Vec<String>,
+
+    /// Date and time at which hyperfine was started (ISO 8601, UTC)
+    pub timestamp: String,
+
+    /// Effective benchmark options
+    pub options: ExportedOptions,
+
+    /// Information about the machine on which the benchmarks were performed
+    pub host: HostInfo,
+
+    /// Groups of benchmarks (numbered from 1, in the order in which they have been specified)
+    /// that run identical commands
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_benchmarks: Vec<Vec<usize>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExportedOptions {
+    /// Shell that is used to run the commands (null for `--shell=none`)
+    pub shell: Option<String>,
+
+    /// All shells that have been compared if `--shell` has been given multiple times, as given
+    /// on the command line. `shell` is the first of them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shells: Vec<String>,
+
+    pub warmup_runs: u64,
+    pub warmup_duration: Option<Second>,
+    pub warmup_auto: bool,
+    pub warmup_per_command: Option<Vec<Option<u64>>>,
+    pub min_runs: u64,
+    pub max_runs: Option<u64>,
+    pub runs_per_command: Option<Vec<Option<u64>>>,
+    pub min_benchmarking_time: Second,
+    pub prepare: Option<Vec<String>>,
+
+    /// How the warmup runs interact with the preparation command (`--warmup-mode`)
+    pub warmup_mode: String,
+
+    pub conclude: Option<Vec<String>>,
+    pub validate: Option<Vec<String>>,
+    pub setup: Option<Vec<String>>,
+    pub cleanup: Option<Vec<String>>,
+
+    /// CPUs on which the benchmarked commands have been run (`--cpu`)
+    pub cpu_affinity: Option<Vec<usize>>,
+
+    /// Scheduling priority that has been applied to the benchmarked commands. This is "normal"
+    /// if a high priority has been requested via `--priority`, but was not permitted.
+    pub priority: String,
+
+    /// How the length of the randomized environment variable has been chosen: "on", "off" or
+    /// "seed" (`--randomization`)
+    pub randomization: String,
+
+    /// The seed given via `--randomization=seed:N`
+    pub randomization_seed: Option<u64>,
+
+    /// Whether only the first of several benchmarks with identical commands has been performed
+    /// (`--deduplicate`)
+    pub deduplicate: bool,
+
+    /// Whether the file system caches have been dropped before each timing run
+    /// (`--drop-file-caches`)
+    pub drop_file_caches: bool,
+
+    /// Whether the shell spawning time has been subtracted from the measurements (false with
+    /// `--no-shell-calibration`). If several shells are compared, this refers to all of them
+    /// except for `--shell=none`.
+    pub shell_calibration: bool,
+
+    /// Whether the process spawning time has been subtracted from the measurements with
+    /// `--shell=none` (false with `--no-spawn-calibration`)
+    pub spawn_calibration: bool,
+
+    /// Whether the commands have been run in a single long-lived shell
+    /// (`--shell-mode=persistent`, false if it was not supported). If several shells are
+    /// compared, see `persistent_shells`.
+    pub persistent_shell: bool,
+
+    /// The shells that have been run in the persistent mode, if several shells are compared
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub persistent_shells: Vec<String>,
+
+    /// How often the whole list of benchmarks has been performed (`--repeat-suite`)
+    pub repeat_suite: u64,
+
+    /// Maximum number of exported run times per benchmark (null if all are exported)
+    pub max_export_samples: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HostInfo {
+    pub os: String,
+    pub arch: String,
+
+    /// Name of the machine (null if it is hidden via `--no-metadata-host`)
+    pub hostname: Option<String>,
+
+    /// Model name of the CPU, if it can be determined
+    pub cpu_model: Option<String>,
+
+    /// Load of the system before the benchmarks have been started, if it can be determined (and
+    /// unless `--no-load-check` is used on Windows)
+    pub system_load: Option<SystemLoad>,
+
+    /// Settings of the CPUs on which the benchmarks have been run
+    pub cpu_settings: CpuSettings,
+}
+
+impl RunMetadata {
+    /// Collect the metadata for the current hyperfine process
+    pub fn collect(options: &Options, commands: &Commands, include_hostname: bool) -> Self {
+        RunMetadata {
+            hyperfine_version: env!("CARGO_PKG_VERSION").to_string(),
+            command_line: std::env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            timestamp: iso8601_timestamp(SystemTime::now()),
+            options: ExportedOptions {
+                deduplicate: commands.is_deduplicated(),
+                ..ExportedOptions::from(options)
+            },
+            host: HostInfo {
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                hostname: if include_hostname { hostname() } else { None },
+                cpu_model: cpu_model(),
+                // Sampling the load takes 100 ms on Windows, so it is skipped with
+                // `--no-load-check`
+                system_load: (options.load_check || !cfg!(windows))
+                    .then(system_load::current)
+                    .flatten(),
+                cpu_settings: cpu_settings::current(options.cpu_affinity.as_deref()),
+            },
+            duplicate_benchmarks: commands
+                .duplicates()
+                .iter()
+                .map(|group| group.iter().map(|index| index + 1).collect())
+                .collect(),
+        }
+    }
+}
+
+/// Whether the commands are run in a persistent shell with the given executor
+fn is_persistent_shell(executor_kind: &ExecutorKind, options: &Options) -> bool {
+    match executor_kind {
+        ExecutorKind::Shell(shell) => {
+            options.shell_mode == ShellMode::Persistent
+                && persistent_shell::unsupported_reason(shell, options).is_none()
+        }
+        _ => false,
+    }
+}
+
+impl From<&Options> for ExportedOptions {
+    fn from(options: &Options) -> Self {
+        let executor_kinds = options.executor_kinds();
+        ExportedOptions {
+            shell: match &options.executor_kind {
+                ExecutorKind::Shell(shell) => Some(shell.to_string()),
+                ExecutorKind::Raw | ExecutorKind::Mock(_) => None,
+            },
+            shells: options
+                .shells
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+            warmup_runs: options.warmup_count,
+            warmup_duration: options.warmup_duration,
+            warmup_auto: options.warmup_auto,
+            warmup_per_command: options.warmup_per_command.clone(),
+            min_runs: options.run_bounds.min,
+            max_runs: options.run_bounds.max,
+            runs_per_command: options.runs_per_command.clone(),
+            min_benchmarking_time: options.min_benchmarking_time,
+            prepare: options.preparation_command.clone(),
+            warmup_mode: options.warmup_mode.to_string(),
+            conclude: options.conclusion_command.clone(),
+            validate: options.validation_command.clone(),
+            setup: options.setup_command.clone(),
+            cleanup: options.cleanup_command.clone(),
+            cpu_affinity: options.cpu_affinity.clone(),
+            priority: priority::effective(options.priority).to_string(),
+            randomization: match options.randomization {
+                Randomization::On => "on",
+                Randomization::Off => "off",
+                Randomization::Seed(_) => "seed",
+            }
+            .to_string(),
+            randomization_seed: match options.randomization {
+                Randomization::Seed(seed) => Some(seed),
+                _ => None,
+            },
+            deduplicate: false,
+            drop_file_caches: options.drop_file_caches,
+            shell_calibration: options.shell_calibration
+                && executor_kinds
+                    .iter()
+                    .any(|kind| matches!(kind, ExecutorKind::Shell(_))),
+            spawn_calibration: options.spawn_calibration
+                && executor_kinds
+                    .iter()
+                    .any(|kind| matches!(kind, ExecutorKind::Raw))
+                && cfg!(unix),
+            persistent_shell: executor_kinds
+                .iter()
+                .all(|kind| is_persistent_shell(kind, options)),
+            persistent_shells: options
+                .shells
+                .iter()
+                .filter(|(_, kind)| is_persistent_shell(kind, options))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            repeat_suite: options.suite_repetitions,
+            max_export_samples: options.max_export_samples,
+        }
+    }
+}
+
+/// Format the given point in time as `YYYY-MM-DDTHH:MM:SSZ`
+fn iso8601_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, seconds_of_day) = (seconds / 86400, seconds % 86400);
+
+    // Convert the number of days since 1970-01-01 to a date in the proleptic Gregorian calendar,
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: `gethostname` writes at most `buffer.len()` bytes into the buffer
+    if unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) } != 0 {
+        return None;
+    }
+    let length = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    Some(String::from_utf8_lossy(&buffer[..length]).into_owned()).filter(|name| !name.is_empty())
+}
+
+#[cfg(windows)]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn hostname() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn cpu_model() -> Option<String> {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "model name").then(|| value.trim().to_string())
+        })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_model() -> Option<String> {
+    None
+}
+
+#[test]
+fn test_iso8601_timestamp() {
+    use std::time::Duration;
+
+    let at = |seconds| iso8601_timestamp(UNIX_EPOCH + Duration::from_secs(seconds));
+
+    assert_eq!(at(0), "1970-01-01T00:00:00Z");
+    assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(at(1_709_251_199), "2024-02-29T23:59:59Z");
+    assert_eq!(at(1_735_689_600), "2025-01-01T00:00:00Z");
+}
+
+#[test]
+fn test_exported_options() {
+    use crate::options::{RunBounds, Shell};
+
+    let options = Options {
+        run_bounds: RunBounds {
+            min: 3,
+            max: Some(5),
+        },
+        warmup_count: 2,
+        setup_command: Some(vec![String::from("make")]),
+        executor_kind: ExecutorKind::Shell(Shell::parse_from_str("bash").unwrap()),
+        ..Default::default()
+    };
+    let exported = ExportedOptions::from(&options);
+
+    assert_eq!(exported.shell.as_deref(), Some("bash"));
+    assert_eq!(exported.warmup_runs, 2);
+    assert_eq!((exported.min_runs, exported.max_runs), (3, Some(5)));
+    assert_eq!(exported.setup, Some(vec![String::from("make")]));
+    assert_eq!(exported.prepare, None);
+    assert_eq!(exported.priority, "normal");
+
+    let options = Options {
+        executor_kind: ExecutorKind::Raw,
+        ..Default::default()
+    };
+    assert_eq!(ExportedOptions::from(&options).shell, None);
+}
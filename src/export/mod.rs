@@ -1,24 +1,50 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::path::PathBuf;
 
 mod asciidoc;
 mod csv;
+mod html;
 mod json;
+mod json_lines;
+mod junit;
 mod markdown;
 mod markup;
+mod metadata;
 mod orgmode;
+mod sql;
+mod toml;
+mod txt;
+mod vega;
 
 use self::asciidoc::AsciidocExporter;
-use self::csv::CsvExporter;
+use self::csv::{CsvExporter, CsvRunsExporter, DecimalSeparator};
+use self::html::HtmlExporter;
 use self::json::JsonExporter;
-use self::markdown::MarkdownExporter;
+use self::json_lines::JsonLinesExporter;
+use self::junit::JunitExporter;
+use self::markup::{Column, WithTableOptions};
+use self::metadata::RunMetadata;
 use self::orgmode::OrgmodeExporter;
+use self::sql::SqlExporter;
+use self::toml::TomlExporter;
+use self::txt::PlainTextExporter;
+use self::vega::VegaExporter;
 
-use crate::benchmark::benchmark_result::BenchmarkResult;
-use crate::options::SortOrder;
-use crate::util::units::Unit;
+pub use self::json_lines::ExportedRun;
+pub(crate) use self::markdown::MarkdownExporter;
+pub(crate) use self::markup::{Alignment, MarkupExporter};
 
-use anyhow::{Context, Result};
+use crate::benchmark::assertions::Assertion;
+use crate::benchmark::benchmark_result::{BenchmarkResult, SkippedBenchmark};
+use crate::command::{Command, Commands};
+use crate::error::OptionsError;
+use crate::options::{Options, SortOrder};
+use crate::parameter::ParameterValue;
+use crate::util::units::{parse_duration, Unit};
+
+use anyhow::{bail, Context, Result};
 use clap::ArgMatches;
 
 /// The desired form of exporter to use for a given file.
@@ -30,29 +56,80 @@ pub enum ExportType {
     /// CSV (comma separated values) format
     Csv,
 
+    /// CSV format with one row per individual run
+    CsvRuns,
+
+    /// HTML report with an embedded bar chart
+    Html,
+
     /// JSON format
     Json,
 
+    /// JUnit XML report with a test case for every benchmark
+    Junit,
+
     /// Markdown table
     Markdown,
 
     /// Emacs org-mode tables
     Orgmode,
+
+    /// Plain text table with aligned columns
+    PlainText,
+
+    /// SQL statements for importing the results into a database
+    Sql,
+
+    /// TOML format with an array of tables for the results
+    Toml,
+
+    /// Vega-Lite specification of a chart with the individual run times
+    Vega,
 }
 
 /// Interface for different exporters.
 trait Exporter {
-    /// Export the given entries in the serialized form.
+    /// Export the given entries in the serialized form. The reference for relative speed
+    /// comparisons is given as an index into `results` (default: the fastest result).
     fn serialize(
         &self,
         results: &[BenchmarkResult],
+        reference: Option<usize>,
         unit: Option<Unit>,
         sort_order: SortOrder,
     ) -> Result<Vec<u8>>;
+
+    /// Like `serialize`, but also list the benchmarks that have been skipped because the
+    /// `--total-time-budget` was exhausted. Only formats that can represent them override this.
+    fn serialize_with_skipped(
+        &self,
+        results: &[BenchmarkResult],
+        _skipped: &[SkippedBenchmark],
+        reference: Option<usize>,
+        unit: Option<Unit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        self.serialize(results, reference, unit, sort_order)
+    }
+
+    /// Whether the exporter gets all run times instead of a uniform sample of them (see
+    /// `--max-export-samples`), because it computes statistics from them. Such an exporter has to
+    /// limit the exported run times itself.
+    fn uses_all_runs(&self) -> bool {
+        false
+    }
 }
 
 pub enum ExportTarget {
     File(String),
+
+    /// One file per combination of parameter values. The file name contains placeholders like
+    /// `{threads}`, and all files that can result from it are known in advance.
+    Files {
+        template: String,
+        file_names: Vec<String>,
+    },
+
     Stdout,
 }
 
@@ -66,56 +143,209 @@ struct ExporterWithTarget {
 pub struct ExportManager {
     exporters: Vec<ExporterWithTarget>,
     time_unit: Option<Unit>,
+
+    /// Decimal separator of the numbers in CSV exports
+    csv_decimal_separator: DecimalSeparator,
+
+    /// Whether or not the markup tables contain the user and system times
+    show_cpu_time: bool,
+
+    /// Whether or not the markup tables contain the number of runs
+    show_runs: bool,
+
+    /// Whether the parameter values are only shown in their own columns of the markup tables
+    parameter_columns: bool,
+
+    /// Whether every row of the markup tables is shown in its own unit
+    unit_per_row: bool,
+
+    /// Assertions whose violation marks a test case of the JUnit export as failed (the
+    /// `--assert` thresholds and the `--junit-fail-above` limit)
+    junit_assertions: Vec<Assertion>,
+
+    /// The columns of the markup tables, if they have been chosen explicitly
+    columns: Option<Vec<Column>>,
+
+    /// Target for the JSON Lines export, which is written after every single run
+    json_lines_target: Option<ExportTarget>,
+
+    /// Information about the hyperfine invocation for the JSON export
+    metadata: Option<RunMetadata>,
+
+    /// Maximum number of exported run times per benchmark (`--max-export-samples`)
+    max_export_samples: Option<usize>,
 }
 
 impl ExportManager {
     /// Build the ExportManager that will export the results specified
-    /// in the given ArgMatches
-    pub fn from_cli_arguments(matches: &ArgMatches, time_unit: Option<Unit>) -> Result<Self> {
+    /// in the given ArgMatches. File names may contain placeholders for the parameters of the
+    /// given commands.
+    pub fn from_cli_arguments(
+        matches: &ArgMatches,
+        options: &Options,
+        commands: &Commands,
+    ) -> Result<Self> {
         let mut export_manager = Self {
             exporters: vec![],
-            time_unit,
+            time_unit: options.time_unit,
+            csv_decimal_separator: match matches
+                .get_one::<String>("csv-decimal-separator")
+                .map(|s| s.as_str())
+            {
+                Some(",") => DecimalSeparator::Comma,
+                _ => DecimalSeparator::Point,
+            },
+            show_cpu_time: matches.get_flag("show-cpu-time-in-exports"),
+            show_runs: matches.get_flag("show-runs-in-exports"),
+            parameter_columns: matches.get_flag("export-parameter-columns"),
+            unit_per_row: options.time_unit_per_row,
+            columns: matches
+                .get_one::<String>("export-columns")
+                .map(|list| {
+                    let parameter_names: BTreeSet<&str> = commands
+                        .iter()
+                        .flat_map(|cmd| cmd.get_parameters().into_iter().map(|(name, _)| name))
+                        .collect();
+                    Column::parse_list(list, &parameter_names.into_iter().collect::<Vec<_>>())
+                })
+                .transpose()?,
+            junit_assertions: options
+                .assertions
+                .iter()
+                .cloned()
+                .chain(
+                    matches
+                        .get_one::<String>("junit-fail-above")
+                        .map(|limit| {
+                            parse_duration(limit).ok_or_else(|| {
+                                OptionsError::DurationParsingError("junit-fail-above", limit.into())
+                            })?;
+                            Ok::<_, OptionsError>(
+                                Assertion::parse(&format!("mean <= {limit}"))
+                                    .expect("the limit is a valid duration"),
+                            )
+                        })
+                        .transpose()?,
+                )
+                .collect(),
+            json_lines_target: None,
+            metadata: Some(RunMetadata::collect(
+                options,
+                commands,
+                !matches.get_flag("no-metadata-host"),
+            )),
+            max_export_samples: options.max_export_samples,
         };
+        // All export files are created here, before the first benchmark, such that invalid
+        // paths are reported immediately
+        let mut used_files = vec![];
         {
             let mut add_exporter = |flag, exporttype| -> Result<()> {
                 if let Some(filename) = matches.get_one::<String>(flag) {
-                    export_manager.add_exporter(exporttype, filename)?;
+                    export_manager.add_exporter(exporttype, filename, commands)?;
+                    let exporter = export_manager.exporters.last().expect("has been added");
+                    ensure_distinct_files(&mut used_files, &exporter.target, flag)?;
                 }
                 Ok(())
             };
             add_exporter("export-asciidoc", ExportType::Asciidoc)?;
             add_exporter("export-json", ExportType::Json)?;
+            add_exporter("export-junit", ExportType::Junit)?;
             add_exporter("export-csv", ExportType::Csv)?;
+            add_exporter("export-csv-runs", ExportType::CsvRuns)?;
+            add_exporter("export-html", ExportType::Html)?;
             add_exporter("export-markdown", ExportType::Markdown)?;
             add_exporter("export-orgmode", ExportType::Orgmode)?;
+            add_exporter("export-sql", ExportType::Sql)?;
+            add_exporter("export-toml", ExportType::Toml)?;
+            add_exporter("export-txt", ExportType::PlainText)?;
+            add_exporter("export-vega", ExportType::Vega)?;
+        }
+        if let Some(filename) = matches.get_one::<String>("export-json-lines") {
+            let target = create_target(filename, commands)?;
+            ensure_distinct_files(&mut used_files, &target, "export-json-lines")?;
+            export_manager.json_lines_target = Some(target);
         }
         Ok(export_manager)
     }
 
     /// Add an additional exporter to the ExportManager
-    pub fn add_exporter(&mut self, export_type: ExportType, filename: &str) -> Result<()> {
+    pub fn add_exporter(
+        &mut self,
+        export_type: ExportType,
+        filename: &str,
+        commands: &Commands,
+    ) -> Result<()> {
         let exporter: Box<dyn Exporter> = match export_type {
-            ExportType::Asciidoc => Box::<AsciidocExporter>::default(),
-            ExportType::Csv => Box::<CsvExporter>::default(),
-            ExportType::Json => Box::<JsonExporter>::default(),
-            ExportType::Markdown => Box::<MarkdownExporter>::default(),
-            ExportType::Orgmode => Box::<OrgmodeExporter>::default(),
+            ExportType::Asciidoc => Box::new(self.with_table_options(AsciidocExporter::default())),
+            ExportType::Csv => Box::new(CsvExporter::new(self.csv_decimal_separator)),
+            ExportType::CsvRuns => Box::new(CsvRunsExporter::new(self.csv_decimal_separator)),
+            ExportType::Html => Box::<HtmlExporter>::default(),
+            ExportType::Json => Box::new(JsonExporter::new(
+                self.metadata.clone(),
+                self.max_export_samples,
+            )),
+            ExportType::Junit => Box::new(JunitExporter::new(self.junit_assertions.clone())),
+            ExportType::Markdown => Box::new(self.with_table_options(MarkdownExporter::default())),
+            ExportType::Orgmode => Box::new(self.with_table_options(OrgmodeExporter::default())),
+            ExportType::PlainText => {
+                Box::new(self.with_table_options(PlainTextExporter::default()))
+            }
+            ExportType::Sql => Box::<SqlExporter>::default(),
+            ExportType::Toml => Box::<TomlExporter>::default(),
+            ExportType::Vega => Box::<VegaExporter>::default(),
         };
 
         self.exporters.push(ExporterWithTarget {
             exporter,
-            target: if filename == "-" {
-                ExportTarget::Stdout
-            } else {
-                let _ = File::create(filename)
-                    .with_context(|| format!("Could not create export file '{filename}'"))?;
-                ExportTarget::File(filename.to_string())
-            },
+            target: create_target(filename, commands)?,
         });
 
         Ok(())
     }
 
+    /// Apply the table layout options to the given markup exporter
+    fn with_table_options<T: MarkupExporter>(&self, exporter: T) -> WithTableOptions<T> {
+        WithTableOptions {
+            exporter,
+            show_cpu_time: self.show_cpu_time,
+            show_runs: self.show_runs,
+            parameter_columns: self.parameter_columns,
+            unit_per_row: self.unit_per_row,
+            columns: self.columns.clone(),
+        }
+    }
+
+    /// Append a single completed run to the JSON Lines export (if any). This is called
+    /// immediately after every run, such that the export contains all runs up to that point,
+    /// even if hyperfine is killed in the middle of a benchmark.
+    pub fn write_run(&self, run: &ExportedRun) -> Result<()> {
+        let line = || JsonLinesExporter::default().serialize(run);
+        let append_to_file = |filename: &str| -> Result<()> {
+            let mut file = OpenOptions::new().append(true).open(filename)?;
+            file.write_all(&line()?)
+                .with_context(|| format!("Failed to export run to '{filename}'"))
+        };
+
+        match self.json_lines_target {
+            Some(ExportTarget::File(ref filename)) => append_to_file(filename),
+            Some(ExportTarget::Files {
+                ref template,
+                ref file_names,
+            }) => match resolve_file_name(template, &run.parameters) {
+                Some(filename) => append_to_file(&filename),
+                // Runs of a reference command without these parameters are part of all files
+                None => file_names.iter().try_for_each(|f| append_to_file(f)),
+            },
+            Some(ExportTarget::Stdout) => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(&line()?)?;
+                Ok(stdout.flush()?)
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Write the given results to all Exporters. The 'intermediate' flag specifies
     /// whether this is being called while still performing benchmarks, or if this
     /// is the final call after all benchmarks have been finished. In the former case,
@@ -125,11 +355,31 @@ impl ExportManager {
     pub fn write_results(
         &self,
         results: &[BenchmarkResult],
+        skipped: &[SkippedBenchmark],
+        reference: Option<usize>,
         sort_order: SortOrder,
         intermediate: bool,
     ) -> Result<()> {
+        // The results keep all run times, such that the statistics are computed over all runs
+        let sampled: Vec<BenchmarkResult> = results
+            .iter()
+            .map(|result| result.subsampled(self.max_export_samples))
+            .collect();
         for e in &self.exporters {
-            let content = || e.exporter.serialize(results, self.time_unit, sort_order);
+            let results = if e.exporter.uses_all_runs() {
+                results
+            } else {
+                &sampled
+            };
+            let content = || {
+                e.exporter.serialize_with_skipped(
+                    results,
+                    skipped,
+                    reference,
+                    self.time_unit,
+                    sort_order,
+                )
+            };
 
             match e.target {
                 ExportTarget::File(ref filename) => {
@@ -137,6 +387,32 @@ impl ExportManager {
                         write_to_file(filename, &content()?)?
                     }
                 }
+                ExportTarget::Files { ref template, .. } => {
+                    if intermediate {
+                        for (filename, indices) in group_by_file_name(template, results) {
+                            let group: Vec<BenchmarkResult> =
+                                indices.iter().map(|&i| results[i].clone()).collect();
+                            let reference =
+                                reference.and_then(|r| indices.iter().position(|&i| i == r));
+                            let skipped: Vec<SkippedBenchmark> = skipped
+                                .iter()
+                                .filter(|s| {
+                                    resolve_file_name(template, &s.parameters)
+                                        .map_or(true, |f| f == filename)
+                                })
+                                .cloned()
+                                .collect();
+                            let content = e.exporter.serialize_with_skipped(
+                                &group,
+                                &skipped,
+                                reference,
+                                self.time_unit,
+                                sort_order,
+                            )?;
+                            write_to_file(&filename, &content)?
+                        }
+                    }
+                }
                 ExportTarget::Stdout => {
                     if !intermediate {
                         println!();
@@ -149,9 +425,272 @@ impl ExportManager {
     }
 }
 
+/// Create (or truncate) the export file with the given name, where '-' stands for stdout. If
+/// the name contains placeholders for parameters, one file is created for every combination of
+/// parameter values of the commands.
+fn create_target(filename: &str, commands: &Commands) -> Result<ExportTarget> {
+    let create_file = |filename: &str| -> Result<()> {
+        let _ = File::create(filename)
+            .with_context(|| format!("Could not create export file '{filename}'"))?;
+        Ok(())
+    };
+
+    if filename == "-" {
+        return Ok(ExportTarget::Stdout);
+    }
+
+    let placeholders = placeholder_names(filename);
+    if placeholders.is_empty() {
+        create_file(filename)?;
+        return Ok(ExportTarget::File(filename.to_string()));
+    }
+
+    for name in &placeholders {
+        let is_parameter = |cmd: &Command| cmd.get_parameters().iter().any(|(p, _)| p == name);
+        if !commands.iter().any(is_parameter) {
+            bail!(
+                "The export file name '{filename}' contains the placeholder '{{{name}}}', \
+                 but there is no parameter with this name"
+            );
+        }
+    }
+
+    let mut file_names: Vec<String> = vec![];
+    for command in commands.iter() {
+        let parameters = command.get_parameters();
+        if !placeholders
+            .iter()
+            .all(|name| parameters.iter().any(|(p, _)| p == name))
+        {
+            continue;
+        }
+
+        let resolved = Command::new_parametrized(None, filename, parameters);
+        resolved.validate_parameter_formats()?;
+        let resolved = resolved.get_command_line();
+        if !file_names.contains(&resolved) {
+            create_file(&resolved)?;
+            file_names.push(resolved);
+        }
+    }
+
+    Ok(ExportTarget::Files {
+        template: filename.to_string(),
+        file_names,
+    })
+}
+
+/// Fail if a file of the given target is also used by an earlier export (given via another
+/// `--export-*` option), since one of the exports would overwrite the other. The files have
+/// been created already, so equivalent paths like `out.json` and `./out.json` are detected.
+fn ensure_distinct_files<'f>(
+    used_files: &mut Vec<(PathBuf, &'f str)>,
+    target: &ExportTarget,
+    flag: &'f str,
+) -> Result<()> {
+    let file_names = match target {
+        ExportTarget::File(filename) => std::slice::from_ref(filename),
+        ExportTarget::Files { file_names, .. } => file_names.as_slice(),
+        ExportTarget::Stdout => &[],
+    };
+    for filename in file_names {
+        let path = std::fs::canonicalize(filename).unwrap_or_else(|_| filename.into());
+        if let Some((_, other_flag)) = used_files.iter().find(|(used, _)| *used == path) {
+            bail!(
+                "The export file '{filename}' is used by both '--{other_flag}' and '--{flag}'. \
+                 Use different files, since one export would overwrite the other."
+            );
+        }
+        used_files.push((path, flag));
+    }
+    Ok(())
+}
+
+/// Names of the parameters that are referenced by placeholders like `{threads}` or `{n:03}` in
+/// the given file name
+fn placeholder_names(filename: &str) -> Vec<&str> {
+    filename
+        .split('{')
+        .skip(1)
+        .filter_map(|part| {
+            let placeholder = &part[..part.find('}')?];
+            let name = placeholder.split(':').next().unwrap_or_default();
+            let is_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+            Some(name).filter(|_| is_name)
+        })
+        .collect()
+}
+
+/// Substitute the given parameter values into the file name. Returns `None` if some of the
+/// placeholders can not be replaced.
+fn resolve_file_name(template: &str, parameters: &BTreeMap<String, String>) -> Option<String> {
+    if !placeholder_names(template)
+        .iter()
+        .all(|name| parameters.contains_key(*name))
+    {
+        return None;
+    }
+
+    let parameters = parameters
+        .iter()
+        .map(|(name, value)| (name.as_str(), ParameterValue::Text(value.clone())));
+    Some(Command::new_parametrized(None, template, parameters).get_command_line())
+}
+
+/// Group the results (given by their indices) by the file name that they are exported to. Results
+/// that do not have all parameters of the file name (like a separate reference command) are part
+/// of all groups.
+fn group_by_file_name(template: &str, results: &[BenchmarkResult]) -> Vec<(String, Vec<usize>)> {
+    let file_names: Vec<Option<String>> = results
+        .iter()
+        .map(|result| resolve_file_name(template, &result.parameters))
+        .collect();
+
+    let mut groups: Vec<(String, Vec<usize>)> = vec![];
+    for filename in file_names.iter().flatten() {
+        if groups.iter().any(|(f, _)| f == filename) {
+            continue;
+        }
+        let indices = file_names
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.is_none() || f.as_ref() == Some(filename))
+            .map(|(i, _)| i)
+            .collect();
+        groups.push((filename.clone(), indices));
+    }
+    groups
+}
+
 /// Write the given content to a file with the specified name
 fn write_to_file(filename: &str, content: &[u8]) -> Result<()> {
     let mut file = OpenOptions::new().write(true).open(filename)?;
     file.write_all(content)
         .with_context(|| format!("Failed to export results to '{filename}'"))
 }
+
+#[test]
+fn test_placeholder_names() {
+    assert!(placeholder_names("results.json").is_empty());
+    assert_eq!(placeholder_names("results-{threads}.json"), vec!["threads"]);
+    assert_eq!(
+        placeholder_names("{compiler}/results-{n:03}.json"),
+        vec!["compiler", "n"]
+    );
+    assert!(placeholder_names("results-{}.json").is_empty());
+    assert!(placeholder_names("results-{a b}.json").is_empty());
+}
+
+#[test]
+fn test_group_by_file_name() {
+    let result = |parameters: &[(&str, &str)]| BenchmarkResult {
+        parameters: parameters
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    let results = [
+        result(&[]),
+        result(&[("n", "1"), ("m", "a")]),
+        result(&[("n", "2"), ("m", "a")]),
+        result(&[("n", "1"), ("m", "b")]),
+    ];
+
+    assert_eq!(
+        group_by_file_name("out-{n:02}.json", &results),
+        vec![
+            ("out-01.json".to_string(), vec![0, 1, 3]),
+            ("out-02.json".to_string(), vec![0, 2])
+        ]
+    );
+}
+
+/// Line breaks in commands must not break the rows of line-based formats, and must survive the
+/// formats that can represent them
+#[test]
+fn test_multi_line_commands() {
+    let command = "echo a\necho b";
+    let results = vec![
+        BenchmarkResult {
+            command: command.into(),
+            name: command.into(),
+            command_with_unused_parameters: command.into(),
+            command_template: command.into(),
+            mean: 1.0,
+            min: 1.0,
+            max: 1.0,
+            times: Some(vec![1.0]),
+            user_times: Some(vec![0.5]),
+            system_times: Some(vec![0.5]),
+            exit_codes: vec![Some(0)],
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: "true".into(),
+            name: "true".into(),
+            command_with_unused_parameters: "true".into(),
+            command_template: "true".into(),
+            mean: 2.0,
+            min: 2.0,
+            max: 2.0,
+            times: Some(vec![2.0]),
+            user_times: Some(vec![1.0]),
+            system_times: Some(vec![1.0]),
+            exit_codes: vec![Some(0)],
+            ..Default::default()
+        },
+    ];
+    let serialize = |exporter: &dyn Exporter| {
+        let output = exporter
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    };
+
+    let line_based: [(&str, Box<dyn Exporter>); 5] = [
+        ("asciidoc", Box::<AsciidocExporter>::default()),
+        ("html", Box::<HtmlExporter>::default()),
+        ("markdown", Box::<MarkdownExporter>::default()),
+        ("orgmode", Box::<OrgmodeExporter>::default()),
+        ("txt", Box::<PlainTextExporter>::default()),
+    ];
+    for (name, exporter) in line_based {
+        let output = serialize(&*exporter);
+        assert!(output.contains("echo a⏎echo b"), "{}: {}", name, output);
+        assert!(!output.contains("echo a\n"), "{}: {}", name, output);
+    }
+
+    // RFC 4180: fields with line breaks are quoted
+    for exporter in [
+        Box::<CsvExporter>::default() as Box<dyn Exporter>,
+        Box::<CsvRunsExporter>::default(),
+    ] {
+        let output = serialize(&*exporter);
+        let mut reader = ::csv::Reader::from_reader(output.as_bytes());
+        let commands: Vec<String> = reader
+            .records()
+            .map(|record| record.unwrap()[0].to_string())
+            .collect();
+        assert_eq!(commands, [command, "true"]);
+    }
+
+    for exporter in [
+        Box::<JsonExporter>::default() as Box<dyn Exporter>,
+        Box::<VegaExporter>::default(),
+    ] {
+        let output = serialize(&*exporter);
+        assert!(output.contains(r#""echo a\necho b""#), "{}", output);
+        serde_json::from_str::<serde_json::Value>(&output).unwrap();
+    }
+
+    let output = serialize(&TomlExporter::default());
+    let toml: ::toml::Value = ::toml::from_str(&output).unwrap();
+    assert_eq!(toml["results"][0]["command"].as_str(), Some(command));
+
+    let output = serialize(&SqlExporter::default());
+    assert!(output.contains("'echo a\necho b'"), "{}", output);
+}
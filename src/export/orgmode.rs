@@ -5,6 +5,20 @@ use crate::export::markup::MarkupExporter;
 pub struct OrgmodeExporter {}
 
 impl MarkupExporter for OrgmodeExporter {
+    /// Org-mode aligns columns with numbers to the right, but only if most of their cells are
+    /// numbers. A row of alignment cookies (which org-mode does not show in exports) makes the
+    /// alignment explicit.
+    fn table_header(&self, cell_aligmnents: &[Alignment], _title: &str) -> String {
+        let cookies: Vec<&str> = cell_aligmnents
+            .iter()
+            .map(|a| match a {
+                Alignment::Left => "<l>",
+                Alignment::Right => "<r>",
+            })
+            .collect();
+        self.table_row(&cookies)
+    }
+
     fn table_row(&self, cells: &[&str]) -> String {
         format!(
             "| {}  |  {} |\n",
@@ -18,7 +32,9 @@ impl MarkupExporter for OrgmodeExporter {
     }
 
     fn command(&self, cmd: &str) -> String {
-        format!("={cmd}=")
+        // A '|' would start a new table cell, even within verbatim text. Use a broken bar
+        // instead, as org-mode does not support escaping characters in verbatim text.
+        format!("={}=", cmd.replace('|', "¦"))
     }
 }
 
@@ -36,6 +52,24 @@ fn test_orgmode_formatter_table_data() {
     assert_eq!(expect, actual);
 }
 
+/// Check Emacs org-mode alignment cookies
+#[test]
+fn test_orgmode_exporter_table_header() {
+    let exporter = OrgmodeExporter::default();
+    let cells_alignment = [
+        Alignment::Left,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Right,
+        Alignment::Right,
+    ];
+
+    let actual = exporter.table_header(&cells_alignment, "Benchmark results");
+    let expect = "| <l>  |  <r> |  <r> |  <r> |  <r> |\n";
+
+    assert_eq!(expect, actual);
+}
+
 /// Check Emacs org-mode horizontal line formatting
 #[test]
 fn test_orgmode_formatter_table_line() {
@@ -58,7 +92,7 @@ fn test_orgmode_formatter_table_line() {
 #[cfg(test)]
 fn cfg_test_table_header(unit_short_name: String) -> String {
     format!(
-        "| Command  |  Mean [{unit_short_name}] |  Min [{unit_short_name}] |  Max [{unit_short_name}] |  Relative |\n|--+--+--+--+--|\n"
+        "| <l>  |  <r> |  <r> |  <r> |  <r> |\n| Command  |  Mean [{unit_short_name}] |  Min [{unit_short_name}] |  Max [{unit_short_name}] |  Relative |\n|--+--+--+--+--|\n"
     )
 }
 
@@ -72,14 +106,15 @@ fn cfg_test_table_header(unit_short_name: String) -> String {
 fn test_orgmode_format_ms() {
     use super::Exporter;
     use crate::benchmark::benchmark_result::BenchmarkResult;
-    use std::collections::BTreeMap;
 
     let exporter = OrgmodeExporter::default();
 
     let results = vec![
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -87,13 +122,16 @@ fn test_orgmode_format_ms() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -101,15 +139,16 @@ fn test_orgmode_format_ms() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&results, None, SortOrder::Command)
+            .serialize(&results, None, None, SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
@@ -131,14 +170,15 @@ fn test_orgmode_format_s() {
     use super::Exporter;
     use crate::benchmark::benchmark_result::BenchmarkResult;
     use crate::util::units::Unit;
-    use std::collections::BTreeMap;
 
     let exporter = OrgmodeExporter::default();
 
     let results = vec![
         BenchmarkResult {
             command: String::from("sleep 2"),
+            name: String::from("sleep 2"),
             command_with_unused_parameters: String::from("sleep 2"),
+            command_template: String::from("sleep 2"),
             mean: 2.0050,
             stddev: Some(0.0020),
             median: 2.0050,
@@ -146,13 +186,16 @@ fn test_orgmode_format_s() {
             system: 0.0012,
             min: 2.0020,
             max: 2.0080,
+            runs: 3,
             times: Some(vec![2.0, 2.0, 2.0]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
+            name: String::from("sleep 0.1"),
             command_with_unused_parameters: String::from("sleep 0.1"),
+            command_template: String::from("sleep 0.1"),
             mean: 0.1057,
             stddev: Some(0.0016),
             median: 0.1057,
@@ -160,15 +203,16 @@ fn test_orgmode_format_s() {
             system: 0.0011,
             min: 0.1023,
             max: 0.1080,
+            runs: 3,
             times: Some(vec![0.1, 0.1, 0.1]),
             exit_codes: vec![Some(0), Some(0), Some(0)],
-            parameters: BTreeMap::new(),
+            ..Default::default()
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&results, Some(Unit::Second), SortOrder::Command)
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
@@ -182,3 +226,100 @@ fn test_orgmode_format_s() {
 
     assert_eq!(expect, actual);
 }
+
+/// Check that vertical bars in commands and parameter values do not break the table
+#[test]
+fn test_orgmode_escapes_vertical_bars() {
+    use super::Exporter;
+    use crate::benchmark::benchmark_result::BenchmarkResult;
+    use crate::util::units::Unit;
+
+    let exporter = OrgmodeExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("cat foo | wc -l"),
+            command_with_unused_parameters: String::from("cat foo | wc -l"),
+            mean: 1.0,
+            min: 1.0,
+            max: 1.0,
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("echo a"),
+            command_with_unused_parameters: String::from("echo a (separator = |)"),
+            mean: 2.0,
+            min: 2.0,
+            max: 2.0,
+            ..Default::default()
+        },
+    ];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::Second), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    let rows: Vec<&str> = actual.lines().filter(|l| !l.starts_with("|--")).collect();
+    assert_eq!(rows[0], "| <l>  |  <r> |  <r> |  <r> |  <r> |");
+    assert_eq!(
+        rows[2],
+        "| =cat foo ¦ wc -l=  |  1.000 |  1.000 |  1.000 |  1.00 |"
+    );
+    assert_eq!(
+        rows[3],
+        "| =echo a (separator = ¦)=  |  2.000 |  2.000 |  2.000 |  2.00 |"
+    );
+
+    // Every row needs to have the same number of cells as the header
+    for row in rows {
+        assert_eq!(row.matches('|').count(), 6);
+    }
+}
+
+/// Check the nanosecond and minute units
+#[test]
+fn test_orgmode_format_ns_and_min() {
+    use super::Exporter;
+    use crate::benchmark::benchmark_result::BenchmarkResult;
+    use crate::util::units::Unit;
+    let exporter = OrgmodeExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("make"),
+        command_with_unused_parameters: String::from("make"),
+        mean: 90.0,
+        min: 60.0,
+        max: 120.0,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(actual.starts_with(&cfg_test_table_header("min".to_string())));
+    assert!(actual.contains("| =make=  |  1.500 |  1.000 |  2.000 |  1.00 |\n"));
+
+    let results = vec![BenchmarkResult {
+        command: String::from("true"),
+        command_with_unused_parameters: String::from("true"),
+        mean: 0.000_000_45,
+        min: 0.000_000_42,
+        max: 0.000_000_51,
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, Some(Unit::NanoSecond), SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(actual.starts_with(&cfg_test_table_header("ns".to_string())));
+    assert!(actual.contains("| =true=  |  450 |  420 |  510 |  1.00 |\n"));
+}
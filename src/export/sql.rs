@@ -0,0 +1,189 @@
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::options::SortOrder;
+use crate::util::units::{Scalar, Unit};
+
+use super::Exporter;
+
+use anyhow::Result;
+
+const CREATE_TABLES: &str = "CREATE TABLE IF NOT EXISTS benchmarks (\n    \
+    id INTEGER PRIMARY KEY,\n    \
+    command TEXT NOT NULL,\n    \
+    mean REAL,\n    \
+    stddev REAL,\n    \
+    median REAL,\n    \
+    user REAL,\n    \
+    system REAL,\n    \
+    min REAL,\n    \
+    max REAL\n\
+    );\n\
+    CREATE TABLE IF NOT EXISTS runs (\n    \
+    benchmark_id INTEGER NOT NULL REFERENCES benchmarks(id),\n    \
+    run INTEGER NOT NULL,\n    \
+    time REAL\n\
+    );\n";
+
+#[derive(Default)]
+pub struct SqlExporter {}
+
+impl Exporter for SqlExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _reference: Option<usize>,
+        _unit: Option<Unit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let mut sql = String::from(CREATE_TABLES);
+        sql.push_str("BEGIN TRANSACTION;\n");
+
+        for result in results {
+            sql.push_str(&format!(
+                "INSERT INTO benchmarks (command, mean, stddev, median, user, system, min, max) \
+                 VALUES ({}, {}, {}, {}, {}, {}, {}, {});\n",
+                quote_string(&result.name),
+                real(Some(result.mean)),
+                real(result.stddev),
+                real(Some(result.median)),
+                real(Some(result.user)),
+                real(Some(result.system)),
+                real(Some(result.min)),
+                real(Some(result.max)),
+            ));
+
+            // The ID of the benchmark is not known in advance (the tables might already contain
+            // results from previous exports), so we refer to the row that was just inserted.
+            if let Some(times) = result.times.as_ref().filter(|t| !t.is_empty()) {
+                sql.push_str("INSERT INTO runs (benchmark_id, run, time) VALUES\n");
+                let rows: Vec<String> = times
+                    .iter()
+                    .enumerate()
+                    .map(|(i, time)| run_row(result.run_index(i) + 1, *time))
+                    .collect();
+                sql.push_str(&rows.join(",\n"));
+                sql.push_str(";\n");
+            }
+        }
+
+        sql.push_str("COMMIT;\n");
+        Ok(sql.into_bytes())
+    }
+}
+
+fn run_row(run: usize, time: Scalar) -> String {
+    format!(
+        "    ((SELECT MAX(id) FROM benchmarks), {run}, {})",
+        real(Some(time))
+    )
+}
+
+/// Produce a SQL literal for a REAL column. Missing and non-finite values (which SQL can not
+/// represent) are written as NULL.
+fn real(value: Option<Scalar>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => "NULL".into(),
+    }
+}
+
+/// Produce a SQL string literal, with single quotes being escaped by doubling them
+fn quote_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[test]
+fn test_quote_string() {
+    assert_eq!(quote_string("sleep 1"), "'sleep 1'");
+    assert_eq!(
+        quote_string("echo 'it''s'; drop table x"),
+        "'echo ''it''''s''; drop table x'"
+    );
+}
+
+#[test]
+fn test_real() {
+    assert_eq!(real(Some(0.15)), "0.15");
+    assert_eq!(real(None), "NULL");
+    assert_eq!(real(Some(f64::NAN)), "NULL");
+    assert_eq!(real(Some(f64::INFINITY)), "NULL");
+    assert_eq!(real(Some(f64::NEG_INFINITY)), "NULL");
+}
+
+#[test]
+fn test_sql_format_non_finite_values() {
+    let exporter = SqlExporter::default();
+
+    let results = vec![BenchmarkResult {
+        command: String::from("sleep 1"),
+        name: String::from("sleep 1"),
+        mean: f64::NAN,
+        stddev: Some(f64::INFINITY),
+        times: Some(vec![f64::NAN]),
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert!(actual.contains("VALUES ('sleep 1', NULL, NULL, 0, 0, 0, 0, 0);\n"));
+    assert!(actual.contains("((SELECT MAX(id) FROM benchmarks), 1, NULL);\n"));
+}
+
+#[test]
+fn test_sql_format() {
+    let exporter = SqlExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("echo 'a'"),
+            name: String::from("echo 'a'"),
+            mean: 0.15,
+            stddev: Some(0.05),
+            median: 0.15,
+            user: 0.01,
+            system: 0.02,
+            min: 0.1,
+            max: 0.2,
+            times: Some(vec![0.1, 0.2]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("sleep 1"),
+            name: String::from("sleep 1"),
+            mean: 1.0,
+            median: 1.0,
+            min: 1.0,
+            max: 1.0,
+            times: Some(vec![1.0]),
+            ..Default::default()
+        },
+    ];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    let expected = format!(
+        "{CREATE_TABLES}\
+         BEGIN TRANSACTION;\n\
+         INSERT INTO benchmarks (command, mean, stddev, median, user, system, min, max) \
+         VALUES ('echo ''a''', 0.15, 0.05, 0.15, 0.01, 0.02, 0.1, 0.2);\n\
+         INSERT INTO runs (benchmark_id, run, time) VALUES\n    \
+         ((SELECT MAX(id) FROM benchmarks), 1, 0.1),\n    \
+         ((SELECT MAX(id) FROM benchmarks), 2, 0.2);\n\
+         INSERT INTO benchmarks (command, mean, stddev, median, user, system, min, max) \
+         VALUES ('sleep 1', 1, NULL, 1, 0, 0, 1, 1);\n\
+         INSERT INTO runs (benchmark_id, run, time) VALUES\n    \
+         ((SELECT MAX(id) FROM benchmarks), 1, 1);\n\
+         COMMIT;\n"
+    );
+
+    assert_eq!(expected, actual);
+}
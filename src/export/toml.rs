@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Serializer};
+
+use super::Exporter;
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::options::SortOrder;
+use crate::util::units::{Second, Unit};
+
+use anyhow::Result;
+
+/// The fields of the JSON export (see `BenchmarkResult`) that are included in the TOML export
+#[derive(Serialize, Debug)]
+struct ExportedResult<'a> {
+    command: &'a str,
+    mean: Second,
+
+    /// Not available for a single run (TOML has no null value, so the key is omitted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stddev: Option<Second>,
+
+    median: Second,
+    user: Second,
+    system: Second,
+    min: Second,
+    max: Second,
+    runs: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    times: Option<&'a [Second]>,
+
+    /// Runs without an exit code (terminated by a signal or aborted after a timeout) are
+    /// represented by the string "none", like in the `exit_code_summary` of the JSON export
+    #[serde(serialize_with = "serialize_exit_codes")]
+    exit_codes: &'a [Option<i32>],
+
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    parameters: &'a BTreeMap<String, String>,
+}
+
+fn serialize_exit_codes<S: Serializer>(
+    exit_codes: &&[Option<i32>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum ExitCode {
+        Code(i32),
+        None(&'static str),
+    }
+
+    serializer.collect_seq(
+        exit_codes
+            .iter()
+            .map(|code| code.map_or(ExitCode::None("none"), ExitCode::Code)),
+    )
+}
+
+#[derive(Serialize, Debug)]
+struct HyperfineSummary<'a> {
+    results: Vec<ExportedResult<'a>>,
+}
+
+#[derive(Default)]
+pub struct TomlExporter {}
+
+impl Exporter for TomlExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _reference: Option<usize>,
+        _unit: Option<Unit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let results = results
+            .iter()
+            .map(|result| ExportedResult {
+                command: &result.command,
+                mean: result.mean,
+                stddev: result.stddev,
+                median: result.median,
+                user: result.user,
+                system: result.system,
+                min: result.min,
+                max: result.max,
+                runs: result.runs,
+                times: result.times.as_deref(),
+                exit_codes: &result.exit_codes,
+                parameters: &result.parameters,
+            })
+            .collect();
+
+        Ok(toml::to_string(&HyperfineSummary { results })?.into_bytes())
+    }
+}
+
+#[test]
+fn test_toml_export() {
+    let exporter = TomlExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("sleep 0.1"),
+            mean: 0.1,
+            stddev: Some(0.01),
+            median: 0.1,
+            user: 0.001,
+            system: 0.002,
+            min: 0.09,
+            max: 0.11,
+            runs: 3,
+            times: Some(vec![0.09, 0.1, 0.11]),
+            exit_codes: vec![Some(0), Some(1), None],
+            parameters: BTreeMap::from([("n".to_string(), "1".to_string())]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("single run"),
+            mean: 2.0,
+            stddev: None,
+            median: 2.0,
+            min: 2.0,
+            max: 2.0,
+            runs: 1,
+            times: Some(vec![2.0]),
+            exit_codes: vec![Some(0)],
+            ..Default::default()
+        },
+    ];
+
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "[[results]]\n\
+         command = \"sleep 0.1\"\n\
+         mean = 0.1\n\
+         stddev = 0.01\n\
+         median = 0.1\n\
+         user = 0.001\n\
+         system = 0.002\n\
+         min = 0.09\n\
+         max = 0.11\n\
+         runs = 3\n\
+         times = [0.09, 0.1, 0.11]\n\
+         exit_codes = [0, 1, \"none\"]\n\
+         \n\
+         [results.parameters]\n\
+         n = \"1\"\n\
+         \n\
+         [[results]]\n\
+         command = \"single run\"\n\
+         mean = 2.0\n\
+         median = 2.0\n\
+         user = 0.0\n\
+         system = 0.0\n\
+         min = 2.0\n\
+         max = 2.0\n\
+         runs = 1\n\
+         times = [2.0]\n\
+         exit_codes = [0]\n"
+    );
+}
@@ -0,0 +1,125 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::benchmark::relative_speed::BenchmarkResultWithRelativeSpeed;
+use crate::export::markup::{cells, MarkupExporter, TableOptions};
+use crate::util::units::Unit;
+
+use super::markup::Alignment;
+
+/// Separator between two columns
+const COLUMN_SEPARATOR: &str = "  ";
+
+/// Plain text table that is aligned with spaces, e.g. for pasting into e-mails
+#[derive(Default)]
+pub struct PlainTextExporter {}
+
+impl PlainTextExporter {
+    /// Pad all cells to the width of their column (measured in terminal columns, such that wide
+    /// characters line up as well)
+    fn aligned_row(&self, row: &[String], widths: &[usize], alignments: &[Alignment]) -> String {
+        let padded: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .zip(alignments)
+            .map(|((cell, &width), alignment)| {
+                let padding = " ".repeat(width - cell.width());
+                match alignment {
+                    Alignment::Left => format!("{cell}{padding}"),
+                    Alignment::Right => format!("{padding}{cell}"),
+                }
+            })
+            .collect();
+        self.table_row(&cells(&padded))
+    }
+}
+
+impl MarkupExporter for PlainTextExporter {
+    fn table_results(
+        &self,
+        entries: &[BenchmarkResultWithRelativeSpeed],
+        unit: Unit,
+        options: &TableOptions,
+    ) -> String {
+        let table = self.table_cells(entries, unit, options);
+
+        let widths: Vec<usize> = (0..table.header.len())
+            .map(|column| {
+                std::iter::once(&table.header)
+                    .chain(&table.rows)
+                    .map(|row| row[column].width())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut output = self.aligned_row(&table.header, &widths, &table.alignments);
+        let divider: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+        output.push_str(&self.table_row(&cells(&divider)));
+        for row in &table.rows {
+            output.push_str(&self.aligned_row(row, &widths, &table.alignments));
+        }
+
+        output
+    }
+
+    fn table_row(&self, cells: &[&str]) -> String {
+        format!("{}\n", cells.join(COLUMN_SEPARATOR).trim_end())
+    }
+
+    fn table_divider(&self, cell_aligmnents: &[Alignment]) -> String {
+        self.table_row(&vec!["--"; cell_aligmnents.len()])
+    }
+
+    fn command(&self, cmd: &str) -> String {
+        cmd.to_string()
+    }
+}
+
+/// Check plain text data row formatting
+#[test]
+fn test_plain_text_formatter_table_data() {
+    let formatter = PlainTextExporter::default();
+
+    assert_eq!(formatter.table_row(&["a", "b", ""]), "a  b\n");
+}
+
+/// Columns are as wide as their longest cell, also for commands with wide characters
+#[test]
+fn test_plain_text_format() {
+    use super::Exporter;
+    use crate::benchmark::benchmark_result::BenchmarkResult;
+    use crate::options::SortOrder;
+    use std::collections::BTreeMap;
+
+    let exporter = PlainTextExporter::default();
+    let result = |command: &str, mean, n: &str| BenchmarkResult {
+        command: command.into(),
+        name: command.into(),
+        command_with_unused_parameters: command.into(),
+        mean,
+        stddev: Some(0.001),
+        min: mean,
+        max: mean,
+        parameters: BTreeMap::from([("n".to_string(), n.to_string())]),
+        ..Default::default()
+    };
+    let results = vec![
+        result("echo 日本語", 0.1, "1"),
+        result("echo abc", 0.25, "10"),
+    ];
+
+    let formatted = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        formatted,
+        "Command       n    Mean [ms]  Min [ms]  Max [ms]     Relative\n\
+         -----------  --  -----------  --------  --------  -----------\n\
+         echo 日本語   1  100.0 ± 1.0     100.0     100.0         1.00\n\
+         echo abc     10  250.0 ± 1.0     250.0     250.0  2.50 ± 0.03\n"
+    );
+}
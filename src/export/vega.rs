@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{json, to_vec_pretty};
+
+use super::markup::determine_unit_from_results;
+use super::Exporter;
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::benchmark::relative_speed;
+use crate::options::SortOrder;
+use crate::util::units::{Scalar, Unit};
+
+use anyhow::Result;
+
+/// Version of the Vega-Lite schema that the exported specification refers to
+const VEGA_LITE_SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+/// Name of the field that combines the values of several parameters into a single facet
+const PARAMETERS_FIELD: &str = "parameters";
+
+/// A single timing run, which is one row of the inline data of the chart
+#[derive(Serialize, Debug)]
+struct DataPoint<'a> {
+    command: &'a str,
+
+    /// Number of the run within its benchmark (starting at 1)
+    run: usize,
+
+    /// Wall clock time in the unit of the time axis
+    time: Scalar,
+
+    /// Values of all parameters, each as a separate field
+    #[serde(flatten)]
+    parameters: &'a BTreeMap<String, String>,
+
+    /// All parameter values like "threads=4, size=10" (only if there is more than one parameter)
+    #[serde(rename = "parameters", skip_serializing_if = "Option::is_none")]
+    combined_parameters: Option<String>,
+}
+
+/// Exports a Vega-Lite specification with a box plot and the individual run times of every
+/// benchmark. The run times are included as inline data, such that the chart can be rendered
+/// without any further files.
+#[derive(Default)]
+pub struct VegaExporter {}
+
+impl Exporter for VegaExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        reference: Option<usize>,
+        unit: Option<Unit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let unit = unit.unwrap_or_else(|| determine_unit_from_results(results));
+        let entries = relative_speed::compute(results, reference, sort_order);
+
+        // Benchmarks are shown in separate rows for the values of a single parameter, or for the
+        // combinations of values of several parameters
+        let parameter_names: BTreeSet<&str> = results
+            .iter()
+            .flat_map(|result| result.parameters.keys().map(|name| name.as_str()))
+            .collect();
+        let facet_field = match parameter_names.len() {
+            0 => None,
+            1 => parameter_names.first().copied(),
+            _ => Some(PARAMETERS_FIELD),
+        };
+
+        let values: Vec<DataPoint> = entries
+            .iter()
+            .flat_map(|entry| {
+                let result = entry.result;
+                let combined_parameters = (parameter_names.len() > 1).then(|| {
+                    result
+                        .parameters
+                        .iter()
+                        .map(|(name, value)| format!("{name}={value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                });
+                result
+                    .times
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(move |(run, &time)| DataPoint {
+                        command: &result.command_with_unused_parameters,
+                        run: result.run_index(run) + 1,
+                        time: time / unit.factor(),
+                        parameters: &result.parameters,
+                        combined_parameters: combined_parameters.clone(),
+                    })
+            })
+            .collect();
+
+        // A box plot per command, overlaid with a strip plot of the individual run times
+        let time_axis_title = format!("Time [{}]", unit.short_name());
+        let box_encoding = json!({
+            "y": { "field": "command", "type": "nominal", "sort": null, "title": null },
+            "x": { "field": "time", "type": "quantitative", "title": time_axis_title },
+        });
+        let mut point_encoding = box_encoding.clone();
+        point_encoding["tooltip"] = json!([
+            { "field": "command", "type": "nominal" },
+            { "field": "run", "type": "quantitative" },
+            { "field": "time", "type": "quantitative", "title": time_axis_title },
+        ]);
+        let layers = json!([
+            {
+                "mark": { "type": "boxplot", "extent": "min-max", "opacity": 0.5 },
+                "encoding": box_encoding,
+            },
+            {
+                "mark": { "type": "point", "filled": true, "size": 20 },
+                "encoding": point_encoding,
+            },
+        ]);
+
+        let mut spec = json!({
+            "$schema": VEGA_LITE_SCHEMA,
+            "description": "Benchmark results of hyperfine",
+            "data": { "values": values },
+        });
+        if let Some(facet_field) = facet_field {
+            spec["facet"] =
+                json!({ "row": { "field": facet_field, "type": "nominal", "sort": null } });
+            spec["spec"] = json!({ "layer": layers });
+            spec["resolve"] = json!({ "scale": { "y": "independent" } });
+        } else {
+            spec["layer"] = layers;
+        }
+
+        let mut output = to_vec_pretty(&spec)?;
+        output.push(b'\n');
+        Ok(output)
+    }
+}
+
+#[test]
+fn test_vega_export() {
+    let exporter = VegaExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("sleep 0.1"),
+            command_with_unused_parameters: String::from("sleep 0.1"),
+            mean: 0.1,
+            times: Some(vec![0.09, 0.11]),
+            ..Default::default()
+        },
+        BenchmarkResult {
+            command: String::from("sleep 0.2"),
+            command_with_unused_parameters: String::from("sleep 0.2"),
+            mean: 0.2,
+            times: Some(vec![0.2]),
+            ..Default::default()
+        },
+    ];
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(spec["$schema"], VEGA_LITE_SCHEMA);
+    let values = spec["data"]["values"].as_array().unwrap();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[1]["command"], "sleep 0.1");
+    assert_eq!(values[1]["run"], 2);
+    assert_eq!(values[1]["time"], 110.0);
+    assert_eq!(values[2]["command"], "sleep 0.2");
+
+    let layers = spec["layer"].as_array().unwrap();
+    assert_eq!(layers[0]["mark"]["type"], "boxplot");
+    assert_eq!(layers[1]["mark"]["type"], "point");
+    assert_eq!(layers[0]["encoding"]["x"]["title"], "Time [ms]");
+    assert!(spec.get("facet").is_none());
+}
+
+#[test]
+fn test_vega_export_with_parameters() {
+    let exporter = VegaExporter::default();
+
+    let result = |n: &str, size: Option<&str>| {
+        let mut parameters = BTreeMap::from([("n".to_string(), n.to_string())]);
+        if let Some(size) = size {
+            parameters.insert("size".to_string(), size.to_string());
+        }
+        BenchmarkResult {
+            command: format!("sleep {n}"),
+            command_with_unused_parameters: format!("sleep {n}"),
+            mean: 1.0,
+            times: Some(vec![1.0]),
+            parameters,
+            ..Default::default()
+        }
+    };
+
+    let output = exporter
+        .serialize(
+            &[result("1", None), result("2", None)],
+            None,
+            None,
+            SortOrder::Command,
+        )
+        .unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(spec["facet"]["row"]["field"], "n");
+    assert_eq!(spec["data"]["values"][1]["n"], "2");
+    assert!(spec["data"]["values"][1].get(PARAMETERS_FIELD).is_none());
+    assert_eq!(spec["spec"]["layer"].as_array().unwrap().len(), 2);
+
+    let output = exporter
+        .serialize(
+            &[result("1", Some("10")), result("2", Some("20"))],
+            None,
+            None,
+            SortOrder::Command,
+        )
+        .unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(spec["facet"]["row"]["field"], PARAMETERS_FIELD);
+    assert_eq!(spec["data"]["values"][0]["size"], "10");
+    assert_eq!(spec["data"]["values"][0][PARAMETERS_FIELD], "n=1, size=10");
+}
@@ -0,0 +1,107 @@
+#![cfg_attr(
+    all(windows, feature = "windows_process_extensions_main_thread_handle"),
+    feature(windows_process_extensions_main_thread_handle)
+)]
+
+//! The library behind the `hyperfine` command-line benchmarking tool.
+//!
+//! The public API is *unstable* and may change in any release. It allows running benchmarks
+//! from Rust code, without having to parse the JSON export of the `hyperfine` binary:
+//!
+//! ```no_run
+//! use hyperfine::{run_benchmarks, Command, Commands, Options};
+//!
+//! let commands = Commands::from(vec![
+//!     Command::new(None, "sleep 0.1"),
+//!     Command::new(Some("longer"), "sleep 0.2"),
+//! ]);
+//! let options = Options::default().with_runs(5).with_warmup(1);
+//!
+//! for result in run_benchmarks(&commands, &options).unwrap() {
+//!     println!("{}: {:.3} s", result.name, result.mean);
+//! }
+//! ```
+
+use std::ffi::OsString;
+
+use anyhow::Result;
+
+mod benchmark;
+mod cli;
+mod command;
+mod compare;
+mod drift_detection;
+mod error;
+mod export;
+pub mod options;
+mod outlier_detection;
+mod output;
+mod parameter;
+mod suite;
+mod timer;
+mod util;
+
+pub use benchmark::benchmark_result::BenchmarkResult;
+pub use benchmark::scheduler::Scheduler;
+pub use command::{Command, Commands};
+pub use export::ExportManager;
+pub use options::Options;
+
+use cli::get_cli_arguments;
+use command::CommandFileEntries;
+use suite::Suite;
+use util::interrupt;
+
+/// Run hyperfine with the given command-line arguments, like the `hyperfine` binary does. This
+/// is the entry point of the binary and not part of the public API.
+#[doc(hidden)]
+pub fn run_cli<I, T>(args: I) -> Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    // Enabled ANSI colors on Windows 10
+    #[cfg(windows)]
+    colored::control::set_virtual_terminal(true).unwrap();
+
+    let cli_arguments = get_cli_arguments(args);
+    if let Some(matches) = cli_arguments.subcommand_matches("compare") {
+        return compare::run(matches);
+    }
+
+    let mut options = Options::from_cli_arguments(&cli_arguments)?;
+    let command_file_entries = CommandFileEntries::from_cli_arguments(&cli_arguments)?;
+    let suite = Suite::from_cli_arguments(&cli_arguments)?;
+    let commands = match &suite {
+        Some(suite) => Commands::from_suite(&cli_arguments, suite)?,
+        None => Commands::from_cli_arguments(&cli_arguments, &command_file_entries)?,
+    };
+    if let Some(suite) = &suite {
+        suite.apply_to(&mut options, &commands)?;
+    }
+    let export_manager = ExportManager::from_cli_arguments(&cli_arguments, &options, &commands)?;
+
+    options.validate_against_command_list(&commands)?;
+
+    interrupt::install_handler();
+
+    let mut scheduler = Scheduler::new(&commands, &options, &export_manager);
+    scheduler.run_benchmarks()?;
+    scheduler.print_relative_speed_comparison();
+    scheduler.final_export()?;
+    scheduler.ensure_assertions_hold()?;
+
+    Ok(())
+}
+
+/// Benchmark all commands with the given options and return the results, in the order of the
+/// commands. Nothing is exported, but the usual terminal output is shown (unless the output
+/// style of the options is `OutputStyleOption::Disabled`).
+pub fn run_benchmarks(commands: &Commands, options: &Options) -> Result<Vec<BenchmarkResult>> {
+    options.validate_against_command_list(commands)?;
+
+    let export_manager = ExportManager::default();
+    let mut scheduler = Scheduler::new(commands, options, &export_manager);
+    scheduler.run_benchmarks()?;
+    Ok(scheduler.into_results())
+}
@@ -1,3 +1,4 @@
+use std::ffi::OsString;
 use std::fs::File;
 use std::io::IsTerminal;
 use std::path::PathBuf;
@@ -7,9 +8,14 @@ use std::{cmp, env, fmt, io};
 use anyhow::ensure;
 use clap::ArgMatches;
 
-use crate::command::Commands;
+use crate::benchmark::assertions::Assertion;
+use crate::benchmark::resume::PreviousResults;
+use crate::command::{self, Commands};
 use crate::error::OptionsError;
-use crate::util::units::{Second, Unit};
+use crate::outlier_detection::OUTLIER_THRESHOLD;
+use crate::output::theme::Theme;
+use crate::util::time_extraction::TimeExtraction;
+use crate::util::units::{parse_duration, parse_size, Scalar, Second, Size, Unit};
 
 use anyhow::Result;
 
@@ -19,6 +25,9 @@ pub const DEFAULT_SHELL: &str = "sh";
 #[cfg(windows)]
 pub const DEFAULT_SHELL: &str = "cmd.exe";
 
+/// Placeholder for the number of the timing run in the `--validate` command
+pub const RUN_PLACEHOLDER: &str = "{run}";
+
 /// Shell to use for executing benchmarked commands
 #[derive(Debug, PartialEq)]
 pub enum Shell {
@@ -66,6 +75,17 @@ impl Shell {
     }
 }
 
+/// How the commands are run through the shell (`--shell-mode`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ShellMode {
+    /// A new shell is spawned for each run
+    #[default]
+    Spawn,
+
+    /// All runs are sent to a single long-lived shell, see `benchmark::persistent_shell`
+    Persistent,
+}
+
 /// Action to take when an executed command fails.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CmdFailureAction {
@@ -76,6 +96,114 @@ pub enum CmdFailureAction {
     Ignore,
 }
 
+/// Scheduling priority of the benchmarked commands (`--priority`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// A nice value 10 below the one of hyperfine on Unix (which usually requires root
+    /// privileges), the high priority class on Windows
+    High,
+
+    /// The priority of hyperfine itself
+    #[default]
+    Normal,
+
+    /// A nice value 10 above the one of hyperfine on Unix, the below-normal priority class on
+    /// Windows
+    Low,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Priority::High => "high",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+        })
+    }
+}
+
+/// Whether the length of the `HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET` variable is randomized
+/// (`--randomization`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Randomization {
+    /// A new random length for every command invocation
+    #[default]
+    On,
+
+    /// The variable is not set at all
+    Off,
+
+    /// The lengths are pseudo-random, but the same for every invocation of hyperfine with this
+    /// seed
+    Seed(u64),
+}
+
+impl Randomization {
+    /// Parse the argument of `--randomization`
+    pub fn parse_from_str<'a>(s: &str) -> Result<Self, OptionsError<'a>> {
+        match s {
+            "on" => Ok(Randomization::On),
+            "off" => Ok(Randomization::Off),
+            _ => s
+                .strip_prefix("seed:")
+                .and_then(|seed| seed.parse().ok())
+                .map(Randomization::Seed)
+                .ok_or_else(|| OptionsError::InvalidRandomization(s.to_string())),
+        }
+    }
+}
+
+/// When the `--prepare` command is executed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PreparationMode {
+    /// Before each (warmup and timing) run
+    #[default]
+    PerRun,
+
+    /// Only once, before the first run of each benchmark
+    PerBenchmark,
+}
+
+/// How the warmup runs interact with the `--prepare` command (`--warmup-mode`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WarmupMode {
+    /// The preparation command runs before the warmup runs, like before the timing runs
+    #[default]
+    AfterPrepare,
+
+    /// The warmup runs are performed without the preparation command, which only runs before
+    /// the timing runs
+    BeforePrepare,
+
+    /// No warmup runs are performed for benchmarks with a preparation command, since it would
+    /// undo their effect anyway (like a command that drops the caches)
+    SkipWithPrepare,
+}
+
+impl fmt::Display for WarmupMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WarmupMode::AfterPrepare => "after-prepare",
+            WarmupMode::BeforePrepare => "before-prepare",
+            WarmupMode::SkipWithPrepare => "skip-with-prepare",
+        })
+    }
+}
+
+/// How the relative speeds are shown in the summary (`--summary-format`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryFormat {
+    /// As a ratio of the run times, like "2.05 ± 0.12 times faster"
+    #[default]
+    Ratio,
+
+    /// As the absolute difference of the run times, like "412.3 ms faster"
+    Delta,
+
+    /// As a ratio, followed by the absolute difference
+    Both,
+}
+
 /// Output style type option
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputStyleOption {
@@ -91,25 +219,80 @@ pub enum OutputStyleOption {
     /// Keep coloring, but use no progress bar
     Color,
 
+    /// Output with full color and formatting, but only use ASCII characters for the progress bar
+    Ascii,
+
     /// Disable all the output
     Disabled,
 }
 
+impl OutputStyleOption {
+    /// Determine the output style from the value of `--style`. An explicitly specified style
+    /// always wins. Otherwise, the output is basic if it is not interactive, without colors if
+    /// `NO_COLOR` is set (or the terminal is unknown), and full otherwise.
+    fn from_cli_argument(
+        style: Option<&str>,
+        interactive: bool,
+        env_var: impl Fn(&str) -> Option<OsString>,
+    ) -> Self {
+        match style {
+            Some("full") => OutputStyleOption::Full,
+            Some("basic") => OutputStyleOption::Basic,
+            Some("nocolor") => OutputStyleOption::NoColor,
+            Some("color") => OutputStyleOption::Color,
+            Some("ascii") => OutputStyleOption::Ascii,
+            Some("none") => OutputStyleOption::Disabled,
+            _ => {
+                if !interactive {
+                    OutputStyleOption::Basic
+                } else if env_var("NO_COLOR").is_some_and(|t| !t.is_empty())
+                    || env_var("TERM")
+                        .map(|t| t == "unknown" || t == "dumb")
+                        .unwrap_or(!cfg!(target_os = "windows"))
+                {
+                    OutputStyleOption::NoColor
+                } else {
+                    OutputStyleOption::Full
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortOrder {
     Command,
     MeanTime,
+    MedianTime,
+    MaxTime,
+    UserTime,
 }
 
+/// Number of runs that are performed at most if no maximum has been specified (unless the
+/// minimum number of runs is larger)
+pub const DEFAULT_MAX_RUNS: u64 = 10_000;
+
+/// Number of run times that are exported at most for each benchmark (see `--max-export-samples`)
+pub const DEFAULT_MAX_EXPORT_SAMPLES: usize = 10_000;
+
 /// Bounds for the number of benchmark runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RunBounds {
     /// Minimum number of benchmark runs
     pub min: u64,
 
-    /// Maximum number of benchmark runs
+    /// Maximum number of benchmark runs. If unset, at most `DEFAULT_MAX_RUNS` are performed.
     pub max: Option<u64>,
 }
 
+impl RunBounds {
+    /// The maximum number of runs, including the default maximum
+    pub fn effective_max(&self) -> u64 {
+        self.max
+            .unwrap_or_else(|| cmp::max(DEFAULT_MAX_RUNS, self.min))
+    }
+}
+
 impl Default for RunBounds {
     fn default() -> Self {
         RunBounds { min: 10, max: None }
@@ -154,12 +337,33 @@ pub enum CommandOutputPolicy {
     /// Redirect output to a file
     File(PathBuf),
 
+    /// Write the output of every timing run to a separate file (`--output=file:<path>`). The
+    /// path may contain `{run}` and parameter placeholders. The output on stderr is written to
+    /// a sibling file with an additional `.stderr` extension.
+    FilePerRun(String),
+
     /// Show command output on the terminal
     Inherit,
+
+    /// Show command output on the terminal, with a marker of the benchmark and the run in front
+    /// of every line (`--show-output=prefixed`)
+    Prefixed,
+
+    /// Capture the output on stdout and stderr, such that the time that the command reports
+    /// itself can be extracted from it (`--extract-time`)
+    Capture,
 }
 
 impl CommandOutputPolicy {
-    pub fn get_stdout_stderr(&self) -> io::Result<(Stdio, Stdio)> {
+    /// Open the streams for the given command. `run` is the number of the timing run, or `None`
+    /// for warmup runs and intermediate commands. Output files are created (and opened) here,
+    /// such that the child process inherits an open file descriptor and writing the output is
+    /// not part of the measured time beyond the cost of the writes themselves.
+    pub fn get_stdout_stderr(
+        &self,
+        command: &command::Command<'_>,
+        run: Option<usize>,
+    ) -> io::Result<(Stdio, Stdio)> {
         let streams = match self {
             CommandOutputPolicy::Null => (Stdio::null(), Stdio::null()),
 
@@ -171,7 +375,24 @@ impl CommandOutputPolicy {
                 (file.into(), Stdio::null())
             }
 
+            CommandOutputPolicy::FilePerRun(template) => match run {
+                Some(run) => {
+                    let path = command.get_output_path(template, run);
+                    let mut stderr_path = path.clone().into_os_string();
+                    stderr_path.push(".stderr");
+                    let stdout = File::create(&path)?;
+                    let stderr = File::create(stderr_path)?;
+                    (stdout.into(), stderr.into())
+                }
+                None => (Stdio::null(), Stdio::null()),
+            },
+
             CommandOutputPolicy::Inherit => (Stdio::inherit(), Stdio::inherit()),
+
+            // These are the only policies that pipe stderr, see `execute_and_measure`
+            CommandOutputPolicy::Prefixed | CommandOutputPolicy::Capture => {
+                (Stdio::piped(), Stdio::piped())
+            }
         };
 
         Ok(streams)
@@ -198,47 +419,217 @@ pub struct Options {
     /// Number of warmup runs
     pub warmup_count: u64,
 
+    /// Perform warmup runs until this amount of time has passed (instead of a fixed number)
+    pub warmup_duration: Option<Second>,
+
+    /// Perform warmup runs until the run times have stabilized (`--warmup auto`)
+    pub warmup_auto: bool,
+
+    /// Exact number of benchmark runs for each command (overrides `run_bounds`, unless it is
+    /// `None` for the respective command)
+    pub runs_per_command: Option<Vec<Option<u64>>>,
+
+    /// Number of warmup runs for each command (overrides `warmup_count`, unless it is `None` for
+    /// the respective command)
+    pub warmup_per_command: Option<Vec<Option<u64>>>,
+
     /// Minimum benchmarking time
     pub min_benchmarking_time: Second,
 
+    /// Maximum time for all benchmarks together, which limits the number of runs
+    pub total_time_budget: Option<Second>,
+
+    /// Maximum time of the timing runs of a single benchmark. No further runs are performed once
+    /// it has been exceeded (but at least `--min-runs`, unless `max_benchmark_time_hard` is set).
+    pub max_benchmark_time: Option<Second>,
+
+    /// Whether the `max_benchmark_time` may also reduce the number of runs below `--min-runs`
+    pub max_benchmark_time_hard: bool,
+
+    /// Maximum number of run times that are exported for each benchmark. If there are more runs,
+    /// a uniform sample of them is exported. `None` exports all run times.
+    pub max_export_samples: Option<usize>,
+
+    /// Thresholds for the statistics of each benchmark
+    pub assertions: Vec<Assertion>,
+
+    /// Whether to perform the timing runs of all benchmarks in a random, interleaved order
+    pub interleave: bool,
+
+    /// How often the whole list of benchmarks is performed (`--repeat-suite`)
+    pub suite_repetitions: u64,
+
+    /// Results of an earlier invocation (`--resume-from`), which are used instead of performing
+    /// the same benchmarks again
+    pub previous_results: Option<PreviousResults>,
+
+    /// CPUs on which the benchmarked commands are allowed to run
+    pub cpu_affinity: Option<Vec<usize>>,
+
+    /// Scheduling priority of the benchmarked commands
+    pub priority: Priority,
+
+    /// How the length of the randomized environment variable is chosen
+    pub randomization: Randomization,
+
+    /// Whether or not braces like `{name}` that do not correspond to a parameter are allowed
+    pub allow_unknown_placeholders: bool,
+
+    /// Whether or not to warn if the system load is high before the benchmarks are started
+    pub load_check: bool,
+
+    /// Whether or not to warn about CPU settings that make the results less stable (see
+    /// `cpu_settings`)
+    pub system_checks: bool,
+
     /// Whether or not to ignore non-zero exit codes
     pub command_failure_action: CmdFailureAction,
 
+    /// Maximum time a single run is allowed to take before it is aborted
+    pub timeout: Option<Second>,
+
+    /// How often a failed run is repeated before it counts as a failure
+    pub retries: u64,
+
     // Command to use as a reference for relative speed comparison
     pub reference_command: Option<String>,
 
-    /// Command(s) to run before each timing run
+    /// Command(s) to run before each timing run. Like all per-benchmark commands, this is given
+    /// once for all benchmarks or once for each of them (see `command_for`).
     pub preparation_command: Option<Vec<String>>,
 
+    /// Whether the preparation command(s) run before each run or once per benchmark
+    pub preparation_mode: PreparationMode,
+
+    /// Whether the warmup runs are performed before or after the preparation command, or not
+    /// at all for benchmarks that have one
+    pub warmup_mode: WarmupMode,
+
+    /// Whether or not to drop the file system caches before each timing run
+    pub drop_file_caches: bool,
+
     /// Command(s) to run after each timing run
     pub conclusion_command: Option<Vec<String>>,
 
-    /// Command to run before each *batch* of timing runs, i.e. before each individual benchmark
-    pub setup_command: Option<String>,
+    /// Command(s) to check the outcome of each timing run, before the conclusion command. `{run}`
+    /// is replaced by the number of the run.
+    pub validation_command: Option<Vec<String>>,
+
+    /// Command to run once before all benchmarks
+    pub setup_global_command: Option<String>,
+
+    /// Command(s) to run before each *batch* of timing runs, i.e. before each individual
+    /// benchmark
+    pub setup_command: Option<Vec<String>>,
 
-    /// Command to run after each *batch* of timing runs, i.e. after each individual benchmark
-    pub cleanup_command: Option<String>,
+    /// Command(s) to run after each *batch* of timing runs, i.e. after each individual benchmark
+    pub cleanup_command: Option<Vec<String>>,
+
+    /// Whether or not to run the cleanup command if a benchmark is interrupted with Ctrl-C
+    pub cleanup_on_interrupt: bool,
 
     /// What color mode to use for the terminal output
     pub output_style: OutputStyleOption,
 
+    /// Colors of the terminal output
+    pub theme: Theme,
+
+    /// Whether to write the progress as JSON events to stderr instead of showing a progress bar
+    pub progress_json: bool,
+
     /// How to order benchmarks in the relative speed comparison
     pub sort_order_speed_comparison: SortOrder,
 
     /// How to order benchmarks in the markup format exports
     pub sort_order_exports: SortOrder,
 
+    /// Whether to show ratios and/or absolute differences in the relative speed comparison
+    pub summary_format: SummaryFormat,
+
     /// Determines how we run commands
     pub executor_kind: ExecutorKind,
 
+    /// The shells that are compared if `--shell` is given multiple times, together with the
+    /// argument of `--shell` (the value of the shell parameter of the benchmarks). The first one
+    /// is also the `executor_kind`. Empty if there is only a single shell.
+    pub shells: Vec<(String, ExecutorKind)>,
+
+    /// Whether the shell spawning time is measured and subtracted from all measurements
+    pub shell_calibration: bool,
+
+    /// Whether a new shell is spawned for each run or a single shell is used for all of them
+    pub shell_mode: ShellMode,
+
+    /// Whether the process spawning time is measured and subtracted from all measurements
+    /// (`--shell=none` on Unix)
+    pub spawn_calibration: bool,
+
     /// Where input to the benchmarked command comes from
     pub command_input_policy: CommandInputPolicy,
 
     /// What to do with the output of the benchmarked command
     pub command_output_policy: CommandOutputPolicy,
 
+    /// Extract the time that the command reports itself from its output
+    pub time_extraction: Option<TimeExtraction>,
+
     /// Which time unit to use when displaying results
     pub time_unit: Option<Unit>,
+
+    /// Whether the rows of the markup exports are shown in their own units (`--time-unit
+    /// auto-per-row`). `time_unit` is `None` in this case.
+    pub time_unit_per_row: bool,
+
+    /// Whether or not to show the peak memory usage in the terminal output
+    pub show_memory: bool,
+
+    /// Sizes (like `500MB`) that are processed by a single run of each command, in order to show
+    /// the throughput. May contain parameter placeholders.
+    pub throughput: Option<Vec<String>>,
+
+    /// Whether or not to show the observed CPU frequency in the terminal output
+    pub show_cpu_frequency: bool,
+
+    /// Whether or not to show context switches and page faults in the terminal output
+    pub show_resource_usage: bool,
+
+    /// Whether or not to show a histogram of the run times in the terminal output
+    pub show_histogram: bool,
+
+    /// Whether or not to show the confidence interval of the mean in the terminal output
+    pub show_confidence_interval: bool,
+
+    /// Whether or not to show the coefficient of variation of the run times in the terminal output
+    pub show_cv: bool,
+
+    /// Coefficient of variation (as a fraction, not in percent) above which a warning is shown
+    pub cv_warning_threshold: Option<Scalar>,
+
+    /// Whether or not to exclude outliers from the statistics
+    pub trim_outliers: bool,
+
+    /// Minimum (absolute) modified Z-score of a run to be considered as an outlier
+    pub outlier_threshold: f64,
+
+    /// Whether or not to show the geometric mean of the relative times in the summary (it is
+    /// always shown for more than two benchmarks)
+    pub show_geometric_mean: bool,
+
+    /// Whether or not to compare the statistics of the first command side by side with the ones
+    /// of all other commands (this is always done for two benchmarks)
+    pub show_side_by_side: bool,
+
+    /// Whether or not to show the current statistics in the progress bar after each run
+    pub show_live_stats: bool,
+
+    /// Whether or not to print additional information, like the estimated shell spawning time
+    pub verbose: bool,
+
+    /// Environment variables that are set for all commands (in addition to the inherited ones)
+    pub environment: Vec<(String, String)>,
+
+    /// Directory in which the commands are executed (may contain parameter placeholders)
+    pub working_dir: Option<String>,
 }
 
 impl Default for Options {
@@ -246,24 +637,115 @@ impl Default for Options {
         Options {
             run_bounds: RunBounds::default(),
             warmup_count: 0,
+            warmup_duration: None,
+            warmup_auto: false,
+            runs_per_command: None,
+            warmup_per_command: None,
             min_benchmarking_time: 3.0,
+            total_time_budget: None,
+            max_benchmark_time: None,
+            max_benchmark_time_hard: false,
+            max_export_samples: Some(DEFAULT_MAX_EXPORT_SAMPLES),
+            assertions: vec![],
+            interleave: false,
+            suite_repetitions: 1,
+            previous_results: None,
+            cpu_affinity: None,
+            load_check: true,
+            system_checks: true,
+            priority: Priority::Normal,
+            randomization: Randomization::default(),
+            allow_unknown_placeholders: false,
             command_failure_action: CmdFailureAction::RaiseError,
+            timeout: None,
+            retries: 0,
             reference_command: None,
             preparation_command: None,
+            drop_file_caches: false,
+            preparation_mode: PreparationMode::default(),
+            warmup_mode: WarmupMode::default(),
             conclusion_command: None,
+            validation_command: None,
+            setup_global_command: None,
             setup_command: None,
             cleanup_command: None,
+            cleanup_on_interrupt: true,
             output_style: OutputStyleOption::Full,
+            theme: Theme::default(),
+            progress_json: false,
             sort_order_speed_comparison: SortOrder::MeanTime,
             sort_order_exports: SortOrder::Command,
+            summary_format: SummaryFormat::default(),
             executor_kind: ExecutorKind::default(),
+            shells: vec![],
+            shell_calibration: true,
+            shell_mode: ShellMode::default(),
+            spawn_calibration: true,
             command_output_policy: CommandOutputPolicy::Null,
+            time_extraction: None,
             time_unit: None,
+            time_unit_per_row: false,
             command_input_policy: CommandInputPolicy::Null,
+            show_memory: false,
+            throughput: None,
+            show_cpu_frequency: false,
+            show_resource_usage: false,
+            show_histogram: false,
+            show_confidence_interval: false,
+            show_cv: false,
+            cv_warning_threshold: None,
+            show_geometric_mean: false,
+            show_side_by_side: false,
+            show_live_stats: false,
+            verbose: false,
+            trim_outliers: false,
+            outlier_threshold: OUTLIER_THRESHOLD,
+            environment: vec![],
+            working_dir: None,
         }
     }
 }
 
+/// Builder-style methods for using hyperfine as a library. All other settings can be changed
+/// through the public fields.
+impl Options {
+    /// Perform exactly `count` benchmark runs
+    pub fn with_runs(mut self, count: u64) -> Self {
+        self.run_bounds = RunBounds {
+            min: count,
+            max: Some(count),
+        };
+        self
+    }
+
+    /// Perform `count` warmup runs before the actual benchmark
+    pub fn with_warmup(mut self, count: u64) -> Self {
+        self.warmup_count = count;
+        self
+    }
+
+    /// Use the given shell to run the commands, or run them without a shell (`None`)
+    pub fn with_shell(mut self, shell: Option<Shell>) -> Self {
+        self.executor_kind = match shell {
+            Some(shell) => ExecutorKind::Shell(shell),
+            None => ExecutorKind::Raw,
+        };
+        self
+    }
+
+    /// Ignore non-zero exit codes of the benchmarked commands
+    pub fn ignore_failure(mut self) -> Self {
+        self.command_failure_action = CmdFailureAction::Ignore;
+        self
+    }
+
+    /// Change how progress and results are shown in the terminal
+    pub fn with_output_style(mut self, style: OutputStyleOption) -> Self {
+        self.output_style = style;
+        self
+    }
+}
+
 impl Options {
     pub fn from_cli_arguments<'a>(matches: &ArgMatches) -> Result<Self, OptionsError<'a>> {
         let mut options = Self::default();
@@ -277,7 +759,55 @@ impl Options {
                 .transpose()
         };
 
-        options.warmup_count = param_to_u64("warmup")?.unwrap_or(options.warmup_count);
+        if let Some(warmup) = matches.get_one::<String>("warmup") {
+            match warmup.parse::<u64>() {
+                Ok(count) => options.warmup_count = count,
+                Err(_) if warmup == "auto" => options.warmup_auto = true,
+                Err(e) => {
+                    // Durations need an explicit unit, such that '--warmup 3' stays a count
+                    let has_unit = warmup.ends_with(|c: char| c.is_alphabetic());
+                    let duration = parse_duration(warmup)
+                        .filter(|_| has_unit)
+                        .ok_or(OptionsError::IntParsingError("warmup", e))?;
+                    options.warmup_duration = Some(duration);
+                }
+            }
+        }
+
+        let param_to_u64_list = |param| {
+            matches
+                .get_one::<String>(param)
+                .map(|list| {
+                    list.split(',')
+                        .map(|n| {
+                            n.trim()
+                                .parse::<u64>()
+                                .map(Some)
+                                .map_err(|e| OptionsError::IntParsingError(param, e))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .transpose()
+        };
+        options.runs_per_command = param_to_u64_list("runs-per-command")?;
+        options.warmup_per_command = param_to_u64_list("warmup-per-command")?;
+
+        options.retries = param_to_u64("retries")?.unwrap_or(0);
+
+        if let Some(repetitions) = param_to_u64("repeat-suite")? {
+            if repetitions == 0 {
+                return Err(OptionsError::ZeroSuiteRepetitions);
+            }
+            options.suite_repetitions = repetitions;
+        }
+
+        // The file is read right away, since it may be overwritten by the exports
+        if let Some(path) = matches.get_one::<String>("resume-from") {
+            options.previous_results = Some(PreviousResults::load(
+                path,
+                matches.get_one::<String>("force-rerun").map(|s| s.as_str()),
+            )?);
+        }
 
         let mut min_runs = param_to_u64("min-runs")?;
         let mut max_runs = param_to_u64("max-runs")?;
@@ -306,27 +836,66 @@ impl Options {
             (None, None) => {}
         };
 
-        options.setup_command = matches.get_one::<String>("setup").map(String::from);
+        options.setup_global_command = matches.get_one::<String>("setup-global").map(String::from);
+        options.setup_command = matches
+            .get_many::<String>("setup")
+            .map(|values| values.map(String::from).collect::<Vec<String>>());
 
         options.reference_command = matches.get_one::<String>("reference").map(String::from);
+        options.working_dir = matches.get_one::<String>("working-dir").map(String::from);
 
         options.preparation_command = matches
             .get_many::<String>("prepare")
             .map(|values| values.map(String::from).collect::<Vec<String>>());
 
+        options.throughput = matches
+            .get_many::<String>("throughput")
+            .map(|values| values.map(String::from).collect::<Vec<String>>());
+
+        options.preparation_mode = match matches
+            .get_one::<String>("prepare-mode")
+            .map(|s| s.as_str())
+        {
+            Some("per-benchmark") => PreparationMode::PerBenchmark,
+            _ => PreparationMode::PerRun,
+        };
+
+        options.warmup_mode = match matches.get_one::<String>("warmup-mode").map(|s| s.as_str()) {
+            Some("before-prepare") => WarmupMode::BeforePrepare,
+            Some("skip-with-prepare") => WarmupMode::SkipWithPrepare,
+            _ => WarmupMode::AfterPrepare,
+        };
+
+        options.drop_file_caches = matches.get_flag("drop-file-caches");
+
         options.conclusion_command = matches
             .get_many::<String>("conclude")
             .map(|values| values.map(String::from).collect::<Vec<String>>());
 
-        options.cleanup_command = matches.get_one::<String>("cleanup").map(String::from);
+        options.validation_command = matches
+            .get_many::<String>("validate")
+            .map(|values| values.map(String::from).collect::<Vec<String>>());
+
+        options.cleanup_command = matches
+            .get_many::<String>("cleanup")
+            .map(|values| values.map(String::from).collect::<Vec<String>>());
+        options.cleanup_on_interrupt = !matches.get_flag("no-cleanup-on-interrupt");
 
-        options.command_output_policy = if matches.get_flag("show-output") {
-            CommandOutputPolicy::Inherit
+        options.command_output_policy = if let Some(mode) = matches.get_one::<String>("show-output")
+        {
+            match mode.as_str() {
+                "raw" => CommandOutputPolicy::Inherit,
+                _ => CommandOutputPolicy::Prefixed,
+            }
         } else if let Some(output) = matches.get_one::<String>("output").map(|s| s.as_str()) {
             match output {
                 "null" => CommandOutputPolicy::Null,
                 "pipe" => CommandOutputPolicy::Pipe,
                 "inherit" => CommandOutputPolicy::Inherit,
+                arg if arg.starts_with("file:") => match &arg["file:".len()..] {
+                    "" => return Err(OptionsError::EmptyOutputPath),
+                    template => CommandOutputPolicy::FilePerRun(template.to_string()),
+                },
                 arg => {
                     let path = PathBuf::from(arg);
                     if path.components().count() <= 1 {
@@ -339,41 +908,32 @@ impl Options {
             CommandOutputPolicy::Null
         };
 
-        options.output_style = match matches.get_one::<String>("style").map(|s| s.as_str()) {
-            Some("full") => OutputStyleOption::Full,
-            Some("basic") => OutputStyleOption::Basic,
-            Some("nocolor") => OutputStyleOption::NoColor,
-            Some("color") => OutputStyleOption::Color,
-            Some("none") => OutputStyleOption::Disabled,
-            _ => {
-                if options.command_output_policy == CommandOutputPolicy::Inherit
-                    || !io::stdout().is_terminal()
-                {
-                    OutputStyleOption::Basic
-                } else if env::var_os("TERM")
-                    .map(|t| t == "unknown" || t == "dumb")
-                    .unwrap_or(!cfg!(target_os = "windows"))
-                    || env::var_os("NO_COLOR")
-                        .map(|t| !t.is_empty())
-                        .unwrap_or(false)
-                {
-                    OutputStyleOption::NoColor
-                } else {
-                    OutputStyleOption::Full
-                }
-            }
-        };
+        if let Some(pattern) = matches.get_one::<String>("extract-time") {
+            options.time_extraction = Some(TimeExtraction::new(pattern)?);
+            options.command_output_policy = CommandOutputPolicy::Capture;
+        }
+
+        options.output_style = OutputStyleOption::from_cli_argument(
+            matches.get_one::<String>("style").map(|s| s.as_str()),
+            options.command_output_policy != CommandOutputPolicy::Inherit
+                && io::stdout().is_terminal(),
+            |name| env::var_os(name),
+        );
 
         match options.output_style {
             OutputStyleOption::Basic | OutputStyleOption::NoColor => {
                 colored::control::set_override(false)
             }
-            OutputStyleOption::Full | OutputStyleOption::Color => {
+            OutputStyleOption::Full | OutputStyleOption::Color | OutputStyleOption::Ascii => {
                 colored::control::set_override(true)
             }
             OutputStyleOption::Disabled => {}
         };
 
+        if let Some(theme) = matches.get_one::<String>("theme") {
+            options.theme = Theme::from_name(theme).unwrap_or_default();
+        }
+
         (
             options.sort_order_speed_comparison,
             options.sort_order_exports,
@@ -381,40 +941,164 @@ impl Options {
             None | Some("auto") => (SortOrder::MeanTime, SortOrder::Command),
             Some("command") => (SortOrder::Command, SortOrder::Command),
             Some("mean-time") => (SortOrder::MeanTime, SortOrder::MeanTime),
+            Some("median-time") => (SortOrder::MedianTime, SortOrder::MedianTime),
+            Some("max-time") => (SortOrder::MaxTime, SortOrder::MaxTime),
+            Some("user-time") => (SortOrder::UserTime, SortOrder::UserTime),
             Some(_) => unreachable!("Unknown sort order"),
         };
+        options.summary_format = match matches
+            .get_one::<String>("summary-format")
+            .map(|s| s.as_str())
+        {
+            Some("delta") => SummaryFormat::Delta,
+            Some("both") => SummaryFormat::Both,
+            _ => SummaryFormat::Ratio,
+        };
 
-        options.executor_kind = if matches.get_flag("no-shell") {
-            ExecutorKind::Raw
-        } else {
-            match (
-                matches.get_flag("debug-mode"),
-                matches.get_one::<String>("shell"),
-            ) {
+        let executor_kind = |shell: Option<&String>| -> Result<ExecutorKind, OptionsError> {
+            Ok(match (matches.get_flag("debug-mode"), shell) {
                 (false, Some(shell)) if shell == "default" => ExecutorKind::Shell(Shell::default()),
                 (false, Some(shell)) if shell == "none" => ExecutorKind::Raw,
                 (false, Some(shell)) => ExecutorKind::Shell(Shell::parse_from_str(shell)?),
                 (false, None) => ExecutorKind::Shell(Shell::default()),
                 (true, Some(shell)) => ExecutorKind::Mock(Some(shell.into())),
                 (true, None) => ExecutorKind::Mock(None),
+            })
+        };
+        let shells: Vec<&String> = matches.get_many("shell").unwrap_or_default().collect();
+        options.executor_kind = if matches.get_flag("no-shell") {
+            ExecutorKind::Raw
+        } else {
+            executor_kind(shells.first().copied())?
+        };
+        if shells.len() > 1 {
+            options.shells = shells
+                .into_iter()
+                .map(|shell| Ok((shell.clone(), executor_kind(Some(shell))?)))
+                .collect::<Result<_, OptionsError>>()?;
+        }
+        options.shell_calibration = !matches.get_flag("no-shell-calibration");
+        options.shell_mode = match matches.get_one::<String>("shell-mode").map(|s| s.as_str()) {
+            Some("persistent") => ShellMode::Persistent,
+            _ => ShellMode::Spawn,
+        };
+        options.spawn_calibration = !matches.get_flag("no-spawn-calibration");
+
+        options.show_memory = matches.get_flag("show-memory");
+        options.show_resource_usage = matches.get_flag("show-resource-usage");
+        options.show_cpu_frequency = matches.get_flag("show-cpu-frequency");
+        options.show_histogram = matches.get_flag("histogram");
+        options.show_live_stats = matches.get_flag("show-live-stats");
+        options.show_geometric_mean = matches.get_flag("geometric-mean");
+        options.show_side_by_side = matches.get_flag("compare");
+        options.trim_outliers = matches.get_flag("trim-outliers");
+        if let Some(threshold) = matches.get_one::<String>("outlier-threshold") {
+            let threshold = threshold
+                .parse::<f64>()
+                .map_err(|e| OptionsError::FloatParsingError("outlier-threshold", e))?;
+            if threshold.is_nan() || threshold <= 0.0 {
+                return Err(OptionsError::NonPositiveOutlierThreshold);
             }
+            options.outlier_threshold = threshold;
+        }
+        options.progress_json = matches.get_flag("progress-json");
+        options.verbose = matches.get_flag("verbose");
+        options.interleave = matches.get_flag("interleave");
+        options.load_check = !matches.get_flag("no-load-check");
+        options.system_checks = !matches.get_flag("no-system-checks");
+        options.allow_unknown_placeholders = matches.get_flag("allow-unknown-placeholders");
+        options.priority = match matches.get_one::<String>("priority").map(|s| s.as_str()) {
+            Some("high") => Priority::High,
+            Some("low") => Priority::Low,
+            _ => Priority::Normal,
         };
 
+        if let Some(randomization) = matches.get_one::<String>("randomization") {
+            options.randomization = Randomization::parse_from_str(randomization)?;
+        }
+
+        options.cpu_affinity = matches
+            .get_one::<String>("cpu")
+            .map(|list| {
+                parse_cpu_list(list).ok_or_else(|| OptionsError::InvalidCpuList(list.to_string()))
+            })
+            .transpose()?;
+        options.show_confidence_interval = matches.get_flag("confidence-interval");
+        options.show_cv = matches.get_flag("show-cv");
+        if let Some(threshold) = matches.get_one::<String>("cv-warning-threshold") {
+            let percent = threshold
+                .strip_suffix('%')
+                .unwrap_or(threshold)
+                .trim()
+                .parse::<f64>()
+                .map_err(|e| OptionsError::FloatParsingError("cv-warning-threshold", e))?;
+            if percent.is_nan() || percent <= 0.0 {
+                return Err(OptionsError::NonPositiveCvWarningThreshold);
+            }
+            options.cv_warning_threshold = Some(percent / 100.0);
+        }
+
+        options.timeout = matches
+            .get_one::<String>("timeout")
+            .map(|t| {
+                parse_duration(t)
+                    .ok_or_else(|| OptionsError::DurationParsingError("timeout", t.to_string()))
+            })
+            .transpose()?;
+        if options.timeout.is_some_and(|timeout| timeout <= 0.0) {
+            return Err(OptionsError::NonPositiveTimeout);
+        }
+
+        options.total_time_budget = matches
+            .get_one::<String>("total-time-budget")
+            .map(|t| {
+                parse_duration(t).ok_or_else(|| {
+                    OptionsError::DurationParsingError("total-time-budget", t.to_string())
+                })
+            })
+            .transpose()?;
+        options.max_benchmark_time = matches
+            .get_one::<String>("max-benchmark-time")
+            .map(|t| {
+                parse_duration(t).ok_or_else(|| {
+                    OptionsError::DurationParsingError("max-benchmark-time", t.to_string())
+                })
+            })
+            .transpose()?;
+        options.max_benchmark_time_hard = matches.get_flag("max-benchmark-time-hard");
+        if let Some(samples) = param_to_u64("max-export-samples")? {
+            options.max_export_samples = Some(samples as usize).filter(|&samples| samples > 0);
+        }
+
+        options.assertions = matches
+            .get_many::<String>("assert")
+            .into_iter()
+            .flatten()
+            .map(|expression| {
+                Assertion::parse(expression)
+                    .ok_or_else(|| OptionsError::InvalidAssertion(expression.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
         if matches.get_flag("ignore-failure") {
             options.command_failure_action = CmdFailureAction::Ignore;
         }
 
         options.time_unit = match matches.get_one::<String>("time-unit").map(|s| s.as_str()) {
+            Some("nanosecond") => Some(Unit::NanoSecond),
             Some("microsecond") => Some(Unit::MicroSecond),
             Some("millisecond") => Some(Unit::MilliSecond),
             Some("second") => Some(Unit::Second),
+            Some("minute") => Some(Unit::Minute),
             _ => None,
         };
+        options.time_unit_per_row =
+            matches.get_one::<String>("time-unit").map(|s| s.as_str()) == Some("auto-per-row");
 
         if let Some(time) = matches.get_one::<String>("min-benchmarking-time") {
-            options.min_benchmarking_time = time
-                .parse::<f64>()
-                .map_err(|e| OptionsError::FloatParsingError("min-benchmarking-time", e))?;
+            options.min_benchmarking_time = parse_duration(time).ok_or_else(|| {
+                OptionsError::DurationParsingError("min-benchmarking-time", time.clone())
+            })?;
         }
 
         options.command_input_policy = if let Some(path_str) = matches.get_one::<String>("input") {
@@ -433,36 +1117,252 @@ impl Options {
             CommandInputPolicy::Null
         };
 
+        // Variables from --env take precedence over the ones from --env-file
+        if let Some(path) = matches.get_one::<String>("env-file") {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| OptionsError::EnvironmentFileReadError(path.clone(), e))?;
+            options.environment = parse_environment_file(&content)
+                .map_err(|line| OptionsError::InvalidEnvironmentFileLine(path.clone(), line))?;
+        }
+        for variable in matches.get_many::<String>("env").into_iter().flatten() {
+            options.environment.push(
+                parse_environment_variable(variable)
+                    .ok_or_else(|| OptionsError::InvalidEnvironmentVariable(variable.clone()))?,
+            );
+        }
+
         Ok(options)
     }
 
+    /// Bounds for the number of runs of the benchmark with the given number (in the order in
+    /// which the benchmarks are performed)
+    pub fn run_bounds_for(&self, number: usize) -> RunBounds {
+        match self
+            .runs_per_command
+            .as_ref()
+            .and_then(|runs| runs.get(number).copied().flatten())
+        {
+            Some(runs) => RunBounds {
+                min: runs,
+                max: Some(runs),
+            },
+            None => self.run_bounds,
+        }
+    }
+
+    /// Number of warmup runs of the benchmark with the given number
+    pub fn warmup_count_for(&self, number: usize) -> u64 {
+        self.warmup_per_command
+            .as_ref()
+            .and_then(|warmup| warmup.get(number).copied().flatten())
+            .unwrap_or(self.warmup_count)
+    }
+
+    /// The size that is processed by a single run of the benchmark with the given number, with
+    /// all parameters of the benchmarked command substituted (`--throughput`)
+    pub fn throughput_size_for(
+        &self,
+        number: usize,
+        command: &command::Command,
+    ) -> Result<Option<Size>, OptionsError<'static>> {
+        let Some(sizes) = &self.throughput else {
+            return Ok(None);
+        };
+        let size = command
+            .related_command(value_for(sizes, number).as_str())
+            .get_command_line();
+        parse_size(&size)
+            .map(Some)
+            .ok_or(OptionsError::InvalidThroughputSize(size))
+    }
+
+    /// How the benchmarks are run: one kind for each shell if several shells are compared
+    pub fn executor_kinds(&self) -> Vec<&ExecutorKind> {
+        if self.shells.is_empty() {
+            vec![&self.executor_kind]
+        } else {
+            self.shells.iter().map(|(_, kind)| kind).collect()
+        }
+    }
+
+    /// How the given benchmark is run: with its own shell if several shells are compared
+    pub fn executor_kind_for(&self, command: &command::Command<'_>) -> &ExecutorKind {
+        command
+            .get_shell()
+            .and_then(|shell| self.shells.iter().find(|(name, _)| *name == shell))
+            .map_or(&self.executor_kind, |(_, kind)| kind)
+    }
+
+    /// The command of the given per-benchmark option (like `preparation_command`) for the
+    /// benchmark with the given number, in the list of all benchmarks after the parameter
+    /// expansion (see `Commands::iter_with_expanded_index`), with a separate reference first.
+    /// An empty command means that nothing is run for the respective benchmark.
+    pub fn command_for(commands: &Option<Vec<String>>, number: usize) -> Option<&str> {
+        commands
+            .as_deref()
+            .map(|commands| value_for(commands, number).as_str())
+            .filter(|command| !command.is_empty())
+    }
+
     pub fn validate_against_command_list(&self, commands: &Commands) -> Result<()> {
-        let num_commands = commands.num_commands()
-            + if self.reference_command.is_some() {
-                1
-            } else {
-                0
-            };
-        if let Some(preparation_command) = &self.preparation_command {
-            ensure!(
-                preparation_command.len() <= 1 || num_commands == preparation_command.len(),
-                "The '--prepare' option has to be provided just once or N times, where N is the \
-             number of benchmark commands including a potential reference."
-            );
+        // A reference that is not part of the command list is benchmarked separately. The
+        // per-benchmark options refer to all commands after the parameter expansion, even if
+        // some of them are skipped with `--deduplicate`.
+        let separate_reference = matches!(
+            &self.reference_command,
+            Some(reference) if commands.position_of(reference).is_none()
+        );
+        let num_commands = commands.num_expanded_commands() + usize::from(separate_reference);
+
+        for (option, values, noun) in [
+            ("setup", &self.setup_command, "setup commands"),
+            ("prepare", &self.preparation_command, "preparation commands"),
+            ("conclude", &self.conclusion_command, "conclusion commands"),
+            ("validate", &self.validation_command, "validation commands"),
+            ("cleanup", &self.cleanup_command, "cleanup commands"),
+            ("throughput", &self.throughput, "sizes"),
+        ] {
+            if let Some(values) = values {
+                ensure!(
+                    values.len() <= 1 || values.len() == num_commands,
+                    "The '--{option}' option has to be provided just once or once for each \
+                     benchmark, but it has been provided with {} {noun} for {num_commands} \
+                     {benchmarks} (after the parameter expansion, including a potential \
+                     reference).",
+                    values.len(),
+                    benchmarks = if num_commands == 1 {
+                        "benchmark"
+                    } else {
+                        "benchmarks"
+                    },
+                );
+            }
         }
 
-        if let Some(conclusion_command) = &self.conclusion_command {
-            ensure!(
-                conclusion_command.len() <= 1 || num_commands == conclusion_command.len(),
-                "The '--conclude' option has to be provided just once or N times, where N is the \
-             number of benchmark commands including a potential reference."
+        if self.throughput.is_some() {
+            let reference = self
+                .reference_command
+                .as_ref()
+                .filter(|reference| commands.position_of(reference).is_none())
+                .map(|reference| command::Command::new(None, reference));
+            let offset = usize::from(reference.is_some());
+            let numbered_commands = reference.iter().map(|command| (0, command)).chain(
+                commands
+                    .iter_with_expanded_index()
+                    .map(|(index, command)| (index + offset, command)),
             );
+            for (number, command) in numbered_commands {
+                self.throughput_size_for(number, command)?;
+            }
+        }
+
+        for (option, values) in [
+            ("runs-per-command", &self.runs_per_command),
+            ("warmup-per-command", &self.warmup_per_command),
+        ] {
+            if let Some(values) = values {
+                ensure!(
+                    values.len() == num_commands,
+                    "The '--{option}' option has {} values, but it needs exactly one value for \
+                     each of the {num_commands} benchmark commands (including a potential \
+                     reference).",
+                    values.len()
+                );
+            }
+        }
+
+        // Each command only needs to provide the parameters of its own per-benchmark commands
+        if !self.allow_unknown_placeholders {
+            let offset = usize::from(separate_reference);
+            let per_benchmark_commands = [
+                &self.setup_command,
+                &self.preparation_command,
+                &self.conclusion_command,
+                &self.cleanup_command,
+            ];
+            for (index, command) in commands.iter_with_expanded_index() {
+                // `{run}` is a placeholder of the validation command, not a parameter
+                let validation_command =
+                    Self::command_for(&self.validation_command, index + offset)
+                        .map(|validation_command| validation_command.replace(RUN_PLACEHOLDER, ""));
+                let related_commands = per_benchmark_commands
+                    .iter()
+                    .filter_map(|values| Self::command_for(values, index + offset))
+                    .chain(validation_command.as_deref());
+                command.validate_placeholders(related_commands)?;
+            }
         }
 
         Ok(())
     }
 }
 
+/// The value of an option that is given once for all benchmarks or once for each benchmark
+fn value_for<T>(values: &[T], number: usize) -> &T {
+    if values.len() == 1 {
+        &values[0]
+    } else {
+        &values[number]
+    }
+}
+
+/// Parse a list of CPUs like `2`, `2,3` or `0-3,6`. The result is sorted and does not contain
+/// duplicates.
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = vec![];
+    for part in list.split(',') {
+        match part.trim().split_once('-') {
+            Some((first, last)) => {
+                let first: usize = first.trim().parse().ok()?;
+                let last: usize = last.trim().parse().ok()?;
+                if first > last {
+                    return None;
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(part.trim().parse().ok()?),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Some(cpus)
+}
+
+/// Parse an environment variable of the form `NAME=VALUE`
+fn parse_environment_variable(variable: &str) -> Option<(String, String)> {
+    match variable.split_once('=') {
+        Some((name, value)) if !name.is_empty() && !name.contains(char::is_whitespace) => {
+            Some((name.to_string(), value.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the content of an environment file with one `NAME=VALUE` pair per line. Empty lines and
+/// comments (starting with '#') are ignored, as well as an optional `export` prefix and quotes
+/// around the value. Returns the (1-based) number of the first invalid line in case of an error.
+fn parse_environment_file(content: &str) -> Result<Vec<(String, String)>, usize> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+            let (name, value) = parse_environment_variable(line).ok_or(number)?;
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|&quote| {
+                    value
+                        .strip_prefix(quote)
+                        .and_then(|v| v.strip_suffix(quote))
+                })
+                .map_or(value.clone(), String::from);
+            Ok((name, value))
+        })
+        .collect()
+}
+
 #[test]
 fn test_default_shell() {
     let shell = Shell::default();
@@ -506,3 +1406,244 @@ fn test_can_parse_shell_command_line_from_str() {
         OptionsError::EmptyShell
     ));
 }
+
+#[test]
+fn test_warmup_can_be_a_count_or_a_duration() {
+    use crate::cli::get_cli_arguments;
+
+    let options =
+        Options::from_cli_arguments(&get_cli_arguments(vec!["hyperfine", "-w", "3", "echo"]))
+            .unwrap();
+    assert_eq!(options.warmup_count, 3);
+    assert_eq!(options.warmup_duration, None);
+
+    let options =
+        Options::from_cli_arguments(&get_cli_arguments(vec!["hyperfine", "-w", "500ms", "echo"]))
+            .unwrap();
+    assert_eq!(options.warmup_count, 0);
+    assert_eq!(options.warmup_duration, Some(0.5));
+
+    let options =
+        Options::from_cli_arguments(&get_cli_arguments(vec!["hyperfine", "-w", "auto", "echo"]))
+            .unwrap();
+    assert!(options.warmup_auto);
+    assert_eq!(options.warmup_duration, None);
+
+    for invalid in ["0.5", "3x", "s", "automatic"] {
+        let matches = get_cli_arguments(vec!["hyperfine", "-w", invalid, "echo"]);
+        assert!(matches!(
+            Options::from_cli_arguments(&matches),
+            Err(OptionsError::IntParsingError("warmup", _))
+        ));
+    }
+}
+
+#[test]
+fn test_runs_and_warmup_per_command() {
+    use crate::cli::get_cli_arguments;
+
+    let options = Options::from_cli_arguments(&get_cli_arguments(vec![
+        "hyperfine",
+        "--runs-per-command",
+        "5,2",
+        "--warmup-per-command",
+        "1,0",
+        "echo a",
+        "echo b",
+    ]))
+    .unwrap();
+    assert_eq!(
+        options.run_bounds_for(0),
+        RunBounds {
+            min: 5,
+            max: Some(5)
+        }
+    );
+    assert_eq!(
+        options.run_bounds_for(1),
+        RunBounds {
+            min: 2,
+            max: Some(2)
+        }
+    );
+    assert_eq!(options.warmup_count_for(0), 1);
+    assert_eq!(options.warmup_count_for(1), 0);
+
+    let matches = get_cli_arguments(vec!["hyperfine", "--runs-per-command", "5,x", "echo"]);
+    assert!(matches!(
+        Options::from_cli_arguments(&matches),
+        Err(OptionsError::IntParsingError("runs-per-command", _))
+    ));
+}
+
+#[test]
+fn test_parse_cpu_list() {
+    assert_eq!(parse_cpu_list("2"), Some(vec![2]));
+    assert_eq!(parse_cpu_list("3,2"), Some(vec![2, 3]));
+    assert_eq!(parse_cpu_list("0-3, 6,2"), Some(vec![0, 1, 2, 3, 6]));
+
+    for invalid in ["", "a", "1,", "-1", "3-1", "1-"] {
+        assert_eq!(parse_cpu_list(invalid), None, "{invalid}");
+    }
+}
+
+#[test]
+fn test_parse_randomization() {
+    assert_eq!(
+        Randomization::parse_from_str("on").unwrap(),
+        Randomization::On
+    );
+    assert_eq!(
+        Randomization::parse_from_str("off").unwrap(),
+        Randomization::Off
+    );
+    assert_eq!(
+        Randomization::parse_from_str("seed:42").unwrap(),
+        Randomization::Seed(42)
+    );
+
+    for invalid in ["", "seed", "seed:", "seed:-1", "seed:x", "random"] {
+        assert!(
+            Randomization::parse_from_str(invalid).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_min_benchmarking_time() {
+    use crate::cli::get_cli_arguments;
+
+    let options =
+        Options::from_cli_arguments(&get_cli_arguments(vec!["hyperfine", "echo"])).unwrap();
+    assert_eq!(options.min_benchmarking_time, 3.0);
+
+    for (value, expected) in [("0", 0.0), ("1.5", 1.5), ("500ms", 0.5)] {
+        let options = Options::from_cli_arguments(&get_cli_arguments(vec![
+            "hyperfine",
+            "--min-benchmarking-time",
+            value,
+            "echo",
+        ]))
+        .unwrap();
+        assert_eq!(options.min_benchmarking_time, expected);
+    }
+
+    let matches = get_cli_arguments(vec!["hyperfine", "--min-benchmarking-time=-1", "echo"]);
+    assert!(matches!(
+        Options::from_cli_arguments(&matches),
+        Err(OptionsError::DurationParsingError(
+            "min-benchmarking-time",
+            _
+        ))
+    ));
+}
+
+#[test]
+fn test_environment_variables() {
+    use crate::cli::get_cli_arguments;
+
+    let dir = tempfile::tempdir().unwrap();
+    let env_file = dir.path().join("test.env");
+    std::fs::write(
+        &env_file,
+        "# comment\nFOO=from-file\n\nexport BAR=\"quoted value\"\nEMPTY=\n",
+    )
+    .unwrap();
+
+    let options = Options::from_cli_arguments(&get_cli_arguments(vec![
+        "hyperfine",
+        "--env-file",
+        env_file.to_str().unwrap(),
+        "--env",
+        "FOO=1",
+        "--env",
+        "EQUALS=a=b",
+        "echo",
+    ]))
+    .unwrap();
+    let environment: Vec<(&str, &str)> = options
+        .environment
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    assert_eq!(
+        environment,
+        vec![
+            ("FOO", "from-file"),
+            ("BAR", "quoted value"),
+            ("EMPTY", ""),
+            ("FOO", "1"),
+            ("EQUALS", "a=b"),
+        ]
+    );
+
+    let matches = get_cli_arguments(vec!["hyperfine", "--env", "FOO", "echo"]);
+    assert!(matches!(
+        Options::from_cli_arguments(&matches),
+        Err(OptionsError::InvalidEnvironmentVariable(_))
+    ));
+
+    std::fs::write(&env_file, "FOO=1\nnot a variable\n").unwrap();
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--env-file",
+        env_file.to_str().unwrap(),
+        "echo",
+    ]);
+    assert!(matches!(
+        Options::from_cli_arguments(&matches),
+        Err(OptionsError::InvalidEnvironmentFileLine(_, 2))
+    ));
+}
+
+#[test]
+fn test_output_style_precedence() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| OsString::from(v))
+        }
+    };
+    let style = OutputStyleOption::from_cli_argument;
+
+    // An explicit style beats NO_COLOR
+    assert_eq!(
+        style(Some("full"), true, env(&[("NO_COLOR", "1")])),
+        OutputStyleOption::Full
+    );
+    assert_eq!(
+        style(Some("ascii"), true, env(&[("NO_COLOR", "1")])),
+        OutputStyleOption::Ascii
+    );
+
+    // NO_COLOR beats the auto-detection
+    assert_eq!(
+        style(
+            Some("auto"),
+            true,
+            env(&[("NO_COLOR", "1"), ("TERM", "xterm")])
+        ),
+        OutputStyleOption::NoColor
+    );
+    assert_eq!(
+        style(None, true, env(&[("NO_COLOR", ""), ("TERM", "xterm")])),
+        OutputStyleOption::Full
+    );
+    assert_eq!(
+        style(None, true, env(&[("TERM", "dumb")])),
+        OutputStyleOption::NoColor
+    );
+
+    // Non-interactive output is always basic, unless a style is specified
+    assert_eq!(
+        style(None, false, env(&[("TERM", "xterm")])),
+        OutputStyleOption::Basic
+    );
+    assert_eq!(
+        style(Some("color"), false, env(&[])),
+        OutputStyleOption::Color
+    );
+}
@@ -7,11 +7,22 @@
 
 use statistical::median;
 
-/// Minimum modified Z-score for a datapoint to be an outlier. Here, 1.4826 is a factor that
-/// converts the MAD to an estimator for the standard deviation. The second factor is the number
-/// of standard deviations.
+/// Default minimum modified Z-score for a datapoint to be an outlier (see `--outlier-threshold`).
+/// Here, 1.4826 is a factor that converts the MAD to an estimator for the standard deviation. The
+/// second factor is the number of standard deviations.
 pub const OUTLIER_THRESHOLD: f64 = 1.4826 * 10.0;
 
+/// Compute the median absolute deviation (MAD) of a given sample, i.e. the median of the absolute
+/// deviations from the sample median. Unlike the standard deviation, it is robust against
+/// outliers.
+pub fn median_absolute_deviation(xs: &[f64]) -> f64 {
+    assert!(!xs.is_empty());
+
+    let x_median = median(xs);
+    let deviations: Vec<f64> = xs.iter().map(|x| (x - x_median).abs()).collect();
+    median(&deviations)
+}
+
 /// Compute modifized Z-scores for a given sample. A (unmodified) Z-score is defined by
 /// `(x_i - x_mean)/x_stddev` whereas the modified Z-score is defined by `(x_i - x_median)/MAD`
 /// where MAD is the median absolute deviation.
@@ -24,11 +35,8 @@ pub fn modified_zscores(xs: &[f64]) -> Vec<f64> {
     // Compute sample median:
     let x_median = median(xs);
 
-    // Compute the absolute deviations from the median:
-    let deviations: Vec<f64> = xs.iter().map(|x| (x - x_median).abs()).collect();
-
     // Compute median absolute deviation:
-    let mad = median(&deviations);
+    let mad = median_absolute_deviation(xs);
 
     // Handle MAD == 0 case
     let mad = if mad > 0.0 { mad } else { f64::EPSILON };
@@ -37,19 +45,26 @@ pub fn modified_zscores(xs: &[f64]) -> Vec<f64> {
     xs.iter().map(|&x| (x - x_median) / mad).collect()
 }
 
-/// Return the number of outliers in a given sample. Outliers are defined as data points with a
-/// modified Z-score that is larger than `OUTLIER_THRESHOLD`.
-#[cfg(test)]
-pub fn num_outliers(xs: &[f64]) -> usize {
+/// Return the indices of all outliers in a given sample, i.e. of the data points with an absolute
+/// modified Z-score that is larger than `threshold`.
+pub fn outlier_indices(xs: &[f64], threshold: f64) -> Vec<usize> {
     if xs.is_empty() {
-        return 0;
+        return vec![];
     }
 
-    let scores = modified_zscores(xs);
-    scores
+    modified_zscores(xs)
         .iter()
-        .filter(|&&s| s.abs() > OUTLIER_THRESHOLD)
-        .count()
+        .enumerate()
+        .filter(|(_, &s)| s.abs() > threshold)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Return the number of outliers in a given sample. Outliers are defined as data points with a
+/// modified Z-score that is larger than `OUTLIER_THRESHOLD`.
+#[cfg(test)]
+pub fn num_outliers(xs: &[f64]) -> usize {
+    outlier_indices(xs, OUTLIER_THRESHOLD).len()
 }
 
 #[test]
@@ -113,3 +128,34 @@ fn test_detect_outliers_if_mad_becomes_0() {
     let xs = [10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 100.0, 100.0];
     assert_eq!(2, num_outliers(&xs));
 }
+
+#[test]
+fn test_median_absolute_deviation() {
+    assert_eq!(median_absolute_deviation(&[5.0]), 0.0);
+    assert_eq!(median_absolute_deviation(&[1.0, 2.0, 3.0]), 1.0);
+    assert_eq!(
+        median_absolute_deviation(&[1.0, 1.0, 2.0, 2.0, 4.0, 6.0, 9.0]),
+        1.0
+    );
+
+    // A single outlier does not affect the MAD
+    assert_eq!(median_absolute_deviation(&[1.0, 2.0, 3.0, 1000.0]), 1.0);
+}
+
+#[test]
+fn test_outlier_indices() {
+    assert!(outlier_indices(&[], OUTLIER_THRESHOLD).is_empty());
+    assert_eq!(
+        vec![0],
+        outlier_indices(&[0.5, 0.30, 0.29, 0.31, 0.30], OUTLIER_THRESHOLD)
+    );
+    assert_eq!(
+        vec![1, 4],
+        outlier_indices(&[1.0, 9.0, 1.1, 0.9, -7.0, 1.0], OUTLIER_THRESHOLD)
+    );
+
+    // A lower threshold detects more outliers
+    let xs = [1.0, 1.1, 0.9, 1.0, 1.3, 1.0];
+    assert!(outlier_indices(&xs, OUTLIER_THRESHOLD).is_empty());
+    assert_eq!(vec![4], outlier_indices(&xs, 3.5));
+}
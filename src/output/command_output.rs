@@ -0,0 +1,115 @@
+//! Show the output of the benchmarked commands on the terminal with a dim marker like
+//! `[cmd 2, run 7/10]` in front of every line (`--show-output=prefixed`), such that it does not
+//! interleave unreadably with the output of hyperfine itself. The progress bar is suspended while
+//! a line is printed.
+
+use std::io::{self, BufRead, Write};
+use std::sync::Mutex;
+
+use indicatif::ProgressBar;
+
+use crate::output::theme::{Role, Theme};
+
+/// The stream of the benchmarked command that a line has been written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Marker of the current run, the progress bar that needs to be suspended and the theme in
+/// which the lines on stderr are shown
+struct Context {
+    marker: String,
+    progress_bar: Option<ProgressBar>,
+    theme: Theme,
+}
+
+/// Only one command runs at a time, so its context can be shared by the threads that read its
+/// output. The lock also keeps the lines of both streams from being mixed up.
+static CONTEXT: Mutex<Context> = Mutex::new(Context {
+    marker: String::new(),
+    progress_bar: None,
+    theme: Theme::Default,
+});
+
+/// Set the marker (like `[cmd 2, run 7/10]`) for the output of the following command, and the
+/// progress bar that is shown while it runs
+pub fn set_context(marker: String, progress_bar: Option<ProgressBar>, theme: Theme) {
+    let mut context = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    context.marker = marker;
+    context.progress_bar = progress_bar;
+    context.theme = theme;
+}
+
+/// The marker of a run of a benchmark (numbered from 1). The total number of runs is not known
+/// before the initial timing run.
+pub fn run_marker(benchmark: usize, kind: &str, run: u64, total: Option<u64>) -> String {
+    match total {
+        Some(total) => format!("[cmd {benchmark}, {kind} {run}/{total}]"),
+        None => format!("[cmd {benchmark}, {kind} {run}]"),
+    }
+}
+
+/// Print every line of the given stream of the benchmarked command as soon as it is complete
+pub fn forward(output: impl io::Read, stream: Stream) {
+    let mut reader = io::BufReader::new(output);
+    let mut line = vec![];
+    while let Ok(bytes) = reader.read_until(b'\n', &mut line) {
+        if bytes == 0 {
+            break;
+        }
+        print_line(&String::from_utf8_lossy(&line), stream);
+        line.clear();
+    }
+}
+
+fn print_line(line: &str, stream: Stream) {
+    let context = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let line = format_line(
+        &context.marker,
+        line.trim_end_matches(['\n', '\r']),
+        stream,
+        context.theme,
+    );
+
+    let print = || match stream {
+        Stream::Stdout => {
+            let _ = writeln!(io::stdout().lock(), "{line}");
+        }
+        Stream::Stderr => {
+            let _ = writeln!(io::stderr().lock(), "{line}");
+        }
+    };
+    match &context.progress_bar {
+        Some(progress_bar) => progress_bar.suspend(print),
+        None => print(),
+    }
+}
+
+/// Lines on stderr are colored, such that they can be told apart from the ones on stdout
+fn format_line(marker: &str, line: &str, stream: Stream, theme: Theme) -> String {
+    match stream {
+        Stream::Stdout => format!("{} {line}", theme.dim(marker)),
+        Stream::Stderr => format!("{} {}", theme.dim(marker), theme.paint(Role::Error, line)),
+    }
+}
+
+#[test]
+fn test_run_marker() {
+    assert_eq!(run_marker(2, "run", 7, Some(10)), "[cmd 2, run 7/10]");
+    assert_eq!(run_marker(1, "run", 1, None), "[cmd 1, run 1]");
+    assert_eq!(run_marker(3, "warmup", 2, Some(5)), "[cmd 3, warmup 2/5]");
+}
+
+#[test]
+fn test_format_line() {
+    assert_eq!(
+        format_line("[cmd 1, run 2/3]", "hello", Stream::Stdout, Theme::Plain),
+        "[cmd 1, run 2/3] hello"
+    );
+    assert_eq!(
+        format_line("[cmd 1, run 2/3]", "oops", Stream::Stderr, Theme::Plain),
+        "[cmd 1, run 2/3] oops"
+    );
+}
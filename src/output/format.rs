@@ -1,4 +1,9 @@
-use crate::util::units::{Second, Unit};
+use std::borrow::Cow;
+
+use crate::util::units::{MegaHertz, Scalar, Second, Size, SizeUnit, Unit};
+
+/// Shown in place of a line break in a command that is displayed on a single line
+pub const LINE_BREAK_MARKER: &str = "⏎";
 
 /// Format the given duration as a string. The output-unit can be enforced by setting `unit` to
 /// `Some(target_unit)`. If `unit` is `None`, it will be determined automatically.
@@ -16,13 +21,67 @@ pub fn format_duration_unit(duration: Second, unit: Option<Unit>) -> (String, Un
 
 /// Like `format_duration`, but returns the target unit as well.
 pub fn format_duration_value(duration: Second, unit: Option<Unit>) -> (String, Unit) {
-    if (duration < 0.001 && unit.is_none()) || unit == Some(Unit::MicroSecond) {
-        (Unit::MicroSecond.format(duration), Unit::MicroSecond)
-    } else if (duration < 1.0 && unit.is_none()) || unit == Some(Unit::MilliSecond) {
-        (Unit::MilliSecond.format(duration), Unit::MilliSecond)
+    let unit = unit.unwrap_or_else(|| Unit::for_duration(duration));
+
+    (unit.format(duration), unit)
+}
+
+/// Format the given number of bytes as a human-readable string, using binary prefixes.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{value:.1} {unit}")
+}
+
+/// Format the given CPU frequency as a human-readable string, in GHz above 1 GHz.
+pub fn format_frequency(frequency: MegaHertz) -> String {
+    if frequency.round() < 1000.0 {
+        format!("{frequency:.0} MHz")
     } else {
-        (Unit::Second.format(duration), Unit::Second)
+        format!("{:.2} GHz", frequency / 1000.0)
+    }
+}
+
+/// Choose a unit for the given throughput (per second), like `MiB/s` or `k items/s`. Returns the
+/// factor by which throughputs need to be divided and the name of the unit. Binary prefixes are
+/// used if the size has been given with a binary prefix.
+pub fn throughput_unit(throughput: Scalar, size: Size) -> (Scalar, String) {
+    let (base, prefixes): (Scalar, [&str; 5]) = match (size.unit, size.binary) {
+        (SizeUnit::Bytes, true) => (1024.0, ["", "Ki", "Mi", "Gi", "Ti"]),
+        (SizeUnit::Bytes, false) => (1000.0, ["", "k", "M", "G", "T"]),
+        (SizeUnit::Items, _) => (1000.0, ["", "k ", "M ", "G ", "T "]),
+    };
+
+    let mut factor = 1.0;
+    let mut prefix = prefixes[0];
+    for next_prefix in &prefixes[1..] {
+        // Compare the value as it is shown (with one decimal place)
+        if (throughput / factor * 10.0).round() / 10.0 < base {
+            break;
+        }
+        factor *= base;
+        prefix = next_prefix;
     }
+
+    let unit = match size.unit {
+        SizeUnit::Bytes => "B/s",
+        SizeUnit::Items => "items/s",
+    };
+    (factor, format!("{prefix}{unit}"))
 }
 
 #[test]
@@ -52,10 +111,20 @@ fn test_format_duration_unit_basic() {
     assert_eq!("0.0 µs", out_str);
     assert_eq!(Unit::MicroSecond, out_unit);
 
-    let (out_str, out_unit) = format_duration_unit(1000.0, None);
+    let (out_str, out_unit) = format_duration_unit(59.9, None);
 
-    assert_eq!("1000.000 s", out_str);
+    assert_eq!("59.900 s", out_str);
     assert_eq!(Unit::Second, out_unit);
+
+    let (out_str, out_unit) = format_duration_unit(1000.0, None);
+
+    assert_eq!("16.667 min", out_str);
+    assert_eq!(Unit::Minute, out_unit);
+
+    let (out_str, out_unit) = format_duration_unit(0.0000005, None);
+
+    assert_eq!("0.5 µs", out_str);
+    assert_eq!(Unit::MicroSecond, out_unit);
 }
 
 #[test]
@@ -74,4 +143,86 @@ fn test_format_duration_unit_with_unit() {
 
     assert_eq!("1300000.0 µs", out_str);
     assert_eq!(Unit::MicroSecond, out_unit);
+
+    let (out_str, out_unit) = format_duration_unit(0.0000013, Some(Unit::NanoSecond));
+
+    assert_eq!("1300 ns", out_str);
+    assert_eq!(Unit::NanoSecond, out_unit);
+
+    let (out_str, out_unit) = format_duration_unit(1.3, Some(Unit::Minute));
+
+    assert_eq!("0.022 min", out_str);
+    assert_eq!(Unit::Minute, out_unit);
+}
+
+/// Replace all line breaks (`\n`, `\r\n` or `\r`) in the given command by `LINE_BREAK_MARKER`,
+/// such that it can be shown on a single line (in the terminal or in a table row).
+pub fn single_line(command: &str) -> Cow<'_, str> {
+    if command.contains(['\n', '\r']) {
+        Cow::Owned(
+            command
+                .replace("\r\n", "\n")
+                .replace(['\n', '\r'], LINE_BREAK_MARKER),
+        )
+    } else {
+        Cow::Borrowed(command)
+    }
+}
+
+#[test]
+fn test_single_line() {
+    assert_eq!("echo a", single_line("echo a"));
+    assert_eq!("echo a⏎echo b⏎", single_line("echo a\necho b\n"));
+    assert_eq!("a⏎b⏎c", single_line("a\r\nb\rc"));
+}
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!("0 B", format_bytes(0));
+    assert_eq!("1023 B", format_bytes(1023));
+    assert_eq!("1.0 KiB", format_bytes(1024));
+    assert_eq!("1.5 KiB", format_bytes(1536));
+    assert_eq!("12.3 MiB", format_bytes(12_897_485));
+    assert_eq!("2.0 GiB", format_bytes(2 * 1024 * 1024 * 1024));
+}
+
+#[test]
+fn test_format_frequency() {
+    assert_eq!("800 MHz", format_frequency(800.0));
+    assert_eq!("1.00 GHz", format_frequency(999.7));
+    assert_eq!("3.40 GHz", format_frequency(3400.0));
+}
+
+#[test]
+fn test_throughput_unit() {
+    let size = |unit, binary| Size {
+        value: 1.0,
+        unit,
+        binary,
+    };
+
+    assert_eq!(
+        (1024.0 * 1024.0, "MiB/s".to_string()),
+        throughput_unit(235.1 * 1024.0 * 1024.0, size(SizeUnit::Bytes, true))
+    );
+    assert_eq!(
+        (1e6, "MB/s".to_string()),
+        throughput_unit(235.1e6, size(SizeUnit::Bytes, false))
+    );
+    assert_eq!(
+        (1e9, "GB/s".to_string()),
+        throughput_unit(999_999_999.9, size(SizeUnit::Bytes, false))
+    );
+    assert_eq!(
+        (1.0, "B/s".to_string()),
+        throughput_unit(12.0, size(SizeUnit::Bytes, false))
+    );
+    assert_eq!(
+        (1e3, "k items/s".to_string()),
+        throughput_unit(4500.0, size(SizeUnit::Items, false))
+    );
+    assert_eq!(
+        (1.0, "items/s".to_string()),
+        throughput_unit(0.5, size(SizeUnit::Items, false))
+    );
 }
@@ -0,0 +1,218 @@
+use crate::benchmark::benchmark_result::percentile_of_sorted;
+use crate::options::OutputStyleOption;
+use crate::output::format::format_duration_value;
+use crate::output::theme::{Role, Theme};
+use crate::util::units::{Second, Unit};
+
+/// Lower and upper bound for the number of bins of the histogram
+const MIN_BINS: usize = 10;
+const MAX_BINS: usize = 20;
+
+/// Width that is used if the width of the terminal can not be determined
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Bars do not grow beyond this width, even on very wide terminals
+const MAX_BAR_WIDTH: usize = 60;
+
+/// Characters for partially filled blocks, in steps of 1/8
+const PARTIAL_BLOCKS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// A histogram bin with the number of run times in the interval [start, end)
+#[derive(Debug, Clone, PartialEq)]
+struct Bin {
+    start: Second,
+    end: Second,
+    count: usize,
+}
+
+/// Print a histogram of the given run times to the terminal
+pub fn print_histogram(times: &[Second], unit: Unit, style: OutputStyleOption, theme: Theme) {
+    if style == OutputStyleOption::Disabled || times.is_empty() {
+        return;
+    }
+
+    print!("{}", render(times, unit, style, theme, terminal_width()));
+}
+
+/// Determine the number of bins via the Freedman–Diaconis rule
+fn num_bins(sorted: &[Second]) -> usize {
+    let range = sorted[sorted.len() - 1] - sorted[0];
+    if range <= 0.0 {
+        return 1;
+    }
+
+    let iqr = percentile_of_sorted(sorted, 75.0) - percentile_of_sorted(sorted, 25.0);
+    let bin_width = 2.0 * iqr / (sorted.len() as f64).cbrt();
+    if bin_width <= 0.0 {
+        return MAX_BINS;
+    }
+
+    ((range / bin_width).ceil() as usize).clamp(MIN_BINS, MAX_BINS)
+}
+
+fn compute_bins(times: &[Second]) -> Vec<Bin> {
+    let mut sorted = times.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let min = sorted[0];
+    let max = sorted[sorted.len() - 1];
+    let num_bins = num_bins(&sorted);
+    let bin_width = (max - min) / num_bins as f64;
+
+    let mut bins: Vec<Bin> = (0..num_bins)
+        .map(|i| Bin {
+            start: min + i as f64 * bin_width,
+            end: if i + 1 == num_bins {
+                max
+            } else {
+                min + (i + 1) as f64 * bin_width
+            },
+            count: 0,
+        })
+        .collect();
+
+    for t in sorted {
+        // The maximum belongs to the last bin. The small offset makes sure that values on a bin
+        // boundary are not moved to the previous bin by rounding errors.
+        let index = if bin_width > 0.0 {
+            (((t - min) / bin_width + 1e-9) as usize).min(num_bins - 1)
+        } else {
+            0
+        };
+        bins[index].count += 1;
+    }
+
+    bins
+}
+
+/// Render a bar of the given relative length (in [0, 1])
+fn bar(fraction: f64, width: usize, style: OutputStyleOption) -> String {
+    if matches!(style, OutputStyleOption::Basic | OutputStyleOption::Ascii) {
+        return "#".repeat((fraction * width as f64).round() as usize);
+    }
+
+    let eighths = (fraction * (width * 8) as f64).round() as usize;
+    format!("{}{}", "█".repeat(eighths / 8), PARTIAL_BLOCKS[eighths % 8])
+}
+
+fn render(
+    times: &[Second],
+    unit: Unit,
+    style: OutputStyleOption,
+    theme: Theme,
+    terminal_width: usize,
+) -> String {
+    let bins = compute_bins(times);
+
+    let label = |value| format_duration_value(value, Some(unit)).0;
+    let label_width = bins
+        .iter()
+        .map(|bin| label(bin.start).len().max(label(bin.end).len()))
+        .max()
+        .unwrap_or(0);
+    let max_count = bins.iter().map(|bin| bin.count).max().unwrap_or(0);
+    let count_width = max_count.to_string().len();
+
+    // Space for the indentation, both labels, the separators and the count
+    let reserved = 4 + 2 * label_width + 3 + 2 + 1 + count_width;
+    let bar_width = terminal_width
+        .saturating_sub(reserved)
+        .clamp(10, MAX_BAR_WIDTH);
+
+    let mut output = format!("  Histogram [{}]:\n", unit.short_name());
+    for bin in &bins {
+        let fraction = bin.count as f64 / max_count.max(1) as f64;
+        let bar = bar(fraction, bar_width, style);
+        output.push_str(&format!(
+            "    {:>label_width$} … {:>label_width$}  {:<bar_width$} {:>count_width$}\n",
+            label(bin.start),
+            label(bin.end),
+            theme.paint(Role::Histogram, bar),
+            bin.count,
+        ));
+    }
+
+    output
+}
+
+/// Width of the terminal, as specified by the `COLUMNS` environment variable or reported by the
+/// terminal itself
+fn terminal_width() -> usize {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+    {
+        return columns;
+    }
+
+    #[cfg(not(windows))]
+    {
+        // SAFETY: `ioctl` with `TIOCGWINSZ` only writes to the given winsize struct
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0
+            && size.ws_col > 0
+        {
+            return size.ws_col as usize;
+        }
+    }
+
+    DEFAULT_TERMINAL_WIDTH
+}
+
+#[test]
+fn test_compute_bins() {
+    let times = [1.0, 1.1, 1.15, 1.2, 1.3, 1.4, 1.5, 1.6, 1.8, 2.0];
+    let bins = compute_bins(&times);
+
+    assert!(bins.len() >= MIN_BINS && bins.len() <= MAX_BINS);
+    assert_eq!(bins.iter().map(|b| b.count).sum::<usize>(), times.len());
+    assert_eq!(bins[0].start, 1.0);
+    assert_eq!(bins[bins.len() - 1].end, 2.0);
+    assert!(bins[bins.len() - 1].count >= 1);
+}
+
+#[test]
+fn test_compute_bins_for_identical_times() {
+    let bins = compute_bins(&[0.5, 0.5, 0.5]);
+    assert_eq!(
+        bins,
+        vec![Bin {
+            start: 0.5,
+            end: 0.5,
+            count: 3
+        }]
+    );
+}
+
+#[test]
+fn test_bar() {
+    assert_eq!(bar(1.0, 4, OutputStyleOption::Full), "████");
+    assert_eq!(bar(0.5625, 4, OutputStyleOption::Full), "██▎");
+    assert_eq!(bar(0.0, 4, OutputStyleOption::Full), "");
+    assert_eq!(bar(0.5, 4, OutputStyleOption::Basic), "##");
+    assert_eq!(bar(0.5, 4, OutputStyleOption::Ascii), "##");
+}
+
+#[test]
+fn test_render() {
+    let times = [0.010, 0.011, 0.011, 0.012, 0.020];
+    let output = render(
+        &times,
+        Unit::MilliSecond,
+        OutputStyleOption::Basic,
+        Theme::Plain,
+        80,
+    );
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines[0], "  Histogram [ms]:");
+    assert!(lines[1].starts_with("    10.0 … 11.0  "));
+    assert!(lines[lines.len() - 1].starts_with("    19.0 … 20.0  "));
+    assert!(lines[lines.len() - 1].ends_with(" 1"));
+
+    // All bars are aligned, and the fullest bin has the longest bar
+    let widths: Vec<usize> = lines[1..].iter().map(|l| l.chars().count()).collect();
+    assert!(widths.iter().all(|&w| w == widths[0]));
+    assert!(lines[2].contains(&"#".repeat(MAX_BAR_WIDTH)));
+    assert!(lines[2].ends_with(" 2"));
+}
@@ -1,3 +1,6 @@
+pub mod command_output;
 pub mod format;
+pub mod histogram;
 pub mod progress_bar;
+pub mod theme;
 pub mod warnings;
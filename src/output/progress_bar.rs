@@ -1,22 +1,82 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
 use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use serde::Serialize;
+use statistical::median;
+
+use crate::options::{Options, OutputStyleOption};
+use crate::output::format::{format_duration, format_duration_unit};
+use crate::output::theme::{Role, Theme};
+use crate::util::units::{Scalar, Second, Unit};
+
+use anyhow::Result;
+
+const UNICODE_TICK_SETTINGS: (&str, u64) = ("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ", 80);
+const ASCII_TICK_SETTINGS: (&str, u64) = (r"+-x| ", 200);
+
+/// Characters for the filled, current and empty parts of an ASCII progress bar
+const ASCII_PROGRESS_CHARS: &str = "#>-";
+
+/// Minimum time between two updates of the live statistics, such that they do not flicker for
+/// very fast commands
+const LIVE_STATS_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Whether the progress bar should only use ASCII characters. This is the case for
+/// `--style=ascii` and if the locale does not use UTF-8.
+fn ascii_only(option: OutputStyleOption) -> bool {
+    option == OutputStyleOption::Ascii || (!cfg!(windows) && !locale_is_utf8())
+}
 
-use crate::options::OutputStyleOption;
+/// Whether the locale (as determined by the first non-empty variable out of `LC_ALL`,
+/// `LC_CTYPE` and `LANG`) uses the UTF-8 encoding
+fn locale_is_utf8() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    uses_utf8(locale.as_deref())
+}
+
+/// Whether the given locale uses the UTF-8 encoding. If no locale is set at all, UTF-8 is
+/// assumed, since the fallback to ASCII is only meant for terminals that are explicitly
+/// configured with a different encoding.
+fn uses_utf8(locale: Option<&str>) -> bool {
+    match locale {
+        Some(locale) => is_utf8_locale(locale),
+        None => true,
+    }
+}
 
-#[cfg(not(windows))]
-const TICK_SETTINGS: (&str, u64) = ("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ", 80);
+fn is_utf8_locale(locale: &str) -> bool {
+    let locale = locale.to_ascii_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
 
-#[cfg(windows)]
-const TICK_SETTINGS: (&str, u64) = (r"+-x| ", 200);
+fn tick_settings(option: OutputStyleOption) -> (&'static str, u64) {
+    if cfg!(windows) || ascii_only(option) {
+        ASCII_TICK_SETTINGS
+    } else {
+        UNICODE_TICK_SETTINGS
+    }
+}
 
 /// Return a pre-configured progress bar
 pub fn get_progress_bar(length: u64, msg: &str, option: OutputStyleOption) -> ProgressBar {
+    let tick_settings = tick_settings(option);
     let progressbar_style = match option {
         OutputStyleOption::Basic | OutputStyleOption::Color => ProgressStyle::default_bar(),
-        _ => ProgressStyle::default_spinner()
-            .tick_chars(TICK_SETTINGS.0)
-            .template(" {spinner} {msg:<30} {wide_bar} ETA {eta_precise} ")
-            .expect("no template error"),
+        _ => {
+            let style = ProgressStyle::default_spinner()
+                .tick_chars(tick_settings.0)
+                .template(" {spinner} {msg:<30} {wide_bar} ETA {eta_precise} ")
+                .expect("no template error");
+            if ascii_only(option) {
+                style.progress_chars(ASCII_PROGRESS_CHARS)
+            } else {
+                style
+            }
+        }
     };
 
     let progress_bar = match option {
@@ -24,8 +84,396 @@ pub fn get_progress_bar(length: u64, msg: &str, option: OutputStyleOption) -> Pr
         _ => ProgressBar::new(length),
     };
     progress_bar.set_style(progressbar_style);
-    progress_bar.enable_steady_tick(Duration::from_millis(TICK_SETTINGS.1));
+    progress_bar.enable_steady_tick(Duration::from_millis(tick_settings.1));
     progress_bar.set_message(msg.to_owned());
 
     progress_bar
 }
+
+/// Return a pre-configured spinner for phases with an unknown number of runs
+pub fn get_spinner(msg: &str, option: OutputStyleOption) -> ProgressBar {
+    let tick_settings = tick_settings(option);
+    let spinner = match option {
+        OutputStyleOption::Basic | OutputStyleOption::Color => ProgressBar::hidden(),
+        _ => ProgressBar::new_spinner(),
+    };
+    spinner.set_style(
+        ProgressStyle::default_spinner()
+            .tick_chars(tick_settings.0)
+            .template(" {spinner} {msg:<30} {elapsed_precise} ")
+            .expect("no template error"),
+    );
+    spinner.enable_steady_tick(Duration::from_millis(tick_settings.1));
+    spinner.set_message(msg.to_owned());
+
+    spinner
+}
+
+/// An event in the course of a single benchmark. The events drive the progress bar, or they are
+/// written to stderr as JSON Lines if `--progress-json` is used.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent<'a> {
+    /// The benchmark with the given index (starting at 1) out of `total` benchmarks has started
+    BenchmarkStarted {
+        command: &'a str,
+        index: usize,
+        total: usize,
+    },
+
+    /// Warmup runs are performed, either the given number of runs or for a certain duration
+    WarmupStarted {
+        runs: Option<u64>,
+    },
+
+    /// A warmup run has completed. `estimate` is the moving average of the last few warmup run
+    /// times, which is used to decide when the times have stabilized with `--warmup auto`.
+    WarmupRunCompleted {
+        run: u64,
+        time: Second,
+        estimate: Second,
+    },
+
+    WarmupFinished {
+        runs: u64,
+    },
+
+    /// The timing runs have started. Their number is only known after the first run.
+    MeasurementStarted,
+
+    /// A timing run has completed. `runs` is the total number of timing runs and `mean` the
+    /// current estimate of the mean run time. With `--interleave`, `benchmark` is the index of
+    /// the benchmark that the run belongs to.
+    RunCompleted {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        benchmark: Option<usize>,
+        run: usize,
+        runs: u64,
+        time: Second,
+        mean: Second,
+    },
+
+    BenchmarkFinished {
+        runs: usize,
+        mean: Second,
+        stddev: Option<Second>,
+        median: Second,
+        min: Second,
+        max: Second,
+        user: Second,
+        system: Second,
+    },
+}
+
+/// Statistics of the run times measured so far, shown in the progress bar with
+/// `--show-live-stats`. The mean and the variance are updated incrementally (Welford's method).
+#[derive(Debug, Default)]
+struct LiveStats {
+    times: Vec<Second>,
+    mean: Second,
+    sum_of_squares: Scalar,
+    min: Second,
+    max: Second,
+    last_update: Option<Instant>,
+}
+
+impl LiveStats {
+    fn push(&mut self, time: Second) {
+        if self.times.is_empty() {
+            self.min = time;
+            self.max = time;
+        }
+        self.times.push(time);
+        self.min = self.min.min(time);
+        self.max = self.max.max(time);
+
+        let delta = time - self.mean;
+        self.mean += delta / self.times.len() as Scalar;
+        self.sum_of_squares += delta * (time - self.mean);
+    }
+
+    fn stddev(&self) -> Option<Second> {
+        let n = self.times.len();
+        (n > 1).then(|| (self.sum_of_squares / (n - 1) as Scalar).sqrt())
+    }
+
+    /// Whether enough time has passed since the last update of the progress bar
+    fn update_due(&mut self) -> bool {
+        let due = self
+            .last_update
+            .map_or(true, |last| last.elapsed() >= LIVE_STATS_UPDATE_INTERVAL);
+        if due {
+            self.last_update = Some(Instant::now());
+        }
+        due
+    }
+
+    /// Format the statistics like the final results: all values are shown in the unit that is
+    /// chosen for the mean (unless a unit has been specified via `--time-unit`)
+    fn format(&self, time_unit: Option<Unit>, theme: Theme) -> String {
+        let (mean, unit) = format_duration_unit(self.mean, time_unit);
+        let stddev = self
+            .stddev()
+            .map(|stddev| format!(" ± {}", format_duration(stddev, Some(unit))))
+            .unwrap_or_default();
+        format!(
+            "{}{} (min {}, median {}, max {})",
+            theme.paint(Role::Value, mean),
+            theme.paint(Role::Value, stddev),
+            format_duration(self.min, Some(unit)),
+            format_duration(median(&self.times), Some(unit)),
+            format_duration(self.max, Some(unit)),
+        )
+    }
+}
+
+/// Reports the progress of a single benchmark, either via an interactive progress bar or as a
+/// stream of JSON events on stderr
+pub struct ProgressReporter {
+    style: OutputStyleOption,
+    json: bool,
+    min_runs: u64,
+    time_unit: Option<Unit>,
+    theme: Theme,
+    bar: Option<ProgressBar>,
+    live_stats: Option<LiveStats>,
+}
+
+impl ProgressReporter {
+    pub fn new(options: &Options, min_runs: u64) -> Self {
+        ProgressReporter {
+            style: options.output_style,
+            json: options.progress_json,
+            min_runs,
+            time_unit: options.time_unit,
+            theme: options.theme,
+            bar: None,
+            live_stats: options.show_live_stats.then(LiveStats::default),
+        }
+    }
+
+    pub fn report(&mut self, event: ProgressEvent) -> Result<()> {
+        if self.json {
+            let mut stderr = io::stderr().lock();
+            serde_json::to_writer(&mut stderr, &event)?;
+            writeln!(stderr)?;
+        } else if self.style != OutputStyleOption::Disabled {
+            self.update_bar(event);
+        }
+        Ok(())
+    }
+
+    /// The progress bar that is currently shown (if any)
+    pub fn bar(&self) -> Option<&ProgressBar> {
+        self.bar.as_ref()
+    }
+
+    /// Remove the progress bar from the terminal. Later events are still reported as JSON, but
+    /// they do not show a progress bar anymore. This is used for `--interleave`, where the
+    /// scheduler shows the progress of the interleaved timing runs.
+    pub fn hide_bar(&mut self) {
+        if let Some(bar) = self.bar.take() {
+            bar.finish_and_clear()
+        }
+    }
+
+    fn update_bar(&mut self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::BenchmarkStarted { .. } => {}
+            ProgressEvent::WarmupStarted { runs: Some(runs) } => {
+                self.bar = Some(get_progress_bar(runs, "Performing warmup runs", self.style));
+            }
+            ProgressEvent::WarmupStarted { runs: None } => {
+                self.bar = Some(get_spinner("Performing warmup runs", self.style));
+            }
+            ProgressEvent::WarmupRunCompleted { run, estimate, .. } => {
+                if let Some(bar) = self.bar.as_ref() {
+                    bar.inc(1);
+                    let estimate = format_duration(estimate, self.time_unit);
+                    bar.set_message(format!(
+                        "Warmup run {run}, estimate: {}",
+                        self.theme.paint(Role::Value, estimate)
+                    ));
+                }
+            }
+            ProgressEvent::MeasurementStarted => {
+                if let Some(live_stats) = self.live_stats.as_mut() {
+                    *live_stats = LiveStats::default();
+                }
+                self.bar = Some(get_progress_bar(
+                    self.min_runs,
+                    "Initial time measurement",
+                    self.style,
+                ));
+            }
+            ProgressEvent::RunCompleted {
+                runs, time, mean, ..
+            } => {
+                if let Some(bar) = self.bar.as_ref() {
+                    bar.set_length(runs);
+                    bar.inc(1);
+                    match self.live_stats.as_mut() {
+                        Some(live_stats) => {
+                            live_stats.push(time);
+                            if live_stats.update_due() {
+                                bar.set_message(format!(
+                                    "Current estimate: {}",
+                                    live_stats.format(self.time_unit, self.theme)
+                                ));
+                            }
+                        }
+                        None => {
+                            let mean = format_duration(mean, self.time_unit);
+                            bar.set_message(format!(
+                                "Current estimate: {}",
+                                self.theme.paint(Role::Value, mean)
+                            ));
+                        }
+                    }
+                }
+            }
+            ProgressEvent::WarmupFinished { .. } | ProgressEvent::BenchmarkFinished { .. } => {
+                if let Some(bar) = self.bar.take() {
+                    bar.finish_and_clear()
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_is_utf8_locale() {
+    assert!(is_utf8_locale("en_US.UTF-8"));
+    assert!(is_utf8_locale("de_DE.utf8"));
+    assert!(is_utf8_locale("C.UTF-8"));
+    assert!(!is_utf8_locale("C"));
+    assert!(!is_utf8_locale("POSIX"));
+    assert!(!is_utf8_locale("en_US.ISO-8859-1"));
+}
+
+#[test]
+fn test_uses_utf8() {
+    assert!(uses_utf8(None));
+    assert!(uses_utf8(Some("en_US.UTF-8")));
+    assert!(!uses_utf8(Some("C")));
+}
+
+#[test]
+fn test_serialize_progress_events() {
+    let json = |event: &ProgressEvent| serde_json::to_string(event).unwrap();
+
+    assert_eq!(
+        json(&ProgressEvent::BenchmarkStarted {
+            command: "sleep 0.1",
+            index: 1,
+            total: 2,
+        }),
+        r#"{"event":"benchmark_started","command":"sleep 0.1","index":1,"total":2}"#
+    );
+    assert_eq!(
+        json(&ProgressEvent::WarmupRunCompleted {
+            run: 4,
+            time: 0.5,
+            estimate: 0.625,
+        }),
+        r#"{"event":"warmup_run_completed","run":4,"time":0.5,"estimate":0.625}"#
+    );
+    assert_eq!(
+        json(&ProgressEvent::MeasurementStarted),
+        r#"{"event":"measurement_started"}"#
+    );
+    assert_eq!(
+        json(&ProgressEvent::RunCompleted {
+            benchmark: None,
+            run: 3,
+            runs: 10,
+            time: 0.5,
+            mean: 0.25,
+        }),
+        r#"{"event":"run_completed","run":3,"runs":10,"time":0.5,"mean":0.25}"#
+    );
+    assert_eq!(
+        json(&ProgressEvent::RunCompleted {
+            benchmark: Some(2),
+            run: 3,
+            runs: 10,
+            time: 0.5,
+            mean: 0.25,
+        }),
+        r#"{"event":"run_completed","benchmark":2,"run":3,"runs":10,"time":0.5,"mean":0.25}"#
+    );
+}
+
+#[test]
+fn test_live_stats() {
+    use approx::assert_relative_eq;
+
+    let mut stats = LiveStats::default();
+    stats.push(0.012);
+    assert_eq!(stats.stddev(), None);
+    assert_eq!(
+        stats.format(None, Theme::Plain),
+        "12.0 ms (min 12.0 ms, median 12.0 ms, max 12.0 ms)"
+    );
+
+    for time in [0.010, 0.014, 0.011] {
+        stats.push(time);
+    }
+    assert_relative_eq!(stats.mean, 0.01175, epsilon = 1e-12);
+    assert_relative_eq!(
+        stats.stddev().unwrap(),
+        statistical::standard_deviation(&stats.times, None),
+        epsilon = 1e-12
+    );
+    assert_eq!(
+        stats.format(None, Theme::Plain),
+        "11.8 ms ± 1.7 ms (min 10.0 ms, median 11.5 ms, max 14.0 ms)"
+    );
+    assert_eq!(
+        stats.format(Some(Unit::MicroSecond), Theme::Plain),
+        "11750.0 µs ± 1707.8 µs (min 10000.0 µs, median 11500.0 µs, max 14000.0 µs)"
+    );
+
+    // Updates are throttled
+    assert!(stats.update_due());
+    assert!(!stats.update_due());
+}
+
+#[test]
+fn test_live_stats_in_progress_bar() {
+    let options = Options {
+        output_style: OutputStyleOption::Basic,
+        show_live_stats: true,
+        theme: Theme::Plain,
+        ..Options::default()
+    };
+    let mut reporter = ProgressReporter::new(&options, 3);
+    let run_completed = |run, time| ProgressEvent::RunCompleted {
+        benchmark: None,
+        run,
+        runs: 3,
+        time,
+        mean: time,
+    };
+
+    reporter.report(ProgressEvent::MeasurementStarted).unwrap();
+    reporter.report(run_completed(1, 0.012)).unwrap();
+    assert_eq!(
+        reporter.bar().unwrap().message(),
+        "Current estimate: 12.0 ms (min 12.0 ms, median 12.0 ms, max 12.0 ms)"
+    );
+
+    // The statistics of the second run are not shown right away, since updates are throttled
+    reporter.report(run_completed(2, 0.010)).unwrap();
+    assert_eq!(
+        reporter.bar().unwrap().message(),
+        "Current estimate: 12.0 ms (min 12.0 ms, median 12.0 ms, max 12.0 ms)"
+    );
+
+    std::thread::sleep(LIVE_STATS_UPDATE_INTERVAL);
+    reporter.report(run_completed(3, 0.014)).unwrap();
+    assert_eq!(
+        reporter.bar().unwrap().message(),
+        "Current estimate: 12.0 ms ± 2.0 ms (min 10.0 ms, median 12.0 ms, max 14.0 ms)"
+    );
+}
@@ -0,0 +1,200 @@
+use colored::{Color, ColoredString, Colorize};
+
+/// Parts of the terminal output that are shown in a color of their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The command that all others are compared to (usually the fastest one)
+    Reference,
+
+    /// The other commands in the relative speed comparison
+    Command,
+
+    /// Mean times, relative speeds and other main results
+    Value,
+
+    /// Minimum of a range
+    Min,
+
+    /// Maximum of a range
+    Max,
+
+    /// User and system time
+    CpuTime,
+
+    /// Additional statistics, like the exit codes or the memory usage
+    Detail,
+
+    /// Warnings and other hints
+    Warning,
+
+    /// Failures, like assertions that do not hold
+    Error,
+
+    /// A decrease of a time in the side-by-side comparison
+    Faster,
+
+    /// An increase of a time in the side-by-side comparison
+    Slower,
+
+    /// The bars of the histogram (`--histogram`)
+    Histogram,
+}
+
+/// Color theme of the terminal output (`--theme`). Colors are still disabled completely with
+/// `--style=nocolor` or `--style=basic`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Default,
+
+    /// Darker colors that are readable on a light background
+    Light,
+
+    /// No colors at all. The most important parts are bold instead.
+    MonochromeBold,
+
+    /// Blue and yellow instead of colors that are hard to tell apart with a red-green color
+    /// vision deficiency
+    Colorblind,
+
+    /// No styling at all, such that tests can compare the plain text without changing the
+    /// global color settings
+    #[cfg(test)]
+    Plain,
+}
+
+impl Theme {
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Theme::Default),
+            "light" => Some(Theme::Light),
+            "monochrome-bold" => Some(Theme::MonochromeBold),
+            "colorblind" => Some(Theme::Colorblind),
+            _ => None,
+        }
+    }
+
+    /// The color of the given role, and whether it is bold (in addition to bold text that is
+    /// bold in all themes)
+    fn style(self, role: Role) -> (Option<Color>, bool) {
+        use Role::*;
+
+        match self {
+            Theme::Default => (
+                Some(match role {
+                    Reference | Min => Color::Cyan,
+                    Command | Max => Color::Magenta,
+                    Value => Color::Green,
+                    CpuTime | Histogram => Color::Blue,
+                    Detail | Warning => Color::Yellow,
+                    Error | Slower => Color::Red,
+                    Faster => Color::Green,
+                }),
+                false,
+            ),
+            Theme::Light => (
+                Some(match role {
+                    Reference | Min | CpuTime | Histogram => Color::Blue,
+                    Command | Max | Detail => Color::Magenta,
+                    Value | Faster => Color::Green,
+                    Warning | Error | Slower => Color::Red,
+                }),
+                false,
+            ),
+            Theme::MonochromeBold => (
+                None,
+                matches!(role, Reference | Value | Warning | Error | Slower),
+            ),
+            Theme::Colorblind => (
+                Some(match role {
+                    Reference => Color::BrightBlue,
+                    Command | Max => Color::BrightYellow,
+                    Value | Min => Color::BrightCyan,
+                    CpuTime | Histogram => Color::Blue,
+                    Detail | Warning | Slower => Color::Yellow,
+                    Error => Color::Magenta,
+                    Faster => Color::BrightBlue,
+                }),
+                false,
+            ),
+            #[cfg(test)]
+            Theme::Plain => (None, false),
+        }
+    }
+
+    /// Show the given text in the style of the given role
+    pub fn paint<S: AsRef<str>>(self, role: Role, text: S) -> ColoredString {
+        let (color, bold) = self.style(role);
+        let text = text.as_ref();
+        let text = match color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        if bold {
+            text.bold()
+        } else {
+            text
+        }
+    }
+
+    /// Show the given text dimmed, such that it stands back behind the actual output
+    pub fn dim<S: AsRef<str>>(self, text: S) -> ColoredString {
+        match self {
+            #[cfg(test)]
+            Theme::Plain => text.as_ref().normal(),
+            _ => text.as_ref().dimmed(),
+        }
+    }
+}
+
+#[test]
+fn test_theme_from_name() {
+    assert_eq!(Theme::from_name("default"), Some(Theme::Default));
+    assert_eq!(
+        Theme::from_name("monochrome-bold"),
+        Some(Theme::MonochromeBold)
+    );
+    assert_eq!(Theme::from_name("solarized"), None);
+}
+
+#[test]
+fn test_theme_styles() {
+    let roles = [
+        Role::Reference,
+        Role::Command,
+        Role::Value,
+        Role::Min,
+        Role::Max,
+        Role::CpuTime,
+        Role::Detail,
+        Role::Warning,
+        Role::Error,
+        Role::Faster,
+        Role::Slower,
+        Role::Histogram,
+    ];
+
+    assert!(roles
+        .iter()
+        .all(|&role| Theme::MonochromeBold.style(role).0.is_none()));
+
+    // The reference command can be told apart from the other commands in all themes
+    for theme in [
+        Theme::Default,
+        Theme::Light,
+        Theme::MonochromeBold,
+        Theme::Colorblind,
+    ] {
+        assert_ne!(theme.style(Role::Reference), theme.style(Role::Command));
+        assert_ne!(theme.style(Role::Faster), theme.style(Role::Slower));
+    }
+
+    // Neither red nor green is used in the colorblind theme
+    for role in roles {
+        let (color, _) = Theme::Colorblind.style(role);
+        assert!(!matches!(
+            color,
+            Some(Color::Red | Color::Green | Color::BrightRed | Color::BrightGreen)
+        ));
+    }
+}
@@ -1,20 +1,57 @@
 use std::fmt;
 
 use crate::benchmark::MIN_EXECUTION_TIME;
+use crate::options::Priority;
 use crate::output::format::format_duration;
-use crate::util::units::Second;
+use crate::util::cpu_frequency::PERFORMANCE_GOVERNOR;
+use crate::util::units::{Scalar, Second};
 
 pub struct OutlierWarningOptions {
     pub warmup_in_use: bool,
     pub prepare_in_use: bool,
 }
 
+/// Maximum number of outliers that are listed individually in the warning
+const MAX_LISTED_OUTLIERS: usize = 5;
+
 /// A list of all possible warnings
 pub enum Warnings {
     FastExecutionTime,
+    ShorterThanShellSpawningTime(usize),
+    /// Number of measured times that have been set to zero with `--shell=none`
+    ShorterThanSpawningTime(usize),
     NonZeroExitCode,
+    TimedOut(usize, Second),
+    Retried(usize),
+    /// Number of runs in whose output the `--extract-time` pattern has not been found
+    TimeNotReported(usize),
     SlowInitialRun(Second, OutlierWarningOptions),
-    OutliersDetected(OutlierWarningOptions),
+    /// Run numbers (starting at 1) and times of the outliers
+    OutliersDetected(Vec<(usize, Second)>, OutlierWarningOptions),
+    ManyBenchmarks(usize),
+    Drift(Scalar),
+    /// Coefficient of variation of the run times and the threshold given via
+    /// `--cv-warning-threshold`
+    HighVariation(Scalar, Scalar),
+    /// Number of performed runs, the number of runs given via `--min-runs` and the
+    /// `--max-benchmark-time`
+    FewerThanMinRuns(u64, u64, Second),
+    CpuAffinityNotSupported,
+    /// Reason why `--shell-mode=persistent` can not be used
+    PersistentShellNotSupported(&'static str),
+    MixedExitCodes(usize),
+    HighSystemLoad(f64, usize),
+    /// Priority that has been requested via `--priority`
+    PriorityNotPermitted(Priority),
+    /// Governor and the files in which it is set
+    CpuGovernorNotPerformance(String, &'static str),
+    /// File in which turbo boost can be disabled
+    TurboBoostEnabled(&'static str),
+    /// File in which simultaneous multithreading can be disabled
+    SmtEnabled(&'static str),
+
+    /// Benchmark numbers (starting at 1) and whether the duplicates are skipped
+    DuplicateCommands(Vec<usize>, bool),
 }
 
 impl fmt::Display for Warnings {
@@ -28,7 +65,44 @@ impl fmt::Display for Warnings {
                 option to disable the shell completely.",
                 MIN_EXECUTION_TIME * 1e3
             ),
+            Warnings::ShorterThanShellSpawningTime(count) => write!(
+                f,
+                "{count} measured {times} shorter than the estimated shell spawning time and \
+                 {have} been set to zero. The startup time of the shell varies too much to \
+                 benchmark such a fast command through it. Use the `-N`/`--shell=none` option \
+                 to run the command without a shell.",
+                times = if count == 1 { "time was" } else { "times were" },
+                have = if count == 1 { "has" } else { "have" },
+            ),
+            Warnings::ShorterThanSpawningTime(count) => write!(
+                f,
+                "{count} measured {times} shorter than the estimated process spawning time \
+                 and {have} been set to zero. Use the `--no-spawn-calibration` option to see \
+                 the times including the spawning time.",
+                times = if count == 1 { "time was" } else { "times were" },
+                have = if count == 1 { "has" } else { "have" },
+            ),
             Warnings::NonZeroExitCode => write!(f, "Ignoring non-zero exit code."),
+            Warnings::TimedOut(count, timeout) => write!(
+                f,
+                "{count} {runs} exceeded the timeout of {timeout} and {was} aborted. The \
+                 measured times of aborted runs are included in the statistics.",
+                runs = if count == 1 { "run" } else { "runs" },
+                was = if count == 1 { "was" } else { "were" },
+                timeout = format_duration(timeout, None)
+            ),
+            Warnings::Retried(count) => write!(
+                f,
+                "{count} failed {runs} had to be repeated. Only the times of the last attempts \
+                 are included in the statistics.",
+                runs = if count == 1 { "run" } else { "runs" },
+            ),
+            Warnings::TimeNotReported(count) => write!(
+                f,
+                "The '--extract-time' pattern did not match the output of {count} {runs}. The \
+                 reported times of these runs are exported as null.",
+                runs = if count == 1 { "run" } else { "runs" },
+            ),
             Warnings::SlowInitialRun(time_first_run, ref options) => write!(
                 f,
                 "The first benchmarking run for this command was significantly slower than the \
@@ -54,16 +128,154 @@ impl fmt::Display for Warnings {
                     option to clear the caches before each timing run."
                 }
             ),
-            Warnings::OutliersDetected(ref options) => write!(
+            Warnings::OutliersDetected(ref outliers, ref options) => write!(
                 f,
-                "Statistical outliers were detected. Consider re-running this benchmark on a quiet \
-                 system without any interferences from other programs.{hint}",
+                "Statistical outliers were detected ({runs}). Consider re-running this benchmark \
+                 on a quiet system without any interferences from other programs.{hint}",
+                runs = format_outlier_runs(outliers),
                 hint=if options.warmup_in_use && options.prepare_in_use {
                     ""
                 } else {
                     " It might help to use the '--warmup' or '--prepare' options."
                 }
             ),
+            Warnings::ManyBenchmarks(count) => write!(
+                f,
+                "{count} benchmarks will be performed (one for each combination of commands and \
+                 parameter values). This might take a long time."
+            ),
+            Warnings::Drift(relative_change) => write!(
+                f,
+                "Performance seems to drift over time (the run time {direction} by {percent:.0}% \
+                 over the course of the benchmark). The results may be affected by thermal \
+                 throttling or caching effects.",
+                direction = if relative_change > 0.0 { "increased" } else { "decreased" },
+                percent = relative_change.abs() * 100.0
+            ),
+            Warnings::HighVariation(cv, threshold) => write!(
+                f,
+                "The run times vary a lot (the coefficient of variation is {:.1}%, which is above \
+                 the threshold of {:.1}% given via '--cv-warning-threshold'). The results may be \
+                 affected by other programs. Consider closing them or performing more runs.",
+                cv * 100.0,
+                threshold * 100.0
+            ),
+            Warnings::FewerThanMinRuns(runs, min_runs, max_benchmark_time) => write!(
+                f,
+                "Only {runs} {runs_str} could be performed within the '--max-benchmark-time' of \
+                 {time}, which is fewer than the minimum of {min_runs} runs. The results may not \
+                 be representative.",
+                runs_str = if runs == 1 { "run" } else { "runs" },
+                time = format_duration(max_benchmark_time, None)
+            ),
+            Warnings::MixedExitCodes(count) => write!(
+                f,
+                "The runs terminated with {count} different exit codes. The timings of runs that \
+                 took different code paths are usually not comparable."
+            ),
+            Warnings::CpuAffinityNotSupported => write!(
+                f,
+                "Setting the CPU affinity via '--cpu' is not supported on this platform. The \
+                 commands can run on all CPUs."
+            ),
+            Warnings::PersistentShellNotSupported(reason) => write!(
+                f,
+                "The persistent shell mode can not be used because {reason}. A new shell is \
+                 spawned for each run instead."
+            ),
+            Warnings::PriorityNotPermitted(priority) => write!(
+                f,
+                "The benchmarked commands could not be run at the {priority} priority specified \
+                 via '--priority' (raising the priority usually requires root privileges). They \
+                 run at the priority of hyperfine itself instead."
+            ),
+            Warnings::HighSystemLoad(load, cores) => write!(
+                f,
+                "System load is high ({load:.1} on {cores} {cores_word}); results may be noisy. \
+                 Close other programs or use '--no-load-check' to disable this warning.",
+                cores_word = if cores == 1 { "core" } else { "cores" },
+            ),
+            Warnings::CpuGovernorNotPerformance(ref governor, path) => write!(
+                f,
+                "The CPU frequency scaling governor is '{governor}' instead of '{PERFORMANCE_GOVERNOR}' \
+                 (see {path}). The CPU frequency may change during the benchmarks (use \
+                 '--show-cpu-frequency' to see it), which makes results less comparable. Consider \
+                 switching the governor, e.g. with 'cpupower frequency-set --governor \
+                 {PERFORMANCE_GOVERNOR}', or use '--no-system-checks' to disable this warning."
+            ),
+            Warnings::TurboBoostEnabled(path) => write!(
+                f,
+                "Turbo boost is enabled (see {path}). The CPU frequency then depends on the \
+                 temperature and on the load of the other cores, which makes results less \
+                 comparable. Consider disabling it, or use '--no-system-checks' to disable this \
+                 warning."
+            ),
+            Warnings::SmtEnabled(path) => write!(
+                f,
+                "Simultaneous multithreading (hyper-threading) is active (see {path}). Other \
+                 processes on the sibling core can affect the results. Consider disabling it or \
+                 pinning the benchmarks to a CPU with '--cpu', or use '--no-system-checks' to \
+                 disable this warning."
+            ),
+            Warnings::DuplicateCommands(ref numbers, deduplicated) => write!(
+                f,
+                "Benchmarks {benchmarks} run identical commands. {consequence}",
+                benchmarks = format_benchmark_numbers(numbers),
+                consequence = if deduplicated {
+                    "Only the first one is performed ('--deduplicate')."
+                } else {
+                    "Use '--deduplicate' to only perform the first one."
+                }
+            ),
         }
     }
 }
+
+/// List the benchmarks like "#1, #2 and #5"
+fn format_benchmark_numbers(numbers: &[usize]) -> String {
+    let numbers: Vec<String> = numbers.iter().map(|number| format!("#{number}")).collect();
+    match numbers.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+        _ => numbers.concat(),
+    }
+}
+
+/// List the outliers like "runs 3 (54.2 ms) and 17 (61.0 ms)"
+fn format_outlier_runs(outliers: &[(usize, Second)]) -> String {
+    let mut runs: Vec<String> = outliers
+        .iter()
+        .take(MAX_LISTED_OUTLIERS)
+        .map(|&(run, time)| format!("{run} ({})", format_duration(time, None)))
+        .collect();
+    if outliers.len() > MAX_LISTED_OUTLIERS {
+        runs.push(format!("{} more", outliers.len() - MAX_LISTED_OUTLIERS));
+    }
+
+    let list = match runs.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+        _ => runs.concat(),
+    };
+    format!(
+        "{} {list}",
+        if outliers.len() == 1 { "run" } else { "runs" }
+    )
+}
+
+#[test]
+fn test_format_outlier_runs() {
+    assert_eq!(format_outlier_runs(&[(3, 0.0542)]), "run 3 (54.2 ms)");
+    assert_eq!(
+        format_outlier_runs(&[(3, 0.0542), (17, 0.061)]),
+        "runs 3 (54.2 ms) and 17 (61.0 ms)"
+    );
+    assert_eq!(
+        format_outlier_runs(&[(1, 1.5), (2, 1.5), (3, 1.5), (4, 1.5), (5, 1.5), (6, 1.5)]),
+        "runs 1 (1.500 s), 2 (1.500 s), 3 (1.500 s), 4 (1.500 s), 5 (1.500 s) and 1 more"
+    );
+}
+
+#[test]
+fn test_format_benchmark_numbers() {
+    assert_eq!(format_benchmark_numbers(&[2, 5]), "#2 and #5");
+    assert_eq!(format_benchmark_numbers(&[1, 2, 5]), "#1, #2 and #5");
+}
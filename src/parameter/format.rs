@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use super::ParameterValue;
+use crate::util::number::Number;
+
+/// Format specification of a parameter placeholder like `{n:03}` (zero-padding to a width of
+/// three) or `{n:.2}` (two decimal places). Both can be combined, e.g. `{n:06.2}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParameterFormat {
+    /// Pad with zeros instead of spaces
+    zero_padding: bool,
+
+    /// Minimum width of the formatted value
+    width: usize,
+
+    /// Number of decimal places (only for numeric values)
+    precision: Option<u32>,
+}
+
+impl FromStr for ParameterFormat {
+    type Err = ();
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (width, precision) = match spec.split_once('.') {
+            Some((width, precision)) => (width, Some(precision)),
+            None => (spec, None),
+        };
+        let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+        if (width.is_empty() && precision.is_none())
+            || (!width.is_empty() && !is_number(width))
+            || precision.is_some_and(|p| !is_number(p))
+        {
+            return Err(());
+        }
+
+        Ok(ParameterFormat {
+            zero_padding: width.starts_with('0'),
+            width: if width.is_empty() {
+                0
+            } else {
+                width.parse().map_err(|_| ())?
+            },
+            precision: precision.map(|p| p.parse()).transpose().map_err(|_| ())?,
+        })
+    }
+}
+
+impl ParameterFormat {
+    /// Format the given value. Returns `None` if decimal places are requested for a value that
+    /// is not a number.
+    pub fn apply(&self, value: &ParameterValue) -> Option<String> {
+        let formatted = match self.precision {
+            Some(precision) => {
+                let decimal = match value {
+                    ParameterValue::Numeric(number) => match number.expand_suffix() {
+                        Number::Int(i) => Decimal::from(i),
+                        Number::Decimal(d) => d,
+                        Number::Suffixed(_) => unreachable!("suffix has been expanded"),
+                    },
+                    ParameterValue::Text(text) => Decimal::from_str(text).ok()?,
+                };
+                let rounded = decimal
+                    .round_dp_with_strategy(precision, RoundingStrategy::MidpointAwayFromZero);
+                format!("{:.*}", precision as usize, rounded)
+            }
+            None => value.to_string(),
+        };
+
+        Some(self.pad(formatted))
+    }
+
+    fn pad(&self, value: String) -> String {
+        let length = value.chars().count();
+        if length >= self.width {
+            return value;
+        }
+
+        let padding = self.width - length;
+        if !self.zero_padding {
+            return format!("{}{}", " ".repeat(padding), value);
+        }
+
+        // Zeros are inserted after the sign
+        match value.strip_prefix('-') {
+            Some(digits) => format!("-{}{}", "0".repeat(padding), digits),
+            None => format!("{}{}", "0".repeat(padding), value),
+        }
+    }
+}
+
+#[test]
+fn test_parse_parameter_format() {
+    assert_eq!(
+        "03".parse(),
+        Ok(ParameterFormat {
+            zero_padding: true,
+            width: 3,
+            precision: None
+        })
+    );
+    assert_eq!(
+        "5".parse(),
+        Ok(ParameterFormat {
+            zero_padding: false,
+            width: 5,
+            precision: None
+        })
+    );
+    assert_eq!(
+        ".2".parse(),
+        Ok(ParameterFormat {
+            zero_padding: false,
+            width: 0,
+            precision: Some(2)
+        })
+    );
+    assert_eq!(
+        "06.1".parse(),
+        Ok(ParameterFormat {
+            zero_padding: true,
+            width: 6,
+            precision: Some(1)
+        })
+    );
+
+    for invalid in ["", "x", "03d", ".", "3.", ".x", "-3", "0 3"] {
+        assert_eq!(invalid.parse::<ParameterFormat>(), Err(()), "{invalid}");
+    }
+}
+
+#[test]
+fn test_apply_parameter_format() {
+    let format =
+        |spec: &str, value: ParameterValue| spec.parse::<ParameterFormat>().unwrap().apply(&value);
+    let int = |i: i32| ParameterValue::Numeric(Number::Int(i));
+    let decimal = |d: &str| ParameterValue::Numeric(Number::Decimal(Decimal::from_str(d).unwrap()));
+    let text = |t: &str| ParameterValue::Text(t.into());
+
+    assert_eq!(format("03", int(7)), Some("007".into()));
+    assert_eq!(format("03", int(1234)), Some("1234".into()));
+    assert_eq!(format("04", int(-7)), Some("-007".into()));
+    assert_eq!(format("3", int(7)), Some("  7".into()));
+    assert_eq!(format(".2", decimal("0.5")), Some("0.50".into()));
+    assert_eq!(format(".1", decimal("0.25")), Some("0.3".into()));
+    assert_eq!(format(".1", int(2)), Some("2.0".into()));
+    assert_eq!(format("05.1", decimal("1.25")), Some("001.3".into()));
+    assert_eq!(format("03", text("a")), Some("00a".into()));
+    assert_eq!(format(".2", text("1.5")), Some("1.50".into()));
+    assert_eq!(format(".2", text("abc")), None);
+}
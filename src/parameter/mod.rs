@@ -1,6 +1,7 @@
 use crate::util::number::Number;
 use std::fmt::Display;
 
+pub mod format;
 pub mod range_step;
 pub mod tokenize;
 
@@ -1,5 +1,5 @@
 use std::convert::TryInto;
-use std::ops::{Add, AddAssign, Div, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
 
 use crate::error::ParameterScanError;
 use crate::util::number::Number;
@@ -30,6 +30,9 @@ impl<
 {
 }
 
+/// Maximum number of parameter values in a single scan
+const MAX_PARAMETERS: usize = 100_000;
+
 #[derive(Debug)]
 pub struct RangeStep<T> {
     state: T,
@@ -47,7 +50,6 @@ impl<T: Numeric> RangeStep<T> {
             return Err(ParameterScanError::ZeroStep);
         }
 
-        const MAX_PARAMETERS: usize = 100_000;
         match range_step_size_hint(start, end, step) {
             (_, Some(size)) if size <= MAX_PARAMETERS => Ok(Self {
                 state: start,
@@ -89,6 +91,59 @@ fn range_step_size_hint<T: Numeric>(start: T, end: T, step: T) -> (usize, Option
         .map_or((usize::MAX, None), |u| (u, Some(u)))
 }
 
+/// A range where each value is obtained by multiplying the previous one by a constant factor
+#[derive(Debug, Clone)]
+pub struct RangeMultiply<T> {
+    state: Option<T>,
+    end: T,
+    factor: T,
+}
+
+impl<T: Numeric + Mul<Output = T>> RangeMultiply<T> {
+    pub fn new(start: T, end: T, factor: T) -> Result<Self, ParameterScanError> {
+        if end < start {
+            return Err(ParameterScanError::EmptyRange);
+        }
+
+        if start <= T::from(0) {
+            return Err(ParameterScanError::NonPositiveStart);
+        }
+
+        if factor <= T::from(1) {
+            return Err(ParameterScanError::InvalidFactor);
+        }
+
+        let range = Self {
+            state: Some(start),
+            end,
+            factor,
+        };
+        if range.clone().nth(MAX_PARAMETERS).is_some() {
+            return Err(ParameterScanError::TooLarge);
+        }
+
+        Ok(range)
+    }
+}
+
+impl<T: Numeric + Mul<Output = T>> Iterator for RangeMultiply<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.state.filter(|state| *state <= self.end)?;
+
+        // Stop before the multiplication if the next value would exceed the range. This also
+        // prevents overflows close to the maximum of the numeric type.
+        self.state = if current > self.end / self.factor {
+            None
+        } else {
+            Some(current * self.factor)
+        };
+
+        Some(current)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,4 +202,44 @@ mod tests {
             "Parameter range is too large"
         );
     }
+
+    #[test]
+    fn test_multiplicative_range() {
+        let param_range: Vec<i32> = RangeMultiply::new(1, 100, 3).unwrap().collect();
+        assert_eq!(param_range, vec![1, 3, 9, 27, 81]);
+
+        let param_range: Vec<i32> = RangeMultiply::new(1 << 20, i32::MAX, 2).unwrap().collect();
+        assert_eq!(param_range.len(), 11);
+        assert_eq!(param_range[10], 1 << 30);
+
+        let param_range: Vec<Decimal> = RangeMultiply::new(
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from(2),
+            Decimal::from_str("1.5").unwrap(),
+        )
+        .unwrap()
+        .collect();
+        assert_eq!(param_range.len(), 4);
+        assert_eq!(param_range[3], Decimal::from_str("1.6875").unwrap());
+    }
+
+    #[test]
+    fn test_multiplicative_range_validate() {
+        let result = RangeMultiply::new(8, 4, 2);
+        assert_eq!(format!("{}", result.unwrap_err()), "Empty parameter range");
+
+        for factor in [0, 1, -2] {
+            let result = RangeMultiply::new(1, 10, factor);
+            assert_eq!(
+                format!("{}", result.unwrap_err()),
+                "The factor of a multiplicative parameter scan needs to be larger than 1"
+            );
+        }
+
+        let result = RangeMultiply::new(0, 10, 2);
+        assert_eq!(
+            format!("{}", result.unwrap_err()),
+            "A multiplicative parameter scan needs to start at a positive value"
+        );
+    }
 }
@@ -0,0 +1,441 @@
+//! Benchmark suites (`--suite FILE`): a TOML or JSON file with a list of benchmarks, each with
+//! its own command and (optionally) name, parameters, preparation command, number of runs, etc.
+//! The options given on the command line act as defaults for the benchmarks that do not specify
+//! the respective value. An example in TOML:
+//!
+//! ```toml
+//! [[benchmarks]]
+//! name = "gzip -{level}"
+//! command = "gzip -{level} --keep --force data.bin"
+//! parameters = { level = [1, 6, 9] }
+//! runs = 20
+//!
+//! [[benchmarks]]
+//! command = "zstd --keep --force data.bin"
+//! prepare = "rm -f data.bin.zst"
+//! ```
+//!
+//! The JSON format has the same structure: `{ "benchmarks": [{ "command": "…" }, …] }`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ArgMatches;
+use serde::Deserialize;
+
+use crate::command::{Command, Commands};
+use crate::options::Options;
+use crate::parameter::ParameterValue;
+
+/// The benchmarks of a suite, in the order in which they are specified in the file
+#[derive(Debug)]
+pub struct Suite {
+    benchmarks: Vec<SuiteEntry>,
+}
+
+/// The top-level structure of a suite file. The entries are deserialized one after the other, in
+/// order to point at the offending one in error messages.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuiteFile<T> {
+    benchmarks: Vec<T>,
+}
+
+/// A single entry of a suite, which results in one benchmark for each combination of the values
+/// of its parameters
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuiteEntry {
+    name: Option<String>,
+    command: String,
+    #[serde(default)]
+    parameters: BTreeMap<String, Vec<ParameterListValue>>,
+    setup: Option<String>,
+    prepare: Option<String>,
+    conclude: Option<String>,
+    cleanup: Option<String>,
+    runs: Option<u64>,
+    warmup: Option<u64>,
+}
+
+/// Parameter values can be given as strings or as numbers
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ParameterListValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+}
+
+impl fmt::Display for ParameterListValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterListValue::Text(text) => write!(f, "{text}"),
+            ParameterListValue::Integer(integer) => write!(f, "{integer}"),
+            ParameterListValue::Float(float) => write!(f, "{float}"),
+        }
+    }
+}
+
+impl Suite {
+    pub fn from_cli_arguments(matches: &ArgMatches) -> Result<Option<Suite>> {
+        matches
+            .get_one::<String>("suite")
+            .map(|path| Self::read(path))
+            .transpose()
+    }
+
+    /// Read the suite from the given file. The format is determined by the file extension.
+    fn read(path: &str) -> Result<Suite> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the benchmark suite '{path}'"))?;
+        let extension = Path::new(path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        let suite = match extension.as_deref() {
+            Some("toml") => Self::parse_toml(&content),
+            Some("json") => Self::parse_json(&content),
+            _ => bail!(
+                "Unknown format of the benchmark suite '{path}'. Use a file with the extension \
+                 '.toml' or '.json'."
+            ),
+        };
+        suite.with_context(|| format!("Invalid benchmark suite '{path}'"))
+    }
+
+    fn parse_toml(content: &str) -> Result<Suite> {
+        let file: SuiteFile<toml::Value> = toml::from_str(content)?;
+        Self::from_entries(file.benchmarks, |entry| Ok(entry.try_into()?))
+    }
+
+    fn parse_json(content: &str) -> Result<Suite> {
+        let file: SuiteFile<serde_json::Value> = serde_json::from_str(content)?;
+        Self::from_entries(file.benchmarks, |entry| Ok(serde_json::from_value(entry)?))
+    }
+
+    fn from_entries<T>(
+        entries: Vec<T>,
+        deserialize: impl Fn(T) -> Result<SuiteEntry>,
+    ) -> Result<Suite> {
+        if entries.is_empty() {
+            bail!("The suite does not contain any benchmarks");
+        }
+
+        let benchmarks = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let entry =
+                    deserialize(entry).map_err(|e| anyhow!("Benchmark #{}: {e}", index + 1))?;
+                entry.validate().map_err(|e| {
+                    anyhow!("Benchmark #{}{}: {e}", index + 1, entry.describe_name())
+                })?;
+                Ok(entry)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Suite { benchmarks })
+    }
+
+    /// The commands of all benchmarks, with the parameter values of each combination
+    pub fn commands(&self) -> Vec<Command<'_>> {
+        self.benchmarks
+            .iter()
+            .flat_map(|entry| entry.commands())
+            .collect()
+    }
+
+    /// Set the per-benchmark options (like `--prepare`) of the given options to the values of
+    /// the benchmarks of the suite. The values given on the command line are used for the
+    /// benchmarks that do not specify them (and for a separate reference).
+    pub fn apply_to(&self, options: &mut Options, commands: &Commands) -> Result<()> {
+        let separate_reference = matches!(
+            &options.reference_command,
+            Some(reference) if commands.position_of(reference).is_none()
+        );
+        let per_benchmark_commands =
+            |field: fn(&SuiteEntry) -> Option<&str>| self.per_benchmark(separate_reference, field);
+        merge_commands(
+            "setup",
+            &mut options.setup_command,
+            per_benchmark_commands(|entry| entry.setup.as_deref()),
+        )?;
+        merge_commands(
+            "prepare",
+            &mut options.preparation_command,
+            per_benchmark_commands(|entry| entry.prepare.as_deref()),
+        )?;
+        merge_commands(
+            "conclude",
+            &mut options.conclusion_command,
+            per_benchmark_commands(|entry| entry.conclude.as_deref()),
+        )?;
+        merge_commands(
+            "cleanup",
+            &mut options.cleanup_command,
+            per_benchmark_commands(|entry| entry.cleanup.as_deref()),
+        )?;
+
+        merge_counts(
+            "runs-per-command",
+            "runs",
+            &mut options.runs_per_command,
+            self.per_benchmark(separate_reference, |entry| entry.runs),
+        )?;
+        merge_counts(
+            "warmup-per-command",
+            "warmup",
+            &mut options.warmup_per_command,
+            self.per_benchmark(separate_reference, |entry| entry.warmup),
+        )?;
+
+        Ok(())
+    }
+
+    /// The value of a field of the suite entries for each benchmark after the parameter
+    /// expansion, with `None` for a separate reference
+    fn per_benchmark<'s, T: Clone>(
+        &'s self,
+        separate_reference: bool,
+        value: fn(&'s SuiteEntry) -> Option<T>,
+    ) -> Vec<Option<T>> {
+        iter::repeat(None)
+            .take(usize::from(separate_reference))
+            .chain(
+                self.benchmarks
+                    .iter()
+                    .flat_map(|entry| iter::repeat(value(entry)).take(entry.num_commands())),
+            )
+            .collect()
+    }
+}
+
+/// Replace the command(s) of a per-benchmark option like `--prepare` by the ones of the suite,
+/// unless none of the benchmarks specifies one. The command given on the command line is used for
+/// the other benchmarks. An empty command is not run at all.
+fn merge_commands(
+    option: &str,
+    commands: &mut Option<Vec<String>>,
+    values: Vec<Option<&str>>,
+) -> Result<()> {
+    if values.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    let default = match commands.as_deref() {
+        None => "",
+        Some([default]) => default.as_str(),
+        Some(_) => bail!(
+            "The '--{option}' option can only be provided once together with '--suite', since \
+             the benchmarks of the suite specify their own '{option}' commands."
+        ),
+    };
+    *commands = Some(
+        values
+            .into_iter()
+            .map(|value| value.unwrap_or(default).to_string())
+            .collect(),
+    );
+    Ok(())
+}
+
+/// Set the number of (warmup) runs of each benchmark, unless none of the benchmarks of the suite
+/// specifies one
+fn merge_counts(
+    option: &str,
+    key: &str,
+    counts: &mut Option<Vec<Option<u64>>>,
+    values: Vec<Option<u64>>,
+) -> Result<()> {
+    if values.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    if counts.is_some() {
+        bail!(
+            "The '--{option}' option can not be combined with '--suite' if the benchmarks of the \
+             suite specify '{key}'."
+        );
+    }
+    *counts = Some(values);
+    Ok(())
+}
+
+impl SuiteEntry {
+    fn validate(&self) -> Result<()> {
+        if self.command.trim().is_empty() {
+            bail!("The 'command' must not be empty");
+        }
+        if let Some((name, _)) = self.parameters.iter().find(|(_, values)| values.is_empty()) {
+            bail!("The list of values of the parameter '{name}' is empty");
+        }
+        if self.runs == Some(0) {
+            bail!("The number of 'runs' needs to be at least 1");
+        }
+        Ok(())
+    }
+
+    /// The name of the benchmark for error messages, like " ('gzip')", if one is specified
+    fn describe_name(&self) -> String {
+        self.name
+            .as_ref()
+            .map_or(String::new(), |name| format!(" ('{name}')"))
+    }
+
+    /// Number of benchmarks that result from this entry
+    fn num_commands(&self) -> usize {
+        self.parameters
+            .values()
+            .map(|values| values.len())
+            .product()
+    }
+
+    /// One command for each combination of the parameter values. As with '--parameter-list',
+    /// the values of the first parameter change the fastest.
+    fn commands(&self) -> Vec<Command<'_>> {
+        let mut combinations: Vec<Vec<(&str, ParameterValue)>> = vec![vec![]];
+        for (name, values) in &self.parameters {
+            combinations = values
+                .iter()
+                .flat_map(|value| {
+                    combinations.iter().map(move |combination| {
+                        let mut combination = combination.clone();
+                        combination.push((name.as_str(), ParameterValue::Text(value.to_string())));
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+            .into_iter()
+            .map(|parameters| {
+                Command::new_parametrized(self.name.as_deref(), &self.command, parameters)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_parse_suite() {
+    let suite = Suite::parse_toml(
+        r#"
+        [[benchmarks]]
+        name = "gzip -{level}"
+        command = "gzip -{level} {file}"
+        parameters = { level = [1, 9], file = ["a.txt", "b.txt"] }
+        runs = 5
+
+        [[benchmarks]]
+        command = "sleep 0.5"
+        prepare = "sync"
+        "#,
+    )
+    .unwrap();
+
+    let commands = suite.commands();
+    let command_lines: Vec<String> = commands.iter().map(|c| c.get_command_line()).collect();
+    assert_eq!(
+        command_lines,
+        [
+            "gzip -1 a.txt",
+            "gzip -1 b.txt",
+            "gzip -9 a.txt",
+            "gzip -9 b.txt",
+            "sleep 0.5"
+        ]
+    );
+    assert_eq!(commands[2].get_name(), "gzip -9");
+    assert_eq!(commands[4].get_name(), "sleep 0.5");
+
+    let json = Suite::parse_json(
+        r#"{"benchmarks": [{"command": "sleep {t}", "parameters": {"t": [0.5, "1"]}}]}"#,
+    )
+    .unwrap();
+    let command_lines: Vec<String> = json
+        .commands()
+        .iter()
+        .map(|c| c.get_command_line())
+        .collect();
+    assert_eq!(command_lines, ["sleep 0.5", "sleep 1"]);
+}
+
+#[test]
+fn test_invalid_suite() {
+    let error = |content: &str| format!("{:#}", Suite::parse_toml(content).unwrap_err());
+
+    assert_eq!(
+        error("benchmarks = []"),
+        "The suite does not contain any benchmarks"
+    );
+    assert!(
+        error("[[benchmarks]]\ncommand = 'a'\n[[benchmarks]]\nname = 'b'")
+            .starts_with("Benchmark #2: missing field `command`")
+    );
+    assert!(error("[[benchmarks]]\ncommand = 'a'\nrun = 3")
+        .starts_with("Benchmark #1: unknown field `run`"));
+    assert_eq!(
+        error("[[benchmarks]]\nname = 'b'\ncommand = 'a {x}'\nparameters = { x = [] }"),
+        "Benchmark #1 ('b'): The list of values of the parameter 'x' is empty"
+    );
+    assert_eq!(
+        error("[[benchmarks]]\ncommand = 'a'\nruns = 0"),
+        "Benchmark #1: The number of 'runs' needs to be at least 1"
+    );
+}
+
+#[test]
+fn test_apply_suite_to_options() {
+    let suite = Suite::parse_toml(
+        r#"
+        [[benchmarks]]
+        command = "sleep {t}"
+        parameters = { t = [1, 2] }
+        prepare = "make"
+        runs = 3
+
+        [[benchmarks]]
+        command = "sleep 3"
+        warmup = 2
+        "#,
+    )
+    .unwrap();
+    let commands = Commands::from(suite.commands());
+
+    let mut options = Options {
+        conclusion_command: Some(vec!["sync".into()]),
+        ..Default::default()
+    };
+    suite.apply_to(&mut options, &commands).unwrap();
+    assert_eq!(
+        options.preparation_command,
+        Some(vec!["make".into(), "make".into(), "".into()])
+    );
+    assert_eq!(options.conclusion_command, Some(vec!["sync".into()]));
+    assert_eq!(options.runs_per_command, Some(vec![Some(3), Some(3), None]));
+    assert_eq!(options.warmup_per_command, Some(vec![None, None, Some(2)]));
+    assert_eq!(Options::command_for(&options.preparation_command, 2), None);
+
+    // The values of the command line are the defaults, also for a separate reference
+    let mut options = Options {
+        preparation_command: Some(vec!["sync".into()]),
+        reference_command: Some("sleep 5".into()),
+        ..Default::default()
+    };
+    suite.apply_to(&mut options, &commands).unwrap();
+    assert_eq!(
+        options.preparation_command,
+        Some(vec![
+            "sync".into(),
+            "make".into(),
+            "make".into(),
+            "sync".into()
+        ])
+    );
+
+    let mut options = Options {
+        preparation_command: Some(vec!["a".into(), "b".into(), "c".into()]),
+        ..Default::default()
+    };
+    assert!(suite.apply_to(&mut options, &commands).is_err());
+}
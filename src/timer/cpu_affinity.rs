@@ -0,0 +1,68 @@
+//! Restrict the benchmarked processes to a set of CPUs (`--cpu`). This is only supported on
+//! Linux and Windows.
+
+use std::io;
+
+/// Whether or not the CPU affinity of the benchmarked processes can be set on this platform
+pub const IS_SUPPORTED: bool = cfg!(any(target_os = "linux", windows));
+
+/// Make sure that the process that is spawned from the given command only runs on the given CPUs
+#[cfg(target_os = "linux")]
+pub fn restrict_command(command: &mut std::process::Command, cpus: &[usize]) -> io::Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: An all-zero `cpu_set_t` is an empty set
+    let mut cpu_set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(out_of_range(cpu));
+        }
+        // SAFETY: The index is within the bounds of the set
+        unsafe { libc::CPU_SET(cpu, &mut cpu_set) };
+    }
+
+    // SAFETY: `sched_setaffinity` is a plain system call, which is safe to use between `fork`
+    // and `exec`
+    unsafe {
+        command.pre_exec(move || {
+            let size = std::mem::size_of::<libc::cpu_set_t>();
+            if libc::sched_setaffinity(0, size, &cpu_set) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    Ok(())
+}
+
+/// Make sure that the given (suspended) process only runs on the given CPUs
+#[cfg(windows)]
+pub fn restrict_process(child: &std::process::Child, cpus: &[usize]) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::Threading::SetProcessAffinityMask;
+
+    let mut mask: usize = 0;
+    for &cpu in cpus {
+        if cpu >= usize::BITS as usize {
+            return Err(out_of_range(cpu));
+        }
+        mask |= 1 << cpu;
+    }
+
+    // SAFETY: The process handle is valid as long as the child exists
+    if unsafe { SetProcessAffinityMask(child.as_raw_handle() as HANDLE, mask) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", windows))]
+fn out_of_range(cpu: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("CPU {cpu} specified via '--cpu' is out of range"),
+    )
+}
@@ -1,3 +1,5 @@
+pub mod cpu_affinity;
+pub mod priority;
 mod wall_clock_timer;
 
 #[cfg(windows)]
@@ -16,11 +18,17 @@ use std::os::unix::io::AsRawFd;
 #[cfg(target_os = "windows")]
 use windows_sys::Win32::System::Threading::CREATE_SUSPENDED;
 
-use crate::util::units::Second;
+use crate::benchmark::timing_result::ResourceUsage;
+use crate::options::Priority;
+use crate::output::command_output::{self, Stream};
+use crate::util::cpu_frequency;
+use crate::util::units::{MegaHertz, Second};
+pub(crate) use wall_clock_timer::unix_timestamp;
 use wall_clock_timer::WallClockTimer;
 
 use std::io::Read;
-use std::process::{ChildStdout, Command, ExitStatus};
+use std::process::{ChildStderr, ChildStdout, Command, ExitStatus};
+use std::time::Duration;
 
 use anyhow::Result;
 
@@ -35,14 +43,33 @@ struct CPUTimes {
 }
 
 /// Used to indicate the result of running a command
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct TimerResult {
     pub time_real: Second,
     pub time_user: Second,
     pub time_system: Second,
 
+    /// Peak resident set size of the process (in bytes)
+    pub memory_usage_byte: u64,
+
+    /// Whether or not the process has been killed because it exceeded the timeout
+    pub timed_out: bool,
+
+    /// Unix timestamp (in seconds) of the start of the process
+    pub start_timestamp: f64,
+
+    /// Context switches and page faults (not available on Windows)
+    pub resource_usage: Option<ResourceUsage>,
+
+    /// Mean frequency of the CPUs right before and after the process (only available on Linux)
+    pub cpu_frequency: Option<[MegaHertz; 2]>,
+
     /// The exit status of the process
     pub status: ExitStatus,
+
+    /// The output of the process on stdout, followed by the one on stderr (only if it has been
+    /// captured)
+    pub output: Option<Vec<u8>>,
 }
 
 /// Discard the output of a child process.
@@ -76,8 +103,79 @@ fn discard(output: ChildStdout) {
     }
 }
 
-/// Execute the given command and return a timing summary
-pub fn execute_and_measure(mut command: Command) -> Result<TimerResult> {
+/// Read the whole output of a child process
+fn capture(mut output: impl Read) -> Vec<u8> {
+    let mut buf = vec![];
+    let _ = output.read_to_end(&mut buf);
+    buf
+}
+
+/// Read the output of a child process, which is only returned if `capture_output` is set.
+fn read_output(
+    stdout: Option<ChildStdout>,
+    stderr: Option<ChildStderr>,
+    capture_output: bool,
+) -> Option<Vec<u8>> {
+    match (stdout, stderr) {
+        // Handle CommandOutputPolicy::Capture and CommandOutputPolicy::Prefixed, the only
+        // policies that pipe stderr. Both streams are read concurrently, such that the command
+        // can not block on a full pipe.
+        (Some(stdout), Some(stderr)) if capture_output => std::thread::scope(|scope| {
+            let stderr = scope.spawn(|| capture(stderr));
+            let mut output = capture(stdout);
+            output.extend(stderr.join().unwrap_or_default());
+            Some(output)
+        }),
+        (Some(stdout), Some(stderr)) => {
+            std::thread::scope(|scope| {
+                scope.spawn(|| command_output::forward(stderr, Stream::Stderr));
+                command_output::forward(stdout, Stream::Stdout);
+            });
+            None
+        }
+        // Handle CommandOutputPolicy::Pipe
+        (Some(output), None) => {
+            discard(output);
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Execute the given command and return a timing summary. If a timeout is given, the process
+/// (and all of its children) will be killed once it exceeds the timeout. If a list of CPUs is
+/// given, the process only runs on these CPUs (on supported platforms). The process runs with
+/// the given scheduling priority. The frequency of the CPUs that it may run on is sampled
+/// before and after the process. If `capture_output` is set, the piped output of the process is
+/// returned instead of being shown on the terminal.
+pub fn execute_and_measure(
+    mut command: Command,
+    timeout: Option<Second>,
+    cpu_affinity: Option<&[usize]>,
+    priority: Priority,
+    capture_output: bool,
+) -> Result<TimerResult> {
+    let timeout = timeout.map(Duration::from_secs_f64);
+
+    #[cfg(unix)]
+    priority::apply_to_command(&mut command, priority);
+
+    #[cfg(target_os = "linux")]
+    if let Some(cpus) = cpu_affinity {
+        cpu_affinity::restrict_command(&mut command, cpus)?;
+    }
+
+    #[cfg(not(windows))]
+    if timeout.is_some() {
+        use std::os::unix::process::CommandExt;
+
+        // Run the command in its own process group, such that we can kill the whole process
+        // tree if the timeout is exceeded.
+        command.process_group(0);
+    }
+
+    let cpu_frequency_before = cpu_frequency::current(cpu_affinity);
+
     #[cfg(not(windows))]
     let cpu_timer = self::unix_timer::CPUTimer::start();
 
@@ -89,29 +187,91 @@ pub fn execute_and_measure(mut command: Command) -> Result<TimerResult> {
         command.creation_flags(CREATE_SUSPENDED);
     }
 
+    let start_timestamp = unix_timestamp();
     let wallclock_timer = WallClockTimer::start();
     let mut child = command.spawn()?;
 
+    #[cfg(windows)]
+    if let Some(cpus) = cpu_affinity {
+        // The process is still suspended, so it has not run on any other CPU yet
+        cpu_affinity::restrict_process(&child, cpus)?;
+    }
+
+    #[cfg(windows)]
+    priority::apply_to_process(&child, priority)?;
+
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let _ = cpu_affinity;
+
+    #[cfg(not(any(unix, windows)))]
+    let _ = priority;
+
     #[cfg(windows)]
     let cpu_timer = {
         // SAFETY: We created a suspended process
         unsafe { self::windows_timer::CPUTimer::start_suspended_process(&child) }
     };
 
-    if let Some(output) = child.stdout.take() {
-        // Handle CommandOutputPolicy::Pipe
-        discard(output);
-    }
+    #[cfg(not(windows))]
+    let watchdog = timeout.map(|timeout| self::unix_timer::Watchdog::start(&child, timeout));
+
+    let (stdout, stderr) = (child.stdout.take(), child.stderr.take());
 
+    #[cfg(not(windows))]
+    let output = read_output(stdout, stderr, capture_output);
+
+    // On Windows, the timeout is enforced while the output is being read, since a command that
+    // exceeds the timeout might keep its pipes open.
+    #[cfg(windows)]
+    let (output, timed_out) = std::thread::scope(|scope| {
+        let waiter = scope.spawn(|| cpu_timer.wait_or_terminate(&child, timeout));
+        let output = read_output(stdout, stderr, capture_output);
+        (output, waiter.join().unwrap_or(false))
+    });
+
+    #[cfg(not(windows))]
+    let (status, memory_usage_byte, resource_usage, timed_out) = {
+        // The watchdog is stopped before the child is reaped, such that it can not kill an
+        // unrelated process group that has reused the ID
+        let timed_out = match watchdog {
+            Some(watchdog) => {
+                self::unix_timer::wait_for_exit(&child)?;
+                watchdog.stop()
+            }
+            None => false,
+        };
+        let (status, rusage) = self::unix_timer::wait_with_resource_usage(&child)?;
+        (
+            status,
+            self::unix_timer::max_rss_bytes(&rusage),
+            Some(self::unix_timer::resource_usage(&rusage)),
+            timed_out,
+        )
+    };
+
+    #[cfg(windows)]
     let status = child.wait()?;
 
     let time_real = wallclock_timer.stop();
     let (time_user, time_system) = cpu_timer.stop();
+    let cpu_frequency = cpu_frequency_before
+        .zip(cpu_frequency::current(cpu_affinity))
+        .map(|(before, after)| [before, after]);
+
+    #[cfg(windows)]
+    let (memory_usage_byte, resource_usage) =
+        (self::windows_timer::get_peak_memory_usage(&child), None);
 
     Ok(TimerResult {
         time_real,
         time_user,
         time_system,
+        memory_usage_byte,
+        timed_out,
+        start_timestamp,
+        resource_usage,
+        cpu_frequency,
         status,
+        output,
     })
 }
@@ -0,0 +1,108 @@
+//! Run the benchmarked processes with a different scheduling priority (`--priority`).
+
+use std::io;
+use std::sync::OnceLock;
+
+use crate::options::Priority;
+
+/// Changes of the nice value (relative to the one of hyperfine itself) for `Priority::High` and
+/// `Priority::Low` on Unix
+#[cfg(unix)]
+const HIGH_PRIORITY_NICE_INCREMENT: libc::c_int = -10;
+#[cfg(unix)]
+const LOW_PRIORITY_NICE_INCREMENT: libc::c_int = 10;
+
+/// The priority that is actually applied to the benchmarked processes. Raising the priority
+/// (above the one of hyperfine itself) usually requires special privileges on Unix. If they are
+/// missing, the processes run at normal priority instead. This is checked once and cached, so
+/// it needs to be called with the same priority every time.
+pub fn effective(requested: Priority) -> Priority {
+    static PERMITTED: OnceLock<bool> = OnceLock::new();
+
+    if requested != Priority::Normal && !*PERMITTED.get_or_init(|| is_permitted(requested)) {
+        Priority::Normal
+    } else {
+        requested
+    }
+}
+
+/// Check whether the priority can be set, by spawning a process that stops right before it
+/// would execute the program
+#[cfg(unix)]
+fn is_permitted(priority: Priority) -> bool {
+    use std::os::unix::process::CommandExt;
+
+    let Ok(executable) = std::env::current_exe() else {
+        return false;
+    };
+    let mut command = std::process::Command::new(executable);
+    apply_to_command(&mut command, priority);
+    // SAFETY: The closure only constructs an error value
+    unsafe {
+        command.pre_exec(|| Err(io::Error::from_raw_os_error(libc::ECANCELED)));
+    }
+
+    // The spawn always fails. It is canceled by the last closure if the priority has been set.
+    command
+        .spawn()
+        .err()
+        .is_some_and(|error| error.raw_os_error() == Some(libc::ECANCELED))
+}
+
+#[cfg(not(unix))]
+fn is_permitted(_priority: Priority) -> bool {
+    true
+}
+
+/// Make sure that the process that is spawned from the given command runs with the given
+/// priority. The nice value is changed relative to the one of hyperfine itself, and
+/// `Priority::Normal` keeps it as it is.
+#[cfg(unix)]
+pub fn apply_to_command(command: &mut std::process::Command, priority: Priority) {
+    use std::os::unix::process::CommandExt;
+
+    let increment = match priority {
+        Priority::High => HIGH_PRIORITY_NICE_INCREMENT,
+        Priority::Normal => return,
+        Priority::Low => LOW_PRIORITY_NICE_INCREMENT,
+    };
+
+    // SAFETY: `getpriority` is a plain system call. It can only fail for invalid arguments, so a
+    // return value of -1 is a valid nice value here.
+    let current = unsafe { libc::getpriority(libc::PRIO_PROCESS as _, 0) };
+    let nice_value = (current + increment).clamp(-20, 19);
+
+    // SAFETY: `setpriority` is a plain system call, which is safe to use between `fork` and
+    // `exec`
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setpriority(libc::PRIO_PROCESS as _, 0, nice_value) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Set the priority class of the given (suspended) process
+#[cfg(windows)]
+pub fn apply_to_process(child: &std::process::Child, priority: Priority) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::Threading::{
+        SetPriorityClass, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS,
+    };
+
+    let priority_class = match priority {
+        Priority::High => HIGH_PRIORITY_CLASS,
+        Priority::Normal => return Ok(()),
+        Priority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+    };
+
+    // SAFETY: The process handle is valid as long as the child exists
+    if unsafe { SetPriorityClass(child.as_raw_handle() as HANDLE, priority_class) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
@@ -1,7 +1,14 @@
 #![cfg(not(windows))]
 
+use std::convert::TryFrom;
+use std::io;
 use std::mem;
+use std::process::{Child, ExitStatus};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 
+use crate::benchmark::timing_result::ResourceUsage;
 use crate::timer::CPUTimes;
 use crate::util::units::Second;
 
@@ -54,6 +61,106 @@ fn get_cpu_times() -> CPUTimes {
     }
 }
 
+/// Wait for the given child process to terminate and return its exit status together with its
+/// resource usage statistics, as reported by `wait4`.
+pub fn wait_with_resource_usage(child: &Child) -> io::Result<(ExitStatus, libc::rusage)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut raw_status = 0;
+    let mut rusage: libc::rusage = unsafe { mem::zeroed() };
+
+    loop {
+        let result =
+            unsafe { libc::wait4(child.id() as libc::pid_t, &mut raw_status, 0, &mut rusage) };
+        if result != -1 {
+            break;
+        }
+
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    Ok((ExitStatus::from_raw(raw_status), rusage))
+}
+
+/// Wait for the given child process to terminate, without reaping it. As long as the child has
+/// not been reaped, its process ID (and the ID of its process group) can not be reused.
+pub fn wait_for_exit(child: &Child) -> io::Result<()> {
+    loop {
+        let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                child.id() as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if result != -1 {
+            return Ok(());
+        }
+
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// A background thread that kills a process group once a timeout has passed
+pub struct Watchdog {
+    sender: mpsc::Sender<()>,
+    handle: thread::JoinHandle<bool>,
+}
+
+impl Watchdog {
+    /// Start watching the process group of the given child. The child needs to be the leader of
+    /// its own process group.
+    pub fn start(child: &Child, timeout: Duration) -> Self {
+        let process_group = child.id() as libc::pid_t;
+        let (sender, receiver) = mpsc::channel();
+
+        let handle = thread::spawn(move || match receiver.recv_timeout(timeout) {
+            Err(RecvTimeoutError::Timeout) => {
+                unsafe { libc::kill(-process_group, libc::SIGKILL) };
+                true
+            }
+            _ => false,
+        });
+
+        Watchdog { sender, handle }
+    }
+
+    /// Stop the watchdog. Returns true if the process group has been killed. This needs to be
+    /// called before the child is reaped, see `wait_for_exit`.
+    pub fn stop(self) -> bool {
+        let _ = self.sender.send(());
+        self.handle.join().unwrap_or(false)
+    }
+}
+
+/// Extract the peak resident set size (in bytes) from the given resource usage statistics
+pub fn max_rss_bytes(rusage: &libc::rusage) -> u64 {
+    // On macOS, `ru_maxrss` is given in bytes. Everywhere else, it is given in kilobytes.
+    let factor = if cfg!(target_os = "macos") { 1 } else { 1024 };
+
+    u64::try_from(rusage.ru_maxrss).unwrap_or(0) * factor
+}
+
+/// Extract the number of context switches and page faults from the given resource usage statistics
+pub fn resource_usage(rusage: &libc::rusage) -> ResourceUsage {
+    let count = |value: libc::c_long| u64::try_from(value).unwrap_or(0);
+
+    ResourceUsage {
+        voluntary_context_switches: count(rusage.ru_nvcsw),
+        involuntary_context_switches: count(rusage.ru_nivcsw),
+        major_page_faults: count(rusage.ru_majflt),
+        minor_page_faults: count(rusage.ru_minflt),
+    }
+}
+
 /// Compute the time intervals in between two `CPUTimes` snapshots
 fn cpu_time_interval(start: &CPUTimes, end: &CPUTimes) -> CPUInterval {
     CPUInterval {
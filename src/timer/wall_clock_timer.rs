@@ -1,4 +1,5 @@
-use std::time::Instant;
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use crate::util::units::Second;
 
@@ -18,3 +19,27 @@ impl WallClockTimer {
         duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
     }
 }
+
+/// The current time as a Unix timestamp (in seconds). The wall clock is only queried once and
+/// later timestamps are derived from the monotonic clock, such that they are not affected by
+/// adjustments of the system time in between runs.
+pub fn unix_timestamp() -> f64 {
+    static ANCHOR: OnceLock<(Instant, f64)> = OnceLock::new();
+    let (anchor_instant, anchor_timestamp) = ANCHOR.get_or_init(|| {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        (Instant::now(), timestamp)
+    });
+    anchor_timestamp + anchor_instant.elapsed().as_secs_f64()
+}
+
+#[test]
+fn test_unix_timestamp_is_monotonic() {
+    let first = unix_timestamp();
+    let second = unix_timestamp();
+
+    // 2020-01-01T00:00:00Z
+    assert!(first > 1_577_836_800.0);
+    assert!(second >= first);
+}
@@ -1,14 +1,19 @@
 #![cfg(windows)]
 #![warn(unsafe_op_in_unsafe_fn)]
 
-use std::{mem, os::windows::io::AsRawHandle, process, ptr};
+use std::convert::TryFrom;
+use std::{mem, os::windows::io::AsRawHandle, process, ptr, time::Duration};
 
 use windows_sys::Win32::{
-    Foundation::{CloseHandle, HANDLE},
+    Foundation::{CloseHandle, HANDLE, WAIT_TIMEOUT},
     System::JobObjects::{
         AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicAccountingInformation,
-        QueryInformationJobObject, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
+        TerminateJobObject, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
     },
+    System::ProcessStatus::{K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+    System::Threading::{WaitForSingleObject, INFINITE},
 };
 
 #[cfg(feature = "windows_process_extensions_main_thread_handle")]
@@ -58,6 +63,24 @@ impl CPUTimer {
         let job_object = unsafe { CreateJobObjectW(ptr::null_mut(), ptr::null_mut()) };
         assert!(job_object != 0, "CreateJobObjectW failed");
 
+        // Kill all processes in the job as soon as the last handle to the job object is closed.
+        // This happens when the `CPUTimer` is dropped, but also when hyperfine itself terminates
+        // (e.g. after Ctrl-C), such that no orphaned child processes are left behind.
+        // SAFETY: An all-zero JOBOBJECT_EXTENDED_LIMIT_INFORMATION is a valid value
+        let mut limit_info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { mem::zeroed() };
+        limit_info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+
+        // SAFETY: The job object handle is valid and the limit information is initialized
+        let ret = unsafe {
+            SetInformationJobObject(
+                job_object,
+                JobObjectExtendedLimitInformation,
+                ptr::addr_of!(limit_info).cast(),
+                mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+        };
+        assert!(ret != 0, "SetInformationJobObject failed");
+
         // SAFETY: The job object handle is valid
         let ret = unsafe { AssignProcessToJobObject(job_object, child_handle) };
         assert!(ret != 0, "AssignProcessToJobObject failed");
@@ -83,6 +106,27 @@ impl CPUTimer {
         Self { job_object }
     }
 
+    /// Wait for the child process to terminate. If it does not terminate within the given timeout
+    /// (if any), all processes in the job are killed. Returns true if the timeout has been exceeded.
+    pub fn wait_or_terminate(&self, child: &process::Child, timeout: Option<Duration>) -> bool {
+        let child_handle = child.as_raw_handle() as HANDLE;
+        let timeout_ms = timeout.map_or(INFINITE, |timeout| {
+            u32::try_from(timeout.as_millis()).unwrap_or(INFINITE - 1)
+        });
+
+        // SAFETY: The process handle is valid as long as `child` is alive
+        let ret = unsafe { WaitForSingleObject(child_handle, timeout_ms) };
+        if ret == WAIT_TIMEOUT {
+            // SAFETY: A valid job object got created in `start_suspended_process`
+            unsafe { TerminateJobObject(self.job_object, 1) };
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Return the user and kernel time of the (terminated) child process of this run. The job
+    /// accounting includes the child itself as well as all processes that it has spawned.
     pub fn stop(&self) -> (Second, Second) {
         let mut job_object_info =
             mem::MaybeUninit::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>::uninit();
@@ -118,8 +162,33 @@ impl CPUTimer {
     }
 }
 
+/// Return the peak working set size (in bytes) of the given (terminated) child process
+pub fn get_peak_memory_usage(child: &process::Child) -> u64 {
+    let child_handle = child.as_raw_handle() as HANDLE;
+    let mut counters = mem::MaybeUninit::<PROCESS_MEMORY_COUNTERS>::uninit();
+
+    // SAFETY: The process handle is valid as long as `child` is alive
+    let res = unsafe {
+        K32GetProcessMemoryInfo(
+            child_handle,
+            counters.as_mut_ptr(),
+            mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+        )
+    };
+
+    if res != 0 {
+        // SAFETY: The memory counters got correctly initialized
+        let counters = unsafe { counters.assume_init() };
+        counters.PeakWorkingSetSize as u64
+    } else {
+        0
+    }
+}
+
 impl Drop for CPUTimer {
     fn drop(&mut self) {
+        // Closing the last handle kills all remaining processes in the job, see
+        // `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE` in `start_suspended_process`.
         // SAFETY: A valid job object got created in `start_suspended_process`
         unsafe { CloseHandle(self.job_object) };
     }
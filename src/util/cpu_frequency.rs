@@ -0,0 +1,135 @@
+//! Sample the current frequency of the CPUs before and after every run, in order to detect
+//! benchmarks whose results are affected by frequency scaling (e.g. turbo states). Only
+//! supported on Linux.
+
+use crate::util::units::MegaHertz;
+
+/// Directory with one `cpu<N>` subdirectory for every CPU
+#[cfg(target_os = "linux")]
+const CPU_DIRECTORY: &str = "/sys/devices/system/cpu";
+
+/// The governor that keeps the CPUs at their highest frequency
+pub const PERFORMANCE_GOVERNOR: &str = "performance";
+
+/// The mean current frequency of the given CPUs (or all CPUs). Returns `None` if the frequency
+/// can not be determined on this platform.
+#[cfg(target_os = "linux")]
+pub fn current(cpus: Option<&[usize]>) -> Option<MegaHertz> {
+    let frequencies: Vec<MegaHertz> = cpufreq_files("scaling_cur_freq", cpus)
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| content.trim().parse::<f64>().ok())
+        .map(|khz| khz / 1000.0)
+        .collect();
+
+    if frequencies.is_empty() {
+        // Without the cpufreq driver (e.g. in virtual machines), the kernel still reports the
+        // frequencies in /proc/cpuinfo
+        parse_cpuinfo(&std::fs::read_to_string("/proc/cpuinfo").ok()?, cpus)
+    } else {
+        Some(frequencies.iter().sum::<f64>() / frequencies.len() as f64)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current(_cpus: Option<&[usize]>) -> Option<MegaHertz> {
+    None
+}
+
+/// The scaling governor of the given CPUs (or all CPUs). If they use different governors, this
+/// is the first one that is not `PERFORMANCE_GOVERNOR`.
+#[cfg(target_os = "linux")]
+pub fn governor(cpus: Option<&[usize]>) -> Option<String> {
+    let governors: Vec<String> = cpufreq_files("scaling_governor", cpus)
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|governor| governor.trim().to_string())
+        .collect();
+    governors
+        .iter()
+        .find(|&governor| governor != PERFORMANCE_GOVERNOR)
+        .or(governors.first())
+        .cloned()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn governor(_cpus: Option<&[usize]>) -> Option<String> {
+    None
+}
+
+/// Paths of the given file in the cpufreq directories of the given CPUs (or all CPUs). The list
+/// of CPUs is only read once, since this is called for every run.
+#[cfg(target_os = "linux")]
+fn cpufreq_files(name: &str, cpus: Option<&[usize]>) -> Vec<std::path::PathBuf> {
+    use std::sync::OnceLock;
+
+    static ALL_CPUS: OnceLock<Vec<usize>> = OnceLock::new();
+
+    let all_cpus = ALL_CPUS.get_or_init(|| {
+        let mut cpus: Vec<usize> = std::fs::read_dir(CPU_DIRECTORY)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("cpu")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        cpus.sort_unstable();
+        cpus
+    });
+
+    all_cpus
+        .iter()
+        .filter(|cpu| cpus.map_or(true, |cpus| cpus.contains(cpu)))
+        .map(|cpu| format!("{CPU_DIRECTORY}/cpu{cpu}/cpufreq/{name}").into())
+        .collect()
+}
+
+/// The mean of the "cpu MHz" entries of the given processors (or all processors) in the
+/// contents of /proc/cpuinfo
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpuinfo(cpuinfo: &str, cpus: Option<&[usize]>) -> Option<MegaHertz> {
+    let mut processor = None;
+    let mut frequencies = vec![];
+
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "processor" => processor = value.trim().parse::<usize>().ok(),
+            "cpu MHz" => {
+                let selected = cpus.map_or(true, |cpus| {
+                    processor.is_some_and(|processor| cpus.contains(&processor))
+                });
+                if let Some(frequency) = value.trim().parse::<f64>().ok().filter(|_| selected) {
+                    frequencies.push(frequency);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (!frequencies.is_empty()).then(|| frequencies.iter().sum::<f64>() / frequencies.len() as f64)
+}
+
+#[test]
+fn test_parse_cpuinfo() {
+    let cpuinfo = "processor\t: 0\n\
+                   model name\t: Some CPU\n\
+                   cpu MHz\t\t: 2000.000\n\
+                   \n\
+                   processor\t: 1\n\
+                   model name\t: Some CPU\n\
+                   cpu MHz\t\t: 3000.500\n";
+
+    assert_eq!(parse_cpuinfo(cpuinfo, None), Some(2500.25));
+    assert_eq!(parse_cpuinfo(cpuinfo, Some(&[1])), Some(3000.5));
+    assert_eq!(parse_cpuinfo(cpuinfo, Some(&[2])), None);
+    assert_eq!(parse_cpuinfo("processor\t: 0\n", None), None);
+}
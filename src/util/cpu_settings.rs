@@ -0,0 +1,139 @@
+//! Detect settings of the CPUs that make benchmark results less stable (frequency scaling
+//! governor, turbo boost, simultaneous multithreading), in order to warn about them before the
+//! benchmarks are started. Only supported on Linux.
+
+use serde::Serialize;
+
+use crate::output::warnings::Warnings;
+use crate::util::cpu_frequency::{self, PERFORMANCE_GOVERNOR};
+
+/// Files with the frequency scaling governor of every CPU
+const GOVERNOR_PATH: &str = "/sys/devices/system/cpu/cpu*/cpufreq/scaling_governor";
+
+/// Contains "1" if turbo boost is disabled (intel_pstate driver)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const NO_TURBO_PATH: &str = "/sys/devices/system/cpu/intel_pstate/no_turbo";
+
+/// Contains "1" if boost is enabled (acpi-cpufreq and amd-pstate drivers)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const BOOST_PATH: &str = "/sys/devices/system/cpu/cpufreq/boost";
+
+/// Contains "1" if simultaneous multithreading is active
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const SMT_ACTIVE_PATH: &str = "/sys/devices/system/cpu/smt/active";
+
+/// Can be used to switch simultaneous multithreading off
+const SMT_CONTROL_PATH: &str = "/sys/devices/system/cpu/smt/control";
+
+/// Settings of the CPUs on which the benchmarks run. Settings that can not be determined (e.g.
+/// on other platforms than Linux) are `None`.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct CpuSettings {
+    /// Frequency scaling governor. If the CPUs use different governors, this is the first one
+    /// that is not "performance".
+    pub governor: Option<String>,
+
+    /// Whether the CPUs may run above their base frequency (turbo boost)
+    pub turbo_boost: Option<bool>,
+
+    /// Whether simultaneous multithreading (hyper-threading) is active
+    pub smt: Option<bool>,
+
+    /// File from which the turbo boost setting has been read
+    #[serde(skip)]
+    turbo_boost_path: &'static str,
+}
+
+impl CpuSettings {
+    /// Warnings about the settings that make the results less stable
+    pub fn warnings(&self) -> Vec<Warnings> {
+        let mut warnings = vec![];
+        if let Some(governor) = self
+            .governor
+            .as_ref()
+            .filter(|&governor| governor != PERFORMANCE_GOVERNOR)
+        {
+            warnings.push(Warnings::CpuGovernorNotPerformance(
+                governor.clone(),
+                GOVERNOR_PATH,
+            ));
+        }
+        if self.turbo_boost == Some(true) {
+            warnings.push(Warnings::TurboBoostEnabled(self.turbo_boost_path));
+        }
+        if self.smt == Some(true) {
+            warnings.push(Warnings::SmtEnabled(SMT_CONTROL_PATH));
+        }
+        warnings
+    }
+}
+
+/// The current settings of the given CPUs (or all CPUs)
+#[cfg(target_os = "linux")]
+pub fn current(cpus: Option<&[usize]>) -> CpuSettings {
+    let read_flag = |path| {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| parse_flag(&content))
+    };
+
+    let (turbo_boost, turbo_boost_path) = match read_flag(NO_TURBO_PATH) {
+        Some(no_turbo) => (Some(!no_turbo), NO_TURBO_PATH),
+        None => (read_flag(BOOST_PATH), BOOST_PATH),
+    };
+
+    CpuSettings {
+        governor: cpu_frequency::governor(cpus),
+        turbo_boost,
+        smt: read_flag(SMT_ACTIVE_PATH),
+        turbo_boost_path,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn current(_cpus: Option<&[usize]>) -> CpuSettings {
+    CpuSettings::default()
+}
+
+/// Parse the contents of a sysfs file that contains "0" or "1"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_flag(content: &str) -> Option<bool> {
+    match content.trim() {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_parse_flag() {
+    assert_eq!(parse_flag("1\n"), Some(true));
+    assert_eq!(parse_flag("0"), Some(false));
+    assert_eq!(parse_flag("notsupported\n"), None);
+}
+
+#[test]
+fn test_cpu_settings_warnings() {
+    assert!(CpuSettings::default().warnings().is_empty());
+
+    let settings = CpuSettings {
+        governor: Some(PERFORMANCE_GOVERNOR.to_string()),
+        turbo_boost: Some(false),
+        smt: Some(false),
+        turbo_boost_path: NO_TURBO_PATH,
+    };
+    assert!(settings.warnings().is_empty());
+
+    let settings = CpuSettings {
+        governor: Some("powersave".to_string()),
+        turbo_boost: Some(true),
+        smt: Some(true),
+        turbo_boost_path: NO_TURBO_PATH,
+    };
+    let warnings: Vec<String> = settings.warnings().iter().map(|w| w.to_string()).collect();
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("'powersave'"));
+    assert!(warnings[0].contains(GOVERNOR_PATH));
+    assert!(warnings[1].contains(NO_TURBO_PATH));
+    assert!(warnings[2].contains(SMT_CONTROL_PATH));
+}
@@ -0,0 +1,118 @@
+//! Drop the file system caches of the operating system before every timing run
+//! (`--drop-file-caches`), in order to benchmark commands with cold caches. This is only
+//! supported on Linux and macOS, and it requires root privileges, which are acquired via `sudo`
+//! once before the benchmarks are started.
+
+use anyhow::{bail, Result};
+
+/// Whether or not the file caches can be dropped on this platform
+pub const IS_SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "macos"));
+
+/// Writing "3" to this file drops the page cache as well as the dentry and inode caches
+#[cfg(target_os = "linux")]
+const DROP_CACHES_FILE: &str = "/proc/sys/vm/drop_caches";
+
+/// Drop the file system caches. At the first call, this makes sure that the necessary
+/// privileges are available, which might prompt for a password. Subsequent calls never prompt.
+pub fn drop() -> Result<()> {
+    use std::sync::OnceLock;
+
+    static PRIVILEGES: OnceLock<Result<(), String>> = OnceLock::new();
+
+    if !IS_SUPPORTED {
+        bail!("Dropping the file caches ('--drop-file-caches') is not supported on this platform.");
+    }
+    if let Err(error) = PRIVILEGES.get_or_init(|| acquire_privileges().map_err(|e| e.to_string())) {
+        bail!("{error}");
+    }
+
+    drop_caches()
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn is_root() -> bool {
+    // SAFETY: `geteuid` is always successful
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Ask for the password of the user (if necessary), such that `sudo` can be used
+/// non-interactively afterwards
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn acquire_privileges() -> Result<()> {
+    if is_root() {
+        return Ok(());
+    }
+
+    let validated = std::process::Command::new("sudo")
+        .arg("-v")
+        .status()
+        .is_ok_and(|status| status.success());
+    if !validated {
+        bail!(
+            "Dropping the file caches ('--drop-file-caches') requires root privileges. Run \
+             hyperfine as root, or make sure that 'sudo' can be used."
+        );
+    }
+    Ok(())
+}
+
+/// Run the given program as root (via `sudo`, if necessary), without prompting for a password
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn run_as_root(program: &str, args: &[&str], input: Option<&str>) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut command = if is_root() {
+        Command::new(program)
+    } else {
+        let mut command = Command::new("sudo");
+        command.arg("-n").arg(program);
+        command
+    };
+    command
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn()?;
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "Failed to drop the file caches ('--drop-file-caches'): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn drop_caches() -> Result<()> {
+    // Dirty pages can not be dropped, so they are written back first
+    // SAFETY: `sync` is always successful
+    unsafe { libc::sync() };
+
+    run_as_root("tee", &[DROP_CACHES_FILE], Some("3"))
+}
+
+#[cfg(target_os = "macos")]
+fn drop_caches() -> Result<()> {
+    run_as_root("purge", &[], None)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn acquire_privileges() -> Result<()> {
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn drop_caches() -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Handling of Ctrl-C. The first interrupt only sets a flag, such that the current benchmark can
+//! be stopped gracefully (including its cleanup command). A second interrupt terminates hyperfine
+//! immediately.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit code of a process that has been terminated by SIGINT
+const EXIT_CODE_INTERRUPTED: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether or not hyperfine has been interrupted with Ctrl-C
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Set the interrupted flag. Returns true if it has already been set before.
+fn interrupt() -> bool {
+    INTERRUPTED.swap(true, Ordering::SeqCst)
+}
+
+#[cfg(not(windows))]
+extern "C" fn handle_sigint(_signal: libc::c_int) {
+    if interrupt() {
+        // SAFETY: `_exit` is async-signal-safe
+        unsafe { libc::_exit(EXIT_CODE_INTERRUPTED) };
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn handle_ctrl_event(
+    ctrl_type: u32,
+) -> windows_sys::Win32::Foundation::BOOL {
+    use windows_sys::Win32::System::Console::{CTRL_BREAK_EVENT, CTRL_C_EVENT};
+
+    if ctrl_type != CTRL_C_EVENT && ctrl_type != CTRL_BREAK_EVENT {
+        return 0;
+    }
+    if interrupt() {
+        std::process::exit(EXIT_CODE_INTERRUPTED);
+    }
+    1
+}
+
+/// Install the Ctrl-C handler for the current process
+pub fn install_handler() {
+    #[cfg(not(windows))]
+    {
+        let handler: extern "C" fn(libc::c_int) = handle_sigint;
+
+        // SAFETY: The handler only accesses an atomic and calls async-signal-safe functions
+        unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) };
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+
+        // SAFETY: The handler only accesses an atomic or exits the process
+        unsafe { SetConsoleCtrlHandler(Some(handle_ctrl_event), 1) };
+    }
+}
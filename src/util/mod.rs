@@ -1,5 +1,11 @@
+pub mod cpu_frequency;
+pub mod cpu_settings;
 pub mod exit_code;
+pub mod file_caches;
+pub mod interrupt;
 pub mod min_max;
 pub mod number;
 pub mod randomized_environment_offset;
+pub mod system_load;
+pub mod time_extraction;
 pub mod units;
@@ -1,5 +1,7 @@
 use std::convert::TryFrom;
 use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+use std::str::FromStr;
 
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
@@ -10,6 +12,7 @@ use serde::Serialize;
 pub enum Number {
     Int(i32),
     Decimal(Decimal),
+    Suffixed(SuffixedInteger),
 }
 
 impl fmt::Display for Number {
@@ -17,6 +20,7 @@ impl fmt::Display for Number {
         match *self {
             Number::Int(i) => fmt::Display::fmt(&i, f),
             Number::Decimal(i) => fmt::Display::fmt(&i, f),
+            Number::Suffixed(i) => fmt::Display::fmt(&i, f),
         }
     }
 }
@@ -33,6 +37,22 @@ impl From<Decimal> for Number {
     }
 }
 
+impl From<SuffixedInteger> for Number {
+    fn from(x: SuffixedInteger) -> Number {
+        Number::Suffixed(x)
+    }
+}
+
+impl Number {
+    /// Write out the full value of numbers with a suffix like '64K' (as '65536')
+    pub fn expand_suffix(self) -> Number {
+        match self {
+            Number::Suffixed(SuffixedInteger(i)) => Number::Decimal(Decimal::from(i)),
+            number => number,
+        }
+    }
+}
+
 impl TryFrom<Number> for usize {
     type Error = ();
 
@@ -43,6 +63,144 @@ impl TryFrom<Number> for usize {
                 Some(u) => usize::try_from(u).map_err(|_| ()),
                 None => Err(()),
             },
+            Number::Suffixed(SuffixedInteger(i)) => usize::try_from(i).map_err(|_| ()),
+        }
+    }
+}
+
+/// Binary multiples that can be used as a suffix for integers, e.g. '64K' for 65536
+const SUFFIXES: [(char, i64); 3] = [('G', 1 << 30), ('M', 1 << 20), ('K', 1 << 10)];
+
+/// An integer that can be written with a 'K', 'M' or 'G' suffix (binary multiples). When
+/// displayed, the largest suffix that represents the value exactly is used.
+#[derive(Debug, Clone, Serialize, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SuffixedInteger(pub i64);
+
+impl SuffixedInteger {
+    /// Whether or not the given string is an integer with one of the supported suffixes
+    pub fn has_suffix(s: &str) -> bool {
+        s.trim_end().ends_with(|c: char| {
+            SUFFIXES
+                .iter()
+                .any(|(suffix, _)| c.eq_ignore_ascii_case(suffix))
+        })
+    }
+}
+
+impl FromStr for SuffixedInteger {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let multiplier = s.chars().last().and_then(|last| {
+            SUFFIXES
+                .iter()
+                .find(|(suffix, _)| last.eq_ignore_ascii_case(suffix))
+                .map(|(_, multiplier)| *multiplier)
+        });
+
+        match multiplier {
+            Some(multiplier) => {
+                let value = s[..s.len() - 1].parse::<i64>()?;
+                // Parse the full value, such that numbers which are too large result in the
+                // usual error
+                (i128::from(value) * i128::from(multiplier))
+                    .to_string()
+                    .parse()
+                    .map(SuffixedInteger)
+            }
+            None => s.parse::<i64>().map(SuffixedInteger),
         }
     }
 }
+
+impl fmt::Display for SuffixedInteger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SuffixedInteger(value) = *self;
+        match SUFFIXES
+            .iter()
+            .find(|(_, multiplier)| value != 0 && value % multiplier == 0)
+        {
+            Some((suffix, multiplier)) => write!(f, "{}{suffix}", value / multiplier),
+            None => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<i32> for SuffixedInteger {
+    fn from(x: i32) -> SuffixedInteger {
+        SuffixedInteger(x.into())
+    }
+}
+
+impl Add for SuffixedInteger {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        SuffixedInteger(self.0 + other.0)
+    }
+}
+
+impl AddAssign for SuffixedInteger {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for SuffixedInteger {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        SuffixedInteger(self.0 - other.0)
+    }
+}
+
+impl Mul for SuffixedInteger {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        SuffixedInteger(self.0 * other.0)
+    }
+}
+
+impl Div for SuffixedInteger {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        SuffixedInteger(self.0 / other.0)
+    }
+}
+
+#[test]
+fn test_parse_suffixed_integer() {
+    assert_eq!(SuffixedInteger(42), "42".parse().unwrap());
+    assert_eq!(SuffixedInteger(1024), "1K".parse().unwrap());
+    assert_eq!(SuffixedInteger(65536), "64k".parse().unwrap());
+    assert_eq!(SuffixedInteger(3 << 20), "3M".parse().unwrap());
+    assert_eq!(SuffixedInteger(1 << 30), "1G".parse().unwrap());
+
+    assert!("K".parse::<SuffixedInteger>().is_err());
+    assert!("1.5K".parse::<SuffixedInteger>().is_err());
+    assert!("1T".parse::<SuffixedInteger>().is_err());
+    assert!("10000000000G".parse::<SuffixedInteger>().is_err());
+
+    assert!(SuffixedInteger::has_suffix("64K"));
+    assert!(!SuffixedInteger::has_suffix("64"));
+}
+
+#[test]
+fn test_format_suffixed_integer() {
+    assert_eq!("0", SuffixedInteger(0).to_string());
+    assert_eq!("1000", SuffixedInteger(1000).to_string());
+    assert_eq!("64K", SuffixedInteger(65536).to_string());
+    assert_eq!("1536K", SuffixedInteger(1536 << 10).to_string());
+    assert_eq!("2M", SuffixedInteger(2 << 20).to_string());
+    assert_eq!("4G", SuffixedInteger(4 << 30).to_string());
+
+    assert_eq!(
+        "65536",
+        Number::Suffixed(SuffixedInteger(65536))
+            .expand_suffix()
+            .to_string()
+    );
+}
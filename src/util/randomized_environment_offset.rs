@@ -1,8 +1,80 @@
-/// Returns a string with a random length. This value will be set as an environment
+use std::cell::RefCell;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::options::Randomization;
+
+/// Maximum length of the value
+const MAX_LENGTH: usize = 4096;
+
+/// Generates strings with a random length. These values will be set as an environment
 /// variable to account for offset effects. See [1] for more details.
 ///
 /// [1] Mytkowicz, 2009. Producing Wrong Data Without Doing Anything Obviously Wrong!.
 ///     Sigplan Notices - SIGPLAN. 44. 265-276. 10.1145/1508284.1508275.
-pub fn value() -> String {
-    "X".repeat(rand::random::<usize>() % 4096usize)
+pub struct RandomizedEnvironmentOffset {
+    randomization: Randomization,
+    /// ChaCha8 is used instead of `StdRng`, whose algorithm may change between versions of
+    /// `rand`, such that the values for a given seed stay the same
+    generator: RefCell<ChaCha8Rng>,
+}
+
+impl RandomizedEnvironmentOffset {
+    pub fn new(randomization: Randomization) -> Self {
+        let generator = match randomization {
+            Randomization::Seed(seed) => ChaCha8Rng::seed_from_u64(seed),
+            _ => ChaCha8Rng::from_entropy(),
+        };
+        RandomizedEnvironmentOffset {
+            randomization,
+            generator: RefCell::new(generator),
+        }
+    }
+
+    /// The value for the next command invocation, or `None` if the variable is not set
+    /// (`--randomization=off`). With a seed, the sequence of values is the same for every
+    /// invocation of hyperfine.
+    pub fn next_value(&self) -> Option<String> {
+        if self.randomization == Randomization::Off {
+            return None;
+        }
+        let length = self.generator.borrow_mut().gen_range(0..MAX_LENGTH);
+        Some("X".repeat(length))
+    }
+}
+
+#[test]
+fn test_randomized_environment_offset() {
+    assert_eq!(
+        RandomizedEnvironmentOffset::new(Randomization::Off).next_value(),
+        None
+    );
+
+    let values = |randomization| {
+        let offset = RandomizedEnvironmentOffset::new(randomization);
+        (0..10)
+            .map(|_| offset.next_value().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        values(Randomization::Seed(42)),
+        values(Randomization::Seed(42))
+    );
+    assert_ne!(
+        values(Randomization::Seed(42)),
+        values(Randomization::Seed(43))
+    );
+
+    // The values for a seed do not depend on the version of `rand`
+    assert_eq!(
+        values(Randomization::Seed(42))
+            .iter()
+            .map(|value| value.len())
+            .collect::<Vec<_>>(),
+        [2793, 3892, 1751, 1182, 614, 3159, 977, 2076, 2985, 633]
+    );
+    assert!(values(Randomization::On)
+        .iter()
+        .all(|value| value.len() < MAX_LENGTH && value.chars().all(|c| c == 'X')));
 }
@@ -0,0 +1,101 @@
+//! Measure the load of the system before the benchmarks are started, in order to warn about
+//! results that might be noisy.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+/// Show a warning if the load exceeds this fraction of the number of CPU cores
+const MAX_LOAD_PER_CORE: f64 = 0.5;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct SystemLoad {
+    /// The 1-minute load average on Unix. On Windows, this is the fraction of CPU time that has
+    /// been spent on other processes (during a short sample), multiplied by the number of cores.
+    pub load: f64,
+
+    /// Number of CPU cores that are available to hyperfine
+    pub cores: usize,
+}
+
+impl SystemLoad {
+    /// Whether the load is high enough that it might affect the results
+    pub fn is_high(&self) -> bool {
+        self.load > MAX_LOAD_PER_CORE * self.cores as f64
+    }
+}
+
+/// The load of the system, as it has been measured at the first call of this function. Returns
+/// `None` if the load can not be determined on this platform.
+pub fn current() -> Option<SystemLoad> {
+    static SYSTEM_LOAD: OnceLock<Option<SystemLoad>> = OnceLock::new();
+
+    *SYSTEM_LOAD.get_or_init(|| {
+        Some(SystemLoad {
+            load: measure_load()?,
+            cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        })
+    })
+}
+
+#[cfg(unix)]
+fn measure_load() -> Option<f64> {
+    let mut load = [0.0f64; 1];
+    // SAFETY: `getloadavg` writes at most one value into the array
+    if unsafe { libc::getloadavg(load.as_mut_ptr(), 1) } != 1 {
+        return None;
+    }
+    Some(load[0])
+}
+
+#[cfg(windows)]
+fn measure_load() -> Option<f64> {
+    use std::time::Duration;
+
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::GetSystemTimes;
+
+    // Idle, kernel (including idle) and user time of all cores, in units of 100 ns
+    let system_times = || -> Option<(u64, u64, u64)> {
+        let mut times = [FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        }; 3];
+        let [idle, kernel, user] = &mut times;
+        // SAFETY: All pointers point to valid `FILETIME` structs
+        if unsafe { GetSystemTimes(idle, kernel, user) } == 0 {
+            return None;
+        }
+        let [idle, kernel, user] =
+            times.map(|t| (u64::from(t.dwHighDateTime) << 32) | u64::from(t.dwLowDateTime));
+        Some((idle, kernel, user))
+    };
+
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    let (idle_start, kernel_start, user_start) = system_times()?;
+    std::thread::sleep(Duration::from_millis(100));
+    let (idle_end, kernel_end, user_end) = system_times()?;
+
+    let total = (kernel_end - kernel_start) + (user_end - user_start);
+    if total == 0 {
+        return None;
+    }
+    let busy = 1.0 - (idle_end - idle_start) as f64 / total as f64;
+    Some(busy.clamp(0.0, 1.0) * cores as f64)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn measure_load() -> Option<f64> {
+    None
+}
+
+#[test]
+fn test_is_high() {
+    let load = |load, cores| SystemLoad { load, cores };
+
+    assert!(!load(0.3, 1).is_high());
+    assert!(load(0.8, 1).is_high());
+    assert!(!load(3.5, 8).is_high());
+    assert!(load(8.3, 8).is_high());
+}
@@ -0,0 +1,62 @@
+//! Extract the time that a command reports itself (like "compiled in 1.234s") from its output
+//! (`--extract-time`). Such times usually exclude the startup of the process.
+
+use regex::Regex;
+
+use crate::error::OptionsError;
+use crate::util::units::{parse_duration, Second};
+
+/// Name of the optional capture group with the unit of the reported time
+const UNIT_GROUP: &str = "unit";
+
+pub struct TimeExtraction {
+    regex: Regex,
+}
+
+impl TimeExtraction {
+    /// The first capture group of the pattern contains the number. An optional capture group
+    /// named `unit` contains its unit (like `ms`), otherwise the number is a number of seconds.
+    pub fn new(pattern: &str) -> Result<Self, OptionsError<'static>> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| OptionsError::InvalidTimeExtraction(pattern.to_string(), e.to_string()))?;
+        if regex.captures_len() < 2 {
+            return Err(OptionsError::InvalidTimeExtraction(
+                pattern.to_string(),
+                "the pattern needs a capture group for the number".to_string(),
+            ));
+        }
+        Ok(TimeExtraction { regex })
+    }
+
+    /// The time that is reported in the given output, or `None` if the pattern does not match
+    /// or the number (and unit) can not be parsed
+    pub fn extract(&self, output: &str) -> Option<Second> {
+        let captures = self.regex.captures(output)?;
+        let value = captures.get(1)?.as_str();
+        let unit = captures.name(UNIT_GROUP).map_or("", |unit| unit.as_str());
+        parse_duration(&format!("{value}{unit}"))
+    }
+}
+
+#[test]
+fn test_time_extraction() {
+    let extraction = TimeExtraction::new(r"compiled in ([0-9.]+)s").unwrap();
+    assert_eq!(
+        extraction.extract("Compiling...\ncompiled in 1.25s\n"),
+        Some(1.25)
+    );
+    assert_eq!(extraction.extract("failed\n"), None);
+
+    let extraction = TimeExtraction::new(r"took ([0-9.]+) ?(?P<unit>ms|s)").unwrap();
+    assert_eq!(extraction.extract("took 250 ms"), Some(0.25));
+    assert_eq!(extraction.extract("took 2s"), Some(2.0));
+
+    assert!(matches!(
+        TimeExtraction::new(r"took [0-9.]+"),
+        Err(OptionsError::InvalidTimeExtraction(..))
+    ));
+    assert!(matches!(
+        TimeExtraction::new(r"took ([0-9.]+"),
+        Err(OptionsError::InvalidTimeExtraction(..))
+    ));
+}
@@ -1,43 +1,170 @@
 //! This module contains common units.
 
+use serde::{Deserialize, Serialize};
+
 pub type Scalar = f64;
 
 /// Type alias for unit of time
 pub type Second = Scalar;
 
+/// Type alias for unit of (CPU) frequency
+pub type MegaHertz = Scalar;
+
 /// Supported time units
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Unit {
+    Minute,
     Second,
     MilliSecond,
     MicroSecond,
+    NanoSecond,
 }
 
 impl Unit {
+    /// The unit in which the given duration is shown if no unit has been specified.
+    /// Nanoseconds are never chosen automatically, as the measurements are not that precise.
+    pub fn for_duration(duration: Second) -> Unit {
+        if duration < 0.001 {
+            Unit::MicroSecond
+        } else if duration < 1.0 {
+            Unit::MilliSecond
+        } else if duration < 60.0 {
+            Unit::Second
+        } else {
+            Unit::Minute
+        }
+    }
+
     /// The abbreviation of the Unit.
     pub fn short_name(self) -> String {
         match self {
+            Unit::Minute => String::from("min"),
             Unit::Second => String::from("s"),
             Unit::MilliSecond => String::from("ms"),
             Unit::MicroSecond => String::from("µs"),
+            Unit::NanoSecond => String::from("ns"),
+        }
+    }
+
+    /// The number of seconds in one Unit.
+    pub fn factor(self) -> Scalar {
+        match self {
+            Unit::Minute => 60.0,
+            Unit::Second => 1.0,
+            Unit::MilliSecond => 1e-3,
+            Unit::MicroSecond => 1e-6,
+            Unit::NanoSecond => 1e-9,
         }
     }
 
     /// Returns the Second value formatted for the Unit.
     pub fn format(self, value: Second) -> String {
+        let value = value / self.factor();
         match self {
-            Unit::Second => format!("{value:.3}"),
-            Unit::MilliSecond => format!("{:.1}", value * 1e3),
-            Unit::MicroSecond => format!("{:.1}", value * 1e6),
+            Unit::Minute | Unit::Second => format!("{value:.3}"),
+            Unit::MilliSecond | Unit::MicroSecond => format!("{value:.1}"),
+            Unit::NanoSecond => format!("{value:.0}"),
         }
     }
 }
 
+/// Parse a duration like `30s`, `500ms` or `2min` into seconds. A plain number without a unit
+/// suffix is interpreted as a number of seconds. Returns `None` if the input can not be parsed or
+/// if the duration is negative.
+pub fn parse_duration(input: &str) -> Option<Second> {
+    let input = input.trim();
+    let split_at = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (value, suffix) = input.split_at(split_at);
+
+    let factor = match suffix.trim() {
+        "" | "s" | "sec" => 1.0,
+        "ms" => 1e-3,
+        "us" | "µs" => 1e-6,
+        "ns" => 1e-9,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        _ => return None,
+    };
+
+    let value = value.parse::<f64>().ok().filter(|v| v.is_finite())?;
+    Some(value * factor)
+}
+
+/// What a size for the throughput computation (`--throughput`) is counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnit {
+    Bytes,
+    Items,
+}
+
+/// The amount of data (or number of items) that is processed by a single run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub value: Scalar,
+    pub unit: SizeUnit,
+
+    /// Whether the size has been given with a binary prefix like `MiB`, such that throughputs
+    /// are shown with binary prefixes as well
+    pub binary: bool,
+}
+
+/// Parse a size like `500MB`, `1.2GiB` or `4096B` into a number of bytes. A plain number (with
+/// an optional decimal prefix like `10k`) is interpreted as a number of items. Returns `None` if
+/// the input can not be parsed or if the size is not positive.
+pub fn parse_size(input: &str) -> Option<Size> {
+    let input = input.trim();
+    let split_at = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (value, suffix) = input.split_at(split_at);
+
+    let suffix = suffix.trim();
+    let (prefix, unit) = match suffix.strip_suffix('B') {
+        Some(prefix) => (prefix, SizeUnit::Bytes),
+        None => (suffix, SizeUnit::Items),
+    };
+    let (factor, binary) = match prefix {
+        "" => (1.0, false),
+        "k" | "K" => (1e3, false),
+        "M" => (1e6, false),
+        "G" => (1e9, false),
+        "T" => (1e12, false),
+        "Ki" if unit == SizeUnit::Bytes => (1024.0, true),
+        "Mi" if unit == SizeUnit::Bytes => (1024.0 * 1024.0, true),
+        "Gi" if unit == SizeUnit::Bytes => (1024.0 * 1024.0 * 1024.0, true),
+        "Ti" if unit == SizeUnit::Bytes => (1024.0 * 1024.0 * 1024.0 * 1024.0, true),
+        _ => return None,
+    };
+
+    let value = value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v > 0.0)?;
+    Some(Size {
+        value: value * factor,
+        unit,
+        binary,
+    })
+}
+
+#[test]
+fn test_unit_for_duration() {
+    assert_eq!(Unit::for_duration(2e-6), Unit::MicroSecond);
+    assert_eq!(Unit::for_duration(2e-3), Unit::MilliSecond);
+    assert_eq!(Unit::for_duration(2.0), Unit::Second);
+    assert_eq!(Unit::for_duration(90.0), Unit::Minute);
+}
+
 #[test]
 fn test_unit_short_name() {
     assert_eq!("s", Unit::Second.short_name());
     assert_eq!("ms", Unit::MilliSecond.short_name());
     assert_eq!("µs", Unit::MicroSecond.short_name());
+    assert_eq!("ns", Unit::NanoSecond.short_name());
+    assert_eq!("min", Unit::Minute.short_name());
 }
 
 // Note - the values are rounded when formatted.
@@ -48,4 +175,52 @@ fn test_unit_format() {
     assert_eq!("123456.8", Unit::MilliSecond.format(value));
 
     assert_eq!("1234.6", Unit::MicroSecond.format(0.00123456));
+    assert_eq!("1235", Unit::NanoSecond.format(0.00000123456));
+    assert_eq!("2.058", Unit::Minute.format(value));
+}
+
+#[test]
+fn test_parse_duration() {
+    use approx::assert_relative_eq;
+
+    assert_relative_eq!(30.0, parse_duration("30").unwrap());
+    assert_relative_eq!(30.0, parse_duration("30s").unwrap());
+    assert_relative_eq!(1.5, parse_duration("1.5 s").unwrap());
+    assert_relative_eq!(0.25, parse_duration("250ms").unwrap());
+    assert_relative_eq!(20e-6, parse_duration("20µs").unwrap());
+    assert_relative_eq!(120.0, parse_duration("2min").unwrap());
+    assert_relative_eq!(7200.0, parse_duration("2h").unwrap());
+
+    assert_eq!(None, parse_duration(""));
+    assert_eq!(None, parse_duration("s"));
+    assert_eq!(None, parse_duration("-1s"));
+    assert_eq!(None, parse_duration("10 parsecs"));
+}
+
+#[test]
+fn test_parse_size() {
+    let size = |value, unit, binary| {
+        Some(Size {
+            value,
+            unit,
+            binary,
+        })
+    };
+
+    assert_eq!(size(500e6, SizeUnit::Bytes, false), parse_size("500MB"));
+    assert_eq!(size(4096.0, SizeUnit::Bytes, false), parse_size("4096 B"));
+    assert_eq!(size(2048.0, SizeUnit::Bytes, true), parse_size("2KiB"));
+    assert_eq!(
+        size(1.5 * 1024.0 * 1024.0 * 1024.0, SizeUnit::Bytes, true),
+        parse_size("1.5GiB")
+    );
+    assert_eq!(size(1000.0, SizeUnit::Items, false), parse_size("1000"));
+    assert_eq!(size(10e3, SizeUnit::Items, false), parse_size("10k"));
+
+    assert_eq!(None, parse_size(""));
+    assert_eq!(None, parse_size("MB"));
+    assert_eq!(None, parse_size("0"));
+    assert_eq!(None, parse_size("-1MB"));
+    assert_eq!(None, parse_size("2Ki"));
+    assert_eq!(None, parse_size("3 parsecs"));
 }
@@ -416,6 +416,20 @@ fn reference_is_executed_separately_from_commands() {
         .run();
 }
 
+#[test]
+fn reference_from_command_list_is_only_executed_once() {
+    ExecutionOrderTest::new()
+        .arg("--runs=1")
+        .arg("--reference=second")
+        .arg("--command-name=first")
+        .arg("--command-name=second")
+        .command("command 1")
+        .command("command 2")
+        .expect_output("command 1")
+        .expect_output("command 2")
+        .run();
+}
+
 #[test]
 fn setup_prepare_reference_conclude_cleanup_combined() {
     ExecutionOrderTest::new()
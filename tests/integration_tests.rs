@@ -1,5 +1,5 @@
 mod common;
-use common::hyperfine;
+use common::{hyperfine, hyperfine_raw_command};
 
 use predicates::prelude::*;
 
@@ -50,6 +50,110 @@ fn fails_with_wrong_number_of_command_name_arguments() {
         .stderr(predicate::str::contains("Too many --command-name options"));
 }
 
+#[test]
+fn reads_commands_from_file() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let command_file = tempdir.path().join("commands.txt");
+    std::fs::write(&command_file, "# comment\nsleep 0.1\n\nsleep 0.2\n").unwrap();
+    let names_file = tempdir.path().join("names.txt");
+    std::fs::write(&names_file, "first\nsecond\nthird\n").unwrap();
+
+    hyperfine_debug()
+        .arg("sleep 0.3")
+        .arg("--command-file")
+        .arg(&command_file)
+        .arg("--command-names-file")
+        .arg(&names_file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: first")
+                .and(predicate::str::contains("Benchmark 2: second"))
+                .and(predicate::str::contains("Benchmark 3: third"))
+                .and(predicate::str::contains("comment").not()),
+        );
+
+    hyperfine_debug()
+        .arg("--command-file=-")
+        .arg("--parameter-list")
+        .arg("foo")
+        .arg("1,2")
+        .write_stdin("sleep 0.{foo}\n")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 0.1")
+                .and(predicate::str::contains("Benchmark 2: sleep 0.2")),
+        );
+}
+
+#[test]
+fn reads_benchmarks_from_a_suite_file() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let suite = tempdir.path().join("suite.toml");
+    std::fs::write(
+        &suite,
+        r#"
+        [[benchmarks]]
+        name = "first"
+        command = "echo a"
+        prepare = "echo 7f3a2e"
+        runs = 2
+
+        [[benchmarks]]
+        command = "echo {x}"
+        parameters = { x = ["b", "c"] }
+        "#,
+    )
+    .unwrap();
+
+    let output = hyperfine()
+        .arg("--runs=1")
+        .arg("--suite")
+        .arg(&suite)
+        .arg("--show-output=raw")
+        .arg("--export-json=-")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("7f3a2e\na\n7f3a2e\na\n"))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let results = json["results"].as_array().unwrap();
+    let names: Vec<&str> = results
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["first", "echo b", "echo c"]);
+    let runs: Vec<usize> = results
+        .iter()
+        .map(|r| r["times"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(runs, [2, 1, 1]);
+
+    let invalid_suite = tempdir.path().join("invalid.json");
+    std::fs::write(
+        &invalid_suite,
+        r#"{"benchmarks": [{"command": "echo a"}, {"name": "b"}]}"#,
+    )
+    .unwrap();
+    hyperfine()
+        .arg("--suite")
+        .arg(&invalid_suite)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Benchmark #2: missing field `command`",
+        ));
+}
+
 #[test]
 fn fails_with_wrong_number_of_prepare_options() {
     hyperfine()
@@ -99,6 +203,81 @@ fn fails_with_wrong_number_of_prepare_options() {
         ));
 }
 
+#[test]
+fn counts_expanded_parameter_combinations_for_per_benchmark_options() {
+    hyperfine_debug()
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("3")
+        .arg("--prepare=sleep 0.1")
+        .arg("--prepare=sleep 0.2")
+        .arg("sleep 0.{n}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The '--prepare' option has to be provided just once or once for each benchmark, \
+             but it has been provided with 2 preparation commands for 3 benchmarks",
+        ));
+
+    hyperfine_debug()
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("2")
+        .arg("--reference=sleep 0.5")
+        .arg("--cleanup=sleep 0.1")
+        .arg("--cleanup=sleep 0.2")
+        .arg("sleep 0.{n}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "provided with 2 cleanup commands for 3 benchmarks",
+        ));
+}
+
+#[test]
+#[cfg(unix)]
+fn runs_the_per_benchmark_commands_of_each_expanded_benchmark() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let log_path = tempdir.path().join("commands.log");
+    let log = log_path.to_str().unwrap();
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--parameter-list")
+        .arg("n")
+        .arg("a,b")
+        .arg(format!("--setup=echo setup 1 {{n}} >> {log}"))
+        .arg(format!("--setup=echo setup 2 {{n}} >> {log}"))
+        .arg(format!("--cleanup=echo cleanup {{n}} >> {log}"))
+        .arg("echo {n}")
+        .assert()
+        .success();
+
+    let contents = std::fs::read_to_string(&log_path).unwrap();
+    assert_eq!(contents, "setup 1 a\ncleanup a\nsetup 2 b\ncleanup b\n");
+
+    // Skipped duplicates keep their place in the list of preparation commands
+    std::fs::remove_file(&log_path).unwrap();
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--deduplicate")
+        .arg(format!("--prepare=echo 1 >> {log}"))
+        .arg(format!("--prepare=echo 2 >> {log}"))
+        .arg(format!("--prepare=echo 3 >> {log}"))
+        .arg("echo a")
+        .arg("echo a")
+        .arg("echo b")
+        .assert()
+        .success();
+
+    let contents = std::fs::read_to_string(&log_path).unwrap();
+    assert_eq!(contents, "1\n3\n");
+}
+
 #[test]
 fn fails_with_wrong_number_of_conclude_options() {
     hyperfine()
@@ -248,7 +427,30 @@ fn fails_for_unknown_conclude_command() {
         .assert()
         .failure()
         .stderr(predicate::str::contains(
-            "The conclusion command terminated with a non-zero exit code.",
+            "The conclusion command terminated with a non-zero exit code after run 1.",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn conclude_failure_names_the_run_number() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let marker = tempdir.path().join("marker");
+
+    // The conclusion command succeeds once and fails afterwards
+    hyperfine()
+        .arg("--runs=3")
+        .arg(format!(
+            "--conclude=test ! -e {marker} && touch {marker}",
+            marker = marker.display()
+        ))
+        .arg("echo test")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The conclusion command terminated with a non-zero exit code after run 2.",
         ));
 }
 
@@ -271,364 +473,3490 @@ fn can_run_failing_commands_with_ignore_failure_option() {
         .success();
 }
 
+#[cfg(unix)]
 #[test]
-fn shows_output_of_benchmarked_command() {
+fn runs_preparation_command_once_per_benchmark() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let log = tempdir.path().join("log");
+
     hyperfine()
-        .arg("--runs=2")
-        .arg("--command-name=dummy")
-        .arg("--show-output")
-        .arg("echo 4fd47015")
+        .arg("--runs=3")
+        .arg("--warmup=2")
+        .arg(format!("--prepare=echo prepare-a >> {}", log.display()))
+        .arg(format!("--prepare=echo prepare-b >> {}", log.display()))
+        .arg("--prepare-mode=per-benchmark")
+        .arg(format!("echo run-a >> {}", log.display()))
+        .arg(format!("echo run-b >> {}", log.display()))
         .assert()
-        .success()
-        .stdout(predicate::str::contains("4fd47015").count(2));
+        .success();
+
+    let log = std::fs::read_to_string(log).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 12);
+    assert_eq!(lines[0], "prepare-a");
+    assert!(lines[1..6].iter().all(|&line| line == "run-a"));
+    assert_eq!(lines[6], "prepare-b");
+    assert!(lines[7..].iter().all(|&line| line == "run-b"));
 }
 
 #[test]
-fn runs_commands_using_user_defined_shell() {
-    hyperfine()
-        .arg("--runs=1")
-        .arg("--show-output")
-        .arg("--shell")
-        .arg("echo 'custom_shell' '--shell-arg'")
-        .arg("echo benchmark")
+fn warmup_runs_can_be_performed_before_the_preparation_command_or_skipped() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let log = tempdir.path().join("log");
+    let logged_runs = |warmup_mode: &str| {
+        std::fs::write(&log, "").unwrap();
+        hyperfine()
+            .arg("--runs=2")
+            .arg("--warmup=2")
+            .arg(format!("--prepare=echo prepare >> {}", log.display()))
+            .arg(format!("--warmup-mode={warmup_mode}"))
+            .arg(format!("echo run >> {}", log.display()))
+            .assert()
+            .success();
+        std::fs::read_to_string(&log).unwrap().replace('\n', " ")
+    };
+
+    assert_eq!(
+        logged_runs("after-prepare"),
+        "prepare run prepare run prepare run prepare run "
+    );
+    assert_eq!(
+        logged_runs("before-prepare"),
+        "run run prepare run prepare run "
+    );
+    assert_eq!(logged_runs("skip-with-prepare"), "prepare run prepare run ");
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--warmup=2")
+        .arg("--prepare=sleep 0.1")
+        .arg("--warmup-mode=skip-with-prepare")
+        .arg("--verbose")
+        .arg("--export-json=-")
+        .arg("sleep 0.2")
         .assert()
         .success()
-        .stdout(
-            predicate::str::contains("custom_shell --shell-arg -c echo benchmark").or(
-                predicate::str::contains("custom_shell --shell-arg /C echo benchmark"),
-            ),
-        );
+        .stdout(predicate::str::contains("  Warmup mode: skip-with-prepare"))
+        .stdout(predicate::str::contains(
+            "\"warmup_mode\": \"skip-with-prepare\"",
+        ))
+        .stdout(predicate::str::contains("\"warmup_runs\": 0"));
 }
 
 #[test]
-fn can_pass_input_to_command_from_a_file() {
+fn shows_shell_spawning_time_in_verbose_mode() {
     hyperfine()
-        .arg("--runs=1")
-        .arg("--input=example_input_file.txt")
-        .arg("--show-output")
-        .arg("cat")
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("echo dummy benchmark")
         .assert()
         .success()
-        .stdout(predicate::str::contains("This text is part of a file"));
-}
+        .stdout(predicate::str::contains("Shell calibration ("))
+        .stdout(predicate::str::contains("  Spawning time: "))
+        .stdout(predicate::str::contains("  Calibration runs: "));
 
-#[test]
-fn fails_if_invalid_stdin_data_file_provided() {
     hyperfine()
-        .arg("--runs=1")
-        .arg("--input=example_non_existent_file.txt")
-        .arg("--show-output")
-        .arg("cat")
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("--shell=none")
+        .arg("echo dummy benchmark")
         .assert()
-        .failure()
-        .stderr(predicate::str::contains(
-            "The file 'example_non_existent_file.txt' specified as '--input' does not exist",
-        ));
+        .success()
+        .stdout(predicate::str::contains("Shell calibration").not());
 }
 
 #[test]
-fn returns_mean_time_in_correct_unit() {
-    hyperfine_debug()
-        .arg("sleep 1.234")
+fn skips_the_shell_calibration_if_requested() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("--no-shell-calibration")
+        .arg("--export-json=-")
+        .arg("echo dummy benchmark")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Time (mean ± σ):      1.234 s ±"));
+        .stdout(predicate::str::contains(
+            "disabled via --no-shell-calibration",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert!(json["metadata"]["shell_calibration"].is_null());
+    assert_eq!(json["metadata"]["options"]["shell_calibration"], false);
 
-    hyperfine_debug()
-        .arg("sleep 0.123")
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--export-json=-")
+        .arg("echo dummy benchmark")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Time (mean ± σ):     123.0 ms ±"));
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert!(
+        json["metadata"]["shell_calibration"]["runs"]
+            .as_u64()
+            .unwrap()
+            >= 50
+    );
+    assert_eq!(json["metadata"]["options"]["shell_calibration"], true);
+}
 
-    hyperfine_debug()
-        .arg("--time-unit=millisecond")
-        .arg("sleep 1.234")
+#[cfg(unix)]
+#[test]
+fn measures_the_process_spawning_time_without_a_shell() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("--shell=none")
+        .arg("--export-json=-")
+        .arg("echo dummy benchmark")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Time (mean ± σ):     1234.0 ms ±"));
+        .stdout(predicate::str::contains(
+            "Spawn calibration (--shell=none):",
+        ))
+        .stdout(predicate::str::contains("  Spawning time: "))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert!(
+        json["metadata"]["spawn_calibration"]["runs"]
+            .as_u64()
+            .unwrap()
+            >= 50
+    );
+    assert!(json["metadata"]["shell_calibration"].is_null());
+    assert_eq!(json["metadata"]["options"]["spawn_calibration"], true);
 
-    hyperfine_debug()
-        .arg("--time-unit=microsecond")
-        .arg("sleep 1.234")
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("--shell=none")
+        .arg("--no-spawn-calibration")
+        .arg("--export-json=-")
+        .arg("echo dummy benchmark")
         .assert()
         .success()
         .stdout(predicate::str::contains(
-            "Time (mean ± σ):     1234000.0 µs ±",
-        ));
+            "disabled via --no-spawn-calibration",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert!(json["metadata"]["spawn_calibration"].is_null());
+    assert_eq!(json["metadata"]["options"]["spawn_calibration"], false);
 }
 
 #[test]
-fn performs_ten_runs_for_slow_commands() {
-    hyperfine_debug()
-        .arg("sleep 0.5")
+fn exports_the_cpu_times_of_every_run() {
+    let output = hyperfine()
+        .arg("--runs=3")
+        .arg("--export-json=-")
+        .arg("echo dummy benchmark")
         .assert()
         .success()
-        .stdout(predicate::str::contains("10 runs"));
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let result = &json["results"][0];
+    for key in ["user_times", "system_times"] {
+        let times = result[key].as_array().unwrap();
+        assert_eq!(times.len(), 3);
+        assert!(times.iter().all(|t| t.as_f64().unwrap() >= 0.0));
+    }
 }
 
 #[test]
-fn performs_three_seconds_of_benchmarking_for_fast_commands() {
-    hyperfine_debug()
-        .arg("sleep 0.01")
+fn measures_the_cpu_time_of_spawned_processes() {
+    // The benchmarked command is run by a shell, and it spawns another process that keeps the
+    // CPU busy
+    #[cfg(unix)]
+    let command = "sh -c 'i=0; while [ $i -lt 200000 ]; do i=$((i + 1)); done'";
+    #[cfg(windows)]
+    let command = "cmd /C \"for /L %i in (1,1,1000000) do @rem\"";
+
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--export-json=-")
+        .arg(command)
         .assert()
         .success()
-        .stdout(predicate::str::contains("300 runs"));
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let result = &json["results"][0];
+    let user_times = result["user_times"].as_array().unwrap();
+    assert!(user_times.iter().all(|t| t.as_f64().unwrap() > 0.01));
 }
 
+#[cfg(unix)]
 #[test]
-fn takes_shell_spawning_time_into_account_for_computing_number_of_runs() {
-    hyperfine_debug()
-        .arg("--shell=sleep 0.02")
-        .arg("sleep 0.01")
+fn excludes_outliers_from_statistics() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let marker = tempdir.path().join("marker");
+
+    // Only the first run is slow
+    let command = format!(
+        "if [ ! -e {marker} ]; then touch {marker}; sleep 0.5; fi",
+        marker = marker.display()
+    );
+
+    let output = hyperfine()
+        .arg("--runs=10")
+        .arg("--trim-outliers")
+        .arg("--export-json=-")
+        .arg(&command)
         .assert()
         .success()
-        .stdout(predicate::str::contains("100 runs"));
+        .stdout(predicate::str::contains("runs excluded as outliers"))
+        .get_output()
+        .stdout
+        .clone();
+
+    let json_start = output.iter().position(|&b| b == b'{').unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output[json_start..]).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["times"].as_array().unwrap().len(), 10);
+    assert_eq!(result["outliers"][0], 0);
+    assert_eq!(result["outliers_excluded"], true);
+    assert!(result["max"].as_f64().unwrap() < 0.5);
+    assert!(result["mean"].as_f64().unwrap() < 0.1);
 }
 
+#[cfg(unix)]
 #[test]
-fn takes_preparation_command_into_account_for_computing_number_of_runs() {
-    hyperfine_debug()
-        .arg("--prepare=sleep 0.02")
-        .arg("sleep 0.01")
+fn reports_the_runs_that_are_outliers() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let counter = tempdir.path().join("counter");
+
+    // Only the fifth run is slow
+    let command = format!(
+        "n=$(cat {counter} 2>/dev/null || echo 0); echo $((n + 1)) > {counter}; \
+         if [ $n -eq 4 ]; then sleep 0.5; fi",
+        counter = counter.display()
+    );
+
+    let output = hyperfine()
+        .arg("--runs=10")
+        .arg("--export-json=-")
+        .arg(&command)
         .assert()
         .success()
-        .stdout(predicate::str::contains("100 runs"));
+        .stderr(predicate::str::contains(
+            "Statistical outliers were detected (",
+        ))
+        .stderr(predicate::str::contains(" 5 (50"))
+        .get_output()
+        .stdout
+        .clone();
 
-    // Shell overhead needs to be added to both the prepare command and the actual command,
-    // leading to a total benchmark time of (prepare + shell + cmd + shell = 0.1 s)
+    let json_start = output.iter().position(|&b| b == b'{').unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output[json_start..]).unwrap();
+    let result = &json["results"][0];
+    assert!(result["outliers"].as_array().unwrap().contains(&4.into()));
+    assert!(result.get("outliers_excluded").is_none());
+}
+
+#[test]
+fn warns_about_duplicate_commands() {
     hyperfine_debug()
-        .arg("--shell=sleep 0.01")
-        .arg("--prepare=sleep 0.03")
-        .arg("sleep 0.05")
+        .arg("--command-name=a")
+        .arg("--command-name=b")
+        .arg("sleep 0.1")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
         .assert()
         .success()
-        .stdout(predicate::str::contains("30 runs"));
+        .stderr(predicate::str::contains(
+            "Benchmarks #1 and #2 run identical commands. Use '--deduplicate'",
+        ))
+        .stdout(predicate::str::contains("Benchmark 2: b"));
 }
 
 #[test]
-fn takes_conclusion_command_into_account_for_computing_number_of_runs() {
-    hyperfine_debug()
-        .arg("--conclude=sleep 0.02")
-        .arg("sleep 0.01")
+fn records_dropping_the_file_caches_in_the_metadata() {
+    let json_for = |args: &[&str]| {
+        let output = hyperfine_debug()
+            .args(args)
+            .arg("--export-json=-")
+            .arg("sleep 0.1")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        serde_json::from_str::<serde_json::Value>(&output[output.find("\n{").unwrap()..]).unwrap()
+    };
+
+    let json = json_for(&["--drop-file-caches"]);
+    assert_eq!(json["metadata"]["options"]["drop_file_caches"], true);
+
+    let json = json_for(&[]);
+    assert_eq!(json["metadata"]["options"]["drop_file_caches"], false);
+}
+
+#[test]
+fn skips_duplicate_commands_with_deduplicate() {
+    let output = hyperfine_debug()
+        .arg("--deduplicate")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .arg("sleep 0.1")
         .assert()
         .success()
-        .stdout(predicate::str::contains("100 runs"));
+        .stderr(predicate::str::contains(
+            "Benchmarks #1 and #3 run identical commands. Only the first one is performed",
+        ))
+        .stdout(predicate::str::contains("Benchmark 3").not())
+        .get_output()
+        .stdout
+        .clone();
 
-    // Shell overhead needs to be added to both the conclude command and the actual command,
-    // leading to a total benchmark time of (cmd + shell + conclude + shell = 0.1 s)
-    hyperfine_debug()
-        .arg("--shell=sleep 0.01")
-        .arg("--conclude=sleep 0.03")
-        .arg("sleep 0.05")
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 2);
+    assert_eq!(json["metadata"]["options"]["deduplicate"], true);
+    assert_eq!(
+        json["metadata"]["duplicate_benchmarks"],
+        serde_json::json!([[1, 3]])
+    );
+}
+
+#[test]
+fn shows_the_throughput() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("2")
+        .arg("--throughput={n}00MB")
+        .arg("--export-json=-")
+        .arg("sleep 0.{n}")
         .assert()
         .success()
-        .stdout(predicate::str::contains("30 runs"));
+        .stdout(predicate::str::contains(
+            "Throughput:           1.0 GB/s ± 0.0    [Range: 1.0 … 1.0 GB/s]",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let throughput = &json["results"][1]["throughput"];
+    assert_eq!(throughput["size"], 200e6);
+    assert_eq!(throughput["unit"], "bytes");
+    assert!((throughput["mean"].as_f64().unwrap() - 1e9).abs() < 1.0);
 }
 
 #[test]
-fn takes_both_preparation_and_conclusion_command_into_account_for_computing_number_of_runs() {
+fn fails_with_invalid_throughput_size() {
     hyperfine_debug()
-        .arg("--prepare=sleep 0.01")
-        .arg("--conclude=sleep 0.01")
+        .arg("--throughput=lots")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid size 'lots' for '--throughput'",
+        ));
+
+    hyperfine_debug()
+        .arg("--throughput=1MB")
+        .arg("--throughput=2MB")
+        .arg("--throughput=3MB")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The '--throughput' option has to be provided just once or once for each benchmark",
+        ));
+}
+
+#[test]
+fn fails_with_invalid_outlier_threshold() {
+    hyperfine_debug()
+        .arg("--outlier-threshold=0")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'--outlier-threshold' needs to be a positive number",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn retries_failed_runs() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let marker = tempdir.path().join("marker");
+
+    // Every other invocation of the command fails
+    let command = format!(
+        "if [ -e {marker} ]; then rm {marker}; else touch {marker}; exit 1; fi",
+        marker = marker.display()
+    );
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg(&command)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Command terminated with non-zero exit code",
+        ));
+    std::fs::remove_file(&marker).unwrap();
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--retries=1")
+        .arg(&command)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 failed runs had to be repeated"));
+
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--retries=1")
+        .arg("--export-json=-")
+        .arg("--style=none")
+        .arg(&command)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["results"][0]["retries"], 2);
+    assert_eq!(json["results"][0]["exit_codes"], serde_json::json!([0, 0]));
+}
+
+#[cfg(unix)]
+#[test]
+fn does_not_count_the_retries_of_warmup_runs() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let marker = tempdir.path().join("marker");
+
+    // Every other invocation of the command fails
+    let command = format!(
+        "if [ -e {marker} ]; then rm {marker}; else touch {marker}; exit 1; fi",
+        marker = marker.display()
+    );
+
+    let output = hyperfine()
+        .arg("--warmup=1")
+        .arg("--runs=2")
+        .arg("--retries=1")
+        .arg("--export-json=-")
+        .arg("--style=none")
+        .arg(&command)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["results"][0]["retries"], 2);
+}
+
+#[test]
+fn shows_output_of_benchmarked_command() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--command-name=dummy")
+        .arg("--show-output")
+        .arg("echo 4fd47015")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("4fd47015").count(2));
+}
+
+#[test]
+fn prefixes_the_shown_output_with_the_benchmark_and_run() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--show-output")
+        .arg("echo 4fd47015")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[cmd 1, run 2/2] 4fd47015"));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--show-output=raw")
+        .arg("echo 4fd47015")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\n4fd47015\n4fd47015\n"))
+        .stdout(predicate::str::contains("[cmd 1,").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_commands_in_a_persistent_shell() {
+    let output = hyperfine()
+        .arg("--runs=3")
+        .arg("--shell=sh")
+        .arg("--shell-mode=persistent")
+        .arg("--export-json=-")
+        .arg("cd /; FOO=bar; sleep 0.1")
+        .arg("test \"$(pwd)\" != / && test -z \"$FOO\"")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["metadata"]["options"]["persistent_shell"], true);
+    assert!(json["results"][0]["mean"].as_f64().unwrap() >= 0.09);
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=sh")
+        .arg("--shell-mode=persistent")
+        .arg("exit 3")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Command terminated with non-zero exit code: 3",
+        ));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=sh")
+        .arg("--shell-mode=persistent")
+        .arg("--show-output")
+        .arg("echo dummy benchmark")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("dummy benchmark"))
+        .stderr(predicate::str::contains(
+            "The persistent shell mode can not be used",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn fails_on_incomplete_commands_in_a_persistent_shell() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=bash")
+        .arg("--shell-mode=persistent")
+        .arg("echo \"abc")
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Command terminated with non-zero exit code",
+        ));
+}
+
+#[test]
+fn benchmarks_the_commands_with_several_shells() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--shell=sleep 0.01")
+        .arg("--shell=sleep 0.02")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Benchmark 2: sleep 0.1 (shell = sleep 0.02)",
+        ))
+        .stdout(predicate::str::contains(
+            "Benchmark 4: sleep 0.2 (shell = sleep 0.02)",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 4);
+    assert_eq!(json["results"][1]["parameters"]["shell"], "sleep 0.02");
+    assert_eq!(
+        json["metadata"]["options"]["shells"],
+        serde_json::json!(["sleep 0.01", "sleep 0.02"])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn exports_the_calibration_of_every_shell() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=sh")
+        .arg("--shell=bash")
+        .arg("--shell=none")
+        .arg("--shell-mode=persistent")
+        .arg("--export-json=-")
+        .arg("true")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let metadata = &json["metadata"];
+    for shell in ["sh", "bash", "none"] {
+        assert!(
+            metadata["shell_calibrations"][shell]["runs"]
+                .as_u64()
+                .unwrap()
+                > 0
+        );
+    }
+    assert_eq!(metadata["options"]["shell_calibration"], true);
+    assert_eq!(metadata["options"]["spawn_calibration"], true);
+    assert_eq!(metadata["options"]["persistent_shell"], false);
+    assert_eq!(
+        metadata["options"]["persistent_shells"],
+        serde_json::json!(["sh", "bash"])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn benchmarks_the_commands_with_a_shell_and_without_one() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--show-output")
+        .arg("--shell=sh")
+        .arg("--shell=none")
+        .arg("echo a")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Benchmark 1: echo a (shell = sh)"))
+        .stdout(predicate::str::contains(
+            "Benchmark 2: echo a (shell = none)",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn does_not_treat_braces_as_placeholders_with_several_shells() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--show-output")
+        .arg("--shell=sh")
+        .arg("--shell=bash")
+        .arg("echo '{print}' '{shell}'")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("{print} {shell}").count(2))
+        .stdout(predicate::str::contains(
+            "Benchmark 2: echo '{print}' '{shell}' (shell = bash)",
+        ));
+}
+
+#[test]
+fn runs_commands_using_user_defined_shell() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--show-output")
+        .arg("--shell")
+        .arg("echo 'custom_shell' '--shell-arg'")
+        .arg("echo benchmark")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("custom_shell --shell-arg -c echo benchmark").or(
+                predicate::str::contains("custom_shell --shell-arg /C echo benchmark"),
+            ),
+        );
+}
+
+#[test]
+fn can_pass_input_to_command_from_a_file() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--input=example_input_file.txt")
+        .arg("--show-output")
+        .arg("cat")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("This text is part of a file"));
+}
+
+#[test]
+fn every_run_reads_the_full_input_file() {
+    hyperfine()
+        .arg("--runs=3")
+        .arg("--shell=none")
+        .arg("--input=example_input_file.txt")
+        .arg("--show-output")
+        .arg("cat")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("This text is part of a file").count(3));
+}
+
+#[test]
+fn fails_if_invalid_stdin_data_file_provided() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--input=example_non_existent_file.txt")
+        .arg("--show-output")
+        .arg("cat")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The file 'example_non_existent_file.txt' specified as '--input' does not exist",
+        ));
+}
+
+#[test]
+fn returns_mean_time_in_correct_unit() {
+    hyperfine_debug()
+        .arg("sleep 1.234")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Time (mean ± σ):      1.234 s ±"));
+
+    hyperfine_debug()
+        .arg("sleep 0.123")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Time (mean ± σ):     123.0 ms ±"));
+
+    hyperfine_debug()
+        .arg("--time-unit=millisecond")
+        .arg("sleep 1.234")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Time (mean ± σ):     1234.0 ms ±"));
+
+    hyperfine_debug()
+        .arg("--time-unit=microsecond")
+        .arg("sleep 1.234")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Time (mean ± σ):     1234000.0 µs ±",
+        ));
+}
+
+#[test]
+fn compares_two_commands_side_by_side() {
+    hyperfine_debug()
+        .arg("sleep 0.1")
+        .arg("sleep 0.12")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Comparison\n  A: sleep 0.1\n  B: sleep 0.12\n",
+        ))
+        .stdout(predicate::str::contains(
+            "  Mean        100.0 ms    120.0 ms    +20.0%\n",
+        ))
+        .stdout(predicate::str::contains(
+            "  Max         100.0 ms    120.0 ms    +20.0%\n",
+        ));
+
+    hyperfine_debug()
+        .arg("sleep 0.1")
+        .arg("sleep 0.12")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Comparison").not());
+
+    hyperfine_debug()
+        .arg("--compare")
+        .arg("sleep 0.1")
+        .arg("sleep 0.12")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  B: sleep 0.12\n"))
+        .stdout(predicate::str::contains("  B: sleep 0.05\n"))
+        .stdout(predicate::str::contains(
+            "  Mean        100.0 ms     50.0 ms    -50.0%\n",
+        ));
+}
+
+#[test]
+fn compares_the_reference_side_by_side_with_the_other_commands() {
+    hyperfine_debug()
+        .arg("--compare")
+        .arg("--reference=sleep 0.12")
+        .arg("sleep 0.1")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "  A: sleep 0.12\n  B: sleep 0.1\n",
+        ))
+        .stdout(predicate::str::contains(
+            "  A: sleep 0.12\n  B: sleep 0.05\n",
+        ))
+        .stdout(predicate::str::contains("  B: sleep 0.12\n").not());
+
+    hyperfine_debug()
+        .arg("--compare")
+        .arg("--repeat-suite=2")
+        .arg("sleep 0.1")
+        .arg("sleep 0.12")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn shows_every_row_of_the_tables_in_its_own_unit() {
+    hyperfine_debug()
+        .arg("--time-unit=auto-per-row")
+        .arg("--export-markdown=-")
+        .arg("sleep 0.002")
+        .arg("sleep 90")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "| Command | Mean | Min | Max | Relative |",
+        ))
+        .stdout(predicate::str::contains(
+            "| `sleep 0.002` | 2.0 ± 0.0 ms | 2.0 ms | 2.0 ms |",
+        ))
+        .stdout(predicate::str::contains(
+            "| `sleep 90` | 1.500 ± 0.000 min | 1.500 min | 1.500 min |",
+        ))
+        .stdout(predicate::str::contains("Time (mean ± σ):       2.0 ms ±"));
+}
+
+#[test]
+fn prints_and_exports_the_executed_command() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#"Executed:    ["sleep", "0.1"]"#))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(
+        json["results"][0]["executed_argv"],
+        serde_json::json!(["sleep", "0.1"])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn prints_the_executed_shell_command() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--verbose")
+        .arg("--shell=sh")
+        .arg("--env=NAME=value")
+        .arg("echo 'a  b'")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"Executed:    ["sh", "-c", "echo 'a  b'"]"#,
+        ))
+        .stdout(predicate::str::contains(r#"Environment: NAME="value""#));
+}
+
+#[cfg(unix)]
+#[test]
+fn randomized_environment_offset_can_be_seeded_or_disabled() {
+    let offsets = |randomization: &str| {
+        let output = hyperfine()
+            .arg("--runs=3")
+            .arg("--show-output")
+            .arg(format!("--randomization={randomization}"))
+            .arg("echo \"offset:${HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET-unset}\" | wc -c")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        // Only the output of the command, without the measured times
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("[cmd 1, run"))
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(offsets("seed:7").len(), 3);
+    assert_eq!(offsets("seed:7"), offsets("seed:7"));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--show-output")
+        .arg("--randomization=off")
+        .arg("echo \"offset:${HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET-unset}\"")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("offset:unset"));
+
+    hyperfine_debug()
+        .arg("--randomization=seed:x")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid argument 'seed:x' for '--randomization'",
+        ));
+
+    let output = hyperfine_debug()
+        .arg("--randomization=seed:7")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["metadata"]["options"]["randomization"], "seed");
+    assert_eq!(json["metadata"]["options"]["randomization_seed"], 7);
+}
+
+#[test]
+fn fails_before_benchmarking_if_an_export_file_can_not_be_written() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    hyperfine_debug()
+        .arg("--export-json")
+        .arg(tempdir.path().join("nonexistent").join("out.json"))
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Benchmark 1").not())
+        .stderr(predicate::str::contains("Could not create export file"))
+        .stderr(predicate::str::contains("out.json"));
+
+    hyperfine_debug()
+        .current_dir(tempdir.path())
+        .arg("--export-json=out.txt")
+        .arg("--export-markdown=./out.txt")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Benchmark 1").not())
+        .stderr(predicate::str::contains(
+            "The export file './out.txt' is used by both '--export-json' and \
+             '--export-markdown'",
+        ));
+
+    hyperfine_debug()
+        .current_dir(tempdir.path())
+        .arg("--export-json=out.json")
+        .arg("--export-markdown=out.md")
+        .arg("--export-csv=-")
+        .arg("--export-toml=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success();
+}
+
+#[test]
+fn exports_the_number_of_runs() {
+    let output = hyperfine_debug()
+        .arg("--runs=4")
+        .arg("--warmup=2")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"][0]["runs"], 4);
+
+    hyperfine_debug()
+        .arg("--runs=4")
+        .arg("--export-csv=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("relative_stddev,mad,cv,runs\n"))
+        .stdout(predicate::str::contains("4 runs"));
+
+    hyperfine_debug()
+        .arg("--runs=1")
+        .arg("--show-runs-in-exports")
+        .arg("--export-markdown=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "| Command | Mean [ms] | Min [ms] | Max [ms] | Runs | Relative |",
+        ))
+        .stdout(predicate::str::contains(
+            "| `sleep 0.1` | 100.0 | 100.0 | 100.0 | 1 | 1.00 |",
+        ))
+        .stdout(predicate::str::contains("1 run\n"));
+}
+
+#[test]
+fn exports_a_junit_report_with_failed_test_cases() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--junit-fail-above=150ms")
+        .arg("--export-junit=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains(
+        "<testsuite name=\"hyperfine\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"0\""
+    ));
+    assert!(
+        output.contains("<testcase name=\"sleep 0.1\" classname=\"hyperfine\" time=\"0.100000\">")
+    );
+    assert_eq!(
+        output
+            .matches("<failure message=\"Assertion failed: mean &lt;= 150ms")
+            .count(),
+        1
+    );
+
+    hyperfine_debug()
+        .arg("--junit-fail-above=fast")
+        .arg("--export-junit=-")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not read duration argument to '--junit-fail-above'",
+        ));
+}
+
+#[test]
+fn markup_exports_show_the_chosen_columns() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--export-columns=size,median,runs")
+        .arg("--export-markdown=-")
+        .arg("--parameter-list")
+        .arg("size")
+        .arg("0.1,0.2")
+        .arg("sleep {size}")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| size | Median [ms] | Runs |"))
+        .stdout(predicate::str::contains("| `0.1` | 100.0 | 2 |"))
+        .stdout(predicate::str::contains("| `0.2` | 200.0 | 2 |"));
+
+    hyperfine_debug()
+        .arg("--export-columns=command,p95")
+        .arg("--export-markdown=-")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown column 'p95' for '--export-columns'",
+        ));
+}
+
+#[test]
+fn shows_peak_memory_usage_only_if_requested() {
+    hyperfine_debug()
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Peak memory").not());
+
+    hyperfine_debug()
+        .arg("--show-memory")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Peak memory:"));
+}
+
+#[test]
+fn min_benchmarking_time_determines_number_of_runs() {
+    // 'sleep 0.1' needs 30 runs to fill the default of three seconds
+    hyperfine_debug()
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+        "Number of runs:     30 (minimum benchmarking time: 3.000 s, min runs: 10, max runs: 10000 (default))",
+    ));
+
+    hyperfine_debug()
+        .arg("--min-benchmarking-time=5")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("50 runs"));
+
+    hyperfine_debug()
+        .arg("--min-benchmarking-time=0")
+        .arg("--min-runs=3")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 runs"));
+}
+
+#[test]
+fn shows_histogram_of_run_times() {
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Histogram").not());
+
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--histogram")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Histogram [ms]:"));
+}
+
+#[test]
+fn writes_progress_as_json_events() {
+    let output = hyperfine_debug()
+        .arg("--progress-json")
+        .arg("--warmup=1")
+        .arg("--runs=2")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Benchmark 2: sleep 0.2"))
+        .get_output()
+        .stderr
+        .clone();
+
+    let events: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let types: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        &types[..7],
+        [
+            "benchmark_started",
+            "warmup_started",
+            "warmup_run_completed",
+            "warmup_finished",
+            "measurement_started",
+            "run_completed",
+            "run_completed"
+        ]
+    );
+    assert_eq!(types.len(), 16);
+
+    assert_eq!(events[8]["command"], "sleep 0.2");
+    assert_eq!(events[8]["index"], 2);
+    assert_eq!(events[8]["total"], 2);
+    assert_eq!(events[14]["run"], 2);
+    assert_eq!(events[14]["runs"], 2);
+    assert_eq!(events[14]["time"], 0.2);
+    assert_eq!(events[15]["event"], "benchmark_finished");
+    assert_eq!(events[15]["runs"], 2);
+    assert_eq!(events[15]["min"], 0.2);
+}
+
+#[test]
+fn shows_confidence_interval_of_the_mean() {
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("95% CI").not());
+
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--confidence-interval")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(r"Mean: +100\.0 ms  \[95% CI: \d+\.\d ms … \d+\.\d ms\]")
+                .unwrap(),
+        );
+
+    hyperfine_debug()
+        .arg("--runs=1")
+        .arg("--confidence-interval")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("95% CI").not());
+}
+
+#[test]
+fn shows_and_exports_the_coefficient_of_variation() {
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(CV ").not());
+
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--show-cv")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("100.0 ms ±   0.0 ms (CV 0.0%)"));
+
+    let output = hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"][0]["mad"], 0.0);
+    assert!(json["results"][0]["cv"].as_f64().unwrap() < 1e-6);
+
+    hyperfine_debug()
+        .arg("--cv-warning-threshold=0")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'--cv-warning-threshold' needs to be a positive percentage",
+        ));
+}
+
+#[test]
+fn records_the_cpu_settings_and_skips_the_system_checks_if_requested() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--no-system-checks")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("--no-system-checks").not())
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let cpu_settings = json["metadata"]["host"]["cpu_settings"]
+        .as_object()
+        .unwrap();
+    assert!(cpu_settings.contains_key("governor"));
+    assert!(cpu_settings.contains_key("turbo_boost"));
+    assert!(cpu_settings.contains_key("smt"));
+}
+
+#[cfg(unix)]
+#[test]
+fn excludes_runs_that_fail_the_validation() {
+    hyperfine()
+        .arg("--runs=3")
+        .arg("--validate=test {run} -ne 2")
+        .arg("echo dummy benchmark")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The validation command failed after run 2",
+        ));
+
+    let output = hyperfine()
+        .arg("--runs=3")
+        .arg("--ignore-failure")
+        .arg("--validate=test {run} -ne 2")
+        .arg("--export-json=-")
+        .arg("echo dummy benchmark")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 of 3 runs failed the validation and are excluded",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"][0]["validation_failures"], 1);
+    assert_eq!(
+        json["results"][0]["failed_validations"],
+        serde_json::json!([1])
+    );
+    assert_eq!(json["results"][0]["times"].as_array().unwrap().len(), 3);
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--ignore-failure")
+        .arg("--validate=false")
+        .arg("echo dummy benchmark")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The validation command failed after all 2 runs",
+        ));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--parameter-list")
+        .arg("n")
+        .arg("1,2")
+        .arg("--validate=test {n} -le 2 && test {run} -le 2")
+        .arg("echo {n}")
+        .assert()
+        .success();
+}
+
+#[cfg(unix)]
+#[test]
+fn does_not_report_runs_that_fail_the_validation_as_outliers() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let counter = tempdir.path().join("counter");
+
+    // Only the fifth run is slow, and it fails the validation
+    let command = format!(
+        "n=$(cat {counter} 2>/dev/null || echo 0); echo $((n + 1)) > {counter}; \
+         if [ $n -eq 4 ]; then sleep 0.5; fi",
+        counter = counter.display()
+    );
+
+    let output = hyperfine()
+        .arg("--runs=10")
+        .arg("--ignore-failure")
+        .arg("--trim-outliers")
+        .arg("--validate=test {run} -ne 5")
+        .arg("--export-json=-")
+        .arg(&command)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 of 10 runs failed the validation and are excluded",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let result = &json["results"][0];
+    assert!(!result["outliers"].as_array().unwrap().contains(&4.into()));
+    assert!(result["max"].as_f64().unwrap() < 0.5);
+}
+
+#[test]
+fn stops_a_benchmark_after_the_max_benchmark_time() {
+    let output = hyperfine_debug()
+        .arg("--min-runs=2")
+        .arg("--max-benchmark-time=250ms")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Stopped after 3 runs because of the --max-benchmark-time",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"][0]["times"].as_array().unwrap().len(), 3);
+    assert_eq!(json["results"][0]["truncated"], true);
+
+    // Without `--max-benchmark-time-hard`, at least `--min-runs` are performed
+    hyperfine_debug()
+        .arg("--runs=5")
+        .arg("--max-benchmark-time=250ms")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("5 runs"))
+        .stdout(predicate::str::contains("Stopped after").not());
+
+    hyperfine_debug()
+        .arg("--runs=5")
+        .arg("--max-benchmark-time=250ms")
+        .arg("--max-benchmark-time-hard")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 runs"))
+        .stderr(predicate::str::contains(
+            "Only 3 runs could be performed within the '--max-benchmark-time' of 250.0 ms",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn reports_context_switches_and_page_faults() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--show-resource-usage")
+        .arg("echo a")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Context switches:")
+                .and(predicate::str::contains("Page faults:")),
+        );
+
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--export-json=-")
+        .arg("--style=none")
+        .arg("echo a")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let result = &json["results"][0];
+    for key in [
+        "voluntary_context_switches",
+        "involuntary_context_switches",
+        "major_page_faults",
+        "minor_page_faults",
+    ] {
+        assert_eq!(result[key].as_array().unwrap().len(), 2);
+    }
+}
+
+#[test]
+fn reports_cpu_frequency_if_available() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--export-json=-")
+        .arg("--show-cpu-frequency")
+        .arg("echo a")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    // Not available on all platforms (and not in all virtual machines)
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    match json["results"][0].get("cpu_frequency") {
+        Some(frequency) => {
+            let value = |key: &str| frequency[key].as_f64().unwrap();
+            assert!(value("min") > 0.0);
+            assert!(value("min") <= value("mean") && value("mean") <= value("max"));
+            assert!(output.contains("CPU frequency:"));
+        }
+        None => assert!(!output.contains("CPU frequency:")),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn aborts_runs_that_exceed_the_timeout() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--timeout=100ms")
+        .arg("sleep 2")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exceeded the timeout of 100.0 ms"));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--timeout=100ms")
+        .arg("--ignore-failure")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 runs exceeded the timeout"));
+}
+
+#[test]
+fn fails_with_a_zero_timeout() {
+    hyperfine_debug()
+        .arg("--timeout=0")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The argument to '--timeout' needs to be a positive duration",
+        ));
+}
+
+#[test]
+fn exports_run_timestamps_to_json() {
+    let output = hyperfine()
+        .arg("--runs=3")
+        .arg("--export-json=-")
+        .arg("--style=none")
+        .arg("echo a")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let timestamps = json["results"][0]["run_timestamps"].as_array().unwrap();
+    assert_eq!(timestamps.len(), 3);
+    assert!(timestamps[0].as_f64().unwrap() <= timestamps[2].as_f64().unwrap());
+}
+
+#[test]
+fn performs_warmup_runs_for_a_given_duration() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--warmup=200ms")
+        .arg("echo a")
+        .assert()
+        .success();
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--warmup=2x")
+        .arg("echo a")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not read numeric integer argument to '--warmup'",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_warming_up_until_the_duration_has_elapsed() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let runs_file = tempdir.path().join("runs.txt");
+
+    hyperfine()
+        .arg("--warmup=300ms")
+        .arg("--runs=2")
+        .arg(format!("echo run >> {}; sleep 0.05", runs_file.display()))
+        .assert()
+        .success();
+
+    // Every run takes at least 50 ms, so there can be at most six warmup runs. There is more
+    // than one of them, since stopping after the first would need it to take 300 ms.
+    let runs = std::fs::read_to_string(&runs_file).unwrap().lines().count();
+    let warmup_runs = runs - 2;
+    assert!(
+        (2..=6).contains(&warmup_runs),
+        "{} warmup runs",
+        warmup_runs
+    );
+}
+
+#[test]
+fn performs_warmup_runs_until_the_times_have_stabilized() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--warmup=auto")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+
+    // The mock executor always reports the same time, so the times are stable after the first
+    // comparison of two moving averages
+    assert_eq!(json["results"][0]["warmup_runs"], 4);
+    assert_eq!(json["metadata"]["options"]["warmup_auto"], true);
+}
+
+#[test]
+fn warns_about_large_number_of_parameter_combinations() {
+    hyperfine_debug()
+        .arg("--runs=1")
+        .arg("-L")
+        .arg("a")
+        .arg("1,2")
+        .arg("-L")
+        .arg("b")
+        .arg("1,2,3")
+        .arg("sleep 0.{a}{b}")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Benchmark 6: sleep 0.23"))
+        .stderr(predicate::str::contains("benchmarks will be performed").not());
+
+    let values: Vec<String> = (0..40).map(|i| i.to_string()).collect();
+    hyperfine_debug()
+        .arg("--runs=1")
+        .arg("--style=basic")
+        .arg("-L")
+        .arg("a")
+        .arg(values.join(","))
+        .arg("-L")
+        .arg("b")
+        .arg("1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26")
+        .arg("sleep 0.{a}{b}")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "1040 benchmarks will be performed",
+        ));
+}
+
+#[test]
+fn performs_ten_runs_for_slow_commands() {
+    hyperfine_debug()
+        .arg("sleep 0.5")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("10 runs"));
+}
+
+#[test]
+fn performs_three_seconds_of_benchmarking_for_fast_commands() {
+    hyperfine_debug()
+        .arg("sleep 0.01")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("300 runs"));
+}
+
+#[test]
+fn takes_shell_spawning_time_into_account_for_computing_number_of_runs() {
+    hyperfine_debug()
+        .arg("--shell=sleep 0.02")
+        .arg("sleep 0.01")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("100 runs"));
+}
+
+#[test]
+fn takes_preparation_command_into_account_for_computing_number_of_runs() {
+    hyperfine_debug()
+        .arg("--prepare=sleep 0.02")
+        .arg("sleep 0.01")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("100 runs"));
+
+    // Shell overhead needs to be added to both the prepare command and the actual command,
+    // leading to a total benchmark time of (prepare + shell + cmd + shell = 0.1 s)
+    hyperfine_debug()
+        .arg("--shell=sleep 0.01")
+        .arg("--prepare=sleep 0.03")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("30 runs"));
+}
+
+#[test]
+fn takes_conclusion_command_into_account_for_computing_number_of_runs() {
+    hyperfine_debug()
+        .arg("--conclude=sleep 0.02")
+        .arg("sleep 0.01")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("100 runs"));
+
+    // Shell overhead needs to be added to both the conclude command and the actual command,
+    // leading to a total benchmark time of (cmd + shell + conclude + shell = 0.1 s)
+    hyperfine_debug()
+        .arg("--shell=sleep 0.01")
+        .arg("--conclude=sleep 0.03")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("30 runs"));
+}
+
+#[test]
+fn takes_both_preparation_and_conclusion_command_into_account_for_computing_number_of_runs() {
+    hyperfine_debug()
+        .arg("--prepare=sleep 0.01")
+        .arg("--conclude=sleep 0.01")
+        .arg("sleep 0.01")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("100 runs"));
+
+    // Shell overhead needs to be added to both the prepare, conclude and the actual command,
+    // leading to a total benchmark time of (prepare + shell + cmd + shell + conclude + shell = 0.1 s)
+    hyperfine_debug()
+        .arg("--shell=sleep 0.01")
+        .arg("--prepare=sleep 0.01")
+        .arg("--conclude=sleep 0.01")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("30 runs"));
+}
+
+#[test]
+fn reports_the_runs_that_are_shorter_than_the_spawning_time() {
+    // The mock executor sets negative times to zero, like the ones that are shorter than the
+    // spawning time
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--export-json=-")
+        .arg("sleep -0.01")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "The measured times of 'sleep -0.01' (2 of 2 runs) were shorter than the estimated \
+             shell (or process) spawning time and have been set to zero.",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"][0]["clamped_runs"], 2);
+    assert_eq!(json["results"][1]["clamped_runs"], 0);
+}
+
+#[test]
+fn shows_benchmark_comparison_with_relative_times() {
+    hyperfine_debug()
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .arg("sleep 3.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("2.00 ± 0.00 times faster")
+                .and(predicate::str::contains("3.00 ± 0.00 times faster"))
+                .and(predicate::str::contains(
+                    "Geometric mean of the times relative to sleep 1.0: 2.45",
+                )),
+        );
+}
+
+#[test]
+fn notes_differences_that_are_not_statistically_significant() {
+    hyperfine_debug()
+        .arg("--runs=4")
+        .arg("sleep 0.1 0.3")
+        .arg("sleep 0.12 0.3")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "(p = 0.90: the difference is not statistically significant at α = 0.05)",
+        ));
+
+    hyperfine_debug()
+        .arg("--runs=4")
+        .arg("sleep 0.1 0.11")
+        .arg("sleep 0.2 0.21")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("statistically significant").not());
+}
+
+#[test]
+fn shows_geometric_mean_of_relative_times() {
+    hyperfine_debug()
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Geometric mean").not());
+
+    hyperfine_debug()
+        .arg("--geometric-mean")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Geometric mean of the times relative to sleep 1.0: 2.00",
+        ));
+}
+
+#[test]
+fn command_names_are_used_in_summary_and_exports() {
+    let output = hyperfine_debug()
+        .arg("--command-name=fast")
+        .arg("--command-name=slow")
+        .arg("--export-json=-")
+        .arg("--style=none")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["results"][0]["command"], "sleep 1.0");
+    assert_eq!(json["results"][0]["name"], "fast");
+    assert_eq!(json["results"][1]["command"], "sleep 2.0");
+    assert_eq!(json["results"][1]["name"], "slow");
+
+    hyperfine_debug()
+        .arg("--command-name=fast")
+        .arg("--command-name=slow")
+        .arg("--export-csv=-")
+        .arg("--export-markdown=-")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("fast ran\n    2.00 ± 0.00 times faster than slow")
+                .and(predicate::str::contains("\nfast,1,"))
+                .and(predicate::str::contains("\nslow,2,"))
+                .and(predicate::str::contains("| `fast` |"))
+                .and(predicate::str::contains("sleep 1.0").not()),
+        );
+}
+
+#[test]
+fn shows_benchmark_comparison_with_same_time() {
+    hyperfine_debug()
+        .arg("--command-name=A")
+        .arg("--command-name=B")
+        .arg("sleep 1.0")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .arg("sleep 1000.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("As fast (1.00) as")
+                .and(predicate::str::contains("2.00 ± 0.00 times faster"))
+                .and(predicate::str::contains("1000.00 ± 0.00 times faster")),
+        );
+}
+
+#[test]
+fn shows_benchmark_comparison_relative_to_reference() {
+    hyperfine_debug()
+        .arg("--reference=sleep 2.0")
+        .arg("sleep 1.0")
+        .arg("sleep 3.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("2.00 ± 0.00 times slower")
+                .and(predicate::str::contains("1.50 ± 0.00 times faster")),
+        );
+}
+
+#[test]
+fn uses_benchmarked_command_as_reference() {
+    hyperfine_debug()
+        .arg("--reference=reference")
+        .arg("--command-name=fast")
+        .arg("--command-name=reference")
+        .arg("--export-markdown=-")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 3")
+                .not()
+                .and(predicate::str::contains("reference ran"))
+                .and(predicate::str::contains(
+                    "2.00 ± 0.00 times slower than fast",
+                ))
+                .and(predicate::str::contains(
+                    "| `fast` | 1.000 ± 0.000 | 1.000 | 1.000 | 0.50 ± 0.00 |",
+                ))
+                .and(predicate::str::contains(
+                    "| `reference` | 2.000 ± 0.000 | 2.000 | 2.000 | 1.00 |",
+                )),
+        );
+
+    // A command line is not matched, so the reference is benchmarked separately
+    hyperfine_debug()
+        .arg("--reference=sleep 2.0")
+        .arg("--sort=command")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 3: sleep 2.0")
+                .and(predicate::str::contains("0.50 ±  0.00  sleep 1.0")),
+        );
+}
+
+#[test]
+fn performs_all_benchmarks_in_parameter_scan() {
+    hyperfine_debug()
+        .arg("--parameter-scan")
+        .arg("time")
+        .arg("30")
+        .arg("45")
+        .arg("--parameter-step-size")
+        .arg("5")
+        .arg("sleep {time}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 30")
+                .and(predicate::str::contains("Benchmark 2: sleep 35"))
+                .and(predicate::str::contains("Benchmark 3: sleep 40"))
+                .and(predicate::str::contains("Benchmark 4: sleep 45"))
+                .and(predicate::str::contains("Benchmark 5: sleep 50").not()),
+        );
+}
+
+#[test]
+fn performs_multiplicative_parameter_scan_with_suffixes() {
+    hyperfine_debug()
+        .arg("--parameter-scan")
+        .arg("size")
+        .arg("1K")
+        .arg("4K")
+        .arg("--parameter-step-size")
+        .arg("2x")
+        .arg("--parameter-expand-suffix")
+        .arg("sleep {size}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 1024")
+                .and(predicate::str::contains("Benchmark 2: sleep 2048"))
+                .and(predicate::str::contains("Benchmark 3: sleep 4096"))
+                .and(predicate::str::contains("Benchmark 4").not()),
+        );
+}
+
+#[test]
+fn formats_parameter_values() {
+    hyperfine_debug()
+        .arg("--parameter-scan")
+        .arg("x")
+        .arg("1")
+        .arg("2")
+        .arg("--parameter-step-size")
+        .arg("0.5")
+        .arg("sleep {x:04.2}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 1.00")
+                .and(predicate::str::contains("Benchmark 2: sleep 1.50"))
+                .and(predicate::str::contains("Benchmark 3: sleep 2.00")),
+        );
+}
+
+#[test]
+fn fails_with_invalid_parameter_format() {
+    hyperfine_debug()
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("2")
+        .arg("sleep {n:abc}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid format 'abc' for the parameter 'n'",
+        ));
+}
+
+#[test]
+fn exports_one_file_per_parameter_value() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let template = tempdir.path().join("results-{time}.json");
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--parameter-list")
+        .arg("time")
+        .arg("0.1,0.2")
+        .arg("--reference=sleep 0.5")
+        .arg(format!("--export-json={}", template.display()))
+        .arg("sleep {time}")
+        .assert()
+        .success();
+
+    for time in ["0.1", "0.2"] {
+        let content =
+            std::fs::read_to_string(tempdir.path().join(format!("results-{time}.json"))).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let results = json["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["command"], "sleep 0.5");
+        assert_eq!(results[1]["command"], format!("sleep {time}"));
+    }
+
+    hyperfine_debug()
+        .arg("--parameter-list")
+        .arg("time")
+        .arg("0.1,0.2")
+        .arg(format!(
+            "--export-json={}",
+            tempdir.path().join("results-{threads}.json").display()
+        ))
+        .arg("sleep {time}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "contains the placeholder '{threads}', but there is no parameter with this name",
+        ));
+}
+
+#[test]
+fn performs_reference_and_all_benchmarks_in_parameter_scan() {
+    hyperfine_debug()
+        .arg("--reference=sleep 25")
+        .arg("--parameter-scan")
+        .arg("time")
+        .arg("30")
+        .arg("45")
+        .arg("--parameter-step-size")
+        .arg("5")
+        .arg("sleep {time}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 25")
+                .and(predicate::str::contains("Benchmark 2: sleep 30"))
+                .and(predicate::str::contains("Benchmark 3: sleep 35"))
+                .and(predicate::str::contains("Benchmark 4: sleep 40"))
+                .and(predicate::str::contains("Benchmark 5: sleep 45"))
+                .and(predicate::str::contains("Benchmark 6: sleep 50").not()),
+        );
+}
+
+#[test]
+fn intermediate_results_are_not_exported_to_stdout() {
+    hyperfine_debug()
+        .arg("--style=none") // To only see the Markdown export on stdout
+        .arg("--export-markdown")
+        .arg("-")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stdout(
+            (predicate::str::contains("sleep 1").count(1))
+                .and(predicate::str::contains("sleep 2").count(1)),
+        );
+}
+
+#[test]
+#[cfg(unix)]
+fn exports_intermediate_results_to_file() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let export_path = tempdir.path().join("results.md");
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--export-markdown")
+        .arg(&export_path)
+        .arg("true")
+        .arg("false")
+        .assert()
+        .failure();
+
+    let contents = std::fs::read_to_string(export_path).unwrap();
+    assert!(contents.contains("true"));
+}
+
+#[test]
+fn exports_every_run_as_json_lines() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let export_path = tempdir.path().join("runs.jsonl");
+
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--export-json-lines")
+        .arg(&export_path)
+        .arg("--parameter-list")
+        .arg("time")
+        .arg("0.1,0.2")
+        .arg("sleep {time}")
+        .assert()
+        .success();
+
+    let contents = std::fs::read_to_string(export_path).unwrap();
+    let runs: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(runs.len(), 6);
+    assert_eq!(runs[0]["command"], "sleep 0.1");
+    assert_eq!(runs[0]["run"], 1);
+    assert_eq!(runs[0]["time"], 0.1);
+    assert_eq!(runs[0]["exit_code"], 0);
+    assert_eq!(runs[0]["parameters"]["time"], "0.1");
+    assert_eq!(runs[5]["command"], "sleep 0.2");
+    assert_eq!(runs[5]["run"], 3);
+}
+
+#[test]
+fn exports_every_run_as_csv() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--export-csv-runs=-")
+        .arg("--style=none")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "command,run,time,user,system,exit_code\n\
+             sleep 0.1,1,0.1,0,0,0\n\
+             sleep 0.1,2,0.1,0,0,0\n\
+             sleep 0.2,1,0.2,0,0,0\n\
+             sleep 0.2,2,0.2,0,0,0\n",
+        ));
+}
+
+#[test]
+#[cfg(unix)]
+fn json_lines_export_contains_runs_before_a_failure() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let export_path = tempdir.path().join("runs.jsonl");
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--export-json-lines")
+        .arg(&export_path)
+        .arg("true")
+        .arg("false")
+        .assert()
+        .failure();
+
+    let contents = std::fs::read_to_string(export_path).unwrap();
+    let commands: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let run: serde_json::Value = serde_json::from_str(line).unwrap();
+            run["command"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(commands, vec!["true", "true"]);
+}
+
+#[test]
+#[cfg(unix)]
+fn setup_global_runs_once_before_all_benchmarks() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let log_path = tempdir.path().join("setup.log");
+    let log = log_path.to_str().unwrap();
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg(format!("--setup-global=echo global >> {log}"))
+        .arg(format!("--setup=echo setup >> {log}"))
+        .arg("true")
+        .arg("echo a")
+        .assert()
+        .success();
+
+    let contents = std::fs::read_to_string(log_path).unwrap();
+    assert_eq!(contents, "global\nsetup\nsetup\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn failing_setup_global_aborts_with_its_error_output() {
+    hyperfine()
+        .arg("--setup-global=echo 'something went wrong' >&2; false")
+        .arg("true")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Benchmark 1").not())
+        .stderr(
+            predicate::str::contains(
+                "The global setup command terminated with a non-zero exit code",
+            )
+            .and(predicate::str::contains("something went wrong")),
+        );
+}
+
+#[test]
+#[cfg(unix)]
+fn interrupted_benchmark_runs_cleanup_and_exports_completed_results() {
+    use std::io::{BufRead, BufReader, Read};
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let log_path = tempdir.path().join("cleanup.log");
+    let export_path = tempdir.path().join("results.json");
+
+    let mut child = common::hyperfine_raw_command()
+        .arg("--runs=20")
+        .arg(format!("--cleanup=echo cleanup >> {}", log_path.display()))
+        .arg(format!("--export-json={}", export_path.display()))
+        .arg("--style=none")
+        .arg("--output=inherit")
+        .arg("true")
+        .arg("echo second benchmark running; sleep 0.5")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Wait until the second benchmark has started its first run, then interrupt it. The first
+    // one does not produce any output.
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    while !line.contains("second benchmark running") {
+        line.clear();
+        assert!(stdout.read_line(&mut line).unwrap() > 0);
+    }
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+
+    stdout.read_to_end(&mut vec![]).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("interrupted"));
+
+    assert_eq!(
+        std::fs::read_to_string(&log_path).unwrap(),
+        "cleanup\ncleanup\n"
+    );
+
+    let export: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+    let results = export["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["command"], "true");
+}
+
+#[test]
+#[cfg(unix)]
+fn sets_environment_variables_for_benchmarked_commands() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let env_file = tempdir.path().join("bench.env");
+    let export_path = tempdir.path().join("results.json");
+    std::fs::write(&env_file, "FOO=from-file\nBAR=2\n").unwrap();
+
+    for shell in ["--shell=default", "--shell=none"] {
+        hyperfine()
+            .arg("--runs=2")
+            .arg(shell)
+            .arg(format!("--env-file={}", env_file.display()))
+            .arg("--env=FOO=1")
+            .arg(format!("--export-json={}", export_path.display()))
+            .arg("sh -c 'test \"$FOO\" = 1 && test \"$BAR\" = 2'")
+            .assert()
+            .success();
+
+        let export: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&export_path).unwrap()).unwrap();
+        assert_eq!(
+            export["results"][0]["environment"],
+            serde_json::json!({"FOO": "1", "BAR": "2"})
+        );
+    }
+
+    hyperfine()
+        .arg("--env=FOO")
+        .arg("true")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Use the form 'NAME=VALUE'"));
+}
+
+#[test]
+fn unused_parameters_are_shown_in_benchmark_name() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--parameter-list")
+        .arg("branch")
+        .arg("master,feature")
+        .arg("echo test")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("echo test (branch = master)")
+                .and(predicate::str::contains("echo test (branch = feature)")),
+        );
+}
+
+#[test]
+fn speed_comparison_sort_order() {
+    for sort_order in ["auto", "mean-time"] {
+        hyperfine_debug()
+            .arg("sleep 2")
+            .arg("sleep 1")
+            .arg(format!("--sort={sort_order}"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "sleep 1 ran\n    2.00 ± 0.00 times faster than sleep 2",
+            ));
+    }
+
+    hyperfine_debug()
+        .arg("sleep 2")
+        .arg("sleep 1")
+        .arg("--sort=command")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2.00 ±  0.00  sleep 2\n        1.00          sleep 1",
+        ));
+
+    for (sort_order, statistic) in [("median-time", "median time"), ("max-time", "maximum time")] {
+        hyperfine_debug()
+            .arg("sleep 2")
+            .arg("sleep 1")
+            .arg(format!("--sort={sort_order}"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!(
+                "Summary (by {statistic})\n  sleep 1 ran\n    2.00 times faster than sleep 2"
+            )));
+    }
+}
+
+#[test]
+fn shows_the_absolute_time_difference_in_the_summary() {
+    hyperfine_debug()
+        .arg("sleep 0.1")
+        .arg("sleep 0.5124")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Δ").not());
+
+    hyperfine_debug()
+        .arg("--summary-format=both")
+        .arg("sleep 0.1")
+        .arg("sleep 0.5124")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "5.12 ± 0.00 times faster than sleep 0.5124 (Δ 412.4 ms)",
+        ));
+
+    hyperfine_debug()
+        .arg("--summary-format=delta")
+        .arg("--time-unit=second")
+        .arg("sleep 0.1")
+        .arg("sleep 0.5124")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "sleep 0.1 ran\n    0.412 s faster than sleep 0.5124",
+        ));
+
+    hyperfine_debug()
+        .arg("--summary-format=both")
+        .arg("--sort=command")
+        .arg("sleep 0.2")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sleep 0.2 (Δ +100.0 ms)"));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_quote_args() {
+    hyperfine()
+        .arg("more \"example_input_file.txt\"")
+        .assert()
+        .success();
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_quote_before_quote_args() {
+    hyperfine()
+        .arg("dir \"..\\src\\\" \"..\\tests\\\"")
+        .assert()
+        .success();
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_commands_in_the_working_directory() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    for dir in ["a", "b"] {
+        std::fs::create_dir(tempdir.path().join(dir)).unwrap();
+        std::fs::write(tempdir.path().join(dir).join("marker"), "").unwrap();
+    }
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--parameter-list")
+        .arg("dir")
+        .arg("a,b")
+        .arg(format!(
+            "--working-dir={}/{{dir}}",
+            tempdir.path().display()
+        ))
+        .arg(format!("--export-json={}", export.display()))
+        .arg("--shell=none")
+        .arg("cat marker")
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    for (i, dir) in ["a", "b"].iter().enumerate() {
+        assert_eq!(
+            json["results"][i]["working_dir"],
+            format!("{}/{dir}", tempdir.path().display())
+        );
+    }
+}
+
+#[test]
+fn fails_for_nonexistent_working_directory() {
+    hyperfine_debug()
+        .arg("--parameter-list")
+        .arg("dir")
+        .arg(".,does-not-exist")
+        .arg("--working-dir={dir}")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The working directory 'does-not-exist' specified via '--working-dir' does not exist \
+             (for the parameter values dir = does-not-exist)",
+        ));
+}
+
+#[test]
+fn shows_live_statistics() {
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--show-live-stats")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Time (mean ± σ):"));
+}
+
+#[test]
+fn divides_total_time_budget_among_benchmarks() {
+    let output = hyperfine_debug()
+        .arg("--min-runs=2")
+        .arg("--total-time-budget=1.1s")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+
+    // The first benchmark gets half of the budget, and the second one gets the remaining 0.6 s
+    assert_eq!(json["results"][0]["times"].as_array().unwrap().len(), 5);
+    assert_eq!(json["results"][1]["times"].as_array().unwrap().len(), 12);
+    assert!(json.get("skipped").is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn skips_benchmarks_if_the_total_time_budget_is_exhausted() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--total-time-budget=500ms")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("sleep 0.3")
+        .arg("sleep 0.01")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Benchmark 2: sleep 0.01 (skipped, the total time budget is exhausted)",
+        ));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 1);
+    assert_eq!(json["skipped"][0]["command"], "sleep 0.01");
+}
+
+#[test]
+fn uses_number_of_runs_per_command() {
+    let output = hyperfine_debug()
+        .arg("--runs-per-command=5,2")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+
+    assert_eq!(json["results"][0]["times"].as_array().unwrap().len(), 5);
+    assert_eq!(json["results"][1]["times"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn fails_with_wrong_number_of_runs_per_command() {
+    hyperfine_debug()
+        .arg("--warmup-per-command=1,2,3")
+        .arg("sleep 0.1")
+        .arg("sleep 0.05")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The '--warmup-per-command' option has 3 values, but it needs exactly one value for \
+             each of the 2 benchmark commands",
+        ));
+}
+
+#[test]
+fn exports_metadata_in_json() {
+    let metadata = |args: &[&str]| {
+        let output = hyperfine_debug()
+            .arg("--runs=2")
+            .arg("--warmup=1")
+            .arg("--setup=sleep 0.01")
+            .arg("--export-json=-")
+            .args(args)
+            .arg("sleep 0.1")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+        json["metadata"].clone()
+    };
+
+    let json = metadata(&[]);
+    assert_eq!(json["hyperfine_version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["command_line"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("--warmup=1")));
+    assert!(json["timestamp"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(json["options"]["warmup_runs"], 1);
+    assert_eq!(json["options"]["min_runs"], 2);
+    assert_eq!(json["options"]["max_runs"], 2);
+    assert_eq!(json["options"]["setup"], serde_json::json!(["sleep 0.01"]));
+    assert_eq!(json["host"]["os"], std::env::consts::OS);
+    assert_eq!(json["host"]["arch"], std::env::consts::ARCH);
+
+    let json = metadata(&["--no-metadata-host"]);
+    assert!(json["host"]["hostname"].is_null());
+}
+
+#[test]
+fn exports_the_system_load_in_json() {
+    let metadata = |args: &[&str]| {
+        let output = hyperfine_debug()
+            .arg("--runs=2")
+            .arg("--export-json=-")
+            .args(args)
+            .arg("sleep 0.1")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+        json["metadata"].clone()
+    };
+
+    let json = metadata(&[]);
+    if cfg!(any(unix, windows)) {
+        assert!(json["host"]["system_load"]["load"].as_f64().unwrap() >= 0.0);
+        assert!(json["host"]["system_load"]["cores"].as_u64().unwrap() >= 1);
+    }
+
+    // The load is not sampled on Windows if the check is disabled
+    let json = metadata(&["--no-load-check"]);
+    if cfg!(windows) {
+        assert!(json["host"]["system_load"].is_null());
+    } else if cfg!(unix) {
+        assert!(json["host"]["system_load"]["load"].as_f64().unwrap() >= 0.0);
+    }
+}
+
+#[test]
+fn succeeds_if_all_assertions_hold() {
+    hyperfine_debug()
+        .arg("--assert=mean < 200ms")
+        .arg("--assert=p95 <= 0.1")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Assertion failed").not());
+}
+
+#[test]
+fn fails_after_all_benchmarks_if_an_assertion_does_not_hold() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--assert=mean < 50ms")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.01")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Assertion failed: mean < 50ms (actual value: 100.0 ms)").and(
+                predicate::str::contains(
+                    "1 of the assertions specified via '--assert' failed:\n  \
+                     sleep 0.1: mean < 50ms",
+                ),
+            ),
+        )
+        .get_output()
+        .stdout
+        .clone();
+
+    // The remaining benchmarks and the export are not affected
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn fails_with_invalid_assertion() {
+    hyperfine_debug()
+        .arg("--assert=mean is fast")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid assertion 'mean is fast' for '--assert'",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn interleaves_the_timing_runs_of_all_commands() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let log = tempdir.path().join("log");
+    let log = log.display();
+
+    hyperfine()
+        .arg("--interleave")
+        .arg("--runs=20")
+        .arg("--warmup=1")
+        .arg(format!("--prepare=echo prepare-a >> {log}"))
+        .arg(format!("--prepare=echo prepare-b >> {log}"))
+        .arg(format!("--setup=echo setup >> {log}"))
+        .arg(format!("--cleanup=echo cleanup >> {log}"))
+        .arg(format!("echo a >> {log}"))
+        .arg(format!("echo b >> {log}"))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match("(?s)Benchmark 1: echo a.*Time.*Benchmark 2: echo b.*Time")
+                .unwrap(),
+        );
+
+    let log = std::fs::read_to_string(tempdir.path().join("log")).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+
+    // Setup, warmup and initial runs are performed for one command after another
+    assert_eq!(
+        lines[..10],
+        [
+            "setup",
+            "prepare-a",
+            "a",
+            "prepare-a",
+            "a",
+            "setup",
+            "prepare-b",
+            "b",
+            "prepare-b",
+            "b"
+        ]
+    );
+    assert_eq!(lines[lines.len() - 2..], ["cleanup", "cleanup"]);
+
+    // Each run is still preceded by the right preparation command
+    let runs = &lines[10..lines.len() - 2];
+    assert_eq!(runs.len(), 2 * 2 * 19);
+    for pair in runs.chunks(2) {
+        assert_eq!(pair[0], format!("prepare-{}", pair[1]));
+    }
+    assert_eq!(runs.iter().filter(|&&line| line == "a").count(), 19);
+
+    // With 38 runs in a random order, it is practically impossible that all runs of the first
+    // command are performed first
+    assert!(runs[..38].contains(&"b"));
+}
+
+#[cfg(unix)]
+#[test]
+fn shows_multi_line_commands_on_a_single_line() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("sleep 0.02\necho b")
         .arg("sleep 0.01")
         .assert()
         .success()
-        .stdout(predicate::str::contains("100 runs"));
+        .stdout(predicate::str::contains("Benchmark 1: sleep 0.02⏎echo b\n"))
+        .stdout(predicate::str::is_match("(?s)Summary.*sleep 0.02⏎echo b").unwrap());
+}
+
+#[test]
+fn subsamples_the_exported_run_times() {
+    let output = hyperfine_debug()
+        .arg("--runs=30")
+        .arg("--max-export-samples=10")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["times"].as_array().unwrap().len(), 10);
+    assert_eq!(result["user_times"].as_array().unwrap().len(), 10);
+    assert_eq!(result["exit_codes"].as_array().unwrap().len(), 30);
+    assert_eq!(result["times_subsampled"], true);
+
+    let output = hyperfine_debug()
+        .arg("--runs=30")
+        .arg("--max-export-samples=0")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["times"].as_array().unwrap().len(), 30);
+    assert!(result["times_subsampled"].is_null());
+}
+
+#[test]
+fn computes_the_statistics_over_all_runs_if_the_run_times_are_subsampled() {
+    // Only the first and the third run are exported, but the slow fourth run is part of the
+    // percentiles and the assertions
+    let output = hyperfine_debug()
+        .arg("--runs=4")
+        .arg("--max-export-samples=2")
+        .arg("--assert=p99 < 0.5")
+        .arg("--export-json=-")
+        .arg("sleep 0.1 0.1 0.1 1.0")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Assertion failed: p99 < 0.5"))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["times"], serde_json::json!([0.1, 0.1]));
+    assert_eq!(result["times_subsampled"], true);
+    assert!(result["percentiles"]["p99"].as_f64().unwrap() > 0.9);
+}
+
+#[test]
+fn caps_the_number_of_runs_of_very_fast_commands() {
+    hyperfine_debug()
+        .arg("sleep 0.0001")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Number of runs:     10000 (minimum benchmarking time: 3.000 s, min runs: 10, max runs: 10000 (default))",
+        ));
+}
+
+#[test]
+fn repeats_the_whole_suite() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--repeat-suite=3")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                "(?s)Iteration 1/3.*Benchmark 2: sleep 0.2.*Iteration 3/3.*Benchmark 2: sleep 0.2",
+            )
+            .unwrap(),
+        )
+        .stdout(predicate::str::contains("Summary (iteration 1)"))
+        .stdout(predicate::str::contains("Summary (iteration 3)"))
+        .stdout(predicate::str::contains("Summary (all 3 iterations)"))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+    let results: Vec<(&str, u64)> = json["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["command"].as_str().unwrap(),
+                r["iteration"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        results,
+        [
+            ("sleep 0.1", 1),
+            ("sleep 0.2", 1),
+            ("sleep 0.1", 2),
+            ("sleep 0.2", 2),
+            ("sleep 0.1", 3),
+            ("sleep 0.2", 3)
+        ]
+    );
+    assert_eq!(json["metadata"]["options"]["repeat_suite"], 3);
+
+    hyperfine_debug()
+        .arg("--repeat-suite=0")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The argument to '--repeat-suite' needs to be at least 1",
+        ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn restricts_commands_to_the_given_cpus() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let output = tempdir.path().join("status");
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--shell=none")
+        .arg("--cpu=0")
+        .arg(format!("--output={}", output.display()))
+        .arg(format!("--export-json={}", export.display()))
+        .arg("grep Cpus_allowed_list /proc/self/status")
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(output).unwrap().trim(),
+        "Cpus_allowed_list:\t0"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    assert_eq!(
+        json["metadata"]["options"]["cpu_affinity"],
+        serde_json::json!([0])
+    );
+}
+
+#[test]
+fn fails_with_invalid_cpu_list() {
+    hyperfine_debug()
+        .arg("--cpu=1-")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid list of CPUs '1-' for '--cpu'",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn shows_exit_codes_if_failures_are_ignored() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let marker = tempdir.path().join("marker");
+    let marker = marker.display();
+
+    // Every other run fails
+    hyperfine()
+        .arg("--runs=4")
+        .arg("--ignore-failure")
+        .arg(format!(
+            "test -e {marker} && rm {marker} || {{ touch {marker}; exit 3; }}"
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Exit codes:         0 (2×), 3 (2×)",
+        ))
+        .stderr(predicate::str::contains(
+            "The runs terminated with 2 different exit codes.",
+        ));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--ignore-failure")
+        .arg("exit 0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exit codes:").not());
+}
 
-    // Shell overhead needs to be added to both the prepare, conclude and the actual command,
-    // leading to a total benchmark time of (prepare + shell + cmd + shell + conclude + shell = 0.1 s)
-    hyperfine_debug()
-        .arg("--shell=sleep 0.01")
-        .arg("--prepare=sleep 0.01")
-        .arg("--conclude=sleep 0.01")
-        .arg("sleep 0.05")
+#[cfg(unix)]
+#[test]
+fn writes_the_output_of_every_run_to_a_separate_file() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let template = tempdir.path().join("{n}-{run}.txt");
+
+    hyperfine()
+        .arg("--warmup=1")
+        .arg("--runs=2")
+        .arg("--parameter-list")
+        .arg("n")
+        .arg("a,b")
+        .arg("--prepare=echo prepare")
+        .arg(format!("--output=file:{}", template.display()))
+        .arg("echo {n} out; echo {n} err >&2")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("30 runs"));
+        .success();
+
+    for n in ["a", "b"] {
+        for run in [1, 2] {
+            let path = tempdir.path().join(format!("{n}-{run}.txt"));
+            assert_eq!(
+                std::fs::read_to_string(&path).unwrap(),
+                format!("{n} out\n")
+            );
+            let path = tempdir.path().join(format!("{n}-{run}.txt.stderr"));
+            assert_eq!(
+                std::fs::read_to_string(&path).unwrap(),
+                format!("{n} err\n")
+            );
+        }
+    }
+
+    // Warmup runs and the preparation command do not create any files
+    assert_eq!(std::fs::read_dir(tempdir.path()).unwrap().count(), 8);
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn shows_benchmark_comparison_with_relative_times() {
-    hyperfine_debug()
-        .arg("sleep 1.0")
-        .arg("sleep 2.0")
-        .arg("sleep 3.0")
+fn hands_the_output_file_to_the_command() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let output = tempdir.path().join("out-{run}.txt");
+
+    // The standard output of the command is the file itself, not a pipe that is read by
+    // hyperfine
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--shell=none")
+        .arg(format!("--output=file:{}", output.display()))
+        .arg("readlink /proc/self/fd/1")
         .assert()
-        .success()
-        .stdout(
-            predicate::str::contains("2.00 ± 0.00 times faster")
-                .and(predicate::str::contains("3.00 ± 0.00 times faster")),
-        );
+        .success();
+
+    let path = tempdir.path().join("out-1.txt");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap().trim(),
+        path.canonicalize().unwrap().to_str().unwrap()
+    );
 }
 
 #[test]
-fn shows_benchmark_comparison_with_same_time() {
+fn fails_with_empty_output_path() {
     hyperfine_debug()
-        .arg("--command-name=A")
-        .arg("--command-name=B")
-        .arg("sleep 1.0")
-        .arg("sleep 1.0")
-        .arg("sleep 2.0")
-        .arg("sleep 1000.0")
+        .arg("--output=file:")
+        .arg("sleep 0.1")
         .assert()
-        .success()
-        .stdout(
-            predicate::str::contains("As fast (1.00 ± 0.00) as")
-                .and(predicate::str::contains("2.00 ± 0.00 times faster"))
-                .and(predicate::str::contains("1000.00 ± 0.00 times faster")),
-        );
+        .failure()
+        .stderr(predicate::str::contains(
+            "An empty path has been specified for '--output=file:<path>'",
+        ));
 }
 
 #[test]
-fn shows_benchmark_comparison_relative_to_reference() {
-    hyperfine_debug()
-        .arg("--reference=sleep 2.0")
-        .arg("sleep 1.0")
-        .arg("sleep 3.0")
+fn compares_two_json_exports() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let export = |file_name: &str, benchmarks: &[(&str, &str)]| {
+        let path = tempdir.path().join(file_name);
+        let mut command = hyperfine_debug();
+        command
+            .arg("--runs=3")
+            .arg(format!("--export-json={}", path.display()));
+        for (name, command_line) in benchmarks {
+            command
+                .arg(format!("--command-name={name}"))
+                .arg(command_line);
+        }
+        command.assert().success();
+        path
+    };
+
+    let old = export(
+        "old.json",
+        &[
+            ("fast", "sleep 0.2"),
+            ("same", "sleep 0.1"),
+            ("removed", "sleep 0.1"),
+        ],
+    );
+    let new = export("new.json", &[("same", "sleep 0.1"), ("fast", "sleep 0.1")]);
+    let markdown = tempdir.path().join("comparison.md");
+
+    hyperfine()
+        .arg("compare")
+        .arg(&old)
+        .arg(&new)
+        .arg(format!("--export-markdown={}", markdown.display()))
         .assert()
         .success()
         .stdout(
-            predicate::str::contains("2.00 ± 0.00 times slower")
-                .and(predicate::str::contains("1.50 ± 0.00 times faster")),
+            predicate::str::is_match("fast .* -50.0% ")
+                .unwrap()
+                .and(predicate::str::is_match("same .* unchanged").unwrap())
+                .and(predicate::str::contains(format!(
+                    "Only in '{}':\n  removed",
+                    old.display()
+                ))),
         );
+
+    let markdown = std::fs::read_to_string(markdown).unwrap();
+    assert!(markdown.starts_with("| Benchmark | Old [ms] | New [ms] | Change | p-value | Result |"));
+    assert!(markdown.contains("| `fast` | 200.0 ± 0.0 | 100.0 ± 0.0 | -50.0% |"));
 }
 
 #[test]
-fn performs_all_benchmarks_in_parameter_scan() {
-    hyperfine_debug()
-        .arg("--parameter-scan")
-        .arg("time")
-        .arg("30")
-        .arg("45")
-        .arg("--parameter-step-size")
-        .arg("5")
-        .arg("sleep {time}")
+fn compares_the_iterations_of_repeated_suites() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let export = |file_name: &str, args: &[&str]| {
+        let path = tempdir.path().join(file_name);
+        hyperfine_debug()
+            .arg("--runs=2")
+            .arg(format!("--export-json={}", path.display()))
+            .args(args)
+            .assert()
+            .success();
+        path
+    };
+
+    let old = export(
+        "old.json",
+        &["--repeat-suite=2", "-n", "bench", "sleep 0.1"],
+    );
+    let new = export(
+        "new.json",
+        &["--repeat-suite=2", "-n", "bench", "sleep 0.2"],
+    );
+    hyperfine()
+        .arg("compare")
+        .arg(&old)
+        .arg(&new)
         .assert()
         .success()
-        .stdout(
-            predicate::str::contains("Benchmark 1: sleep 30")
-                .and(predicate::str::contains("Benchmark 2: sleep 35"))
-                .and(predicate::str::contains("Benchmark 3: sleep 40"))
-                .and(predicate::str::contains("Benchmark 4: sleep 45"))
-                .and(predicate::str::contains("Benchmark 5: sleep 50").not()),
-        );
+        .stdout(predicate::str::is_match("bench \\(iteration 1\\) .* \\+100.0% ").unwrap())
+        .stdout(predicate::str::is_match("bench \\(iteration 2\\) .* \\+100.0% ").unwrap());
+
+    let duplicates = export("duplicates.json", &["sleep 0.1", "sleep 0.1"]);
+    hyperfine()
+        .arg("compare")
+        .arg(&duplicates)
+        .arg(&new)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The old export contains several results for the benchmark 'sleep 0.1'",
+        ));
 }
 
 #[test]
-fn performs_reference_and_all_benchmarks_in_parameter_scan() {
-    hyperfine_debug()
-        .arg("--reference=sleep 25")
-        .arg("--parameter-scan")
-        .arg("time")
-        .arg("30")
-        .arg("45")
-        .arg("--parameter-step-size")
-        .arg("5")
-        .arg("sleep {time}")
+fn fails_to_compare_invalid_json_exports() {
+    hyperfine()
+        .arg("compare")
+        .arg("does-not-exist.json")
+        .arg("does-not-exist.json")
         .assert()
-        .success()
-        .stdout(
-            predicate::str::contains("Benchmark 1: sleep 25")
-                .and(predicate::str::contains("Benchmark 2: sleep 30"))
-                .and(predicate::str::contains("Benchmark 3: sleep 35"))
-                .and(predicate::str::contains("Benchmark 4: sleep 40"))
-                .and(predicate::str::contains("Benchmark 5: sleep 45"))
-                .and(predicate::str::contains("Benchmark 6: sleep 50").not()),
-        );
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to read the file 'does-not-exist.json'",
+        ));
 }
 
 #[test]
-fn intermediate_results_are_not_exported_to_stdout() {
+fn resumes_from_the_results_of_an_earlier_invocation() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let path = tempdir.path().join("results.json");
+    let export = format!("--export-json={}", path.display());
+    let resume = format!("--resume-from={}", path.display());
+    let runs = || -> Vec<(String, u64)> {
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                (
+                    result["command"].as_str().unwrap().to_string(),
+                    result["runs"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    };
+
     hyperfine_debug()
-        .arg("--style=none") // To only see the Markdown export on stdout
-        .arg("--export-markdown")
-        .arg("-")
-        .arg("sleep 1")
-        .arg("sleep 2")
+        .arg("--runs=2")
+        .arg(&export)
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success();
+
+    // The same file can be used for the export
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg(&resume)
+        .arg(&export)
+        .arg("sleep 0.1")
+        .arg("sleep 0.3")
+        .arg("sleep 0.2")
         .assert()
         .success()
-        .stdout(
-            (predicate::str::contains("sleep 1").count(1))
-                .and(predicate::str::contains("sleep 2").count(1)),
-        );
+        .stdout(predicate::str::contains(
+            "2 of 3 benchmarks have a result already",
+        ))
+        .stdout(predicate::str::contains(
+            "Benchmark 3: sleep 0.2 (result of an earlier invocation)",
+        ))
+        .stdout(predicate::str::contains("sleep 0.1 ran"));
+    assert_eq!(
+        runs(),
+        [
+            ("sleep 0.1".to_string(), 2),
+            ("sleep 0.3".to_string(), 3),
+            ("sleep 0.2".to_string(), 2)
+        ]
+    );
+
+    hyperfine_debug()
+        .arg("--runs=4")
+        .arg(&resume)
+        .arg("--force-rerun=0\\.[12]")
+        .arg(&export)
+        .arg("sleep 0.1")
+        .arg("sleep 0.3")
+        .arg("sleep 0.2")
+        .assert()
+        .success();
+    assert_eq!(
+        runs(),
+        [
+            ("sleep 0.1".to_string(), 4),
+            ("sleep 0.3".to_string(), 3),
+            ("sleep 0.2".to_string(), 4)
+        ]
+    );
+
+    hyperfine_debug()
+        .arg("--resume-from=does-not-exist.json")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not read the results 'does-not-exist.json' specified as '--resume-from'",
+        ));
 }
 
-#[test]
 #[cfg(unix)]
-fn exports_intermediate_results_to_file() {
+#[test]
+fn runs_commands_with_the_given_priority() {
     use tempfile::tempdir;
 
     let tempdir = tempdir().unwrap();
-    let export_path = tempdir.path().join("results.md");
+    let output = tempdir.path().join("niceness");
+    let export = tempdir.path().join("results.json");
 
+    // Lowering the priority does not require any privileges
     hyperfine()
         .arg("--runs=1")
-        .arg("--export-markdown")
-        .arg(&export_path)
-        .arg("true")
-        .arg("false")
+        .arg("--shell=none")
+        .arg("--priority=low")
+        .arg(format!("--output={}", output.display()))
+        .arg(format!("--export-json={}", export.display()))
+        .arg("nice")
         .assert()
-        .failure();
+        .success();
 
-    let contents = std::fs::read_to_string(export_path).unwrap();
-    assert!(contents.contains("true"));
+    let niceness: i32 = std::fs::read_to_string(output)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    assert!(niceness >= 10);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    assert_eq!(json["metadata"]["options"]["priority"], "low");
 }
 
+#[cfg(unix)]
 #[test]
-fn unused_parameters_are_shown_in_benchmark_name() {
-    hyperfine()
-        .arg("--runs=2")
+fn changes_the_priority_relative_to_the_one_of_hyperfine() {
+    use tempfile::tempdir;
+
+    let tempdir = tempdir().unwrap();
+    let output = tempdir.path().join("niceness");
+
+    let read_niceness =
+        |output: &[u8]| -> i32 { String::from_utf8_lossy(output).trim().parse().unwrap() };
+    let base_niceness = read_niceness(&std::process::Command::new("nice").output().unwrap().stdout);
+
+    // hyperfine itself runs with a nice value that is increased by 5
+    let hyperfine = hyperfine_raw_command();
+    let mut command = std::process::Command::new("nice");
+    command
+        .current_dir(hyperfine.get_current_dir().unwrap())
+        .arg("-n")
+        .arg("5")
+        .arg(hyperfine.get_program());
+    assert_cmd::Command::from_std(command)
+        .arg("--runs=1")
+        .arg("--shell=none")
+        .arg("--priority=low")
+        .arg(format!("--output={}", output.display()))
+        .arg("nice")
+        .assert()
+        .success();
+
+    let niceness = read_niceness(&std::fs::read(output).unwrap());
+    assert_eq!(niceness, (base_niceness + 15).min(19));
+}
+
+#[test]
+fn fails_with_unknown_placeholders() {
+    hyperfine_debug()
         .arg("--parameter-list")
-        .arg("branch")
-        .arg("master,feature")
-        .arg("echo test")
+        .arg("threads")
+        .arg("1,2")
+        .arg("--prepare=sleep 0.{thread}")
+        .arg("sleep 0.{threads}")
         .assert()
-        .success()
-        .stdout(
-            predicate::str::contains("echo test (branch = master)")
-                .and(predicate::str::contains("echo test (branch = feature)")),
-        );
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown parameter placeholder(s) '{thread}' in 'sleep 0.{thread}'. \
+             The known parameters are: '{threads}'.",
+        ));
+
+    hyperfine_debug()
+        .arg("--parameter-list")
+        .arg("threads")
+        .arg("1,2")
+        .arg("--allow-unknown-placeholders")
+        .arg("--command-name=sleep {other}")
+        .arg("sleep 0.{threads}")
+        .assert()
+        .success();
 }
 
 #[test]
-fn speed_comparison_sort_order() {
-    for sort_order in ["auto", "mean-time"] {
-        hyperfine_debug()
-            .arg("sleep 2")
-            .arg("sleep 1")
-            .arg(format!("--sort={sort_order}"))
-            .assert()
-            .success()
-            .stdout(predicate::str::contains(
-                "sleep 1 ran\n    2.00 ± 0.00 times faster than sleep 2",
-            ));
-    }
+fn uses_the_colors_of_the_chosen_theme() {
+    let color_code = r"\x1b\[([0-9]+;)*(3|9)[0-7]m";
 
     hyperfine_debug()
-        .arg("sleep 2")
-        .arg("sleep 1")
-        .arg("--sort=command")
+        .arg("--style=color")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
         .assert()
         .success()
-        .stdout(predicate::str::contains(
-            "2.00 ±  0.00  sleep 2\n        1.00          sleep 1",
-        ));
+        .stdout(predicate::str::is_match(color_code).unwrap());
+
+    hyperfine_debug()
+        .arg("--style=color")
+        .arg("--theme=monochrome-bold")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(color_code).unwrap().not())
+        .stdout(predicate::str::contains("\x1b[1msleep 0.1"));
+
+    hyperfine_debug()
+        .arg("--style=nocolor")
+        .arg("--theme=colorblind")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[").not());
 }
 
-#[cfg(windows)]
+#[cfg(unix)]
 #[test]
-fn windows_quote_args() {
-    hyperfine()
-        .arg("more \"example_input_file.txt\"")
+fn extracts_the_time_that_the_command_reports_itself() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=none")
+        .arg("--extract-time=took ([0-9.]+) ?(?P<unit>ms|s)")
+        .arg("--export-json=-")
+        .arg("echo took 12.5 ms")
+        .arg("echo nothing")
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains(
+            "Self-reported:        12.5 ms ±   0.0 ms",
+        ))
+        .stderr(predicate::str::contains(
+            "The '--extract-time' pattern did not match the output of 2 runs",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("\n{").unwrap()..]).unwrap();
+
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(
+        results[0]["reported_times"],
+        serde_json::json!([0.0125, 0.0125])
+    );
+    assert_eq!(
+        results[1]["reported_times"],
+        serde_json::json!([null, null])
+    );
 }
 
-#[cfg(windows)]
 #[test]
-fn windows_quote_before_quote_args() {
-    hyperfine()
-        .arg("dir \"..\\src\\\" \"..\\tests\\\"")
+fn fails_with_an_invalid_time_extraction_pattern() {
+    hyperfine_debug()
+        .arg("--extract-time=took [0-9.]+")
+        .arg("sleep 0.1")
         .assert()
-        .success();
+        .failure()
+        .stderr(predicate::str::contains(
+            "the pattern needs a capture group for the number",
+        ));
 }
@@ -0,0 +1,63 @@
+use hyperfine::options::{ExecutorKind, OutputStyleOption};
+use hyperfine::{run_benchmarks, Command, Commands, Options};
+
+fn mock_options() -> Options {
+    Options {
+        executor_kind: ExecutorKind::Mock(None),
+        ..Options::default()
+    }
+    .with_runs(3)
+    .with_output_style(OutputStyleOption::Disabled)
+}
+
+#[test]
+fn runs_benchmarks_through_the_library() {
+    let commands = Commands::from(vec![
+        Command::new(None, "sleep 0.1"),
+        Command::new(Some("longer"), "sleep 0.2"),
+    ]);
+
+    let results = run_benchmarks(&commands, &mock_options()).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].name, "sleep 0.1");
+    assert_eq!(results[1].name, "longer");
+    assert_eq!(results[1].command, "sleep 0.2");
+    assert_eq!(results[0].times.as_ref().unwrap().len(), 3);
+    assert!(results[0].mean < results[1].mean);
+}
+
+#[test]
+fn validates_options_against_commands() {
+    let commands = Commands::from(vec![Command::new(None, "sleep 0.1")]);
+    let options = Options {
+        reference_command: Some("sleep 0.1".into()),
+        ..mock_options()
+    };
+    assert!(run_benchmarks(&commands, &options).is_ok());
+}
+
+#[test]
+fn fails_with_options_that_do_not_match_the_commands() {
+    let commands = Commands::from(vec![Command::new(None, "sleep 0.1")]);
+    let options = Options {
+        setup_command: Some(vec!["sleep 0.1".into(), "sleep 0.2".into()]),
+        ..mock_options()
+    };
+
+    let error = run_benchmarks(&commands, &options).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("The '--setup' option has to be provided just once or once for each benchmark"));
+}
+
+#[test]
+fn results_can_be_serialized_and_deserialized() {
+    let commands = Commands::from(vec![Command::new(None, "sleep 0.1")]);
+    let results = run_benchmarks(&commands, &mock_options()).unwrap();
+
+    let json = serde_json::to_string(&results[0]).unwrap();
+    let deserialized: hyperfine::BenchmarkResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.command, results[0].command);
+    assert_eq!(deserialized.times, results[0].times);
+}